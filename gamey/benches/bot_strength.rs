@@ -0,0 +1,91 @@
+//! Strength comparison between [`ResistanceBot`] and [`ShortestPathBot`].
+//!
+//! Plays a short match on several board sizes, alternating which bot moves
+//! first, and prints the win rate of each bot together with the average time
+//! spent per move.
+//!
+//! ```sh
+//! cargo bench --bench bot_strength
+//! ```
+
+use gamey::{GameStatus, GameY, Movement, ResistanceBot, ShortestPathBot, YBot};
+use std::time::{Duration, Instant};
+
+const GAMES_PER_SIZE: usize = 20;
+
+/// Accumulated results of one bot during a match.
+#[derive(Default)]
+struct BotStats {
+    wins: usize,
+    moves: u32,
+    thinking: Duration,
+}
+
+impl BotStats {
+    fn average_move_time(&self) -> Duration {
+        if self.moves == 0 {
+            Duration::ZERO
+        } else {
+            self.thinking / self.moves
+        }
+    }
+}
+
+/// Plays one game and returns the index (0 or 1) of the bot that won.
+fn play_game(size: u32, bots: [&dyn YBot; 2], stats: &mut [BotStats; 2]) -> usize {
+    let mut game = GameY::new(size);
+    loop {
+        match game.status() {
+            GameStatus::Finished { winner } => return winner.id() as usize,
+            GameStatus::Ongoing { next_player } => {
+                let player = *next_player;
+                let turn = player.id() as usize;
+                let start = Instant::now();
+                let coords = bots[turn]
+                    .choose_move(&game)
+                    .expect("an ongoing game always has available cells");
+                stats[turn].thinking += start.elapsed();
+                stats[turn].moves += 1;
+                game.add_move(Movement::Placement { player, coords })
+                    .expect("bots only choose available cells");
+            }
+        }
+    }
+}
+
+fn main() {
+    let resistance = ResistanceBot;
+    let shortest_path = ShortestPathBot::default();
+    let contenders: [&dyn YBot; 2] = [&resistance, &shortest_path];
+
+    println!(
+        "{:>4}  {:>20}  {:>8}  {:>12}",
+        "size", "bot", "win rate", "time / move"
+    );
+    for size in [5, 7, 9] {
+        let mut stats = [BotStats::default(), BotStats::default()];
+        for game in 0..GAMES_PER_SIZE {
+            // Alternate colours so that the first-move advantage is shared.
+            let swapped = game % 2 == 1;
+            let seats = if swapped { [1, 0] } else { [0, 1] };
+            let bots = [contenders[seats[0]], contenders[seats[1]]];
+            let mut seat_stats = [BotStats::default(), BotStats::default()];
+            let winner_seat = play_game(size, bots, &mut seat_stats);
+            stats[seats[winner_seat]].wins += 1;
+            for (seat, seat_stat) in seat_stats.into_iter().enumerate() {
+                let total = &mut stats[seats[seat]];
+                total.moves += seat_stat.moves;
+                total.thinking += seat_stat.thinking;
+            }
+        }
+        for (bot, stat) in contenders.iter().zip(stats.iter()) {
+            println!(
+                "{:>4}  {:>20}  {:>7.1}%  {:>12.2?}",
+                size,
+                bot.name(),
+                100.0 * stat.wins as f64 / GAMES_PER_SIZE as f64,
+                stat.average_move_time()
+            );
+        }
+    }
+}
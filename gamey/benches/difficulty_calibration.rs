@@ -0,0 +1,44 @@
+//! Calibration of the difficulty levels of the bot server.
+//!
+//! Plays every level of the default [`DifficultyTable`] against the level
+//! below it and checks that the stronger one wins at least [`TARGET`] of the
+//! games. The process exits with a non-zero status if any pair misses the
+//! target, so it can be used as a check after retuning the levels.
+//!
+//! ```sh
+//! cargo bench --bench difficulty_calibration
+//! ```
+
+use gamey::{DifficultyTable, create_default_state, difficulty::calibrate};
+
+const BOARD_SIZE: u32 = 7;
+const GAMES: usize = 40;
+const TARGET: f64 = 0.6;
+
+fn main() {
+    let state = create_default_state();
+    let results = calibrate(
+        &DifficultyTable::default(),
+        &state.bots(),
+        BOARD_SIZE,
+        GAMES,
+        TARGET,
+    )
+    .expect("difficulty levels should be playable");
+
+    println!("{:>8} vs {:<8}  {:>8}  {:>6}", "stronger", "weaker", "win rate", "result");
+    let mut all_passed = true;
+    for result in &results {
+        all_passed &= result.passed();
+        println!(
+            "{:>8} vs {:<8}  {:>7.1}%  {:>6}",
+            result.stronger.as_str(),
+            result.weaker.as_str(),
+            100.0 * result.win_rate(),
+            if result.passed() { "ok" } else { "FAILED" }
+        );
+    }
+    if !all_passed {
+        std::process::exit(1);
+    }
+}
@@ -1,5 +1,8 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
-use gamey::{Coordinates, GameY, Movement, PlayerId, RenderOptions};
+use gamey::{
+    Coordinates, GameY, Movement, PlayerId, RenderOptions, ResistanceBot, ShortestPathBot, YBot,
+    evaluate_resistance,
+};
 
 /// Benchmarks for coordinate conversion functions
 fn bench_coordinates(c: &mut Criterion) {
@@ -198,6 +201,51 @@ fn bench_touches_side(c: &mut Criterion) {
     group.finish();
 }
 
+/// Builds a mid-game position by filling every third cell alternately.
+fn mid_game(size: u32) -> GameY {
+    let mut game = GameY::new(size);
+    let total_cells = (size * (size + 1)) / 2;
+    for (turn, idx) in (0..total_cells).step_by(3).enumerate() {
+        let coords = Coordinates::from_index(idx, size);
+        let player = PlayerId::new(turn as u32 % 2);
+        let _ = game.add_move(Movement::Placement { player, coords });
+    }
+    game
+}
+
+/// Benchmarks for position evaluation and bot move selection cost
+fn bench_bots(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bots");
+    group.sample_size(10);
+
+    let shortest_path = ShortestPathBot::default();
+    let resistance = ResistanceBot;
+
+    for board_size in [5, 9, 13].iter() {
+        let game = mid_game(*board_size);
+
+        group.bench_with_input(
+            BenchmarkId::new("resistance_evaluation", board_size),
+            &game,
+            |b, game| b.iter(|| black_box(evaluate_resistance(game, PlayerId::new(0)))),
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("shortest_path_bot", board_size),
+            &game,
+            |b, game| b.iter(|| black_box(shortest_path.choose_move(game))),
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("resistance_bot", board_size),
+            &game,
+            |b, game| b.iter(|| black_box(resistance.choose_move(game))),
+        );
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_coordinates,
@@ -205,6 +253,7 @@ criterion_group!(
     bench_add_move,
     bench_render,
     bench_touches_side,
+    bench_bots,
 );
 
 criterion_main!(benches);
@@ -0,0 +1,39 @@
+//! Strength comparison between plain UCT and RAVE in [`gamey::MctsBot`].
+//!
+//! Runs a tournament between `mcts_bot` with and without `rave=true`, with
+//! the same number of playouts per move, on several board sizes, and prints
+//! the win rate and Elo estimate of each variant.
+//!
+//! ```sh
+//! cargo bench --bench mcts_rave
+//! ```
+
+use gamey::{TournamentConfig, default_registry, run_tournament};
+
+const GAMES: u32 = 40;
+const ITERATIONS: u32 = 500;
+
+fn main() {
+    let registry = default_registry();
+    let variants = vec![
+        format!("mcts_bot?iterations={ITERATIONS}"),
+        format!("mcts_bot?iterations={ITERATIONS}&rave=true"),
+    ];
+
+    println!("{:>4}  {:>40}  {:>8}  {:>16}  {:>7}", "size", "bot", "win rate", "95% interval", "elo");
+    for size in [5, 7, 9] {
+        let config = TournamentConfig::new(variants.clone(), size).with_games(GAMES).with_seed(1);
+        let report = run_tournament(&registry, &config).expect("both variants should be playable");
+        for standing in &report.standings {
+            println!(
+                "{:>4}  {:>40}  {:>7.1}%  {:>6.1}% - {:>5.1}%  {:>7.1}",
+                size,
+                standing.bot,
+                100.0 * standing.win_rate,
+                100.0 * standing.ci_low,
+                100.0 * standing.ci_high,
+                standing.elo
+            );
+        }
+    }
+}
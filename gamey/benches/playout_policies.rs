@@ -0,0 +1,64 @@
+//! Speed and strength of the playout policies.
+//!
+//! First measures how many playouts per second each [`PlayoutKind`] runs from
+//! an empty board (and, for reference, random playouts placed stone by stone
+//! instead of filled in one pass), then plays a tournament between `mcts_bot` with each
+//! policy and the same number of iterations, so that the strength gained can
+//! be weighed against the playouts lost.
+//!
+//! ```sh
+//! cargo bench --bench playout_policies
+//! ```
+
+use gamey::{GameY, PlayoutKind, PlayoutPolicy, RandomPolicy, TournamentConfig, default_registry, run_tournament};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::time::{Duration, Instant};
+
+const KINDS: [(PlayoutKind, &str); 3] = [
+    (PlayoutKind::Random, "random"),
+    (PlayoutKind::Bridge, "bridge"),
+    (PlayoutKind::Pattern, "pattern"),
+];
+const SPEED_SIZE: u32 = 9;
+const SPEED_TIME: Duration = Duration::from_secs(2);
+const MATCH_SIZE: u32 = 7;
+const GAMES: u32 = 30;
+const ITERATIONS: u32 = 300;
+
+fn main() {
+    println!("{:>22}  {:>14}", "policy", "playouts / s");
+    let mut rng = StdRng::seed_from_u64(1);
+    let game = GameY::new(SPEED_SIZE);
+    let rate = |playout: &mut dyn FnMut()| {
+        let start = Instant::now();
+        let mut playouts = 0u32;
+        while start.elapsed() < SPEED_TIME {
+            playout();
+            playouts += 1;
+        }
+        playouts as f64 / start.elapsed().as_secs_f64()
+    };
+    let stone_by_stone = rate(&mut || {
+        RandomPolicy.playout(&mut game.clone(), None, &mut rng);
+    });
+    println!("{:>22}  {:>14.0}", "random (stone by stone)", stone_by_stone);
+    for (kind, name) in KINDS {
+        let speed = rate(&mut || {
+            kind.run(&game, None, &mut rng);
+        });
+        println!("{:>22}  {:>14.0}", name, speed);
+    }
+
+    let bots = KINDS
+        .iter()
+        .map(|(_, name)| format!("mcts_bot?iterations={ITERATIONS}&playout={name}"))
+        .collect();
+    let config = TournamentConfig::new(bots, MATCH_SIZE).with_games(GAMES).with_seed(1);
+    let report = run_tournament(&default_registry(), &config).expect("every policy should be playable");
+    println!();
+    println!("{:>45}  {:>8}  {:>7}", "bot", "win rate", "elo");
+    for standing in &report.standings {
+        println!("{:>45}  {:>7.1}%  {:>7.1}", standing.bot, 100.0 * standing.win_rate, standing.elo);
+    }
+}
@@ -0,0 +1,181 @@
+//! Scored candidate moves.
+//!
+//! This module provides [`MoveAnalysis`], the result of [`YBot::analyze`]: the
+//! candidate moves a bot considered, ranked by the score the bot gave them,
+//! together with the line of play it expects. The bot server exposes it so
+//! that clients can draw heatmaps and explain hints.
+
+use crate::{Coordinates, GameY, Movement, YBot};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A candidate move together with the score a bot gave it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct MoveCandidate {
+    /// The cell of the move.
+    pub coords: Coordinates,
+    /// The bot's score for the move; higher is better. Scores are only
+    /// comparable between candidates of the same analysis.
+    pub score: f64,
+    /// Fraction of simulated games won after the move, for bots that simulate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub win_rate: Option<f64>,
+}
+
+impl MoveCandidate {
+    /// Creates a candidate without a win rate.
+    pub fn new(coords: Coordinates, score: f64) -> Self {
+        MoveCandidate {
+            coords,
+            score,
+            win_rate: None,
+        }
+    }
+
+    /// Sets the win rate of the candidate.
+    pub fn with_win_rate(mut self, win_rate: f64) -> Self {
+        self.win_rate = Some(win_rate);
+        self
+    }
+}
+
+/// The candidate moves of a position, best first, and the expected line of play.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, ToSchema)]
+pub struct MoveAnalysis {
+    /// Candidate moves sorted by decreasing score.
+    pub candidates: Vec<MoveCandidate>,
+    /// The moves the bot expects to be played from the position, alternating
+    /// between both players and starting with the player to move.
+    pub principal_variation: Vec<Coordinates>,
+}
+
+impl MoveAnalysis {
+    /// Creates an analysis from unsorted candidates.
+    ///
+    /// Candidates are sorted by decreasing score (keeping the given order among
+    /// equal scores) and the principal variation is the best candidate alone.
+    pub fn new(mut candidates: Vec<MoveCandidate>) -> Self {
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        let principal_variation = candidates.first().map(|c| c.coords).into_iter().collect();
+        MoveAnalysis {
+            candidates,
+            principal_variation,
+        }
+    }
+
+    /// Replaces the principal variation.
+    pub fn with_principal_variation(mut self, principal_variation: Vec<Coordinates>) -> Self {
+        self.principal_variation = principal_variation;
+        self
+    }
+
+    /// Returns the best candidate, if any.
+    pub fn best(&self) -> Option<&MoveCandidate> {
+        self.candidates.first()
+    }
+
+    /// Keeps only the `limit` best candidates.
+    pub fn truncate(&mut self, limit: usize) {
+        self.candidates.truncate(limit);
+    }
+}
+
+/// Builds a principal variation by letting `best_move` pick the move of each
+/// side in turn, for at most `depth` moves or until the game ends.
+pub(crate) fn greedy_principal_variation(
+    board: &GameY,
+    depth: usize,
+    mut best_move: impl FnMut(&GameY) -> Option<Coordinates>,
+) -> Vec<Coordinates> {
+    let mut board = board.clone();
+    let mut variation = Vec::new();
+    while variation.len() < depth && !board.check_game_over() {
+        let (Some(player), Some(coords)) = (board.next_player(), best_move(&board)) else {
+            break;
+        };
+        if board.add_move(Movement::Placement { player, coords }).is_err() {
+            break;
+        }
+        variation.push(coords);
+    }
+    variation
+}
+
+/// Analysis used by bots that do not score their moves: the chosen move alone,
+/// with a score of 0.
+pub(crate) fn single_move_analysis(bot: &(impl YBot + ?Sized), board: &GameY) -> MoveAnalysis {
+    let candidates = bot
+        .choose_move(board)
+        .map(|coords| MoveCandidate::new(coords, 0.0))
+        .into_iter()
+        .collect();
+    MoveAnalysis::new(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RandomBot;
+
+    #[test]
+    fn test_move_analysis_sorts_candidates() {
+        let analysis = MoveAnalysis::new(vec![
+            MoveCandidate::new(Coordinates::new(2, 0, 0), 1.0),
+            MoveCandidate::new(Coordinates::new(0, 2, 0), 3.0),
+            MoveCandidate::new(Coordinates::new(0, 0, 2), 2.0),
+        ]);
+        let scores: Vec<f64> = analysis.candidates.iter().map(|c| c.score).collect();
+        assert_eq!(scores, vec![3.0, 2.0, 1.0]);
+        assert_eq!(analysis.best().unwrap().coords, Coordinates::new(0, 2, 0));
+        assert_eq!(analysis.principal_variation, vec![Coordinates::new(0, 2, 0)]);
+    }
+
+    #[test]
+    fn test_move_analysis_empty() {
+        let analysis = MoveAnalysis::new(vec![]);
+        assert!(analysis.best().is_none());
+        assert!(analysis.principal_variation.is_empty());
+    }
+
+    #[test]
+    fn test_move_analysis_truncate() {
+        let mut analysis = MoveAnalysis::new(vec![
+            MoveCandidate::new(Coordinates::new(1, 0, 0), 1.0),
+            MoveCandidate::new(Coordinates::new(0, 1, 0), 2.0),
+        ]);
+        analysis.truncate(1);
+        assert_eq!(analysis.candidates.len(), 1);
+        assert_eq!(analysis.candidates[0].score, 2.0);
+    }
+
+    #[test]
+    fn test_move_candidate_serialization_skips_missing_win_rate() {
+        let json = serde_json::to_value(MoveCandidate::new(Coordinates::new(1, 0, 0), 0.5)).unwrap();
+        assert!(json.get("win_rate").is_none());
+        let json = serde_json::to_value(
+            MoveCandidate::new(Coordinates::new(1, 0, 0), 0.5).with_win_rate(0.25),
+        )
+        .unwrap();
+        assert_eq!(json["win_rate"], 0.25);
+    }
+
+    #[test]
+    fn test_greedy_principal_variation_stops_at_depth_and_game_over() {
+        let game = GameY::new(4);
+        let first_cell = |board: &GameY| {
+            let cell = board.available_cells().first()?;
+            Some(Coordinates::from_index(*cell, board.board_size()))
+        };
+        assert_eq!(greedy_principal_variation(&game, 3, first_cell).len(), 3);
+        let variation = greedy_principal_variation(&game, 100, first_cell);
+        assert!(variation.len() <= 10);
+    }
+
+    #[test]
+    fn test_single_move_analysis_uses_choose_move() {
+        let game = GameY::new(3);
+        let analysis = single_move_analysis(&RandomBot, &game);
+        assert_eq!(analysis.candidates.len(), 1);
+        assert_eq!(analysis.candidates[0].score, 0.0);
+    }
+}
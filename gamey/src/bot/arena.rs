@@ -0,0 +1,144 @@
+//! Bot versus bot games.
+//!
+//! This module provides [`play_game`], which lets two [`YBot`]s play a full
+//! game against each other. It is the building block for strength
+//! measurements such as the difficulty calibration of the bot server.
+//! [`play_recorded_game`] also keeps the moves as a [`GameRecord`], from which
+//! opening books are built (see [`crate::OpeningBook::from_records`]).
+
+use crate::{BotDecision, DecisionOptions, GameStatus, GameY, GameYError, PlayerId, YBot};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// The moves of a finished bot versus bot game.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GameRecord {
+    /// Size of the board.
+    pub size: u32,
+    /// The bots of player 0 and player 1.
+    pub players: [String; 2],
+    /// The decisions of both players in order, starting with player 0.
+    pub moves: Vec<BotDecision>,
+    /// Id of the winning player.
+    pub winner: u32,
+}
+
+/// Plays a game between two bots on an empty board of the given size.
+///
+/// `bots[0]` plays as player 0 (moves first) and `bots[1]` as player 1.
+/// Bots may resign (see [`YBot::decide`]), and a bot that has no move to
+/// offer resigns too. The pie rule is not used. Returns the winner.
+///
+/// # Errors
+/// Returns an error if a bot chooses an invalid move (e.g. an occupied cell).
+///
+/// # Example
+///
+/// ```
+/// use gamey::{RandomBot, play_game};
+///
+/// let winner = play_game([&RandomBot, &RandomBot], 5).unwrap();
+/// assert!(winner.id() < 2);
+/// ```
+pub fn play_game(bots: [&dyn YBot; 2], size: u32) -> Result<PlayerId, GameYError> {
+    play_game_with_rng(bots, size, &mut rand::rng())
+}
+
+/// Like [`play_game`], but both bots draw their randomness from `rng`
+/// (see [`YBot::decide_with_rng`]), so a seeded `rng` replays the same game.
+pub fn play_game_with_rng(
+    bots: [&dyn YBot; 2],
+    size: u32,
+    rng: &mut dyn RngCore,
+) -> Result<PlayerId, GameYError> {
+    Ok(PlayerId::new(play_recorded_game(bots, size, rng)?.winner))
+}
+
+/// Like [`play_game_with_rng`], but returns the whole game as a [`GameRecord`].
+pub fn play_recorded_game(
+    bots: [&dyn YBot; 2],
+    size: u32,
+    rng: &mut dyn RngCore,
+) -> Result<GameRecord, GameYError> {
+    let options = DecisionOptions::default().with_resign(true);
+    let mut game = GameY::new(size);
+    let mut moves = Vec::new();
+    loop {
+        match game.status() {
+            GameStatus::Finished { winner } => {
+                return Ok(GameRecord {
+                    size,
+                    players: [bots[0].name().to_string(), bots[1].name().to_string()],
+                    moves,
+                    winner: winner.id(),
+                });
+            }
+            GameStatus::Ongoing { next_player } => {
+                let player = *next_player;
+                let bot = bots[player.id() as usize];
+                let decision = bot.decide_with_rng(&game, &options, rng);
+                game.add_move(decision.into_movement(player))?;
+                moves.push(decision);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Coordinates, RandomBot};
+
+    /// A bot that never finds a move.
+    struct PassiveBot;
+
+    impl YBot for PassiveBot {
+        fn name(&self) -> &str {
+            "passive_bot"
+        }
+
+        fn choose_move(&self, _board: &GameY) -> Option<Coordinates> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_play_game_returns_a_winner() {
+        let winner = play_game([&RandomBot, &RandomBot], 4).unwrap();
+        assert!(winner == PlayerId::new(0) || winner == PlayerId::new(1));
+    }
+
+    #[test]
+    fn test_play_game_single_cell_first_player_wins() {
+        let winner = play_game([&RandomBot, &RandomBot], 1).unwrap();
+        assert_eq!(winner, PlayerId::new(0));
+    }
+
+    #[test]
+    fn test_play_game_with_seeded_rng_is_reproducible() {
+        use rand::{SeedableRng, rngs::StdRng};
+        let first = (0..10)
+            .map(|_| play_game_with_rng([&RandomBot, &RandomBot], 5, &mut StdRng::seed_from_u64(7)).unwrap())
+            .collect::<Vec<_>>();
+        assert!(first.windows(2).all(|w| w[0] == w[1]));
+    }
+
+    #[test]
+    fn test_play_recorded_game_replays_to_the_same_winner() {
+        use rand::{SeedableRng, rngs::StdRng};
+        let record = play_recorded_game([&RandomBot, &RandomBot], 4, &mut StdRng::seed_from_u64(3)).unwrap();
+        assert_eq!(record.players, ["random_bot".to_string(), "random_bot".to_string()]);
+
+        let mut game = GameY::new(record.size);
+        for (ply, decision) in record.moves.iter().enumerate() {
+            game.add_move(decision.into_movement(PlayerId::new(ply as u32 % 2))).unwrap();
+        }
+        assert!(matches!(game.status(), GameStatus::Finished { winner } if winner.id() == record.winner));
+    }
+
+    #[test]
+    fn test_play_game_bot_without_moves_resigns() {
+        let winner = play_game([&PassiveBot, &RandomBot], 3).unwrap();
+        assert_eq!(winner, PlayerId::new(1));
+    }
+}
@@ -0,0 +1,145 @@
+//! A wrapper bot that makes deliberate mistakes.
+//!
+//! This module provides [`BlunderBot`], which delegates to another bot but,
+//! with a configurable probability, plays a random move instead. It is used by
+//! the difficulty levels of the bot server to weaken strong bots in a
+//! controlled way.
+
+use std::sync::Arc;
+
+use crate::{BotDecision, BotMetadata, Coordinates, DecisionOptions, GameY, MoveAnalysis, RandomBot, YBot};
+use rand::{Rng, RngCore};
+
+/// A bot that plays a random move with probability `blunder_probability` and
+/// otherwise asks the inner bot.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use gamey::{BlunderBot, GameY, ShortestPathBot, YBot};
+///
+/// let bot = BlunderBot::new(Arc::new(ShortestPathBot::default()), 0.25);
+/// let game = GameY::new(5);
+///
+/// assert_eq!(bot.name(), "shortest_path_bot");
+/// assert!(bot.choose_move(&game).is_some());
+/// ```
+pub struct BlunderBot {
+    inner: Arc<dyn YBot>,
+    blunder_probability: f64,
+}
+
+impl BlunderBot {
+    /// Wraps `inner`, blundering with the given probability.
+    ///
+    /// The probability is clamped to the range `[0, 1]`.
+    pub fn new(inner: Arc<dyn YBot>, blunder_probability: f64) -> Self {
+        BlunderBot {
+            inner,
+            blunder_probability: blunder_probability.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Returns the probability of playing a random move.
+    pub fn blunder_probability(&self) -> f64 {
+        self.blunder_probability
+    }
+}
+
+impl YBot for BlunderBot {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn metadata(&self) -> BotMetadata {
+        self.inner.metadata()
+    }
+
+    fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
+        self.choose_move_with_rng(board, &mut rand::rng())
+    }
+
+    fn choose_move_with_rng(&self, board: &GameY, rng: &mut dyn RngCore) -> Option<Coordinates> {
+        if rng.random_bool(self.blunder_probability) {
+            RandomBot.choose_move_with_rng(board, rng)
+        } else {
+            self.inner.choose_move_with_rng(board, rng)
+        }
+    }
+
+    fn decide_with_rng(&self, board: &GameY, options: &DecisionOptions, rng: &mut dyn RngCore) -> BotDecision {
+        if rng.random_bool(self.blunder_probability) {
+            BotDecision::from_choice(RandomBot.choose_move_with_rng(board, rng))
+        } else {
+            self.inner.decide_with_rng(board, options, rng)
+        }
+    }
+
+    /// Returns the analysis of the inner bot; blunders are not part of it.
+    fn analyze(&self, board: &GameY) -> MoveAnalysis {
+        self.inner.analyze(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Movement, PlayerId};
+
+    /// A bot that always plays the first available cell.
+    struct FirstCellBot;
+
+    impl YBot for FirstCellBot {
+        fn name(&self) -> &str {
+            "first_cell_bot"
+        }
+
+        fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
+            let cell = board.available_cells().first()?;
+            Some(Coordinates::from_index(*cell, board.board_size()))
+        }
+    }
+
+    #[test]
+    fn test_blunder_bot_uses_inner_name() {
+        let bot = BlunderBot::new(Arc::new(FirstCellBot), 0.5);
+        assert_eq!(bot.name(), "first_cell_bot");
+    }
+
+    #[test]
+    fn test_blunder_bot_never_blunders_with_zero_probability() {
+        let bot = BlunderBot::new(Arc::new(FirstCellBot), 0.0);
+        let game = GameY::new(6);
+        for _ in 0..20 {
+            assert_eq!(bot.choose_move(&game), Some(Coordinates::from_index(0, 6)));
+        }
+    }
+
+    #[test]
+    fn test_blunder_bot_always_blunders_with_probability_one() {
+        let bot = BlunderBot::new(Arc::new(FirstCellBot), 1.0);
+        let game = GameY::new(8);
+        let moves: Vec<_> = (0..30).filter_map(|_| bot.choose_move(&game)).collect();
+        assert_eq!(moves.len(), 30);
+        assert!(moves.iter().any(|c| *c != Coordinates::from_index(0, 8)));
+    }
+
+    #[test]
+    fn test_blunder_probability_is_clamped() {
+        assert_eq!(BlunderBot::new(Arc::new(FirstCellBot), 3.0).blunder_probability(), 1.0);
+        assert_eq!(BlunderBot::new(Arc::new(FirstCellBot), -1.0).blunder_probability(), 0.0);
+    }
+
+    #[test]
+    fn test_blunder_bot_returns_none_on_full_board() {
+        let bot = BlunderBot::new(Arc::new(FirstCellBot), 0.5);
+        let mut game = GameY::new(1);
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(0),
+            coords: Coordinates::new(0, 0, 0),
+        })
+        .unwrap();
+        assert!(bot.choose_move(&game).is_none());
+    }
+}
@@ -0,0 +1,131 @@
+//! A wrapper bot that plays from an opening book.
+//!
+//! This module provides [`BookBot`], which looks the position up in an
+//! [`OpeningBook`] and only asks the inner bot when the position is not in the
+//! book. It saves the slow searches of the first moves, where bots such as
+//! [`crate::MonteCarloBot`] have the most cells to evaluate.
+
+use std::sync::Arc;
+
+use crate::{BotDecision, BotMetadata, Coordinates, DecisionOptions, GameY, MoveAnalysis, OpeningBook, YBot};
+use rand::RngCore;
+
+/// A bot that plays book moves while it can and otherwise asks the inner bot.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use gamey::{BookBot, Coordinates, GameY, OpeningBook, RandomBot, YBot};
+///
+/// let mut book = OpeningBook::new();
+/// book.add(&GameY::new(5), Coordinates::new(2, 1, 1), 1);
+/// let bot = BookBot::new(Arc::new(RandomBot), Arc::new(book));
+///
+/// assert_eq!(bot.name(), "random_bot");
+/// assert_eq!(bot.choose_move(&GameY::new(5)), Some(Coordinates::new(2, 1, 1)));
+/// ```
+pub struct BookBot {
+    inner: Arc<dyn YBot>,
+    book: Arc<OpeningBook>,
+}
+
+impl BookBot {
+    /// Wraps `inner` with the given book.
+    pub fn new(inner: Arc<dyn YBot>, book: Arc<OpeningBook>) -> Self {
+        BookBot { inner, book }
+    }
+
+    /// Returns the book of this bot.
+    pub fn book(&self) -> &OpeningBook {
+        &self.book
+    }
+}
+
+impl YBot for BookBot {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn metadata(&self) -> BotMetadata {
+        self.inner.metadata()
+    }
+
+    fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
+        self.choose_move_with_rng(board, &mut rand::rng())
+    }
+
+    fn choose_move_with_rng(&self, board: &GameY, rng: &mut dyn RngCore) -> Option<Coordinates> {
+        self.book
+            .choose(board, rng)
+            .or_else(|| self.inner.choose_move_with_rng(board, rng))
+    }
+
+    fn decide_with_rng(&self, board: &GameY, options: &DecisionOptions, rng: &mut dyn RngCore) -> BotDecision {
+        match self.book.choose(board, rng) {
+            Some(coords) => BotDecision::Place { coords },
+            None => self.inner.decide_with_rng(board, options, rng),
+        }
+    }
+
+    /// Returns the analysis of the inner bot; the book is not part of it.
+    fn analyze(&self, board: &GameY) -> MoveAnalysis {
+        self.inner.analyze(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Movement, PlayerId};
+
+    /// A bot that always plays the first available cell.
+    struct FirstCellBot;
+
+    impl YBot for FirstCellBot {
+        fn name(&self) -> &str {
+            "first_cell_bot"
+        }
+
+        fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
+            let cell = board.available_cells().first()?;
+            Some(Coordinates::from_index(*cell, board.board_size()))
+        }
+    }
+
+    fn book_with_center_opening() -> Arc<OpeningBook> {
+        let mut book = OpeningBook::new();
+        book.add(&GameY::new(5), Coordinates::new(2, 1, 1), 1);
+        Arc::new(book)
+    }
+
+    #[test]
+    fn test_book_bot_uses_inner_name() {
+        let bot = BookBot::new(Arc::new(FirstCellBot), book_with_center_opening());
+        assert_eq!(bot.name(), "first_cell_bot");
+        assert_eq!(bot.book().len(), 1);
+    }
+
+    #[test]
+    fn test_book_bot_plays_book_moves() {
+        let bot = BookBot::new(Arc::new(FirstCellBot), book_with_center_opening());
+        let game = GameY::new(5);
+        assert_eq!(bot.choose_move(&game), Some(Coordinates::new(2, 1, 1)));
+        assert_eq!(
+            bot.decide(&game, &DecisionOptions::default()),
+            BotDecision::Place { coords: Coordinates::new(2, 1, 1) }
+        );
+    }
+
+    #[test]
+    fn test_book_bot_delegates_out_of_book() {
+        let bot = BookBot::new(Arc::new(FirstCellBot), book_with_center_opening());
+        let mut game = GameY::new(5);
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(0),
+            coords: Coordinates::new(0, 0, 4),
+        })
+        .unwrap();
+        assert_eq!(bot.choose_move(&game), Some(Coordinates::from_index(0, 5)));
+    }
+}
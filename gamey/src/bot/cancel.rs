@@ -0,0 +1,125 @@
+//! Cooperative cancellation of searches.
+//!
+//! Search bots such as [`crate::MctsBot`] and [`crate::MonteCarloBot`] can run
+//! for as long as their configuration asks. A caller with a deadline runs the
+//! bot inside [`with_cancel_token`]; the bots poll [`search_cancelled`]
+//! between iterations and, once it returns true, stop searching and play the
+//! best move found so far. Bots that do not poll it are not interrupted.
+//!
+//! The token is kept per thread, so that the [`crate::YBot`] methods need not
+//! carry it and wrappers such as [`crate::BlunderBot`] pass it on unchanged.
+
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+/// Tells a search to stop, when it is cancelled or its deadline passes.
+///
+/// Clones share the cancellation: cancelling one cancels all of them.
+///
+/// # Example
+/// ```
+/// use gamey::{CancelToken, GameY, MctsBot, MctsConfig, YBot, with_cancel_token};
+/// use std::time::Instant;
+///
+/// let bot = MctsBot::new(MctsConfig { iterations: u32::MAX, ..MctsConfig::default() });
+/// // The deadline has already passed, so the search stops at once.
+/// let token = CancelToken::new().with_deadline(Instant::now());
+/// let chosen = with_cancel_token(&token, || bot.choose_move(&GameY::new(5)));
+/// assert!(chosen.is_some());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    /// Creates a token that is only cancelled by [`CancelToken::cancel`].
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    /// Cancels the token at `deadline` as well and returns it for chaining.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Returns the deadline of the token, if it has one.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Cancels the token and every clone of it.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true once the token is cancelled or its deadline has passed.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) || self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<CancelToken>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with `token` as the cancellation token of the searches on this
+/// thread, restoring the previous token afterwards.
+pub fn with_cancel_token<R>(token: &CancelToken, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<CancelToken>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            CURRENT.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+    let _restore = Restore(CURRENT.with(|current| current.replace(Some(token.clone()))));
+    f()
+}
+
+/// Returns true if the search running on this thread should stop.
+///
+/// Always false outside [`with_cancel_token`].
+pub fn search_cancelled() -> bool {
+    CURRENT.with(|current| current.borrow().as_ref().is_some_and(CancelToken::is_cancelled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cancel_is_shared_by_clones() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+        token.cancel();
+        assert!(clone.is_cancelled());
+    }
+
+    #[test]
+    fn test_deadline_cancels() {
+        assert!(CancelToken::new().with_deadline(Instant::now()).is_cancelled());
+        let later = CancelToken::new().with_deadline(Instant::now() + Duration::from_secs(60));
+        assert!(!later.is_cancelled());
+    }
+
+    #[test]
+    fn test_search_cancelled_follows_the_scope() {
+        assert!(!search_cancelled());
+        let token = CancelToken::new();
+        with_cancel_token(&token, || {
+            assert!(!search_cancelled());
+            token.cancel();
+            assert!(search_cancelled());
+            with_cancel_token(&CancelToken::new(), || assert!(!search_cancelled()));
+            assert!(search_cancelled());
+        });
+        assert!(!search_cancelled());
+    }
+}
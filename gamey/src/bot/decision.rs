@@ -0,0 +1,319 @@
+//! What a bot does on its turn.
+//!
+//! [`YBot::choose_move`] can only place stones. This module provides
+//! [`BotDecision`], returned by [`YBot::decide`], which can also swap (under
+//! the pie rule) or resign, and [`DecisionOptions`], which tells the bot which
+//! of those actions the match allows.
+
+use crate::{Coordinates, DEFAULT_ENDGAME_CELLS, GameAction, GameStatus, GameY, Movement, PlayerId, YBot, solve_endgame};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// The decision of a bot for the player to move.
+///
+/// Unlike [`Movement`] it does not carry a player; use
+/// [`BotDecision::into_movement`] to apply it to a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum BotDecision {
+    /// Place a stone on the given cell.
+    Place {
+        /// The cell to place the stone on.
+        coords: Coordinates,
+    },
+    /// Take over the opponent's first stone (pie rule).
+    Swap,
+    /// Concede the game.
+    Resign,
+}
+
+impl BotDecision {
+    /// Turns the result of [`crate::YBot::choose_move`] into a decision: a
+    /// placement, or a resignation when the bot has no move.
+    pub fn from_choice(choice: Option<Coordinates>) -> Self {
+        match choice {
+            Some(coords) => BotDecision::Place { coords },
+            None => BotDecision::Resign,
+        }
+    }
+
+    /// Returns the cell of a placement, or `None` for an action.
+    pub fn coords(&self) -> Option<Coordinates> {
+        match self {
+            BotDecision::Place { coords } => Some(*coords),
+            BotDecision::Swap | BotDecision::Resign => None,
+        }
+    }
+
+    /// Returns the game action of a swap or a resignation, or `None` for a placement.
+    pub fn action(&self) -> Option<GameAction> {
+        match self {
+            BotDecision::Place { .. } => None,
+            BotDecision::Swap => Some(GameAction::Swap),
+            BotDecision::Resign => Some(GameAction::Resign),
+        }
+    }
+
+    /// Converts the decision into the movement of `player`.
+    pub fn into_movement(self, player: PlayerId) -> Movement {
+        match self {
+            BotDecision::Place { coords } => Movement::Placement { player, coords },
+            BotDecision::Swap => Movement::Action {
+                player,
+                action: GameAction::Swap,
+            },
+            BotDecision::Resign => Movement::Action {
+                player,
+                action: GameAction::Resign,
+            },
+        }
+    }
+}
+
+/// The actions besides placing a stone that a bot may take.
+///
+/// Both are off by default, so a bot asked with the default options only
+/// resigns when it has no move at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct DecisionOptions {
+    /// The pie rule is active: the second player may swap after the first stone.
+    pub pie_rule: bool,
+    /// The bot may resign positions it has lost by force (see [`is_lost`]).
+    pub allow_resign: bool,
+}
+
+impl DecisionOptions {
+    /// Sets whether the pie rule is active.
+    pub fn with_pie_rule(mut self, pie_rule: bool) -> Self {
+        self.pie_rule = pie_rule;
+        self
+    }
+
+    /// Sets whether the bot may resign.
+    pub fn with_resign(mut self, allow_resign: bool) -> Self {
+        self.allow_resign = allow_resign;
+        self
+    }
+}
+
+/// Returns true if the player to move may swap: the pie rule is active and
+/// the first player has placed exactly one stone.
+pub fn swap_available(board: &GameY, options: &DecisionOptions) -> bool {
+    options.pie_rule
+        && board.next_player() == Some(PlayerId::new(1))
+        && board.available_cells().len() as u32 + 1 == board.total_cells()
+}
+
+/// Returns true if the only stone on the board is worth taking over.
+///
+/// When the rest of the board has at most [`DEFAULT_ENDGAME_CELLS`] empty
+/// cells, the opening is strong exactly when [`solve_endgame`] proves a win
+/// for the player who placed it. On larger boards a stone touching a side is
+/// a weak opening and any other stone is strong, the usual pie rule advice
+/// for Y. The resistance evaluation is not used: after a single stone it
+/// rates the corners, which lose, above every other cell.
+pub fn is_strong_opening(board: &GameY) -> bool {
+    let opener = PlayerId::new(0);
+    let Some(stone) = board.cells_for_player(opener).first().copied() else {
+        return false;
+    };
+    if let Some(solution) = solve_endgame(board, DEFAULT_ENDGAME_CELLS) {
+        return solution.winner == opener.id();
+    }
+    !stone.touches_side_a() && !stone.touches_side_b() && !stone.touches_side_c()
+}
+
+/// Returns the empty cells where `player` would win at once by placing a stone.
+pub fn winning_cells(board: &GameY, player: PlayerId) -> Vec<Coordinates> {
+    if board.check_game_over() {
+        return Vec::new();
+    }
+    board
+        .available_cells()
+        .iter()
+        .map(|&cell| Coordinates::from_index(cell, board.board_size()))
+        .filter(|&coords| {
+            let mut simulated_board = board.clone();
+            simulated_board.add_move(Movement::Placement { player, coords }).is_ok()
+                && matches!(simulated_board.status(), GameStatus::Finished { winner } if *winner == player)
+        })
+        .collect()
+}
+
+/// Returns true if the player to move has lost by force.
+///
+/// Positions with at most [`DEFAULT_ENDGAME_CELLS`] empty cells are lost when
+/// [`solve_endgame`] proves a win for the opponent. Larger positions are lost
+/// when the player cannot win with its next stone and the opponent has two or
+/// more winning cells, so blocking one of them still leaves the other.
+pub fn is_lost(board: &GameY) -> bool {
+    let Some(me) = board.next_player() else {
+        return false;
+    };
+    if let Some(solution) = solve_endgame(board, DEFAULT_ENDGAME_CELLS) {
+        return solution.winner != me.id();
+    }
+    let rival = if me.id() == 0 { PlayerId::new(1) } else { PlayerId::new(0) };
+    winning_cells(board, me).is_empty() && winning_cells(board, rival).len() >= 2
+}
+
+/// The swap and resign policy shared by the search bots.
+///
+/// Swaps strong openings (see [`is_strong_opening`]) when the pie rule allows
+/// it and resigns lost positions (see [`is_lost`]) when resigning is allowed.
+/// Returns `None` when the bot should place a stone.
+pub fn search_bot_action(board: &GameY, options: &DecisionOptions) -> Option<BotDecision> {
+    board.next_player()?;
+    if swap_available(board, options) && is_strong_opening(board) {
+        return Some(BotDecision::Swap);
+    }
+    if options.allow_resign && is_lost(board) {
+        return Some(BotDecision::Resign);
+    }
+    None
+}
+
+/// The [`YBot::decide_with_rng`] of the search bots: the action of
+/// [`search_bot_action`] if there is one, otherwise the stone chosen by
+/// [`YBot::choose_move_with_rng`].
+pub fn search_bot_decision<B: YBot + ?Sized>(
+    bot: &B,
+    board: &GameY,
+    options: &DecisionOptions,
+    rng: &mut dyn RngCore,
+) -> BotDecision {
+    search_bot_action(board, options).unwrap_or_else(|| BotDecision::from_choice(bot.choose_move_with_rng(board, rng)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RandomBot;
+
+    fn place(game: &mut GameY, player: u32, coords: Coordinates) {
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(player),
+            coords,
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_decision_from_choice() {
+        let coords = Coordinates::new(1, 0, 0);
+        assert_eq!(BotDecision::from_choice(Some(coords)), BotDecision::Place { coords });
+        assert_eq!(BotDecision::from_choice(None), BotDecision::Resign);
+    }
+
+    #[test]
+    fn test_decision_into_movement() {
+        let player = PlayerId::new(1);
+        assert!(matches!(
+            BotDecision::Swap.into_movement(player),
+            Movement::Action { action: GameAction::Swap, .. }
+        ));
+        assert!(matches!(
+            BotDecision::Resign.into_movement(player),
+            Movement::Action { action: GameAction::Resign, .. }
+        ));
+        assert!(matches!(
+            BotDecision::Place { coords: Coordinates::new(0, 0, 0) }.into_movement(player),
+            Movement::Placement { .. }
+        ));
+    }
+
+    #[test]
+    fn test_decision_serialization() {
+        assert_eq!(serde_json::to_value(BotDecision::Swap).unwrap(), serde_json::json!({"action": "swap"}));
+        assert_eq!(
+            serde_json::to_value(BotDecision::Place { coords: Coordinates::new(1, 0, 0) }).unwrap(),
+            serde_json::json!({"action": "place", "coords": {"x": 1, "y": 0, "z": 0}})
+        );
+    }
+
+    #[test]
+    fn test_swap_available_only_after_first_stone() {
+        let options = DecisionOptions::default().with_pie_rule(true);
+        let mut game = GameY::new(5);
+        assert!(!swap_available(&game, &options));
+        place(&mut game, 0, Coordinates::new(2, 1, 1));
+        assert!(swap_available(&game, &options));
+        assert!(!swap_available(&game, &DecisionOptions::default()));
+        place(&mut game, 1, Coordinates::new(4, 0, 0));
+        assert!(!swap_available(&game, &options));
+    }
+
+    /// Returns true if the first stone at `coords` is a strong opening.
+    fn strong_opening(size: u32, coords: Coordinates) -> bool {
+        let mut game = GameY::new(size);
+        place(&mut game, 0, coords);
+        is_strong_opening(&game)
+    }
+
+    #[test]
+    fn test_is_strong_opening_solves_small_boards() {
+        // The solver proves that only the corners lose on a size 4 board
+        assert!(!strong_opening(4, Coordinates::new(3, 0, 0)));
+        assert!(!strong_opening(4, Coordinates::new(0, 0, 3)));
+        assert!(strong_opening(4, Coordinates::new(2, 1, 0)));
+        assert!(strong_opening(4, Coordinates::new(1, 1, 1)));
+    }
+
+    #[test]
+    fn test_is_strong_opening_swaps_stones_off_the_sides() {
+        for size in [5, 7] {
+            assert!(!strong_opening(size, Coordinates::new(size - 1, 0, 0)));
+            assert!(!strong_opening(size, Coordinates::new(0, 2, size - 3)));
+            assert!(!strong_opening(size, Coordinates::new(size - 2, 1, 0)));
+            assert!(strong_opening(size, Coordinates::new(size - 3, 1, 1)));
+            assert!(strong_opening(size, Coordinates::new(1, 1, size - 3)));
+        }
+        assert!(strong_opening(7, Coordinates::new(2, 2, 2)));
+    }
+
+    /// Player 1 needs one of two cells to win and player 0 is to move.
+    fn double_threat() -> GameY {
+        let mut game = GameY::new(4);
+        place(&mut game, 0, Coordinates::new(2, 1, 0));
+        place(&mut game, 1, Coordinates::new(3, 0, 0));
+        place(&mut game, 0, Coordinates::new(1, 1, 1));
+        place(&mut game, 1, Coordinates::new(2, 0, 1));
+        place(&mut game, 1, Coordinates::new(1, 0, 2));
+        game
+    }
+
+    #[test]
+    fn test_winning_cells() {
+        let game = double_threat();
+        assert!(winning_cells(&game, PlayerId::new(0)).is_empty());
+        assert_eq!(winning_cells(&game, PlayerId::new(1)).len(), 2);
+    }
+
+    #[test]
+    fn test_is_lost() {
+        assert!(!is_lost(&GameY::new(4)));
+        assert!(is_lost(&double_threat()));
+    }
+
+    #[test]
+    fn test_search_bot_action() {
+        let both = DecisionOptions::default().with_pie_rule(true).with_resign(true);
+        let mut game = GameY::new(5);
+        place(&mut game, 0, Coordinates::new(2, 1, 1));
+        assert_eq!(search_bot_action(&game, &both), Some(BotDecision::Swap));
+        assert_eq!(search_bot_action(&game, &DecisionOptions::default()), None);
+
+        assert_eq!(search_bot_action(&double_threat(), &both), Some(BotDecision::Resign));
+        assert_eq!(search_bot_action(&double_threat(), &DecisionOptions::default()), None);
+    }
+
+    #[test]
+    fn test_search_bot_decision() {
+        let resign = DecisionOptions::default().with_resign(true);
+        assert_eq!(search_bot_decision(&RandomBot, &double_threat(), &resign, &mut rand::rng()), BotDecision::Resign);
+        let decision = search_bot_decision(&RandomBot, &double_threat(), &DecisionOptions::default(), &mut rand::rng());
+        assert!(decision.coords().is_some());
+    }
+}
@@ -0,0 +1,222 @@
+//! Feature planes for learned evaluators.
+//!
+//! This module turns a [`GameY`] position into a flat `f32` tensor that a
+//! neural network can read. The triangle is laid out on a `size x size`
+//! square grid, row `r` and column `c` holding the cell at distance `r` from
+//! the top and `c` from side B (coordinates `(size - 1 - r, c, r - c)`); the
+//! cells with `c > r` are padding and are always zero.
+//!
+//! The planes, in order, are:
+//!
+//! | plane | content                                                      |
+//! |-------|--------------------------------------------------------------|
+//! | 0     | stones of the player to move                                 |
+//! | 1     | stones of the opponent                                       |
+//! | 2     | empty cells                                                  |
+//! | 3-5   | cells on side A, B and C                                     |
+//! | 6     | 1 on every cell if player 1 is to move                       |
+//! | 7     | distance map of the player to move                           |
+//! | 8     | distance map of the opponent                                 |
+//!
+//! A distance map holds, for each cell, the empty cells the player needs to
+//! link that cell to the three sides, divided by the board size and capped
+//! at 1 (also used for cells the player can no longer link).
+
+use crate::{Coordinates, GameY, PlayerId};
+use std::collections::VecDeque;
+
+/// Number of planes produced by [`encode_position`].
+pub const FEATURE_PLANES: usize = 9;
+
+/// A position encoded as feature planes, stored plane by plane, row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionEncoding {
+    size: usize,
+    data: Vec<f32>,
+}
+
+impl PositionEncoding {
+    /// Returns the board size.
+    pub fn board_size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the value of a plane at the given row and column.
+    pub fn get(&self, plane: usize, row: usize, col: usize) -> f32 {
+        self.data[feature_index(self.size, plane, row, col)]
+    }
+
+    /// Returns the flat tensor, of length `FEATURE_PLANES * size * size`.
+    pub fn as_slice(&self) -> &[f32] {
+        &self.data
+    }
+
+    /// Consumes the encoding and returns the flat tensor.
+    pub fn into_vec(self) -> Vec<f32> {
+        self.data
+    }
+}
+
+/// Returns the position of a plane value in the flat tensor.
+pub fn feature_index(size: usize, plane: usize, row: usize, col: usize) -> usize {
+    (plane * size + row) * size + col
+}
+
+/// Returns the grid row and column of a cell.
+pub fn grid_position(coords: &Coordinates, board_size: u32) -> (usize, usize) {
+    ((board_size - 1 - coords.x()) as usize, coords.y() as usize)
+}
+
+/// Encodes `board` from the point of view of the player to move (player 0
+/// once the game is over).
+pub fn encode_position(board: &GameY) -> PositionEncoding {
+    let board_size = board.board_size();
+    let size = board_size as usize;
+    let me = board.next_player().unwrap_or(PlayerId::new(0));
+    let rival = PlayerId::new(1 - me.id());
+    let mut data = vec![0.0; FEATURE_PLANES * size * size];
+    let own_distance = distance_map(board, me);
+    let rival_distance = distance_map(board, rival);
+    let cap = board_size.max(1) as f32;
+    for cell in 0..board.total_cells() {
+        let coords = Coordinates::from_index(cell, board_size);
+        let (row, col) = grid_position(&coords, board_size);
+        let mut set = |plane: usize, value: f32| data[feature_index(size, plane, row, col)] = value;
+        match board.player_at(&coords) {
+            Some(player) if player == me => set(0, 1.0),
+            Some(_) => set(1, 1.0),
+            None => set(2, 1.0),
+        }
+        set(3, coords.touches_side_a() as u8 as f32);
+        set(4, coords.touches_side_b() as u8 as f32);
+        set(5, coords.touches_side_c() as u8 as f32);
+        set(6, me.id() as f32);
+        let distance = |d: Option<u32>| d.map_or(1.0, |d| (d as f32 / cap).min(1.0));
+        set(7, distance(own_distance[cell as usize]));
+        set(8, distance(rival_distance[cell as usize]));
+    }
+    PositionEncoding { size, data }
+}
+
+/// Returns, for each cell, the empty cells `player` needs to link it to the
+/// three sides (the sum of its distances to each side), or `None` if some
+/// side cannot be reached any more.
+pub fn distance_map(board: &GameY, player: PlayerId) -> Vec<Option<u32>> {
+    let size = board.board_size();
+    let total = board.total_cells() as usize;
+    let coords: Vec<Coordinates> = (0..total as u32).map(|i| Coordinates::from_index(i, size)).collect();
+    // Entering a cell costs 0 for own stones and 1 for empty cells.
+    let cost: Vec<Option<u32>> = coords
+        .iter()
+        .map(|c| match board.player_at(c) {
+            None => Some(1),
+            Some(owner) if owner == player => Some(0),
+            Some(_) => None,
+        })
+        .collect();
+    let sides: [fn(&Coordinates) -> bool; 3] = [
+        Coordinates::touches_side_a,
+        Coordinates::touches_side_b,
+        Coordinates::touches_side_c,
+    ];
+    let mut total_distance = vec![Some(0u32); total];
+    for touches in sides {
+        // 0-1 BFS from the cells of the side.
+        let mut distance: Vec<Option<u32>> = vec![None; total];
+        let mut queue = VecDeque::new();
+        for (cell, c) in coords.iter().enumerate() {
+            if touches(c) && let Some(cost) = cost[cell] {
+                distance[cell] = Some(cost);
+                if cost == 0 { queue.push_front(cell) } else { queue.push_back(cell) }
+            }
+        }
+        while let Some(cell) = queue.pop_front() {
+            let current = distance[cell].expect("queued cells have a distance");
+            for neighbor in board.get_neighbors(&coords[cell]) {
+                let n = neighbor.to_index(size) as usize;
+                let Some(step) = cost[n] else { continue };
+                if distance[n].is_none_or(|d| current + step < d) {
+                    distance[n] = Some(current + step);
+                    if step == 0 { queue.push_front(n) } else { queue.push_back(n) }
+                }
+            }
+        }
+        for (sum, d) in total_distance.iter_mut().zip(distance) {
+            *sum = sum.zip(d).map(|(a, b)| a + b);
+        }
+    }
+    // A cell was counted once per side it needs; count it only once.
+    total_distance
+        .iter()
+        .zip(&cost)
+        .map(|(d, cost)| d.zip(*cost).map(|(d, c)| d - 2 * c))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Movement;
+
+    fn place(game: &mut GameY, player: u32, coords: Coordinates) {
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(player),
+            coords,
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_encoding_shape_and_padding() {
+        let encoding = encode_position(&GameY::new(4));
+        assert_eq!(encoding.board_size(), 4);
+        assert_eq!(encoding.as_slice().len(), FEATURE_PLANES * 16);
+        // (row 0, col 1) is outside the triangle.
+        assert!((0..FEATURE_PLANES).all(|plane| encoding.get(plane, 0, 1) == 0.0));
+        // Every cell of the empty board is empty.
+        let empty: f32 = (0..4).flat_map(|r| (0..=r).map(move |c| (r, c))).map(|(r, c)| encoding.get(2, r, c)).sum();
+        assert_eq!(empty, 10.0);
+    }
+
+    #[test]
+    fn test_encoding_is_relative_to_the_player_to_move() {
+        let mut game = GameY::new(4);
+        let stone = Coordinates::new(1, 1, 1);
+        place(&mut game, 0, stone);
+        let (row, col) = grid_position(&stone, 4);
+        assert_eq!((row, col), (2, 1));
+        let encoding = encode_position(&game);
+        // Player 1 is to move, so player 0's stone is on the opponent plane.
+        assert_eq!(encoding.get(0, row, col), 0.0);
+        assert_eq!(encoding.get(1, row, col), 1.0);
+        assert_eq!(encoding.get(2, row, col), 0.0);
+        assert_eq!(encoding.get(6, 0, 0), 1.0);
+    }
+
+    #[test]
+    fn test_encoding_sides() {
+        let encoding = encode_position(&GameY::new(3));
+        // The top cell touches sides B and C; the bottom row is side A.
+        assert_eq!((encoding.get(3, 0, 0), encoding.get(4, 0, 0), encoding.get(5, 0, 0)), (0.0, 1.0, 1.0));
+        assert_eq!(encoding.get(3, 2, 1), 1.0);
+    }
+
+    #[test]
+    fn test_distance_map() {
+        let game = GameY::new(3);
+        let distances = distance_map(&game, PlayerId::new(0));
+        // The middle of the bottom row needs itself and two more cells.
+        let bottom_middle = Coordinates::new(0, 1, 1).to_index(3) as usize;
+        assert_eq!(distances[bottom_middle], Some(3));
+        // The top cell touches B and C and needs two more cells to reach A.
+        assert_eq!(distances[0], Some(3));
+
+        let mut game = GameY::new(3);
+        place(&mut game, 0, Coordinates::new(1, 0, 1));
+        let distances = distance_map(&game, PlayerId::new(0));
+        // The stone touches side B and needs one cell towards A and one towards C.
+        assert_eq!(distances[Coordinates::new(1, 0, 1).to_index(3) as usize], Some(2));
+        let rival = distance_map(&game, PlayerId::new(1));
+        assert_eq!(rival[Coordinates::new(1, 0, 1).to_index(3) as usize], None);
+    }
+}
@@ -0,0 +1,515 @@
+//! Exact endgame solver.
+//!
+//! Y has no draws, so once few cells are left every position can be solved:
+//! one of the players can force a win, and the solver finds it together with
+//! the number of stones it takes. This module provides [`solve_endgame`], a
+//! depth-first search over a small board with a union-find that is updated on
+//! every move and undone on the way back, and [`endgame_move`], which the
+//! search bots use to play solved positions perfectly.
+//!
+//! The search takes exponential time in the number of empty cells, so it is
+//! capped at [`MAX_SOLVER_CELLS`] and stops early when the search is
+//! cancelled (see [`crate::search_cancelled`]).
+
+use crate::{Coordinates, GameY, GameYError, search_cancelled};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use std::collections::HashMap;
+
+/// Empty cells below which the search bots switch to the solver by default.
+pub const DEFAULT_ENDGAME_CELLS: u32 = 10;
+
+/// Largest number of empty cells the solver is used for, by the solve
+/// endpoint and by the `endgame_cells` setting of the search bots.
+pub const MAX_SOLVER_CELLS: u32 = 12;
+
+/// Nodes searched between two polls of [`search_cancelled`].
+const CANCEL_POLL_NODES: u64 = 256;
+
+/// The proven outcome of a position.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub struct EndgameSolution {
+    /// The player that wins with perfect play.
+    pub winner: u32,
+    /// Stones the winner still has to place, counting the winning stone.
+    pub moves_to_win: u32,
+    /// Moves of both players until the winning stone, counting it.
+    pub plies: u32,
+    /// The best move for the player to move: the fastest win, or the move
+    /// that delays the loss the longest.
+    pub best_move: Option<Coordinates>,
+}
+
+impl EndgameSolution {
+    /// Returns the solution as a sentence, e.g. `"win in 2 for player 1"`.
+    pub fn summary(&self) -> String {
+        format!("win in {} for player {}", self.moves_to_win, self.winner)
+    }
+}
+
+/// Solves the position if it has at most `max_empty_cells` empty cells.
+///
+/// Returns `None` when the game is over, too many cells are empty to solve
+/// or the search is cancelled before it finishes.
+///
+/// # Example
+///
+/// ```
+/// use gamey::{GameY, solve_endgame};
+///
+/// // The first stone on a board of size 1 wins.
+/// let solution = solve_endgame(&GameY::new(1), 10).unwrap();
+/// assert_eq!(solution.winner, 0);
+/// assert_eq!(solution.summary(), "win in 1 for player 0");
+/// ```
+pub fn solve_endgame(board: &GameY, max_empty_cells: u32) -> Option<EndgameSolution> {
+    let me = board.next_player()?;
+    if board.check_game_over() || board.available_cells().len() as u32 > max_empty_cells {
+        return None;
+    }
+    let mut solver = Solver::new(board);
+    let (value, best) = solver.search_root(me.id() as u8);
+    if solver.cancelled {
+        return None;
+    }
+    let plies = value.unsigned_abs();
+    let winner = if value > 0 { me.id() } else { 1 - me.id() };
+    Some(EndgameSolution {
+        winner,
+        moves_to_win: plies.div_ceil(2),
+        plies,
+        best_move: best.map(|cell| Coordinates::from_index(cell as u32, board.board_size())),
+    })
+}
+
+/// Returns the best move of a solved position, or `None` when the position
+/// has more than `max_empty_cells` empty cells (or the game is over).
+pub fn endgame_move(board: &GameY, max_empty_cells: u32) -> Option<Coordinates> {
+    solve_endgame(board, max_empty_cells)?.best_move
+}
+
+/// Checks the `endgame_cells` setting of a search bot.
+///
+/// # Errors
+/// [`GameYError::InvalidBotParams`] when it is above [`MAX_SOLVER_CELLS`].
+pub(crate) fn check_endgame_cells(bot_id: &str, endgame_cells: u32) -> Result<(), GameYError> {
+    if endgame_cells > MAX_SOLVER_CELLS {
+        return Err(GameYError::InvalidBotParams {
+            bot_id: bot_id.to_string(),
+            message: format!("endgame_cells must be at most {}", MAX_SOLVER_CELLS),
+        });
+    }
+    Ok(())
+}
+
+/// The moves worth searching in a position.
+enum Candidates {
+    /// The player to move wins by playing this cell.
+    Win(usize),
+    /// The rival has two winning cells and the player to move can block only one.
+    Lost(usize),
+    /// The cells to try: the only rival winning cell, or every empty cell.
+    Moves(Vec<usize>),
+}
+
+/// A board with a union-find that supports undoing moves.
+///
+/// Roots keep the sides touched by their group as a bit mask; the union is by
+/// rank and without path compression, so undoing a union only has to restore
+/// the parent, rank and mask it changed.
+struct Solver {
+    neighbors: Vec<Vec<usize>>,
+    sides: Vec<u8>,
+    owner: Vec<Option<u8>>,
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    mask: Vec<u8>,
+    /// Unions to undo: (absorbed root, surviving root, old rank, old mask).
+    unions: Vec<(usize, usize, u8, u8)>,
+    /// Per move: the cell and the number of unions before it.
+    moves: Vec<(usize, usize)>,
+    zobrist: Vec<[u64; 2]>,
+    hash: u64,
+    /// Solved positions, keyed by hash and player to move.
+    memo: HashMap<(u64, u8), i32>,
+    /// Nodes searched so far.
+    nodes: u64,
+    /// The search was cancelled; the values found since are meaningless.
+    cancelled: bool,
+}
+
+const ALL_SIDES: u8 = 0b111;
+
+impl Solver {
+    fn new(board: &GameY) -> Self {
+        let size = board.board_size();
+        let total = board.total_cells() as usize;
+        let coords: Vec<Coordinates> = (0..total as u32).map(|i| Coordinates::from_index(i, size)).collect();
+        let neighbors = coords
+            .iter()
+            .map(|c| board.get_neighbors(c).iter().map(|n| n.to_index(size) as usize).collect())
+            .collect();
+        let sides: Vec<u8> = coords
+            .iter()
+            .map(|c| c.touches_side_a() as u8 | (c.touches_side_b() as u8) << 1 | (c.touches_side_c() as u8) << 2)
+            .collect();
+        let mut seed = 0x9e37_79b9_7f4a_7c15_u64;
+        let zobrist = (0..total).map(|_| [splitmix64(&mut seed), splitmix64(&mut seed)]).collect();
+        let mut solver = Solver {
+            neighbors,
+            mask: sides.clone(),
+            sides,
+            owner: vec![None; total],
+            parent: (0..total).collect(),
+            rank: vec![0; total],
+            unions: Vec::new(),
+            moves: Vec::new(),
+            zobrist,
+            hash: 0,
+            memo: HashMap::new(),
+            nodes: 0,
+            cancelled: false,
+        };
+        for (cell, c) in coords.iter().enumerate() {
+            if let Some(player) = board.player_at(c) {
+                solver.place(cell, player.id() as u8);
+            }
+        }
+        solver.moves.clear();
+        solver.unions.clear();
+        solver
+    }
+
+    fn find(&self, mut cell: usize) -> usize {
+        while self.parent[cell] != cell {
+            cell = self.parent[cell];
+        }
+        cell
+    }
+
+    /// Returns true if `player` would connect the three sides by playing `cell`.
+    fn wins_at(&self, cell: usize, player: u8) -> bool {
+        let mut mask = self.sides[cell];
+        for &n in &self.neighbors[cell] {
+            if self.owner[n] == Some(player) {
+                mask |= self.mask[self.find(n)];
+            }
+        }
+        mask == ALL_SIDES
+    }
+
+    fn place(&mut self, cell: usize, player: u8) {
+        self.moves.push((cell, self.unions.len()));
+        self.owner[cell] = Some(player);
+        self.hash ^= self.zobrist[cell][player as usize];
+        self.parent[cell] = cell;
+        self.rank[cell] = 0;
+        self.mask[cell] = self.sides[cell];
+        for i in 0..self.neighbors[cell].len() {
+            let n = self.neighbors[cell][i];
+            if self.owner[n] != Some(player) {
+                continue;
+            }
+            let (a, b) = (self.find(cell), self.find(n));
+            if a == b {
+                continue;
+            }
+            let (child, root) = if self.rank[a] > self.rank[b] { (b, a) } else { (a, b) };
+            self.unions.push((child, root, self.rank[root], self.mask[root]));
+            self.parent[child] = root;
+            self.mask[root] |= self.mask[child];
+            if self.rank[child] == self.rank[root] {
+                self.rank[root] += 1;
+            }
+        }
+    }
+
+    fn undo(&mut self) {
+        let Some((cell, unions)) = self.moves.pop() else {
+            return;
+        };
+        while self.unions.len() > unions {
+            let (child, root, rank, mask) = self.unions.pop().expect("union recorded by place");
+            self.parent[child] = child;
+            self.rank[root] = rank;
+            self.mask[root] = mask;
+        }
+        if let Some(player) = self.owner[cell].take() {
+            self.hash ^= self.zobrist[cell][player as usize];
+        }
+    }
+
+    /// Counts a node and returns true if the search is cancelled, polling
+    /// [`search_cancelled`] every [`CANCEL_POLL_NODES`] nodes.
+    fn poll_cancelled(&mut self) -> bool {
+        self.nodes += 1;
+        if !self.cancelled && self.nodes.is_multiple_of(CANCEL_POLL_NODES) {
+            self.cancelled = search_cancelled();
+        }
+        self.cancelled
+    }
+
+    fn candidates(&self, me: u8) -> Candidates {
+        let empty: Vec<usize> = (0..self.owner.len()).filter(|&c| self.owner[c].is_none()).collect();
+        if let Some(&cell) = empty.iter().find(|&&c| self.wins_at(c, me)) {
+            return Candidates::Win(cell);
+        }
+        let threats: Vec<usize> = empty.iter().copied().filter(|&c| self.wins_at(c, 1 - me)).collect();
+        match threats.len() {
+            0 => Candidates::Moves(empty),
+            1 => Candidates::Moves(threats),
+            _ => Candidates::Lost(threats[0]),
+        }
+    }
+
+    /// Returns the value of the position for `me` and the move that reaches
+    /// it: `+n` wins with the n-th ply from here, `-n` loses to the n-th ply.
+    fn search_root(&mut self, me: u8) -> (i32, Option<usize>) {
+        match self.candidates(me) {
+            Candidates::Win(cell) => (1, Some(cell)),
+            Candidates::Lost(cell) => (-2, Some(cell)),
+            Candidates::Moves(cells) => {
+                let mut best = (i32::MIN, None);
+                for cell in cells {
+                    self.place(cell, me);
+                    let value = parent_value(self.value(1 - me));
+                    self.undo();
+                    if self.cancelled {
+                        break;
+                    }
+                    if best.1.is_none() || rank(value) > rank(best.0) {
+                        best = (value, Some(cell));
+                    }
+                }
+                best
+            }
+        }
+    }
+
+    fn value(&mut self, me: u8) -> i32 {
+        if let Some(&value) = self.memo.get(&(self.hash, me)) {
+            return value;
+        }
+        if self.poll_cancelled() {
+            return 0;
+        }
+        let value = match self.candidates(me) {
+            Candidates::Win(_) => 1,
+            Candidates::Lost(_) => -2,
+            Candidates::Moves(cells) => {
+                let mut best = i32::MIN;
+                for cell in cells {
+                    self.place(cell, me);
+                    let value = parent_value(self.value(1 - me));
+                    self.undo();
+                    if self.cancelled {
+                        // Unfinished values must not reach the memo.
+                        return 0;
+                    }
+                    if best == i32::MIN || rank(value) > rank(best) {
+                        best = value;
+                    }
+                    // A win on the next own stone was already ruled out.
+                    if best == 3 {
+                        break;
+                    }
+                }
+                best
+            }
+        };
+        self.memo.insert((self.hash, me), value);
+        value
+    }
+}
+
+/// Converts the value of a position for the rival into the value of the
+/// move that led to it for the player who made it.
+fn parent_value(child: i32) -> i32 {
+    if child > 0 { -(child + 1) } else { -child + 1 }
+}
+
+/// Orders values from the player's point of view: quick wins first, then
+/// slow wins, then slow losses, then quick losses.
+fn rank(value: i32) -> i32 {
+    if value > 0 { i32::MAX / 2 - value } else { i32::MIN / 2 - value }
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameStatus, Movement, PlayerId};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+
+    fn place(game: &mut GameY, player: u32, coords: Coordinates) {
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(player),
+            coords,
+        })
+        .unwrap();
+    }
+
+    /// Plain minimax over [`GameY`], with the same values as the solver.
+    fn brute_force(game: &GameY) -> i32 {
+        let me = game.next_player().unwrap();
+        let mut best = i32::MIN;
+        for &cell in game.available_cells() {
+            let mut child = game.clone();
+            place(&mut child, me.id(), Coordinates::from_index(cell, game.board_size()));
+            let value = match child.status() {
+                GameStatus::Finished { winner } if *winner == me => 1,
+                GameStatus::Finished { .. } => unreachable!("the rival cannot win on our move"),
+                GameStatus::Ongoing { .. } => parent_value(brute_force(&child)),
+            };
+            if best == i32::MIN || rank(value) > rank(best) {
+                best = value;
+            }
+        }
+        best
+    }
+
+    fn signed_plies(solution: &EndgameSolution, game: &GameY) -> i32 {
+        if solution.winner == game.next_player().unwrap().id() {
+            solution.plies as i32
+        } else {
+            -(solution.plies as i32)
+        }
+    }
+
+    /// Player 1 needs one of two cells to win and player 0 is to move.
+    fn double_threat() -> GameY {
+        let mut game = GameY::new(4);
+        place(&mut game, 0, Coordinates::new(2, 1, 0));
+        place(&mut game, 1, Coordinates::new(3, 0, 0));
+        place(&mut game, 0, Coordinates::new(1, 1, 1));
+        place(&mut game, 1, Coordinates::new(2, 0, 1));
+        place(&mut game, 1, Coordinates::new(1, 0, 2));
+        game
+    }
+
+    #[test]
+    fn test_solve_respects_the_threshold() {
+        let game = GameY::new(4);
+        assert!(solve_endgame(&game, 9).is_none());
+        assert!(solve_endgame(&game, 10).is_some());
+    }
+
+    #[test]
+    fn test_cancelled_search_is_not_solved() {
+        let token = crate::CancelToken::new();
+        token.cancel();
+        let game = GameY::new(4);
+        assert!(crate::with_cancel_token(&token, || solve_endgame(&game, 10)).is_none());
+        assert!(solve_endgame(&game, 10).is_some());
+    }
+
+    #[test]
+    fn test_check_endgame_cells() {
+        assert!(check_endgame_cells("mcts_bot", MAX_SOLVER_CELLS).is_ok());
+        assert!(matches!(
+            check_endgame_cells("mcts_bot", MAX_SOLVER_CELLS + 1),
+            Err(GameYError::InvalidBotParams { .. })
+        ));
+    }
+
+    #[test]
+    fn test_solve_finished_game_is_none() {
+        let mut game = GameY::new(1);
+        place(&mut game, 0, Coordinates::new(0, 0, 0));
+        assert!(solve_endgame(&game, 10).is_none());
+    }
+
+    #[test]
+    fn test_solve_double_threat_is_lost_in_one() {
+        let solution = solve_endgame(&double_threat(), 10).unwrap();
+        assert_eq!(solution.winner, 1);
+        assert_eq!(solution.moves_to_win, 1);
+        assert_eq!(solution.plies, 2);
+        assert_eq!(solution.summary(), "win in 1 for player 1");
+    }
+
+    #[test]
+    fn test_solve_finds_the_winning_stone() {
+        let mut game = double_threat();
+        place(&mut game, 0, Coordinates::new(0, 3, 0));
+        let solution = solve_endgame(&game, 10).unwrap();
+        assert_eq!(solution.winner, 1);
+        assert_eq!(solution.plies, 1);
+        let mut finished = game.clone();
+        place(&mut finished, 1, solution.best_move.unwrap());
+        assert!(matches!(finished.status(), GameStatus::Finished { winner } if winner.id() == 1));
+    }
+
+    #[test]
+    fn test_first_player_wins_small_boards() {
+        for size in 1..=4 {
+            let solution = solve_endgame(&GameY::new(size), 10).unwrap();
+            assert_eq!(solution.winner, 0, "size {size}");
+        }
+    }
+
+    #[test]
+    fn test_solver_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..40 {
+            let mut game = GameY::new(4);
+            let mut cells = game.available_cells().clone();
+            cells.shuffle(&mut rng);
+            for &cell in cells.iter().take(3) {
+                let player = game.next_player().unwrap().id();
+                place(&mut game, player, Coordinates::from_index(cell, 4));
+            }
+            if game.check_game_over() {
+                continue;
+            }
+            let solution = solve_endgame(&game, 10).unwrap();
+            assert_eq!(signed_plies(&solution, &game), brute_force(&game));
+        }
+    }
+
+    #[test]
+    fn test_endgame_move_plays_the_solution() {
+        let game = double_threat();
+        let coords = endgame_move(&game, 10).unwrap();
+        assert!(crate::winning_cells(&game, PlayerId::new(1)).contains(&coords));
+        assert!(endgame_move(&GameY::new(5), 10).is_none());
+    }
+
+    #[test]
+    fn test_search_bots_play_solved_moves() {
+        use crate::{MonteCarloBot, MonteCarloConfig, ResistanceBot, ShortestPathBot, YBot};
+        let monte_carlo = MonteCarloBot::new(MonteCarloConfig { simulations: 1, ..Default::default() });
+        let bots: [&dyn YBot; 3] = [&monte_carlo, &ShortestPathBot::default(), &ResistanceBot];
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..10 {
+            let mut game = GameY::new(4);
+            let mut cells = game.available_cells().clone();
+            cells.shuffle(&mut rng);
+            for &cell in cells.iter().take(2) {
+                let player = game.next_player().unwrap().id();
+                place(&mut game, player, Coordinates::from_index(cell, 4));
+            }
+            let expected = solve_endgame(&game, 10).unwrap();
+            for bot in bots {
+                let coords = bot.choose_move_with_rng(&game, &mut rng).unwrap();
+                let mut after = game.clone();
+                place(&mut after, game.next_player().unwrap().id(), coords);
+                let reached = match after.status() {
+                    GameStatus::Finished { .. } => 1,
+                    GameStatus::Ongoing { .. } => parent_value(brute_force(&after)),
+                };
+                assert_eq!(reached, signed_plies(&expected, &game), "{}", bot.name());
+            }
+        }
+    }
+}
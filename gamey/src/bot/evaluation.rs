@@ -0,0 +1,389 @@
+//! Electrical-resistance (Shannon) evaluation of Y positions.
+//!
+//! The board is modelled as a resistor network for each player: every cell is a
+//! resistor whose value depends on who owns it, adjacent cells are wired together
+//! and the cells on a side of the triangle are wired to a terminal representing
+//! that side. The easier it is for current to flow between two sides, the closer
+//! the player is to connecting them.
+//!
+//! For Y a player has to connect all three sides, so the score of a player is the
+//! sum of the resistances between each pair of sides (A-B, B-C and C-A). Lower is
+//! better; a player who has already won has a score close to zero and a player who
+//! can no longer connect two of the sides has an infinite score.
+
+use crate::{Coordinates, GameY, PlayerId};
+
+/// Resistance of an empty cell.
+const EMPTY_RESISTANCE: f64 = 1.0;
+
+/// Resistance of a cell occupied by the player being evaluated.
+///
+/// It is not zero so that the linear system stays well conditioned, but it is
+/// small enough for chains of stones to behave almost like a single node.
+const OWN_STONE_RESISTANCE: f64 = 0.01;
+
+/// Relative tolerance used to stop the conjugate gradient solver.
+const SOLVER_TOLERANCE: f64 = 1e-10;
+
+/// One of the three sides of the triangular board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// The side where `x == 0`.
+    A,
+    /// The side where `y == 0`.
+    B,
+    /// The side where `z == 0`.
+    C,
+}
+
+impl Side {
+    /// Returns true if the given cell lies on this side.
+    pub fn touches(&self, coords: &Coordinates) -> bool {
+        match self {
+            Side::A => coords.touches_side_a(),
+            Side::B => coords.touches_side_b(),
+            Side::C => coords.touches_side_c(),
+        }
+    }
+}
+
+/// Resistance and score of both players for a single position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResistanceEvaluation {
+    /// Sum of the pairwise side resistances of the player the evaluation was
+    /// computed for (the `player` of [`evaluate_resistance`]).
+    pub own: f64,
+    /// Sum of the pairwise side resistances of the opponent.
+    pub rival: f64,
+}
+
+impl ResistanceEvaluation {
+    /// Returns `ln(rival / own)`.
+    ///
+    /// Positive values favour the player the evaluation was computed for. An
+    /// infinite rival resistance (or a zero own resistance) yields
+    /// `f64::INFINITY` and the opposite yields `f64::NEG_INFINITY`.
+    pub fn score(&self) -> f64 {
+        match (self.own.is_infinite(), self.rival.is_infinite()) {
+            (true, true) => 0.0,
+            (true, false) => f64::NEG_INFINITY,
+            (false, true) => f64::INFINITY,
+            (false, false) => (self.rival / self.own).ln(),
+        }
+    }
+}
+
+/// Evaluates the position from the point of view of `player`.
+///
+/// # Example
+///
+/// ```
+/// use gamey::{GameY, PlayerId, evaluate_resistance};
+///
+/// let game = GameY::new(5);
+/// let eval = evaluate_resistance(&game, PlayerId::new(0));
+///
+/// // On an empty board both players are in the same situation
+/// assert!(eval.score().abs() < 1e-6);
+/// ```
+pub fn evaluate_resistance(board: &GameY, player: PlayerId) -> ResistanceEvaluation {
+    let rival = if player.id() == 0 { PlayerId::new(1) } else { PlayerId::new(0) };
+    ResistanceEvaluation {
+        own: total_resistance(board, player),
+        rival: total_resistance(board, rival),
+    }
+}
+
+/// Returns the sum of the resistances between each pair of sides for `player`.
+pub fn total_resistance(board: &GameY, player: PlayerId) -> f64 {
+    let network = Network::build(board, player);
+    network.resistance(Side::A, Side::B)
+        + network.resistance(Side::B, Side::C)
+        + network.resistance(Side::C, Side::A)
+}
+
+/// Returns the resistance between two sides of the board for `player`.
+///
+/// Cells owned by the opponent are removed from the network, so the result is
+/// `f64::INFINITY` when the opponent has cut the two sides apart.
+pub fn side_resistance(board: &GameY, player: PlayerId, from: Side, to: Side) -> f64 {
+    Network::build(board, player).resistance(from, to)
+}
+
+/// Resistor network of a board seen from one player.
+///
+/// Nodes are the cell indices of the board; cells owned by the opponent have no
+/// resistance value and are left out of the network.
+struct Network {
+    coords: Vec<Coordinates>,
+    resistances: Vec<Option<f64>>,
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl Network {
+    fn build(board: &GameY, player: PlayerId) -> Self {
+        let size = board.board_size();
+        let total = board.total_cells();
+        let coords: Vec<Coordinates> = (0..total).map(|idx| Coordinates::from_index(idx, size)).collect();
+        let resistances = coords
+            .iter()
+            .map(|c| match board.player_at(c) {
+                None => Some(EMPTY_RESISTANCE),
+                Some(p) if p == player => Some(OWN_STONE_RESISTANCE),
+                Some(_) => None,
+            })
+            .collect();
+        let neighbors = coords
+            .iter()
+            .map(|c| {
+                board
+                    .get_neighbors(c)
+                    .iter()
+                    .map(|n| n.to_index(size) as usize)
+                    .collect()
+            })
+            .collect();
+        Network {
+            coords,
+            resistances,
+            neighbors,
+        }
+    }
+
+    /// Conductance between two adjacent cells, both present in the network.
+    fn conductance(&self, i: usize, j: usize) -> f64 {
+        match (self.resistances[i], self.resistances[j]) {
+            (Some(ri), Some(rj)) => 1.0 / (ri + rj),
+            _ => 0.0,
+        }
+    }
+
+    /// Conductance between a cell and the terminal of a side it touches.
+    fn terminal_conductance(&self, i: usize, side: Side) -> f64 {
+        match self.resistances[i] {
+            Some(r) if side.touches(&self.coords[i]) => 1.0 / r,
+            _ => 0.0,
+        }
+    }
+
+    /// Computes the equivalent resistance between the terminals of two sides.
+    ///
+    /// The `from` terminal is held at potential 1 and the `to` terminal at 0.
+    /// The potentials of the cells are found by solving the reduced Laplacian
+    /// with conjugate gradient, and the resistance is the inverse of the
+    /// current leaving the `from` terminal.
+    fn resistance(&self, from: Side, to: Side) -> f64 {
+        let active = self.reachable_from(from);
+        let n = self.coords.len();
+        if !(0..n).any(|i| active[i] && self.terminal_conductance(i, to) > 0.0) {
+            return f64::INFINITY;
+        }
+
+        // Diagonal of the Laplacian and right-hand side (current injected by the
+        // terminal at potential 1).
+        let mut diagonal = vec![0.0; n];
+        let mut rhs = vec![0.0; n];
+        for i in (0..n).filter(|&i| active[i]) {
+            let g_from = self.terminal_conductance(i, from);
+            let g_to = self.terminal_conductance(i, to);
+            diagonal[i] = g_from
+                + g_to
+                + self.neighbors[i]
+                    .iter()
+                    .map(|&j| self.conductance(i, j))
+                    .sum::<f64>();
+            rhs[i] = g_from;
+        }
+
+        let potentials = self.solve(&active, &diagonal, &rhs);
+
+        let current: f64 = (0..n)
+            .filter(|&i| active[i])
+            .map(|i| self.terminal_conductance(i, from) * (1.0 - potentials[i]))
+            .sum();
+
+        1.0 / current
+    }
+
+    /// Marks the cells connected to the terminal of `side`.
+    ///
+    /// Cells in components isolated from that terminal carry no current and
+    /// would make the system singular, so they are left out of the solve.
+    fn reachable_from(&self, side: Side) -> Vec<bool> {
+        let n = self.coords.len();
+        let mut active = vec![false; n];
+        let mut stack: Vec<usize> = (0..n)
+            .filter(|&i| self.terminal_conductance(i, side) > 0.0)
+            .collect();
+        for &i in &stack {
+            active[i] = true;
+        }
+        while let Some(i) = stack.pop() {
+            for &j in &self.neighbors[i] {
+                if !active[j] && self.resistances[j].is_some() {
+                    active[j] = true;
+                    stack.push(j);
+                }
+            }
+        }
+        active
+    }
+
+    /// Multiplies the reduced Laplacian by `v`.
+    fn apply(&self, active: &[bool], diagonal: &[f64], v: &[f64], out: &mut [f64]) {
+        for i in 0..v.len() {
+            if !active[i] {
+                out[i] = 0.0;
+                continue;
+            }
+            let off: f64 = self.neighbors[i]
+                .iter()
+                .filter(|&&j| active[j])
+                .map(|&j| self.conductance(i, j) * v[j])
+                .sum();
+            out[i] = diagonal[i] * v[i] - off;
+        }
+    }
+
+    /// Solves `L x = b` for the active cells with Jacobi-preconditioned conjugate gradient.
+    fn solve(&self, active: &[bool], diagonal: &[f64], rhs: &[f64]) -> Vec<f64> {
+        let n = rhs.len();
+        let mut x = vec![0.0; n];
+        let mut r = rhs.to_vec();
+        let precondition = |r: &[f64]| -> Vec<f64> {
+            (0..n)
+                .map(|i| if active[i] && diagonal[i] > 0.0 { r[i] / diagonal[i] } else { 0.0 })
+                .collect()
+        };
+        let mut z = precondition(&r);
+        let mut p = z.clone();
+        let mut rz: f64 = dot(&r, &z);
+        let rhs_norm = dot(rhs, rhs).sqrt();
+        if rhs_norm == 0.0 {
+            return x;
+        }
+        let mut lp = vec![0.0; n];
+
+        for _ in 0..(4 * n).max(16) {
+            self.apply(active, diagonal, &p, &mut lp);
+            let p_lp = dot(&p, &lp);
+            if p_lp <= 0.0 {
+                break;
+            }
+            let alpha = rz / p_lp;
+            for i in 0..n {
+                x[i] += alpha * p[i];
+                r[i] -= alpha * lp[i];
+            }
+            if dot(&r, &r).sqrt() <= SOLVER_TOLERANCE * rhs_norm {
+                break;
+            }
+            z = precondition(&r);
+            let rz_next = dot(&r, &z);
+            let beta = rz_next / rz;
+            rz = rz_next;
+            for i in 0..n {
+                p[i] = z[i] + beta * p[i];
+            }
+        }
+        x
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Movement;
+
+    fn place(game: &mut GameY, player: u32, coords: Coordinates) {
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(player),
+            coords,
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_empty_board_is_symmetric() {
+        let game = GameY::new(5);
+        let eval = evaluate_resistance(&game, PlayerId::new(0));
+        assert!((eval.own - eval.rival).abs() < 1e-9);
+        assert!(eval.score().abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_side_resistances_are_symmetric() {
+        let game = GameY::new(4);
+        let player = PlayerId::new(0);
+        let ab = side_resistance(&game, player, Side::A, Side::B);
+        let ba = side_resistance(&game, player, Side::B, Side::A);
+        let bc = side_resistance(&game, player, Side::B, Side::C);
+        assert!((ab - ba).abs() < 1e-9);
+        assert!((ab - bc).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_single_cell_board() {
+        // The only cell touches all sides: from terminal, through the cell, to terminal.
+        let game = GameY::new(1);
+        let r = side_resistance(&game, PlayerId::new(0), Side::A, Side::B);
+        assert!((r - 2.0 * EMPTY_RESISTANCE).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_own_stone_lowers_resistance() {
+        let mut game = GameY::new(5);
+        let before = total_resistance(&game, PlayerId::new(0));
+        place(&mut game, 0, Coordinates::new(2, 1, 1));
+        let after = total_resistance(&game, PlayerId::new(0));
+        assert!(after < before);
+        assert!(evaluate_resistance(&game, PlayerId::new(0)).score() > 0.0);
+    }
+
+    #[test]
+    fn test_rival_stone_raises_resistance() {
+        let mut game = GameY::new(5);
+        let before = total_resistance(&game, PlayerId::new(0));
+        place(&mut game, 1, Coordinates::new(2, 1, 1));
+        let after = total_resistance(&game, PlayerId::new(0));
+        assert!(after > before);
+    }
+
+    #[test]
+    fn test_cut_sides_give_infinite_resistance() {
+        // Player 1 fills the whole side A row, so player 0 cannot reach side A.
+        let mut game = GameY::new(3);
+        place(&mut game, 1, Coordinates::new(0, 2, 0));
+        place(&mut game, 0, Coordinates::new(2, 0, 0));
+        place(&mut game, 1, Coordinates::new(0, 1, 1));
+        place(&mut game, 0, Coordinates::new(1, 1, 0));
+        place(&mut game, 1, Coordinates::new(0, 0, 2));
+        let r = side_resistance(&game, PlayerId::new(0), Side::A, Side::B);
+        assert!(r.is_infinite());
+        let eval = evaluate_resistance(&game, PlayerId::new(0));
+        assert_eq!(eval.score(), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_winning_chain_has_low_resistance() {
+        let mut game = GameY::new(3);
+        place(&mut game, 0, Coordinates::new(0, 2, 0));
+        place(&mut game, 1, Coordinates::new(2, 0, 0));
+        place(&mut game, 0, Coordinates::new(0, 1, 1));
+        place(&mut game, 1, Coordinates::new(1, 1, 0));
+        place(&mut game, 0, Coordinates::new(0, 0, 2));
+        assert!(total_resistance(&game, PlayerId::new(0)) < 0.2);
+    }
+
+    #[test]
+    fn test_side_touches() {
+        let c = Coordinates::new(0, 0, 2);
+        assert!(Side::A.touches(&c));
+        assert!(Side::B.touches(&c));
+        assert!(!Side::C.touches(&c));
+    }
+}
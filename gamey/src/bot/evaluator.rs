@@ -0,0 +1,285 @@
+//! Learned position evaluators.
+//!
+//! This module provides the [`Evaluator`] trait, which scores a position and
+//! gives a prior probability to each of its moves, and [`MlpEvaluator`], a
+//! small multilayer perceptron written in plain Rust that reads the feature
+//! planes of [`crate::encode_position`]. Its weights are loaded from a JSON
+//! file (see [`MlpWeights`]), so networks trained elsewhere can be tried
+//! without adding a machine learning framework to the server.
+
+use crate::{Coordinates, FEATURE_PLANES, GameY, GameYError, encode_position, grid_position};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// The output of an [`Evaluator`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    /// Expected result for the player to move, from -1 (loss) to 1 (win).
+    pub value: f32,
+    /// Prior probability of each available cell; they add up to 1.
+    pub priors: Vec<(Coordinates, f32)>,
+}
+
+impl Evaluation {
+    /// An evaluation without knowledge: value 0 and the same prior for every
+    /// available cell.
+    pub fn uniform(board: &GameY) -> Self {
+        let cells = board.available_cells();
+        let prior = 1.0 / cells.len().max(1) as f32;
+        Evaluation {
+            value: 0.0,
+            priors: cells
+                .iter()
+                .map(|&cell| (Coordinates::from_index(cell, board.board_size()), prior))
+                .collect(),
+        }
+    }
+}
+
+/// Scores positions, typically with a learned model.
+pub trait Evaluator: Send + Sync + std::fmt::Debug {
+    /// Evaluates `board` for the player to move.
+    fn evaluate(&self, board: &GameY) -> Evaluation;
+}
+
+/// A fully connected layer: `output[i] = biases[i] + sum_j weights[i][j] * input[j]`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DenseLayer {
+    /// One row of input weights per output.
+    pub weights: Vec<Vec<f32>>,
+    /// One bias per output.
+    pub biases: Vec<f32>,
+}
+
+impl DenseLayer {
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        self.weights
+            .iter()
+            .zip(&self.biases)
+            .map(|(row, bias)| bias + row.iter().zip(input).map(|(w, x)| w * x).sum::<f32>())
+            .collect()
+    }
+}
+
+/// The weights file of an [`MlpEvaluator`].
+///
+/// The network reads the `FEATURE_PLANES * size * size` values of
+/// [`crate::encode_position`] and its last layer has `size * size + 1`
+/// outputs: a policy logit per grid cell (row by row) followed by the value,
+/// which goes through `tanh`. Hidden layers use ReLU.
+///
+/// # Example
+/// ```json
+/// {
+///   "board_size": 5,
+///   "layers": [
+///     { "weights": [[0.1, ...], ...], "biases": [0.0, ...] },
+///     { "weights": [[...], ...], "biases": [...] }
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MlpWeights {
+    /// The board size the network was trained for.
+    pub board_size: u32,
+    /// The layers, from the input to the output.
+    pub layers: Vec<DenseLayer>,
+}
+
+/// A multilayer perceptron evaluator for one board size.
+///
+/// Positions of another size get [`Evaluation::uniform`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MlpEvaluator {
+    weights: MlpWeights,
+}
+
+impl MlpEvaluator {
+    /// Creates an evaluator, checking that the layer shapes fit together.
+    ///
+    /// # Errors
+    /// Returns [`GameYError::InvalidModel`] if a layer does not match the size
+    /// of its input or the network does not end in `size * size + 1` outputs.
+    pub fn new(weights: MlpWeights) -> Result<Self, GameYError> {
+        let invalid = |message: String| Err(GameYError::InvalidModel { message });
+        let size = weights.board_size as usize;
+        if size == 0 || weights.layers.is_empty() {
+            return invalid("the network needs a board size and at least one layer".to_string());
+        }
+        let mut inputs = FEATURE_PLANES * size * size;
+        for (index, layer) in weights.layers.iter().enumerate() {
+            if layer.weights.len() != layer.biases.len() {
+                return invalid(format!(
+                    "layer {index} has {} weight rows and {} biases",
+                    layer.weights.len(),
+                    layer.biases.len()
+                ));
+            }
+            if let Some(row) = layer.weights.iter().find(|row| row.len() != inputs) {
+                return invalid(format!("layer {index} expects {inputs} inputs, found {}", row.len()));
+            }
+            inputs = layer.biases.len();
+        }
+        if inputs != size * size + 1 {
+            return invalid(format!("the last layer must have {} outputs, found {inputs}", size * size + 1));
+        }
+        Ok(MlpEvaluator { weights })
+    }
+
+    /// Parses the weights from JSON.
+    pub fn from_json(json: &str) -> Result<Self, GameYError> {
+        let weights = serde_json::from_str(json).map_err(|e| GameYError::SerdeError { error: e })?;
+        Self::new(weights)
+    }
+
+    /// Loads the weights from a JSON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, GameYError> {
+        let filename = path.as_ref().display().to_string();
+        let content = std::fs::read_to_string(path).map_err(|e| GameYError::IoError {
+            message: format!("Failed to read file: {}", filename),
+            error: e.to_string(),
+        })?;
+        Self::from_json(&content)
+    }
+
+    /// Returns the weights of the network.
+    pub fn weights(&self) -> &MlpWeights {
+        &self.weights
+    }
+
+    /// Returns the board size of the network.
+    pub fn board_size(&self) -> u32 {
+        self.weights.board_size
+    }
+
+    /// Runs the network on an encoded position and returns the raw outputs.
+    pub fn forward(&self, input: &[f32]) -> Vec<f32> {
+        let last = self.weights.layers.len() - 1;
+        let mut values = input.to_vec();
+        for (index, layer) in self.weights.layers.iter().enumerate() {
+            values = layer.forward(&values);
+            if index < last {
+                values.iter_mut().for_each(|v| *v = v.max(0.0));
+            }
+        }
+        values
+    }
+}
+
+impl Evaluator for MlpEvaluator {
+    fn evaluate(&self, board: &GameY) -> Evaluation {
+        let size = board.board_size();
+        if size != self.weights.board_size || board.available_cells().is_empty() {
+            return Evaluation::uniform(board);
+        }
+        let outputs = self.forward(encode_position(board).as_slice());
+        let grid = size as usize;
+        let logits: Vec<(Coordinates, f32)> = board
+            .available_cells()
+            .iter()
+            .map(|&cell| {
+                let coords = Coordinates::from_index(cell, size);
+                let (row, col) = grid_position(&coords, size);
+                (coords, outputs[row * grid + col])
+            })
+            .collect();
+        let max = logits.iter().map(|&(_, logit)| logit).fold(f32::NEG_INFINITY, f32::max);
+        let exps: Vec<f32> = logits.iter().map(|&(_, logit)| (logit - max).exp()).collect();
+        let total: f32 = exps.iter().sum();
+        Evaluation {
+            value: outputs[grid * grid].tanh(),
+            priors: logits
+                .iter()
+                .zip(exps)
+                .map(|(&(coords, _), e)| (coords, e / total))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A one-layer network for size 2 whose policy logit for a cell is 2 when
+    /// the cell is empty, and whose value is 0.5 * the number of own stones.
+    fn tiny_weights() -> MlpWeights {
+        let size = 2;
+        let inputs = FEATURE_PLANES * size * size;
+        let mut weights = vec![vec![0.0; inputs]; size * size + 1];
+        for (cell, row) in weights.iter_mut().take(size * size).enumerate() {
+            row[2 * size * size + cell] = 2.0;
+        }
+        weights[size * size][..size * size].fill(0.5);
+        MlpWeights {
+            board_size: 2,
+            layers: vec![DenseLayer {
+                weights,
+                biases: vec![0.0; size * size + 1],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_mlp_priors_are_a_distribution() {
+        let evaluator = MlpEvaluator::new(tiny_weights()).unwrap();
+        let evaluation = evaluator.evaluate(&GameY::new(2));
+        assert_eq!(evaluation.priors.len(), 3);
+        let total: f32 = evaluation.priors.iter().map(|&(_, p)| p).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+        assert!(evaluation.priors.iter().all(|&(_, p)| (p - 1.0 / 3.0).abs() < 1e-6));
+        assert_eq!(evaluation.value, 0.0);
+    }
+
+    #[test]
+    fn test_mlp_other_board_size_is_uniform() {
+        let evaluator = MlpEvaluator::new(tiny_weights()).unwrap();
+        assert_eq!(evaluator.evaluate(&GameY::new(3)), Evaluation::uniform(&GameY::new(3)));
+    }
+
+    #[test]
+    fn test_mlp_forward_uses_relu_on_hidden_layers() {
+        let mut weights = tiny_weights();
+        let inputs = FEATURE_PLANES * 4;
+        weights.layers.insert(
+            0,
+            DenseLayer {
+                weights: vec![vec![0.0; inputs]; inputs],
+                biases: (0..inputs).map(|i| if i % 2 == 0 { -1.0 } else { 1.0 }).collect(),
+            },
+        );
+        let evaluator = MlpEvaluator::new(weights).unwrap();
+        let outputs = evaluator.forward(&vec![0.0; inputs]);
+        // Only the odd inputs of the second layer are 1 after the ReLU.
+        assert_eq!(outputs[0], 0.0);
+        assert_eq!(outputs[1], 2.0);
+        assert_eq!(outputs.len(), 5);
+    }
+
+    #[test]
+    fn test_mlp_rejects_bad_shapes() {
+        let mut weights = tiny_weights();
+        weights.layers[0].biases.pop();
+        assert!(matches!(MlpEvaluator::new(weights), Err(GameYError::InvalidModel { .. })));
+
+        let mut weights = tiny_weights();
+        weights.layers[0].weights[0].pop();
+        assert!(matches!(MlpEvaluator::new(weights), Err(GameYError::InvalidModel { .. })));
+
+        let mut weights = tiny_weights();
+        weights.board_size = 3;
+        assert!(MlpEvaluator::new(weights).is_err());
+    }
+
+    #[test]
+    fn test_mlp_load_from_json_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.json");
+        std::fs::write(&path, serde_json::to_string(&tiny_weights()).unwrap()).unwrap();
+        let evaluator = MlpEvaluator::load(&path).unwrap();
+        assert_eq!(evaluator.board_size(), 2);
+        assert_eq!(evaluator.weights(), &tiny_weights());
+        assert!(MlpEvaluator::load(dir.path().join("missing.json")).is_err());
+        assert!(MlpEvaluator::from_json("{\"board_size\": 2}").is_err());
+    }
+}
@@ -4,7 +4,8 @@
 //! play with a certain logic: it seeks to group its pieces by connecting new moves to those 
 //! already on the board.
 
-use crate::{Coordinates, GameY, YBot};
+use crate::{BotMetadata, Difficulty, Coordinates, GameY, YBot};
+use rand::RngCore;
 use rand::prelude::IndexedRandom;
 
 /// A bot that chooses moves following the group's expansion strategy
@@ -32,8 +33,6 @@ use rand::prelude::IndexedRandom;
 /// let chosen_move = bot.choose_move(&game);
 /// assert!(chosen_move.is_some());
 /// ```
-
-
 pub struct GroupExpansionBot;
 
 impl YBot for GroupExpansionBot {
@@ -41,7 +40,17 @@ impl YBot for GroupExpansionBot {
         "group_expansion_bot"
     }
 
+    fn metadata(&self) -> BotMetadata {
+        BotMetadata::new(self.name())
+            .with_description("Grows its groups by playing next to its own stones.")
+            .with_difficulty(Difficulty::Easy)
+    }
+
     fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
+        self.choose_move_with_rng(board, &mut rand::rng())
+    }
+
+    fn choose_move_with_rng(&self, board: &GameY, rng: &mut dyn RngCore) -> Option<Coordinates> {
         let available_cells = board.available_cells();
         if available_cells.is_empty() {
             return None;
@@ -50,18 +59,18 @@ impl YBot for GroupExpansionBot {
         let bot_cells = board.cells_for_player(actual_player);
         let _candidates = obtein_all_available_neighbors(bot_cells,board, available_cells);
         if _candidates.is_empty() {
-            let cell = available_cells.choose(&mut rand::rng())?;
+            let cell = available_cells.choose(rng)?;
             let coordinates = Coordinates::from_index(*cell, board.board_size());
             return Some(coordinates);
         }
-        let cell = _candidates.choose(&mut rand::rng())?;
+        let cell = _candidates.choose(rng)?;
         let coordinates = Coordinates::from_index(*cell, board.board_size());
         Some(coordinates)
     }
     
 }
 
-fn obtein_all_available_neighbors(bot_cells: Vec<Coordinates>, board: &GameY, available_cells: &Vec<u32>) -> Vec<u32>{
+fn obtein_all_available_neighbors(bot_cells: Vec<Coordinates>, board: &GameY, available_cells: &[u32]) -> Vec<u32>{
         let mut candidates = Vec::new();
 
         for cell in bot_cells {
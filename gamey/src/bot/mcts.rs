@@ -0,0 +1,744 @@
+//! Implementation of a Monte Carlo Tree Search bot
+//!
+//! This module contains the structure [`MctsBot`]. Unlike
+//! [`crate::MonteCarloBot`], which only simulates from each candidate cell,
+//! this bot grows a search tree with UCT and keeps it between moves: when it is
+//! asked about a position that follows from the one it searched last (after
+//! its own move and the opponent's reply), it continues from the matching
+//! subtree instead of starting from nothing.
+//!
+//! With [`MctsConfig::rave`] the bot also keeps all-moves-as-first (AMAF)
+//! statistics: a playout credits every move the player made in it, not only
+//! the first, which makes the estimates of young nodes converge much faster
+//! in connection games, where the order of the stones matters little.
+//!
+//! A learned [`Evaluator`] can be attached with [`MctsBot::with_evaluator`]:
+//! its move priors decide which moves are expanded first and add a bonus to
+//! the UCT value of the children that fades as they are visited.
+
+use crate::{
+    BotDecision, BotMetadata, BotParams, Coordinates, DEFAULT_ENDGAME_CELLS, Difficulty, DecisionOptions, Evaluator, GameY, GameYError,
+    MoveAnalysis, MoveCandidate, Movement, PlayerId, PlayoutBoard, PlayoutKind, YBot, check_endgame_cells, config_from_params, endgame_move, search_bot_decision, search_cancelled,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// Tunable parameters of [`MctsBot`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MctsConfig {
+    /// Number of playouts added to the tree on each move.
+    pub iterations: u32,
+    /// Exploration constant of the UCT formula.
+    pub exploration: f64,
+    /// How the board is filled after the tree is left.
+    pub playout: PlayoutKind,
+    /// Keep the tree between moves and continue from the matching subtree.
+    pub reuse_tree: bool,
+    /// Blend the AMAF statistics into the UCT value (RAVE).
+    pub rave: bool,
+    /// Visits at which the AMAF statistics and the node's own statistics
+    /// weigh roughly the same; the AMAF weight is `sqrt(k / (3n + k))` for a
+    /// node visited `n` times. Only used with [`MctsConfig::rave`].
+    pub rave_equivalence: f64,
+    /// Positions with at most this many empty cells (up to
+    /// [`crate::MAX_SOLVER_CELLS`]) are solved exactly with
+    /// [`crate::solve_endgame`] instead of searched; 0 turns the solver off.
+    pub endgame_cells: u32,
+    /// Weight of the evaluator prior in the UCT value; a child gets
+    /// `prior_weight * prior / (1 + visits)`. Only used with an evaluator.
+    pub prior_weight: f64,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        MctsConfig {
+            iterations: 1000,
+            exploration: 0.7,
+            playout: PlayoutKind::Random,
+            reuse_tree: true,
+            rave: false,
+            rave_equivalence: 300.0,
+            endgame_cells: DEFAULT_ENDGAME_CELLS,
+            prior_weight: 1.0,
+        }
+    }
+}
+
+/// A bot that chooses moves following a Monte Carlo Tree Search (UCT)
+///
+/// To decide where to place its next piece, this bot follows this reasoning:
+/// 1. Starting at the current position, it walks down its search tree picking
+///    the child with the best UCT value (win rate plus an exploration bonus).
+///    With RAVE the win rate is blended with the AMAF win rate of the move,
+///    which dominates while the child has few visits.
+/// 2. At the first node with untried moves it adds one of them to the tree.
+/// 3. From there it plays a random game until the board is decided, with the
+///    policy chosen in [`MctsConfig::playout`].
+/// 4. It credits the result to every node on the path, from the point of view
+///    of the player who moved into it. With RAVE it also credits the AMAF
+///    statistics of every sibling whose cell the same player took later on.
+/// 5. After [`MctsConfig::iterations`] playouts it plays the most visited move.
+///
+/// The tree is kept inside the bot, so the same instance should be used for
+/// the whole game (the bot server keeps one per session): the next call starts
+/// from the subtree of the moves played in between.
+///
+/// # Example
+///
+/// ```
+/// use gamey::{GameY, MctsBot, MctsConfig, YBot};
+///
+/// let bot = MctsBot::new(MctsConfig { iterations: 50, ..Default::default() });
+/// let game = GameY::new(5);
+///
+/// let chosen_move = bot.choose_move(&game);
+/// assert!(chosen_move.is_some());
+/// ```
+#[derive(Debug, Default)]
+pub struct MctsBot {
+    config: MctsConfig,
+    evaluator: Option<Arc<dyn Evaluator>>,
+    tree: Mutex<Option<SearchTree>>,
+}
+
+impl MctsBot {
+    /// Creates a bot with the given configuration.
+    pub fn new(config: MctsConfig) -> Self {
+        MctsBot {
+            config,
+            evaluator: None,
+            tree: Mutex::new(None),
+        }
+    }
+
+    /// Uses the priors of `evaluator` to order and bias the search.
+    pub fn with_evaluator(mut self, evaluator: Arc<dyn Evaluator>) -> Self {
+        self.evaluator = Some(evaluator);
+        self
+    }
+
+    /// Creates a bot from request parameters, using defaults for missing fields.
+    pub fn from_params(params: &BotParams) -> Result<Self, GameYError> {
+        let config: MctsConfig = config_from_params("mcts_bot", params)?;
+        if config.iterations == 0 {
+            return Err(GameYError::InvalidBotParams {
+                bot_id: "mcts_bot".to_string(),
+                message: "iterations must be greater than 0".to_string(),
+            });
+        }
+        if !(config.exploration >= 0.0 && config.exploration.is_finite()) {
+            return Err(GameYError::InvalidBotParams {
+                bot_id: "mcts_bot".to_string(),
+                message: "exploration must be a non-negative number".to_string(),
+            });
+        }
+        if !(config.rave_equivalence > 0.0 && config.rave_equivalence.is_finite()) {
+            return Err(GameYError::InvalidBotParams {
+                bot_id: "mcts_bot".to_string(),
+                message: "rave_equivalence must be a positive number".to_string(),
+            });
+        }
+        if !(config.prior_weight >= 0.0 && config.prior_weight.is_finite()) {
+            return Err(GameYError::InvalidBotParams {
+                bot_id: "mcts_bot".to_string(),
+                message: "prior_weight must be a non-negative number".to_string(),
+            });
+        }
+        check_endgame_cells("mcts_bot", config.endgame_cells)?;
+        Ok(MctsBot::new(config))
+    }
+
+    /// Returns the configuration of this bot.
+    pub fn config(&self) -> &MctsConfig {
+        &self.config
+    }
+
+    /// Returns the playouts the bot already has for `board` from its previous
+    /// searches, which it will reuse when asked about that position.
+    pub fn reused_visits(&self, board: &GameY) -> u32 {
+        let tree = self.tree.lock().unwrap_or_else(|e| e.into_inner());
+        tree.as_ref()
+            .and_then(|tree| tree.find(board))
+            .map_or(0, |node| node.visits)
+    }
+
+    /// Searches `board` and returns the visits of every move at the root,
+    /// the distribution a learned policy is trained to imitate. Unlike
+    /// [`YBot::choose_move`] it always searches, even in solvable endgames.
+    pub fn root_visits_with_rng(&self, board: &GameY, rng: &mut dyn RngCore) -> Vec<(Coordinates, u32)> {
+        if board.check_game_over() {
+            return Vec::new();
+        }
+        let size = board.board_size();
+        self.search(board, rng, |root| {
+            root.children
+                .iter()
+                .map(|child| (Coordinates::from_index(child.cell, size), child.visits))
+                .collect()
+        })
+    }
+
+    /// Runs the search for `board`, starting from the retained subtree when
+    /// there is one, reads the result with `read` and stores the tree for the
+    /// next move.
+    fn search<R>(&self, board: &GameY, rng: &mut dyn RngCore, read: impl FnOnce(&Node) -> R) -> R {
+        let retained = if self.config.reuse_tree {
+            self.tree.lock().unwrap_or_else(|e| e.into_inner()).take()
+        } else {
+            None
+        };
+        let context = SearchContext {
+            config: &self.config,
+            evaluator: self.evaluator.as_deref(),
+        };
+        let mut root = retained
+            .and_then(|tree| tree.into_subtree(board))
+            .unwrap_or_else(|| Node::new(0, None, 0.0, board, &context));
+        for iteration in 0..self.config.iterations {
+            // A cancelled search still runs one iteration, so that it has a move.
+            if iteration > 0 && search_cancelled() {
+                break;
+            }
+            let mut simulated_board = board.clone();
+            root.iterate(&mut simulated_board, &context, rng);
+        }
+        let result = read(&root);
+        if self.config.reuse_tree {
+            *self.tree.lock().unwrap_or_else(|e| e.into_inner()) = Some(SearchTree {
+                board: board.clone(),
+                root,
+            });
+        }
+        result
+    }
+}
+
+impl YBot for MctsBot {
+    fn name(&self) -> &str {
+        "mcts_bot"
+    }
+
+    fn metadata(&self) -> BotMetadata {
+        BotMetadata::new(self.name())
+            .with_display_name("MCTS")
+            .with_description("Grows a Monte Carlo search tree and keeps it between moves.")
+            .with_difficulty(Difficulty::Expert)
+            .with_params(&MctsConfig::default())
+    }
+
+    fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
+        self.choose_move_with_rng(board, &mut rand::rng())
+    }
+
+    fn choose_move_with_rng(&self, board: &GameY, rng: &mut dyn RngCore) -> Option<Coordinates> {
+        if board.check_game_over() {
+            return None;
+        }
+        if let Some(coords) = endgame_move(board, self.config.endgame_cells) {
+            return Some(coords);
+        }
+        let best = self.search(board, rng, |root| {
+            root.children.iter().max_by_key(|child| child.visits).map(|child| child.cell)
+        })?;
+        Some(Coordinates::from_index(best, board.board_size()))
+    }
+
+    fn decide_with_rng(&self, board: &GameY, options: &DecisionOptions, rng: &mut dyn RngCore) -> BotDecision {
+        search_bot_decision(self, board, options, rng)
+    }
+
+    /// Scores every move in the tree with its win rate, and follows the most
+    /// visited children for the principal variation.
+    fn analyze(&self, board: &GameY) -> MoveAnalysis {
+        if board.check_game_over() {
+            return MoveAnalysis::default();
+        }
+        let size = board.board_size();
+        self.search(board, &mut rand::rng(), |root| {
+            let candidates = root
+                .children
+                .iter()
+                .map(|child| {
+                    let win_rate = child.win_rate();
+                    MoveCandidate::new(Coordinates::from_index(child.cell, size), win_rate).with_win_rate(win_rate)
+                })
+                .collect();
+            let mut variation = Vec::new();
+            let mut node = root;
+            while let Some(best) = node.children.iter().max_by_key(|child| child.visits) {
+                variation.push(Coordinates::from_index(best.cell, size));
+                node = best;
+            }
+            MoveAnalysis::new(candidates).with_principal_variation(variation)
+        })
+    }
+}
+
+/// The tree kept between moves, with the position at its root.
+#[derive(Debug)]
+struct SearchTree {
+    board: GameY,
+    root: Node,
+}
+
+impl SearchTree {
+    /// Returns the stones of `board` that are not on the root position, or
+    /// `None` if `board` does not follow from it.
+    fn new_stones(&self, board: &GameY) -> Option<Vec<(u32, PlayerId)>> {
+        let size = board.board_size();
+        if size != self.board.board_size() {
+            return None;
+        }
+        let mut stones = Vec::new();
+        for cell in 0..board.total_cells() {
+            let coords = Coordinates::from_index(cell, size);
+            match (self.board.player_at(&coords), board.player_at(&coords)) {
+                (None, None) => {}
+                (Some(before), Some(after)) if before == after => {}
+                (None, Some(player)) => stones.push((cell, player)),
+                _ => return None,
+            }
+        }
+        Some(stones)
+    }
+
+    /// Follows the new stones of `board` from the root, each player in turn.
+    /// Returns the path of child indices, or `None` if it leaves the tree.
+    fn path_to(&self, board: &GameY) -> Option<Vec<usize>> {
+        let mut stones = self.new_stones(board)?;
+        let mut path = Vec::new();
+        let mut node = &self.root;
+        let mut to_move = self.board.next_player()?;
+        while !stones.is_empty() {
+            let stone = stones.iter().position(|&(_, player)| player == to_move)?;
+            let (cell, _) = stones.swap_remove(stone);
+            let child = node.children.iter().position(|child| child.cell == cell)?;
+            path.push(child);
+            node = &node.children[child];
+            to_move = other(to_move);
+        }
+        (board.next_player() == Some(to_move)).then_some(path)
+    }
+
+    fn find(&self, board: &GameY) -> Option<&Node> {
+        let mut node = &self.root;
+        for child in self.path_to(board)? {
+            node = &node.children[child];
+        }
+        Some(node)
+    }
+
+    fn into_subtree(self, board: &GameY) -> Option<Node> {
+        let path = self.path_to(board)?;
+        let mut node = self.root;
+        for child in path {
+            node = node.children.swap_remove(child);
+        }
+        Some(node)
+    }
+}
+
+/// What a search needs besides the tree: the configuration and the
+/// optional evaluator.
+struct SearchContext<'a> {
+    config: &'a MctsConfig,
+    evaluator: Option<&'a dyn Evaluator>,
+}
+
+/// A node of the search tree.
+#[derive(Debug)]
+struct Node {
+    /// The cell played to reach this node (unused at the root).
+    cell: u32,
+    /// The player who played `cell`; wins are counted for this player.
+    mover: Option<PlayerId>,
+    visits: u32,
+    wins: f64,
+    /// Playouts through the parent in which `mover` took `cell` (RAVE).
+    amaf_visits: u32,
+    /// Those of the AMAF playouts that `mover` won.
+    amaf_wins: f64,
+    /// Prior probability of `cell` given by the evaluator (0 without one).
+    prior: f32,
+    children: Vec<Node>,
+    /// The moves not expanded yet with their priors; with an evaluator they
+    /// are sorted so that the most likely one is last.
+    untried: Vec<(u32, f32)>,
+}
+
+impl Node {
+    fn new(cell: u32, mover: Option<PlayerId>, prior: f32, board: &GameY, context: &SearchContext) -> Self {
+        let untried = if board.check_game_over() {
+            Vec::new()
+        } else if let Some(evaluator) = context.evaluator {
+            let mut untried: Vec<(u32, f32)> = evaluator
+                .evaluate(board)
+                .priors
+                .iter()
+                .map(|(coords, prior)| (coords.to_index(board.board_size()), *prior))
+                .collect();
+            untried.sort_by(|a, b| a.1.total_cmp(&b.1));
+            untried
+        } else {
+            board.available_cells().iter().map(|&cell| (cell, 0.0)).collect()
+        };
+        Node {
+            cell,
+            mover,
+            prior,
+            visits: 0,
+            wins: 0.0,
+            amaf_visits: 0,
+            amaf_wins: 0.0,
+            children: Vec::new(),
+            untried,
+        }
+    }
+
+    fn win_rate(&self) -> f64 {
+        if self.visits == 0 { 0.0 } else { self.wins / self.visits as f64 }
+    }
+
+    fn amaf_rate(&self) -> f64 {
+        if self.amaf_visits == 0 { self.win_rate() } else { self.amaf_wins / self.amaf_visits as f64 }
+    }
+
+    fn uct(&self, parent_visits: u32, config: &MctsConfig) -> f64 {
+        if self.visits == 0 {
+            return f64::INFINITY;
+        }
+        let visits = self.visits as f64;
+        let value = if config.rave {
+            let beta = (config.rave_equivalence / (3.0 * visits + config.rave_equivalence)).sqrt();
+            (1.0 - beta) * self.win_rate() + beta * self.amaf_rate()
+        } else {
+            self.win_rate()
+        };
+        let prior = config.prior_weight * self.prior as f64 / (1.0 + visits);
+        value + prior + config.exploration * ((parent_visits as f64).ln() / visits).sqrt()
+    }
+
+    /// Runs one selection, expansion, playout and backpropagation step from
+    /// this node, whose position is `board`. Returns the end of the playout.
+    fn iterate(&mut self, board: &mut GameY, context: &SearchContext, rng: &mut dyn RngCore) -> PlayoutBoard {
+        let config = context.config;
+        let end = if board.check_game_over() {
+            PlayoutBoard::from_game(board)
+        } else if !self.untried.is_empty() {
+            let (cell, prior) = if context.evaluator.is_some() {
+                self.untried.pop().unwrap_or_default()
+            } else {
+                let index = (rng.next_u32() as usize) % self.untried.len();
+                self.untried.swap_remove(index)
+            };
+            let end = config
+                .playout
+                .run(board, Some(Coordinates::from_index(cell, board.board_size())), rng);
+            let mover = play(board, cell);
+            let mut child = Node::new(cell, mover, prior, board, context);
+            child.record(end.winner());
+            self.children.push(child);
+            end
+        } else {
+            let parent_visits = self.visits.max(1);
+            let best = self
+                .children
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| {
+                    a.uct(parent_visits, config).total_cmp(&b.uct(parent_visits, config))
+                })
+                .map(|(index, _)| index);
+            match best {
+                Some(index) => {
+                    play(board, self.children[index].cell);
+                    self.children[index].iterate(board, context, rng)
+                }
+                None => PlayoutBoard::from_game(board),
+            }
+        };
+        self.record(end.winner());
+        if config.rave {
+            self.record_amaf(&end);
+        }
+        end
+    }
+
+    /// Credits the AMAF statistics of the children whose cell their mover
+    /// took at some point after this node.
+    fn record_amaf(&mut self, end: &PlayoutBoard) {
+        let winner = end.winner();
+        for child in &mut self.children {
+            if child.mover.is_some() && end.owner(child.cell) == child.mover {
+                child.amaf_visits += 1;
+                if winner == child.mover {
+                    child.amaf_wins += 1.0;
+                }
+            }
+        }
+    }
+
+    fn record(&mut self, winner: Option<PlayerId>) {
+        self.visits += 1;
+        if winner.is_some() && winner == self.mover {
+            self.wins += 1.0;
+        }
+    }
+}
+
+fn other(player: PlayerId) -> PlayerId {
+    PlayerId::new(1 - player.id())
+}
+
+/// Places a stone of the player to move on `cell` and returns that player.
+fn play(board: &mut GameY, cell: u32) -> Option<PlayerId> {
+    let player = board.next_player()?;
+    let coords = Coordinates::from_index(cell, board.board_size());
+    board.add_move(Movement::Placement { player, coords }).ok()?;
+    Some(player)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn place(game: &mut GameY, player: u32, coords: Coordinates) {
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(player),
+            coords,
+        })
+        .unwrap();
+    }
+
+    fn small_bot() -> MctsBot {
+        MctsBot::new(MctsConfig {
+            iterations: 200,
+            endgame_cells: 0,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_mcts_bot_name() {
+        assert_eq!(MctsBot::default().name(), "mcts_bot");
+    }
+
+    #[test]
+    fn test_mcts_bot_returns_valid_move() {
+        let bot = small_bot();
+        let game = GameY::new(5);
+        let coords = bot.choose_move_with_rng(&game, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert!(game.available_cells().contains(&coords.to_index(5)));
+    }
+
+    #[test]
+    fn test_mcts_bot_finished_game() {
+        let mut game = GameY::new(1);
+        place(&mut game, 0, Coordinates::new(0, 0, 0));
+        assert!(small_bot().choose_move(&game).is_none());
+    }
+
+    #[test]
+    fn test_mcts_bot_reuses_the_subtree_after_the_reply() {
+        let bot = small_bot();
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut game = GameY::new(5);
+        let own = bot.choose_move_with_rng(&game, &mut rng).unwrap();
+        place(&mut game, 0, own);
+        let reply = Coordinates::from_index(game.available_cells()[0], 5);
+        place(&mut game, 1, reply);
+        // Some playouts went through the bot's move and the first reply.
+        let reused = bot.reused_visits(&game);
+        assert!(reused > 0);
+
+        bot.choose_move_with_rng(&game, &mut rng).unwrap();
+        let tree = bot.tree.lock().unwrap();
+        assert_eq!(tree.as_ref().unwrap().root.visits, reused + 200);
+    }
+
+    #[test]
+    fn test_mcts_bot_does_not_reuse_unrelated_positions() {
+        let bot = small_bot();
+        let mut game = GameY::new(5);
+        bot.choose_move(&game);
+        assert_eq!(bot.reused_visits(&GameY::new(4)), 0);
+        // A stone removed from the root position cannot follow from it.
+        place(&mut game, 0, Coordinates::new(2, 1, 1));
+        bot.choose_move(&game);
+        assert_eq!(bot.reused_visits(&GameY::new(5)), 0);
+    }
+
+    #[test]
+    fn test_mcts_bot_without_reuse_keeps_no_tree() {
+        let bot = MctsBot::new(MctsConfig {
+            iterations: 50,
+            reuse_tree: false,
+            ..Default::default()
+        });
+        let game = GameY::new(4);
+        bot.choose_move(&game);
+        assert_eq!(bot.reused_visits(&game), 0);
+    }
+
+    #[test]
+    fn test_mcts_bot_stops_when_cancelled() {
+        let bot = MctsBot::new(MctsConfig {
+            iterations: u32::MAX,
+            ..Default::default()
+        });
+        let game = GameY::new(5);
+        let token = crate::CancelToken::new();
+        token.cancel();
+        assert!(crate::with_cancel_token(&token, || bot.choose_move(&game)).is_some());
+        assert_eq!(bot.reused_visits(&game), 1);
+    }
+
+    #[test]
+    fn test_mcts_bot_analyze() {
+        let bot = small_bot();
+        let game = GameY::new(4);
+        let analysis = bot.analyze(&game);
+        assert_eq!(analysis.candidates.len(), 10);
+        assert!(analysis.candidates.iter().all(|c| c.win_rate.is_some()));
+        assert!(!analysis.principal_variation.is_empty());
+        // The analysis is kept for the next move.
+        assert_eq!(bot.reused_visits(&game), 200);
+    }
+
+    #[test]
+    fn test_mcts_bot_with_rave_collects_amaf_statistics() {
+        let bot = MctsBot::new(MctsConfig {
+            iterations: 200,
+            rave: true,
+            endgame_cells: 0,
+            ..Default::default()
+        });
+        let game = GameY::new(5);
+        let coords = bot.choose_move_with_rng(&game, &mut StdRng::seed_from_u64(5)).unwrap();
+        assert!(game.available_cells().contains(&coords.to_index(5)));
+        let tree = bot.tree.lock().unwrap();
+        let root = &tree.as_ref().unwrap().root;
+        // Each playout credits many cells, so AMAF counts exceed the visits.
+        let amaf: u32 = root.children.iter().map(|child| child.amaf_visits).sum();
+        assert!(amaf > root.visits);
+        assert!(root.children.iter().all(|child| child.amaf_visits >= child.visits));
+    }
+
+    #[test]
+    fn test_rave_weight_fades_with_visits() {
+        let config = MctsConfig {
+            rave: true,
+            exploration: 0.0,
+            rave_equivalence: 100.0,
+            ..Default::default()
+        };
+        let context = SearchContext {
+            config: &config,
+            evaluator: None,
+        };
+        let mut node = Node::new(0, Some(PlayerId::new(0)), 0.0, &GameY::new(3), &context);
+        node.amaf_visits = 10;
+        node.amaf_wins = 10.0;
+        node.visits = 1;
+        assert!(node.uct(1, &config) > 0.9);
+        node.visits = 100_000;
+        assert!(node.uct(1, &config) < 0.1);
+        let plain = MctsConfig { rave: false, ..config };
+        assert_eq!(node.uct(1, &plain), 0.0);
+    }
+
+    #[test]
+    fn test_mcts_bot_root_visits() {
+        let bot = small_bot();
+        let game = GameY::new(4);
+        let visits = bot.root_visits_with_rng(&game, &mut StdRng::seed_from_u64(4));
+        assert_eq!(visits.len(), 10);
+        assert_eq!(visits.iter().map(|&(_, v)| v).sum::<u32>(), 200);
+
+        let mut finished = GameY::new(1);
+        place(&mut finished, 0, Coordinates::new(0, 0, 0));
+        assert!(bot.root_visits_with_rng(&finished, &mut StdRng::seed_from_u64(4)).is_empty());
+    }
+
+    /// Puts almost all the prior on one cell.
+    #[derive(Debug)]
+    struct FavouriteCell(Coordinates);
+
+    impl Evaluator for FavouriteCell {
+        fn evaluate(&self, board: &GameY) -> crate::Evaluation {
+            let mut evaluation = crate::Evaluation::uniform(board);
+            for (coords, prior) in &mut evaluation.priors {
+                *prior = if *coords == self.0 { 0.9 } else { 0.01 };
+            }
+            evaluation
+        }
+    }
+
+    #[test]
+    fn test_mcts_bot_expands_the_evaluator_favourite_first() {
+        let favourite = Coordinates::new(2, 1, 1);
+        let bot = MctsBot::new(MctsConfig {
+            iterations: 1,
+            endgame_cells: 0,
+            ..Default::default()
+        })
+        .with_evaluator(Arc::new(FavouriteCell(favourite)));
+        let game = GameY::new(5);
+        let chosen = bot.choose_move_with_rng(&game, &mut StdRng::seed_from_u64(2));
+        assert_eq!(chosen, Some(favourite));
+        let tree = bot.tree.lock().unwrap();
+        let root = &tree.as_ref().unwrap().root;
+        assert_eq!(root.children[0].prior, 0.9);
+    }
+
+    #[test]
+    fn test_prior_bonus_fades_with_visits() {
+        let config = MctsConfig {
+            exploration: 0.0,
+            prior_weight: 2.0,
+            ..Default::default()
+        };
+        let context = SearchContext {
+            config: &config,
+            evaluator: None,
+        };
+        let mut node = Node::new(0, Some(PlayerId::new(0)), 0.5, &GameY::new(3), &context);
+        node.visits = 1;
+        assert_eq!(node.uct(1, &config), 0.5);
+        node.visits = 9;
+        assert_eq!(node.uct(1, &config), 0.1);
+    }
+
+    #[test]
+    fn test_mcts_from_params() {
+        let params: BotParams = serde_json::from_str(r#"{"iterations": 10, "reuse_tree": false}"#).unwrap();
+        let bot = MctsBot::from_params(&params).unwrap();
+        assert_eq!(bot.config().iterations, 10);
+        assert!(!bot.config().reuse_tree);
+
+        let zero: BotParams = serde_json::from_str(r#"{"iterations": 0}"#).unwrap();
+        assert!(MctsBot::from_params(&zero).is_err());
+        let negative: BotParams = serde_json::from_str(r#"{"exploration": -1.0}"#).unwrap();
+        assert!(MctsBot::from_params(&negative).is_err());
+        let unbounded: BotParams = serde_json::from_str(r#"{"endgame_cells": 40}"#).unwrap();
+        assert!(MctsBot::from_params(&unbounded).is_err());
+
+        let bridge: BotParams = serde_json::from_str(r#"{"iterations": 20, "playout": "bridge"}"#).unwrap();
+        let bot = MctsBot::from_params(&bridge).unwrap();
+        assert_eq!(bot.config().playout, PlayoutKind::Bridge);
+        assert!(bot.choose_move(&GameY::new(5)).is_some());
+
+        let rave: BotParams = serde_json::from_str(r#"{"rave": true, "rave_equivalence": 500}"#).unwrap();
+        let bot = MctsBot::from_params(&rave).unwrap();
+        assert!(bot.config().rave);
+        assert_eq!(bot.config().rave_equivalence, 500.0);
+        let zero: BotParams = serde_json::from_str(r#"{"rave_equivalence": 0}"#).unwrap();
+        assert!(MctsBot::from_params(&zero).is_err());
+        let negative: BotParams = serde_json::from_str(r#"{"prior_weight": -1}"#).unwrap();
+        assert!(MctsBot::from_params(&negative).is_err());
+    }
+}
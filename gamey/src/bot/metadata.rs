@@ -0,0 +1,169 @@
+//! Descriptions of bots for clients.
+//!
+//! Every bot describes itself through [`YBot::metadata`], so that clients can
+//! list the opponents of a server (see [`crate::YBotRegistry::catalogue`])
+//! instead of guessing bot ids.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use serde_json::Value;
+
+use crate::Difficulty;
+#[cfg(doc)]
+use crate::YBot;
+
+/// The board sizes a bot can play on.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub struct BoardSizes {
+    /// The smallest size.
+    pub min: u32,
+    /// The largest size, or `None` if there is no limit.
+    pub max: Option<u32>,
+}
+
+impl BoardSizes {
+    /// Every board size.
+    pub const ANY: BoardSizes = BoardSizes { min: 1, max: None };
+
+    /// Returns true if a board of `size` is supported.
+    pub fn contains(&self, size: u32) -> bool {
+        size >= self.min && self.max.is_none_or(|max| size <= max)
+    }
+}
+
+/// A parameter that tunes a configurable bot.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct BotParameter {
+    /// The key of the parameter in a `params` object.
+    pub name: String,
+    /// The value used when the parameter is not given.
+    pub default: Value,
+}
+
+/// What a client needs to know to offer a bot as an opponent.
+///
+/// # Example
+/// ```json
+/// {
+///   "id": "monte_carlo_bot",
+///   "display_name": "Monte Carlo",
+///   "description": "Plays the move that wins most random playouts.",
+///   "difficulty": "hard",
+///   "board_sizes": { "min": 1, "max": null },
+///   "params": [{ "name": "simulations", "default": 100 }]
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct BotMetadata {
+    /// The id the bot is registered under.
+    pub id: String,
+    /// A name to show to players.
+    pub display_name: String,
+    /// What the bot does, in one or two sentences.
+    pub description: String,
+    /// How strong the bot is, if it has been rated.
+    pub difficulty: Option<Difficulty>,
+    /// The board sizes the bot can play on.
+    pub board_sizes: BoardSizes,
+    /// The parameters accepted in a `params` object, with their defaults.
+    pub params: Vec<BotParameter>,
+}
+
+impl BotMetadata {
+    /// Describes the bot `id` by its id alone: the display name is the id in
+    /// words, it plays on every board size and takes no parameters.
+    pub fn new(id: &str) -> Self {
+        BotMetadata {
+            id: id.to_string(),
+            display_name: display_name(id),
+            description: String::new(),
+            difficulty: None,
+            board_sizes: BoardSizes::ANY,
+            params: Vec::new(),
+        }
+    }
+
+    /// Sets the display name and returns the metadata for chaining.
+    pub fn with_display_name(mut self, display_name: &str) -> Self {
+        self.display_name = display_name.to_string();
+        self
+    }
+
+    /// Sets the description and returns the metadata for chaining.
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    /// Sets the difficulty and returns the metadata for chaining.
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = Some(difficulty);
+        self
+    }
+
+    /// Sets the supported board sizes and returns the metadata for chaining.
+    pub fn with_board_sizes(mut self, board_sizes: BoardSizes) -> Self {
+        self.board_sizes = board_sizes;
+        self
+    }
+
+    /// Lists the fields of `config`, the default configuration of the bot,
+    /// as its parameters and returns the metadata for chaining.
+    pub fn with_params<T: Serialize>(mut self, config: &T) -> Self {
+        if let Ok(Value::Object(fields)) = serde_json::to_value(config) {
+            self.params = fields
+                .into_iter()
+                .map(|(name, default)| BotParameter { name, default })
+                .collect();
+        }
+        self
+    }
+}
+
+/// Turns an id such as `shortest_path_bot` into `Shortest Path`.
+fn display_name(id: &str) -> String {
+    let id = id.strip_suffix("_bot").unwrap_or(id);
+    id.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MonteCarloConfig;
+
+    #[test]
+    fn test_new_derives_the_display_name() {
+        let metadata = BotMetadata::new("shortest_path_bot");
+        assert_eq!(metadata.display_name, "Shortest Path");
+        assert_eq!(metadata.board_sizes, BoardSizes::ANY);
+        assert!(metadata.params.is_empty());
+        assert_eq!(BotMetadata::new("mcts").display_name, "Mcts");
+    }
+
+    #[test]
+    fn test_with_params_lists_the_config_fields() {
+        let metadata = BotMetadata::new("monte_carlo_bot").with_params(&MonteCarloConfig::default());
+        let simulations = metadata.params.iter().find(|param| param.name == "simulations").unwrap();
+        assert_eq!(simulations.default, serde_json::json!(MonteCarloConfig::default().simulations));
+    }
+
+    #[test]
+    fn test_board_sizes_contains() {
+        let sizes = BoardSizes { min: 3, max: Some(9) };
+        assert!(!sizes.contains(2));
+        assert!(sizes.contains(3));
+        assert!(sizes.contains(9));
+        assert!(!sizes.contains(10));
+        assert!(BoardSizes::ANY.contains(50));
+    }
+}
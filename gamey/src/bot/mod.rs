@@ -6,6 +6,18 @@
 //! - [`YBot`] - A trait that defines the interface for all bots
 //! - [`YBotRegistry`] - A registry for managing multiple bot implementations
 //! - [`RandomBot`] - A simple bot that makes random valid moves
+//! - [`ResistanceBot`] - A bot driven by the electrical-resistance evaluation in [`evaluation`]
+//! - [`BlunderBot`] - A wrapper that weakens another bot with random mistakes
+//! - [`play_game`] - Plays a full game between two bots
+//! - [`MoveAnalysis`] - Scored candidate moves returned by [`YBot::analyze`]
+//! - [`OpeningBook`] and [`BookBot`] - Opening books and the bot that plays from them
+//! - [`BotDecision`] - A placement, swap or resignation returned by [`YBot::decide`]
+//! - [`MctsBot`] - A Monte Carlo Tree Search bot that keeps its tree between moves
+//! - [`PlayoutPolicy`] - How the Monte Carlo bots finish their simulated games
+//! - [`encode_position`] and [`Evaluator`] - Feature planes and learned evaluators such as [`MlpEvaluator`]
+//! - [`solve_endgame`] - Exact solver for positions with few empty cells
+//! - [`BotMetadata`] - The description of a bot returned by [`YBot::metadata`]
+//! - [`CancelToken`] - Cooperative cancellation of the searches of the search bots
 
 pub mod random;
 pub mod ybot;
@@ -16,6 +28,21 @@ pub mod monte_carlo;
 pub mod simple_blocker;
 pub mod triangle_attack;
 pub mod shortest_path;
+pub mod evaluation;
+pub mod resistance;
+pub mod blunder;
+pub mod arena;
+pub mod analysis;
+pub mod decision;
+pub mod opening_book;
+pub mod book_bot;
+pub mod endgame;
+pub mod mcts;
+pub mod playout;
+pub mod encoding;
+pub mod evaluator;
+pub mod metadata;
+pub mod cancel;
 pub use random::*;
 pub use ybot::*;
 pub use ybot_registry::*;
@@ -25,4 +52,19 @@ pub use monte_carlo::*;
 pub use simple_blocker::*;
 pub use triangle_attack::*;
 pub use shortest_path::*;
+pub use evaluation::*;
+pub use resistance::*;
+pub use blunder::*;
+pub use arena::*;
+pub use analysis::*;
+pub use decision::*;
+pub use opening_book::*;
+pub use book_bot::*;
+pub use endgame::*;
+pub use mcts::*;
+pub use playout::*;
+pub use encoding::*;
+pub use evaluator::*;
+pub use metadata::*;
+pub use cancel::*;
 
@@ -1,106 +1,188 @@
 //! Implementation of a hard-level bot
 //!
-//! This module contains the structure [`MonteCarloBot`]. This opponent attempts to 
-//! play with a strategic and probabilistic logic: it uses a Flat Monte Carlo 
-//! algorithm to simulate hundreds of random futures and picks the one with the 
+//! This module contains the structure [`MonteCarloBot`]. This opponent attempts to
+//! play with a strategic and probabilistic logic: it uses a Flat Monte Carlo
+//! algorithm to simulate hundreds of random futures and picks the one with the
 //! highest win rate.
 
-use crate::{Coordinates, GameStatus,GameY, Movement, YBot};
+use crate::{
+    BotDecision, BotMetadata, BotParams, Coordinates, DEFAULT_ENDGAME_CELLS, DecisionOptions,
+    Difficulty, GameY, GameYError, MoveAnalysis, MoveCandidate, PlayoutKind, YBot,
+    check_endgame_cells, config_from_params, endgame_move, search_bot_decision, search_cancelled,
+};
+use rand::RngCore;
 use rand::prelude::IndexedRandom;
-use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+/// Tunable parameters of [`MonteCarloBot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MonteCarloConfig {
+    /// Number of random playouts simulated for each candidate cell.
+    pub simulations: u32,
+    /// How the rest of the board is filled in each simulation.
+    pub playout: PlayoutKind,
+    /// Positions with at most this many empty cells (up to
+    /// [`crate::MAX_SOLVER_CELLS`]) are solved exactly with
+    /// [`crate::solve_endgame`] instead of simulated; 0 turns the solver off.
+    pub endgame_cells: u32,
+}
+
+impl Default for MonteCarloConfig {
+    fn default() -> Self {
+        MonteCarloConfig {
+            simulations: 100,
+            playout: PlayoutKind::Random,
+            endgame_cells: DEFAULT_ENDGAME_CELLS,
+        }
+    }
+}
 
 /// A bot that chooses moves following a Flat Monte Carlo strategy
 ///
 /// To decide where to place its next piece, this bot follows this reasoning:
 /// 1. It identifies all available (empty) cells on the board.
 /// 2. For each empty cell, it clones the current board state and places its piece there.
-/// 3. From that point, it simulates a fixed number of games (e.g., 100) by filling the 
-///    rest of the board with completely random moves for both players.
+/// 3. From that point, it simulates a fixed number of games (100 by default, see
+///    [`MonteCarloConfig::simulations`]) by filling the
+///    rest of the board with completely random moves for both players (or with
+///    the policy chosen in [`MonteCarloConfig::playout`]).
 /// 4. It records how many of those random simulations result in a victory for the bot.
 /// 5. It selects the cell with the highest number of simulated victories.
 /// 6. If multiple cells share the highest score, it randomly chooses one among the best.
 ///
-/// This statistical approach allows the bot to naturally discover complex blocking 
+/// This statistical approach allows the bot to naturally discover complex blocking
 /// and connection strategies without having them explicitly programmed.
-/// 
+///
 /// # Example
 ///
 /// ```
 /// use gamey::{GameY, MonteCarloBot, YBot};
 ///
-/// let bot = MonteCarloBot;
+/// let bot = MonteCarloBot::default();
 /// let game = GameY::new(5);
 ///
 /// // The bot will always return Some when there are available moves
 /// let chosen_move = bot.choose_move(&game);
 /// assert!(chosen_move.is_some());
 /// ```
-pub struct MonteCarloBot;
+#[derive(Debug, Clone, Default)]
+pub struct MonteCarloBot {
+    config: MonteCarloConfig,
+}
+
+impl MonteCarloBot {
+    /// Creates a bot with the given configuration.
+    pub fn new(config: MonteCarloConfig) -> Self {
+        MonteCarloBot { config }
+    }
+
+    /// Creates a bot from request parameters, using defaults for missing fields.
+    pub fn from_params(params: &BotParams) -> Result<Self, GameYError> {
+        let config: MonteCarloConfig = config_from_params("monte_carlo_bot", params)?;
+        if config.simulations == 0 {
+            return Err(GameYError::InvalidBotParams {
+                bot_id: "monte_carlo_bot".to_string(),
+                message: "simulations must be greater than 0".to_string(),
+            });
+        }
+        check_endgame_cells("monte_carlo_bot", config.endgame_cells)?;
+        Ok(MonteCarloBot::new(config))
+    }
+
+    /// Returns the configuration of this bot.
+    pub fn config(&self) -> &MonteCarloConfig {
+        &self.config
+    }
+}
 
 impl YBot for MonteCarloBot {
     fn name(&self) -> &str {
-        "monte_carlo_bot"   
+        "monte_carlo_bot"
+    }
+
+    fn metadata(&self) -> BotMetadata {
+        BotMetadata::new(self.name())
+            .with_description("Plays the move that wins most random playouts.")
+            .with_difficulty(Difficulty::Hard)
+            .with_params(&MonteCarloConfig::default())
     }
 
     fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
-        let available_cells = board.available_cells();
-        if available_cells.is_empty() {
-            return None;
+        self.choose_move_with_rng(board, &mut rand::rng())
+    }
+
+    fn choose_move_with_rng(&self, board: &GameY, rng: &mut dyn RngCore) -> Option<Coordinates> {
+        if let Some(coords) = endgame_move(board, self.config.endgame_cells) {
+            return Some(coords);
         }
+        let (win_counts, _) = self.win_counts(board, rng);
+        let best_score = win_counts.iter().map(|&(_, wins)| wins).max()?;
+        let best_cells: Vec<Coordinates> = win_counts
+            .into_iter()
+            .filter(|&(_, wins)| wins == best_score)
+            .map(|(coords, _)| coords)
+            .collect();
 
-        let bot_id = board.next_player()?;
-        let mut best_score = -1;
-        let mut best_cells = Vec::new();
-        
-        let simulations = 100;
-
-        for &cell in available_cells {
-            let coords = Coordinates::from_index(cell, board.board_size());
-            let mut wins = 0;
-
-            for _ in 0..simulations {
-                let mut simulated_board = board.clone();
-                
-                let _ = simulated_board.add_move(Movement::Placement {
-                    player: bot_id,
-                    coords,
-                });
-
-                let mut remaining_cells = simulated_board.available_cells().clone();
-                remaining_cells.shuffle(&mut rand::rng());
-
-                for random_cell in remaining_cells {
-                    if simulated_board.check_game_over() {
-                        break; 
-                    }
-
-                    if let Some(current_player) = simulated_board.next_player() {
-                        let random_coords = Coordinates::from_index(random_cell, simulated_board.board_size());
-                        let _ = simulated_board.add_move(Movement::Placement {
-                            player: current_player,
-                            coords: random_coords,
-                        });
-                    }
-                }
+        let chosen_coords = best_cells.choose(rng)?;
+        Some(*chosen_coords)
+    }
 
-                if let GameStatus::Finished { winner } = simulated_board.status() {
-                    if *winner == bot_id {
-                        wins += 1;
-                    }
-                }
-            }
+    fn decide_with_rng(
+        &self,
+        board: &GameY,
+        options: &DecisionOptions,
+        rng: &mut dyn RngCore,
+    ) -> BotDecision {
+        search_bot_decision(self, board, options, rng)
+    }
 
-            if best_score < wins {
-                best_score = wins;
-                best_cells.clear();
-                best_cells.push(coords);
-            } else if best_score == wins {
-                best_cells.push(coords);
+    /// Scores every available cell with the win rate of its simulations.
+    ///
+    /// Flat Monte Carlo does not model the replies, so the principal
+    /// variation is just the best move.
+    fn analyze(&self, board: &GameY) -> MoveAnalysis {
+        let (win_counts, playouts) = self.win_counts(board, &mut rand::rng());
+        let candidates = win_counts
+            .into_iter()
+            .map(|(coords, wins)| {
+                let win_rate = wins as f64 / playouts as f64;
+                MoveCandidate::new(coords, win_rate).with_win_rate(win_rate)
+            })
+            .collect();
+        MoveAnalysis::new(candidates)
+    }
+}
+
+impl MonteCarloBot {
+    /// Counts, for every available cell, the random playouts won by the player
+    /// to move after playing there, together with the playouts run per cell.
+    ///
+    /// The playouts go round-robin over the cells, so a cancelled search has
+    /// run the same number of them, at least one, for every cell.
+    fn win_counts(&self, board: &GameY, rng: &mut dyn RngCore) -> (Vec<(Coordinates, u32)>, u32) {
+        let Some(bot_id) = board.next_player() else {
+            return (Vec::new(), 0);
+        };
+        let mut win_counts: Vec<(Coordinates, u32)> = board
+            .available_cells()
+            .iter()
+            .map(|&cell| (Coordinates::from_index(cell, board.board_size()), 0))
+            .collect();
+
+        let mut playouts = 0;
+        while playouts < self.config.simulations {
+            if playouts > 0 && search_cancelled() {
+                break;
+            }
+            for (coords, wins) in &mut win_counts {
+                if self.config.playout.run(board, Some(*coords), rng).winner() == Some(bot_id) {
+                    *wins += 1;
+                }
             }
+            playouts += 1;
         }
-
-        let chosen_coords = best_cells.choose(&mut rand::rng())?;
-        Some(*chosen_coords)
+        (win_counts, playouts)
     }
 }
 
@@ -111,13 +193,65 @@ mod tests {
 
     #[test]
     fn test_monte_carlo_bot_name() {
-        let bot = MonteCarloBot;
+        let bot = MonteCarloBot::default();
         assert_eq!(bot.name(), "monte_carlo_bot");
     }
 
+    #[test]
+    fn test_monte_carlo_bot_default_config() {
+        let bot = MonteCarloBot::default();
+        assert_eq!(bot.config().simulations, 100);
+    }
+
+    #[test]
+    fn test_monte_carlo_bot_from_params() {
+        let mut params = BotParams::new();
+        params.insert("simulations".to_string(), serde_json::Value::from(5));
+        let bot = MonteCarloBot::from_params(&params).unwrap();
+        assert_eq!(bot.config().simulations, 5);
+        assert!(bot.choose_move(&GameY::new(4)).is_some());
+    }
+
+    #[test]
+    fn test_monte_carlo_bot_with_playout_policy() {
+        let mut params = BotParams::new();
+        params.insert("simulations".to_string(), serde_json::Value::from(3));
+        params.insert("playout".to_string(), serde_json::Value::from("pattern"));
+        let bot = MonteCarloBot::from_params(&params).unwrap();
+        assert_eq!(bot.config().playout, PlayoutKind::Pattern);
+        assert!(bot.choose_move(&GameY::new(5)).is_some());
+
+        params.insert("playout".to_string(), serde_json::Value::from("ladder"));
+        assert!(MonteCarloBot::from_params(&params).is_err());
+    }
+
+    #[test]
+    fn test_monte_carlo_bot_rejects_zero_simulations() {
+        let mut params = BotParams::new();
+        params.insert("simulations".to_string(), serde_json::Value::from(0));
+        assert!(MonteCarloBot::from_params(&params).is_err());
+    }
+
+    #[test]
+    fn test_monte_carlo_bot_rejects_too_many_endgame_cells() {
+        let mut params = BotParams::new();
+        params.insert(
+            "endgame_cells".to_string(),
+            serde_json::Value::from(crate::MAX_SOLVER_CELLS + 1),
+        );
+        assert!(MonteCarloBot::from_params(&params).is_err());
+    }
+
+    #[test]
+    fn test_monte_carlo_bot_rejects_unknown_params() {
+        let mut params = BotParams::new();
+        params.insert("depth".to_string(), serde_json::Value::from(3));
+        assert!(MonteCarloBot::from_params(&params).is_err());
+    }
+
     #[test]
     fn test_monte_carlo_bot_returns_move_on_empty_board() {
-        let bot = MonteCarloBot;
+        let bot = MonteCarloBot::default();
         let game = GameY::new(5);
 
         let chosen_move = bot.choose_move(&game);
@@ -126,7 +260,7 @@ mod tests {
 
     #[test]
     fn test_monte_carlo_bot_returns_valid_coordinates() {
-        let bot = MonteCarloBot;
+        let bot = MonteCarloBot::default();
         let game = GameY::new(5);
 
         let coords = bot.choose_move(&game).unwrap();
@@ -139,7 +273,7 @@ mod tests {
 
     #[test]
     fn test_monte_carlo_bot_returns_none_on_full_board() {
-        let bot = MonteCarloBot;
+        let bot = MonteCarloBot::default();
         let mut game = GameY::new(2);
 
         // Fill the board (size 2 has 3 cells)
@@ -170,7 +304,7 @@ mod tests {
 
     #[test]
     fn test_monte_carlo_bot_chooses_from_available_cells() {
-        let bot = MonteCarloBot;
+        let bot = MonteCarloBot::default();
         let mut game = GameY::new(3);
 
         // Make some moves to reduce available cells
@@ -189,7 +323,7 @@ mod tests {
 
     #[test]
     fn test_monte_carlo_bot_multiple_calls_return_valid_moves() {
-        let bot = MonteCarloBot;
+        let bot = MonteCarloBot::default();
         let game = GameY::new(7);
 
         // Call choose_move multiple times to exercise the randomness
@@ -202,4 +336,71 @@ mod tests {
             assert!(game.available_cells().contains(&index));
         }
     }
+
+    #[test]
+    fn test_monte_carlo_bot_analyze_reports_win_rates() {
+        let bot = MonteCarloBot::new(MonteCarloConfig {
+            simulations: 10,
+            endgame_cells: 0,
+            ..Default::default()
+        });
+        let game = GameY::new(4);
+
+        let analysis = bot.analyze(&game);
+        assert_eq!(analysis.candidates.len(), 10);
+        for candidate in &analysis.candidates {
+            let win_rate = candidate.win_rate.unwrap();
+            assert!((0.0..=1.0).contains(&win_rate));
+            assert_eq!(candidate.score, win_rate);
+        }
+        assert_eq!(
+            analysis.principal_variation,
+            vec![analysis.candidates[0].coords]
+        );
+    }
+
+    #[test]
+    fn test_monte_carlo_bot_scores_every_cell_before_cancellation() {
+        let bot = MonteCarloBot::new(MonteCarloConfig {
+            simulations: 10,
+            endgame_cells: 0,
+            ..Default::default()
+        });
+        let game = GameY::new(4);
+        let token = crate::CancelToken::new();
+        token.cancel();
+
+        // One playout per cell, so every win rate is 0 or 1.
+        let analysis = crate::with_cancel_token(&token, || bot.analyze(&game));
+        assert_eq!(analysis.candidates.len(), 10);
+        assert!(
+            analysis
+                .candidates
+                .iter()
+                .all(|candidate| matches!(candidate.win_rate, Some(0.0) | Some(1.0)))
+        );
+        assert!(crate::with_cancel_token(&token, || bot.choose_move(&game)).is_some());
+    }
+
+    #[test]
+    fn test_monte_carlo_bot_swaps_strong_opening_under_pie_rule() {
+        let bot = MonteCarloBot::new(MonteCarloConfig {
+            simulations: 5,
+            endgame_cells: 0,
+            ..Default::default()
+        });
+        let mut game = GameY::new(5);
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(0),
+            coords: Coordinates::new(2, 1, 1),
+        })
+        .unwrap();
+
+        let pie_rule = DecisionOptions::default().with_pie_rule(true);
+        assert_eq!(bot.decide(&game, &pie_rule), BotDecision::Swap);
+        assert!(matches!(
+            bot.decide(&game, &DecisionOptions::default()),
+            BotDecision::Place { .. }
+        ));
+    }
 }
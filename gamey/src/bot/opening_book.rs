@@ -0,0 +1,393 @@
+//! Opening books.
+//!
+//! An [`OpeningBook`] maps positions to weighted moves, so that bots can play
+//! the first moves of a game without searching. Positions are identified by a
+//! hash of their canonical form: the triangular board has six symmetries
+//! (every permutation of the `x`, `y` and `z` coordinates), and all the
+//! positions related by a symmetry share a single entry.
+//!
+//! Books are stored as JSON and are usually built from the [`GameRecord`]s of
+//! a tournament with [`OpeningBook::from_records`]; [`crate::BookBot`] plays
+//! from them.
+//!
+//! # Example
+//!
+//! ```
+//! use gamey::{Coordinates, GameY, OpeningBook};
+//!
+//! let mut book = OpeningBook::new();
+//! book.add(&GameY::new(5), Coordinates::new(2, 1, 1), 3);
+//!
+//! // The same opening is found from any orientation of the board.
+//! let moves = book.moves(&GameY::new(5));
+//! assert_eq!(moves.len(), 1);
+//! assert_eq!(moves[0].weight, 3);
+//! ```
+
+use crate::{BotDecision, Coordinates, GameRecord, GameY, GameYError, PlayerId};
+use rand::RngCore;
+use rand::prelude::IndexedRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The six symmetries of the board, as permutations of the coordinates.
+const SYMMETRIES: [[usize; 3]; 6] = [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+
+/// A move of the book together with how often it should be played.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookMove {
+    /// The cell to play.
+    pub coords: Coordinates,
+    /// Relative weight of the move among the moves of its position.
+    pub weight: u32,
+}
+
+/// Weighted moves indexed by a symmetry-aware position hash.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct OpeningBook {
+    /// Moves of each position, in the orientation of its canonical form.
+    entries: HashMap<u64, Vec<BookMove>>,
+}
+
+impl OpeningBook {
+    /// Creates an empty book.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of positions in the book.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the book has no positions.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds `weight` to the move `coords` of the position `board`.
+    pub fn add(&mut self, board: &GameY, coords: Coordinates, weight: u32) {
+        let canonical = CanonicalPosition::of(board);
+        let coords = canonical.canonical_move(coords);
+        let moves = self.entries.entry(canonical.key).or_default();
+        match moves.iter_mut().find(|m| m.coords == coords) {
+            Some(book_move) => book_move.weight += weight,
+            None => moves.push(BookMove { coords, weight }),
+        }
+    }
+
+    /// Returns the moves of the position `board`, in its own orientation.
+    pub fn moves(&self, board: &GameY) -> Vec<BookMove> {
+        let canonical = CanonicalPosition::of(board);
+        self.entries
+            .get(&canonical.key)
+            .map(|moves| {
+                moves
+                    .iter()
+                    .map(|m| BookMove {
+                        coords: canonical.board_move(m.coords),
+                        weight: m.weight,
+                    })
+                    .filter(|m| board.player_at(&m.coords).is_none())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Picks a move of the position `board` with probability proportional to
+    /// its weight, or `None` if the position is not in the book.
+    pub fn choose(&self, board: &GameY, rng: &mut dyn RngCore) -> Option<Coordinates> {
+        self.moves(board)
+            .choose_weighted(rng, |m| m.weight)
+            .ok()
+            .map(|m| m.coords)
+    }
+
+    /// Removes the moves with a weight below `min_weight`, and the positions
+    /// left without moves.
+    pub fn prune(&mut self, min_weight: u32) {
+        self.entries.retain(|_, moves| {
+            moves.retain(|m| m.weight >= min_weight);
+            !moves.is_empty()
+        });
+    }
+
+    /// Builds a book from the first `depth` moves of each game.
+    ///
+    /// Only the moves of the winner are added, each with weight 1, so the
+    /// weight of a move is the number of games it won. Replaying a game stops
+    /// at its first swap or resignation.
+    ///
+    /// # Errors
+    /// Returns an error if a record contains an invalid move.
+    pub fn from_records(records: &[GameRecord], depth: usize) -> Result<Self, GameYError> {
+        let mut book = OpeningBook::new();
+        for record in records {
+            let mut game = GameY::new(record.size);
+            for (ply, decision) in record.moves.iter().take(depth).enumerate() {
+                let BotDecision::Place { coords } = *decision else {
+                    break;
+                };
+                let player = PlayerId::new(ply as u32 % 2);
+                if player.id() == record.winner {
+                    book.add(&game, coords, 1);
+                }
+                game.add_move(decision.into_movement(player))?;
+            }
+        }
+        Ok(book)
+    }
+
+    /// Parses a book from JSON.
+    pub fn from_json(json: &str) -> Result<Self, GameYError> {
+        serde_json::from_str(json).map_err(|e| GameYError::SerdeError { error: e })
+    }
+
+    /// Serializes the book as JSON.
+    pub fn to_json(&self) -> Result<String, GameYError> {
+        serde_json::to_string(self).map_err(|e| GameYError::SerdeError { error: e })
+    }
+
+    /// Loads a book from a JSON file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, GameYError> {
+        let filename = path.as_ref().display().to_string();
+        let content = std::fs::read_to_string(path).map_err(|e| GameYError::IoError {
+            message: format!("Failed to read file: {}", filename),
+            error: e.to_string(),
+        })?;
+        Self::from_json(&content)
+    }
+
+    /// Saves the book to a JSON file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), GameYError> {
+        let filename = path.as_ref().display().to_string();
+        std::fs::write(path, self.to_json()?).map_err(|e| GameYError::IoError {
+            message: format!("Failed to write file: {}", filename),
+            error: e.to_string(),
+        })
+    }
+}
+
+/// A position reduced to its canonical orientation.
+struct CanonicalPosition {
+    /// Hash of the canonical form and the player to move.
+    key: u64,
+    /// Size of the board.
+    size: u32,
+    /// The symmetries that map the position onto its canonical form.
+    symmetries: Vec<[usize; 3]>,
+}
+
+impl CanonicalPosition {
+    fn of(board: &GameY) -> Self {
+        let size = board.board_size();
+        let mut best: Option<Vec<u8>> = None;
+        let mut symmetries = Vec::new();
+        for symmetry in SYMMETRIES {
+            let cells = transformed_cells(board, symmetry);
+            match &best {
+                Some(current) if cells > *current => {}
+                Some(current) if cells == *current => symmetries.push(symmetry),
+                _ => {
+                    best = Some(cells);
+                    symmetries = vec![symmetry];
+                }
+            }
+        }
+
+        let mut key = fnv1a(FNV_OFFSET, &size.to_le_bytes());
+        let to_move = board.next_player().map_or(u8::MAX, |p| p.id() as u8);
+        key = fnv1a(key, &[to_move]);
+        key = fnv1a(key, &best.unwrap_or_default());
+        CanonicalPosition {
+            key,
+            size,
+            symmetries,
+        }
+    }
+
+    /// Maps a move of the board to the canonical orientation. When the
+    /// position is itself symmetric, equivalent moves map to the same cell.
+    fn canonical_move(&self, coords: Coordinates) -> Coordinates {
+        self.symmetries
+            .iter()
+            .map(|&symmetry| apply(symmetry, coords))
+            .min_by_key(|c| c.to_index(self.size))
+            .unwrap_or(coords)
+    }
+
+    /// Maps a move of the canonical orientation back to the board.
+    fn board_move(&self, coords: Coordinates) -> Coordinates {
+        self.symmetries
+            .first()
+            .map_or(coords, |&symmetry| apply(inverse(symmetry), coords))
+    }
+}
+
+/// Returns the cells of the board transformed by `symmetry`, in index order:
+/// 0 for empty cells and 1 + the player id for stones.
+fn transformed_cells(board: &GameY, symmetry: [usize; 3]) -> Vec<u8> {
+    let size = board.board_size();
+    let mut cells = vec![0; board.total_cells() as usize];
+    for index in 0..board.total_cells() {
+        let coords = Coordinates::from_index(index, size);
+        if let Some(player) = board.player_at(&coords) {
+            cells[apply(symmetry, coords).to_index(size) as usize] = 1 + player.id() as u8;
+        }
+    }
+    cells
+}
+
+fn apply(symmetry: [usize; 3], coords: Coordinates) -> Coordinates {
+    let c = [coords.x(), coords.y(), coords.z()];
+    Coordinates::new(c[symmetry[0]], c[symmetry[1]], c[symmetry[2]])
+}
+
+fn inverse(symmetry: [usize; 3]) -> [usize; 3] {
+    let mut inverse = [0; 3];
+    for (i, &s) in symmetry.iter().enumerate() {
+        inverse[s] = i;
+    }
+    inverse
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// FNV-1a hash, stable across runs and platforms so that books can be shared.
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Movement;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    fn game_with(size: u32, stones: &[(u32, Coordinates)]) -> GameY {
+        let mut game = GameY::new(size);
+        for &(player, coords) in stones {
+            game.add_move(Movement::Placement {
+                player: PlayerId::new(player),
+                coords,
+            })
+            .unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn test_symmetries_are_bijections() {
+        let coords = Coordinates::new(3, 1, 0);
+        for symmetry in SYMMETRIES {
+            assert_eq!(apply(inverse(symmetry), apply(symmetry, coords)), coords);
+        }
+    }
+
+    #[test]
+    fn test_symmetric_positions_share_an_entry() {
+        let mut book = OpeningBook::new();
+        let original = game_with(5, &[(0, Coordinates::new(3, 1, 0))]);
+        book.add(&original, Coordinates::new(1, 2, 1), 2);
+
+        // Swap x and y: the stone and the move are mirrored too.
+        let mirrored = game_with(5, &[(0, Coordinates::new(1, 3, 0))]);
+        let moves = book.moves(&mirrored);
+        assert_eq!(book.len(), 1);
+        assert_eq!(moves, vec![BookMove { coords: Coordinates::new(2, 1, 1), weight: 2 }]);
+        assert_eq!(book.moves(&original)[0].coords, Coordinates::new(1, 2, 1));
+    }
+
+    #[test]
+    fn test_equivalent_moves_of_a_symmetric_position_merge() {
+        let mut book = OpeningBook::new();
+        let empty = GameY::new(4);
+        // The three corners of the empty board are the same opening.
+        book.add(&empty, Coordinates::new(3, 0, 0), 1);
+        book.add(&empty, Coordinates::new(0, 3, 0), 1);
+        book.add(&empty, Coordinates::new(0, 0, 3), 1);
+
+        let moves = book.moves(&empty);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].weight, 3);
+    }
+
+    #[test]
+    fn test_side_to_move_is_part_of_the_key() {
+        let mut book = OpeningBook::new();
+        let stones = [(0, Coordinates::new(2, 1, 1))];
+        book.add(&game_with(5, &stones), Coordinates::new(1, 1, 2), 1);
+        let swapped = game_with(5, &[(1, Coordinates::new(2, 1, 1))]);
+        assert!(book.moves(&swapped).is_empty());
+    }
+
+    #[test]
+    fn test_choose_is_weighted() {
+        let mut book = OpeningBook::new();
+        let game = game_with(5, &[(0, Coordinates::new(3, 1, 0))]);
+        book.add(&game, Coordinates::new(1, 2, 1), 1);
+        book.add(&game, Coordinates::new(0, 2, 2), 0);
+
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..20 {
+            assert_eq!(book.choose(&game, &mut rng), Some(Coordinates::new(1, 2, 1)));
+        }
+        assert_eq!(book.choose(&GameY::new(6), &mut rng), None);
+    }
+
+    #[test]
+    fn test_prune_removes_rare_moves() {
+        let mut book = OpeningBook::new();
+        book.add(&GameY::new(5), Coordinates::new(2, 1, 1), 5);
+        book.add(&GameY::new(4), Coordinates::new(1, 1, 1), 1);
+        book.prune(2);
+        assert_eq!(book.len(), 1);
+        assert!(book.moves(&GameY::new(4)).is_empty());
+    }
+
+    #[test]
+    fn test_from_records_keeps_winning_moves() {
+        let record = GameRecord {
+            size: 4,
+            players: ["a".to_string(), "b".to_string()],
+            moves: vec![
+                BotDecision::Place { coords: Coordinates::new(1, 1, 1) },
+                BotDecision::Place { coords: Coordinates::new(3, 0, 0) },
+                BotDecision::Resign,
+            ],
+            winner: 0,
+        };
+        let book = OpeningBook::from_records(&[record.clone(), record], 4).unwrap();
+        assert_eq!(book.len(), 1);
+        assert_eq!(
+            book.moves(&GameY::new(4)),
+            vec![BookMove { coords: Coordinates::new(1, 1, 1), weight: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut book = OpeningBook::new();
+        book.add(&GameY::new(5), Coordinates::new(2, 1, 1), 4);
+        let json = book.to_json().unwrap();
+        assert_eq!(OpeningBook::from_json(&json).unwrap(), book);
+        assert!(OpeningBook::from_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("book.json");
+        let mut book = OpeningBook::new();
+        book.add(&GameY::new(5), Coordinates::new(2, 1, 1), 4);
+        book.save(&path).unwrap();
+        assert_eq!(OpeningBook::load(&path).unwrap(), book);
+        assert!(OpeningBook::load(dir.path().join("missing.json")).is_err());
+    }
+}
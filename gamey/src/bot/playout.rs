@@ -0,0 +1,558 @@
+//! Playout policies for the Monte Carlo bots.
+//!
+//! A playout finishes a game from a position so that its result can be
+//! counted. Uniformly random stones rarely resemble real play in Y, so this
+//! module provides the [`PlayoutPolicy`] trait and three policies:
+//!
+//! - [`RandomPolicy`] - Uniformly random stones
+//! - [`BridgePolicy`] - Saves two-bridges when the opponent intrudes on them
+//! - [`PatternPolicy`] - Saves bridges and prefers cells around the last stone
+//!
+//! Bots pick one through a [`PlayoutKind`] in their configuration and run it
+//! with [`PlayoutKind::run`], which returns a [`PlayoutBoard`].
+//!
+//! Random playouts take a faster path: a full Y board always has exactly one
+//! winner, and stones never stop connecting once placed, so instead of
+//! checking for a winner after every stone the empty cells are filled in one
+//! pass and the winner is found with a single union-find sweep.
+
+use crate::{Coordinates, GameStatus, GameY, Movement, PlayerId};
+use rand::RngCore;
+use rand::prelude::IndexedRandom;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+/// Chooses the stones of a playout.
+pub trait PlayoutPolicy: Send + Sync {
+    /// Returns the name of the policy.
+    fn name(&self) -> &str;
+
+    /// Chooses the next stone for the player to move, knowing the last stone
+    /// that was placed (by the opponent), if any.
+    fn select(&self, board: &GameY, last_move: Option<Coordinates>, rng: &mut dyn RngCore) -> Option<Coordinates>;
+
+    /// Plays stones chosen by [`PlayoutPolicy::select`] until the game is
+    /// decided and returns the winner.
+    fn playout(&self, board: &mut GameY, last_move: Option<Coordinates>, rng: &mut dyn RngCore) -> Option<PlayerId> {
+        let mut last_move = last_move;
+        while !board.check_game_over() {
+            let (Some(player), Some(coords)) = (board.next_player(), self.select(board, last_move, rng)) else {
+                break;
+            };
+            if board.add_move(Movement::Placement { player, coords }).is_err() {
+                break;
+            }
+            last_move = Some(coords);
+        }
+        winner(board)
+    }
+}
+
+/// The playout policies that bots can be configured with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlayoutKind {
+    /// See [`RandomPolicy`].
+    #[default]
+    Random,
+    /// See [`BridgePolicy`].
+    Bridge,
+    /// See [`PatternPolicy`].
+    Pattern,
+}
+
+impl PlayoutKind {
+    /// Plays `first_move` (if any) for the player to move and then finishes
+    /// the game with this policy. `board` is left untouched.
+    ///
+    /// [`PlayoutKind::Random`] fills the board in one pass (see
+    /// [`PlayoutBoard::random_fill`]); the other policies play stone by stone.
+    pub fn run(self, board: &GameY, first_move: Option<Coordinates>, rng: &mut dyn RngCore) -> PlayoutBoard {
+        if board.check_game_over() {
+            return PlayoutBoard::from_game(board);
+        }
+        if self == PlayoutKind::Random {
+            return PlayoutBoard::random_fill(board, first_move, rng);
+        }
+        let mut simulated_board = board.clone();
+        if let (Some(coords), Some(player)) = (first_move, board.next_player()) {
+            let _ = simulated_board.add_move(Movement::Placement { player, coords });
+        }
+        self.policy().playout(&mut simulated_board, first_move, rng);
+        PlayoutBoard::from_game(&simulated_board)
+    }
+
+    /// Returns the policy of this kind.
+    pub fn policy(self) -> &'static dyn PlayoutPolicy {
+        match self {
+            PlayoutKind::Random => &RandomPolicy,
+            PlayoutKind::Bridge => &BridgePolicy,
+            PlayoutKind::Pattern => &PatternPolicy::DEFAULT,
+        }
+    }
+}
+
+/// Plays uniformly random stones.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomPolicy;
+
+impl PlayoutPolicy for RandomPolicy {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn select(&self, board: &GameY, _last_move: Option<Coordinates>, rng: &mut dyn RngCore) -> Option<Coordinates> {
+        let cell = board.available_cells().choose(rng)?;
+        Some(Coordinates::from_index(*cell, board.board_size()))
+    }
+
+    /// Fills the empty cells in a random order, which is the same as picking
+    /// each stone at random but cheaper.
+    fn playout(&self, board: &mut GameY, _last_move: Option<Coordinates>, rng: &mut dyn RngCore) -> Option<PlayerId> {
+        let mut cells = board.available_cells().clone();
+        cells.shuffle(rng);
+        for cell in cells {
+            if board.check_game_over() {
+                break;
+            }
+            let Some(player) = board.next_player() else {
+                break;
+            };
+            let coords = Coordinates::from_index(cell, board.board_size());
+            if board.add_move(Movement::Placement { player, coords }).is_err() {
+                break;
+            }
+        }
+        winner(board)
+    }
+}
+
+/// Answers intrusions into two-bridges and plays randomly otherwise.
+///
+/// Two stones that are not adjacent but share two empty neighbours are
+/// virtually connected: if the opponent takes one of the shared cells, taking
+/// the other keeps them connected. A stone one row away from a side is
+/// connected to that side in the same way through its two neighbours on it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BridgePolicy;
+
+impl PlayoutPolicy for BridgePolicy {
+    fn name(&self) -> &str {
+        "bridge"
+    }
+
+    fn select(&self, board: &GameY, last_move: Option<Coordinates>, rng: &mut dyn RngCore) -> Option<Coordinates> {
+        bridge_saves(board, last_move)
+            .choose(rng)
+            .copied()
+            .or_else(|| RandomPolicy.select(board, last_move, rng))
+    }
+}
+
+/// Saves bridges like [`BridgePolicy`] and otherwise picks a cell at random,
+/// weighting cells by their relation to the last stone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PatternPolicy {
+    /// Weight of the empty cells adjacent to the last stone.
+    pub adjacent: f64,
+    /// Weight of the empty cells a bridge away from the last stone.
+    pub bridge: f64,
+    /// Weight of every other empty cell.
+    pub other: f64,
+}
+
+impl PatternPolicy {
+    /// The weights used by [`PlayoutKind::Pattern`].
+    pub const DEFAULT: PatternPolicy = PatternPolicy {
+        adjacent: 4.0,
+        bridge: 2.0,
+        other: 1.0,
+    };
+
+    /// Returns the weight of `cell` given the last stone.
+    fn weight(&self, cell: &Coordinates, last_move: Option<&Coordinates>) -> f64 {
+        let Some(last) = last_move else {
+            return self.other;
+        };
+        let distance = [
+            cell.x().abs_diff(last.x()),
+            cell.y().abs_diff(last.y()),
+            cell.z().abs_diff(last.z()),
+        ];
+        match distance.iter().max() {
+            // Neighbours differ by one in two components.
+            Some(1) => self.adjacent,
+            // Bridge cells differ by two in one component and one in the others.
+            Some(2) if distance.iter().filter(|&&d| d == 1).count() == 2 => self.bridge,
+            _ => self.other,
+        }
+    }
+}
+
+impl Default for PatternPolicy {
+    fn default() -> Self {
+        PatternPolicy::DEFAULT
+    }
+}
+
+impl PlayoutPolicy for PatternPolicy {
+    fn name(&self) -> &str {
+        "pattern"
+    }
+
+    fn select(&self, board: &GameY, last_move: Option<Coordinates>, rng: &mut dyn RngCore) -> Option<Coordinates> {
+        if let Some(save) = bridge_saves(board, last_move).choose(rng) {
+            return Some(*save);
+        }
+        let size = board.board_size();
+        let cells: Vec<(Coordinates, f64)> = board
+            .available_cells()
+            .iter()
+            .map(|&cell| {
+                let coords = Coordinates::from_index(cell, size);
+                (coords, self.weight(&coords, last_move.as_ref()))
+            })
+            .collect();
+        let total: f64 = cells.iter().map(|(_, weight)| weight).sum();
+        if total <= 0.0 {
+            return RandomPolicy.select(board, last_move, rng);
+        }
+        let mut target = total * (rng.next_u64() as f64 / u64::MAX as f64);
+        for &(coords, weight) in &cells {
+            if target < weight {
+                return Some(coords);
+            }
+            target -= weight;
+        }
+        cells.last().map(|&(coords, _)| coords)
+    }
+}
+
+/// The stones at the end of a playout, and its winner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayoutBoard {
+    size: u32,
+    owners: Vec<Option<PlayerId>>,
+    winner: Option<PlayerId>,
+}
+
+impl PlayoutBoard {
+    /// Copies the stones and the winner of a game.
+    pub fn from_game(board: &GameY) -> Self {
+        let size = board.board_size();
+        let owners = (0..board.total_cells())
+            .map(|cell| board.player_at(&Coordinates::from_index(cell, size)))
+            .collect();
+        PlayoutBoard {
+            size,
+            owners,
+            winner: winner(board),
+        }
+    }
+
+    /// Fills every empty cell of `board` at random, alternating players from
+    /// the player to move, who takes `first_move` if given, and finds the
+    /// winner with [`sweep_winner`].
+    ///
+    /// This gives the same winner as placing the stones one by one until the
+    /// game ends, because the first group to connect the three sides stays
+    /// connected, and a full board has no second winner.
+    pub fn random_fill(board: &GameY, first_move: Option<Coordinates>, rng: &mut dyn RngCore) -> Self {
+        let size = board.board_size();
+        let mut owners: Vec<Option<PlayerId>> = (0..board.total_cells())
+            .map(|cell| board.player_at(&Coordinates::from_index(cell, size)))
+            .collect();
+        let Some(mut player) = board.next_player() else {
+            return PlayoutBoard::from_game(board);
+        };
+        let mut cells = board.available_cells().clone();
+        if let Some(first) = first_move.map(|coords| coords.to_index(size))
+            && let Some(position) = cells.iter().position(|&cell| cell == first)
+        {
+            cells.swap_remove(position);
+            owners[first as usize] = Some(player);
+            player = PlayerId::new(1 - player.id());
+        }
+        cells.shuffle(rng);
+        for cell in cells {
+            owners[cell as usize] = Some(player);
+            player = PlayerId::new(1 - player.id());
+        }
+        let winner = sweep_winner(size, &owners);
+        PlayoutBoard { size, owners, winner }
+    }
+
+    /// Returns the board size.
+    pub fn board_size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the owner of the cell with the given index, if any.
+    pub fn owner(&self, cell: u32) -> Option<PlayerId> {
+        self.owners.get(cell as usize).copied().flatten()
+    }
+
+    /// Returns the winner of the playout.
+    pub fn winner(&self) -> Option<PlayerId> {
+        self.winner
+    }
+}
+
+/// Returns the player with a group touching the three sides, given the owner
+/// of every cell in index order, with one union-find pass over the board.
+///
+/// Each cell is joined to its already visited neighbours (left, and the two
+/// above it), so the sweep stops as soon as a group reaches the three sides.
+pub fn sweep_winner(size: u32, owners: &[Option<PlayerId>]) -> Option<PlayerId> {
+    const SIDE_A: u8 = 0b001;
+    const SIDE_B: u8 = 0b010;
+    const SIDE_C: u8 = 0b100;
+
+    fn find(parent: &mut [usize], mut cell: usize) -> usize {
+        while parent[cell] != cell {
+            parent[cell] = parent[parent[cell]];
+            cell = parent[cell];
+        }
+        cell
+    }
+
+    let size = size as usize;
+    let mut parent: Vec<usize> = (0..owners.len()).collect();
+    let mut sides = vec![0u8; owners.len()];
+    for row in 0..size {
+        let row_start = row * (row + 1) / 2;
+        for col in 0..=row {
+            let cell = row_start + col;
+            let Some(owner) = owners.get(cell).copied().flatten() else {
+                continue;
+            };
+            // Row `row`, column `col` is the cell (size - 1 - row, col, row - col).
+            sides[cell] = (if row == size - 1 { SIDE_A } else { 0 })
+                | (if col == 0 { SIDE_B } else { 0 })
+                | (if col == row { SIDE_C } else { 0 });
+            let above = row_start - row;
+            let earlier = [
+                (col > 0).then(|| cell - 1),
+                (row > 0 && col > 0).then(|| above + col - 1),
+                (row > 0 && col < row).then(|| above + col),
+            ];
+            for neighbor in earlier.into_iter().flatten() {
+                if owners[neighbor] != Some(owner) {
+                    continue;
+                }
+                let (a, b) = (find(&mut parent, cell), find(&mut parent, neighbor));
+                if a != b {
+                    parent[a] = b;
+                    sides[b] |= sides[a];
+                }
+            }
+            let root = find(&mut parent, cell);
+            if sides[root] == SIDE_A | SIDE_B | SIDE_C {
+                return Some(owner);
+            }
+        }
+    }
+    None
+}
+
+/// Returns the cells that restore a bridge of the player to move after the
+/// opponent's stone at `last_move` took one of its two shared cells.
+pub fn bridge_saves(board: &GameY, last_move: Option<Coordinates>) -> Vec<Coordinates> {
+    let (Some(intrusion), Some(me)) = (last_move, board.next_player()) else {
+        return Vec::new();
+    };
+    let mine: Vec<Coordinates> = board
+        .get_neighbors(&intrusion)
+        .into_iter()
+        .filter(|c| board.player_at(c) == Some(me))
+        .collect();
+    let is_empty = |c: &Coordinates| board.player_at(c).is_none();
+    let mut saves = Vec::new();
+
+    // Bridges between two stones.
+    for (i, a) in mine.iter().enumerate() {
+        let a_neighbors = board.get_neighbors(a);
+        for b in &mine[i + 1..] {
+            if a_neighbors.contains(b) {
+                continue;
+            }
+            let b_neighbors = board.get_neighbors(b);
+            let shared: Vec<&Coordinates> = a_neighbors.iter().filter(|c| b_neighbors.contains(c)).collect();
+            if shared.len() == 2
+                && let Some(other) = shared.into_iter().find(|c| **c != intrusion)
+                && is_empty(other)
+                && !saves.contains(other)
+            {
+                saves.push(*other);
+            }
+        }
+    }
+
+    // Bridges between a stone and a side.
+    let component = |c: &Coordinates, side: usize| [c.x(), c.y(), c.z()][side];
+    for a in &mine {
+        for side in 0..3 {
+            if component(a, side) != 1 || component(&intrusion, side) != 0 {
+                continue;
+            }
+            if let Some(other) = board
+                .get_neighbors(a)
+                .into_iter()
+                .find(|c| component(c, side) == 0 && *c != intrusion)
+                && is_empty(&other)
+                && !saves.contains(&other)
+            {
+                saves.push(other);
+            }
+        }
+    }
+    saves
+}
+
+fn winner(board: &GameY) -> Option<PlayerId> {
+    match board.status() {
+        GameStatus::Finished { winner } => Some(*winner),
+        GameStatus::Ongoing { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn place(game: &mut GameY, player: u32, coords: Coordinates) {
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(player),
+            coords,
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_every_policy_finishes_the_game() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for kind in [PlayoutKind::Random, PlayoutKind::Bridge, PlayoutKind::Pattern] {
+            let mut game = GameY::new(7);
+            let winner = kind.policy().playout(&mut game, None, &mut rng);
+            assert!(winner.is_some(), "{}", kind.policy().name());
+            assert!(game.check_game_over());
+        }
+    }
+
+    #[test]
+    fn test_bridge_saves_between_two_stones() {
+        // Stones at (2,1,1) and (0,2,2) share the cells (1,2,1) and (1,1,2).
+        let mut game = GameY::new(5);
+        place(&mut game, 0, Coordinates::new(2, 1, 1));
+        place(&mut game, 1, Coordinates::new(4, 0, 0));
+        place(&mut game, 0, Coordinates::new(0, 2, 2));
+        place(&mut game, 1, Coordinates::new(1, 1, 2));
+        let intrusion = Some(Coordinates::new(1, 1, 2));
+        let save = Coordinates::new(1, 2, 1);
+        assert!(bridge_saves(&game, intrusion).contains(&save));
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!(bridge_saves(&game, intrusion).contains(&BridgePolicy.select(&game, intrusion, &mut rng).unwrap()));
+        assert!(
+            bridge_saves(&game, intrusion).contains(&PatternPolicy::DEFAULT.select(&game, intrusion, &mut rng).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_bridge_saves_to_the_side() {
+        // (1,1,2) reaches side A through (0,1,3) and (0,2,2).
+        let mut game = GameY::new(5);
+        place(&mut game, 0, Coordinates::new(1, 1, 2));
+        place(&mut game, 1, Coordinates::new(0, 1, 3));
+        assert_eq!(bridge_saves(&game, Some(Coordinates::new(0, 1, 3))), vec![Coordinates::new(0, 2, 2)]);
+    }
+
+    #[test]
+    fn test_no_bridge_saves_without_an_intrusion() {
+        let mut game = GameY::new(5);
+        place(&mut game, 0, Coordinates::new(2, 1, 1));
+        place(&mut game, 1, Coordinates::new(4, 0, 0));
+        assert!(bridge_saves(&game, Some(Coordinates::new(4, 0, 0))).is_empty());
+        assert!(bridge_saves(&game, None).is_empty());
+    }
+
+    #[test]
+    fn test_pattern_weights() {
+        let policy = PatternPolicy::DEFAULT;
+        let last = Coordinates::new(2, 1, 1);
+        assert_eq!(policy.weight(&Coordinates::new(1, 2, 1), Some(&last)), 4.0);
+        assert_eq!(policy.weight(&Coordinates::new(0, 2, 2), Some(&last)), 2.0);
+        assert_eq!(policy.weight(&Coordinates::new(4, 0, 0), Some(&last)), 2.0);
+        assert_eq!(policy.weight(&Coordinates::new(0, 4, 0), Some(&last)), 1.0);
+        assert_eq!(policy.weight(&Coordinates::new(4, 0, 0), None), 1.0);
+    }
+
+    #[test]
+    fn test_random_fill_fills_the_board() {
+        let mut game = GameY::new(6);
+        place(&mut game, 0, Coordinates::new(2, 1, 2));
+        let first = Coordinates::new(3, 1, 1);
+        let playout = PlayoutBoard::random_fill(&game, Some(first), &mut StdRng::seed_from_u64(2));
+        assert_eq!(playout.board_size(), 6);
+        assert!((0..21).all(|cell| playout.owner(cell).is_some()));
+        assert_eq!(playout.owner(first.to_index(6)), Some(PlayerId::new(1)));
+        assert_eq!(playout.owner(Coordinates::new(2, 1, 2).to_index(6)), Some(PlayerId::new(0)));
+        let stones = |id| (0..21).filter(|&cell| playout.owner(cell) == Some(PlayerId::new(id))).count();
+        assert_eq!((stones(0), stones(1)), (11, 10));
+        assert!(playout.winner().is_some());
+    }
+
+    #[test]
+    fn test_sweep_winner_matches_the_game() {
+        // Play random games stone by stone and check the sweep over the final
+        // board finds the same winner as the game.
+        let mut rng = StdRng::seed_from_u64(4);
+        for size in 1..=8 {
+            for _ in 0..10 {
+                let mut game = GameY::new(size);
+                RandomPolicy.playout(&mut game, None, &mut rng);
+                let end = PlayoutBoard::from_game(&game);
+                assert_eq!(sweep_winner(size, &end.owners), end.winner(), "size {size}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_fill_matches_stone_by_stone_winner() {
+        // Filling the rest of the board cannot change who connected first.
+        let mut rng = StdRng::seed_from_u64(6);
+        for _ in 0..50 {
+            let mut game = GameY::new(7);
+            RandomPolicy.playout(&mut game, None, &mut rng);
+            let mut owners = PlayoutBoard::from_game(&game).owners;
+            let mut player = 0;
+            for owner in owners.iter_mut().filter(|owner| owner.is_none()) {
+                *owner = Some(PlayerId::new(player));
+                player = 1 - player;
+            }
+            assert_eq!(sweep_winner(7, &owners), winner(&game));
+        }
+    }
+
+    #[test]
+    fn test_run_leaves_the_board_untouched() {
+        let game = GameY::new(5);
+        let mut rng = StdRng::seed_from_u64(8);
+        for kind in [PlayoutKind::Random, PlayoutKind::Bridge, PlayoutKind::Pattern] {
+            let first = Coordinates::new(2, 1, 1);
+            let playout = kind.run(&game, Some(first), &mut rng);
+            assert_eq!(playout.owner(first.to_index(5)), Some(PlayerId::new(0)));
+            assert!(playout.winner().is_some());
+            assert_eq!(game.available_cells().len(), 15);
+        }
+    }
+
+    #[test]
+    fn test_playout_kind_serialization() {
+        assert_eq!(serde_json::to_value(PlayoutKind::Bridge).unwrap(), "bridge");
+        let kind: PlayoutKind = serde_json::from_str("\"pattern\"").unwrap();
+        assert_eq!(kind.policy().name(), "pattern");
+        assert_eq!(PlayoutKind::default(), PlayoutKind::Random);
+    }
+}
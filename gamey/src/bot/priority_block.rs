@@ -4,8 +4,32 @@
 //! play with a tactical logic: it prioritizes blocking the opponent's paths while 
 //! simultaneously seeking to expand its own groups, with a special focus on edge defense.
 
-use crate::{Coordinates, GameY, YBot};
+use crate::{BotMetadata, Difficulty, BotParams, Coordinates, GameY, GameYError, YBot, config_from_params};
+use rand::RngCore;
 use rand::prelude::IndexedRandom;
+use serde::{Deserialize, Serialize};
+
+/// Tunable parameters of [`PriorityBlockBot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PriorityBlockConfig {
+    /// Points for every adjacent allied piece.
+    pub ally_weight: i32,
+    /// Points for every adjacent enemy piece.
+    pub enemy_weight: i32,
+    /// Extra points for an edge cell that touches an enemy piece.
+    pub edge_block_bonus: i32,
+}
+
+impl Default for PriorityBlockConfig {
+    fn default() -> Self {
+        PriorityBlockConfig {
+            ally_weight: 1,
+            enemy_weight: 2,
+            edge_block_bonus: 3,
+        }
+    }
+}
 
 /// A bot that chooses moves following a defensive expansion strategy
 ///
@@ -20,6 +44,8 @@ use rand::prelude::IndexedRandom;
 /// 5. It selects the cell with the highest total score.
 /// 6. If multiple cells share the highest score, it randomly chooses one among the best.
 ///
+/// The point values above are the defaults of [`PriorityBlockConfig`].
+///
 /// If the board is completely empty (e.g., if it plays the first turn of the game) or 
 /// if no adjacent pieces are found anywhere, all empty cells will tie with a score of 0, 
 /// and the bot will simply place its piece in any random available space.
@@ -29,34 +55,68 @@ use rand::prelude::IndexedRandom;
 /// ```
 /// use gamey::{GameY, PriorityBlockBot, YBot};
 ///
-/// let bot = PriorityBlockBot;
+/// let bot = PriorityBlockBot::default();
 /// let game = GameY::new(5);
 ///
 /// // The bot will always return Some when there are available moves
 /// let chosen_move = bot.choose_move(&game);
 /// assert!(chosen_move.is_some());
 /// ```
+#[derive(Debug, Clone, Default)]
+pub struct PriorityBlockBot {
+    config: PriorityBlockConfig,
+}
+
+impl PriorityBlockBot {
+    /// Creates a bot with the given configuration.
+    pub fn new(config: PriorityBlockConfig) -> Self {
+        PriorityBlockBot { config }
+    }
 
+    /// Creates a bot from request parameters, using defaults for missing fields.
+    pub fn from_params(params: &BotParams) -> Result<Self, GameYError> {
+        Ok(PriorityBlockBot::new(config_from_params("priority_block_bot", params)?))
+    }
 
-pub struct PriorityBlockBot;
+    /// Returns the configuration of this bot.
+    pub fn config(&self) -> &PriorityBlockConfig {
+        &self.config
+    }
+}
 
 impl YBot for PriorityBlockBot {
     fn name(&self) -> &str {
         "priority_block_bot"   
     }
 
+    fn metadata(&self) -> BotMetadata {
+        BotMetadata::new(self.name())
+            .with_description("Blocks the opponent's paths first, with special care for the edges, and expands its groups otherwise.")
+            .with_difficulty(Difficulty::Medium)
+            .with_params(&PriorityBlockConfig::default())
+    }
+
     fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
+        self.choose_move_with_rng(board, &mut rand::rng())
+    }
+
+    fn choose_move_with_rng(&self, board: &GameY, rng: &mut dyn RngCore) -> Option<Coordinates> {
         let available_cells = board.available_cells();
         if available_cells.is_empty() {
             return None;
         }
-        get_better_cell(board,available_cells)      
+        get_better_cell(board, available_cells, &self.config, rng)
     }
     
 }
 
-fn get_better_cell(board: &GameY, available_cells: &Vec<u32>) -> Option<Coordinates> {
-    let mut max_score = -1;
+fn get_better_cell(
+    board: &GameY,
+    available_cells: &[u32],
+    config: &PriorityBlockConfig,
+    rng: &mut dyn RngCore,
+) -> Option<Coordinates> {
+    let mut max_score = i32::MIN;
     let mut best_cells = Vec::new();
     let bot_id = board.next_player()?;
     for &cell in available_cells {
@@ -71,15 +131,15 @@ fn get_better_cell(board: &GameY, available_cells: &Vec<u32>) -> Option<Coordina
             let cell_propietary = board.player_at(&neighbor);
             
             if cell_propietary == Some(bot_id) {
-                actual_score += 1; 
+                actual_score += config.ally_weight;
             } else if cell_propietary.is_some() { 
-                actual_score += 2; 
+                actual_score += config.enemy_weight;
                 touches_enemy = true;
             }       
         }
 
         if is_edge && touches_enemy {
-            actual_score += 3;
+            actual_score += config.edge_block_bonus;
         }
 
         if max_score < actual_score {
@@ -90,7 +150,7 @@ fn get_better_cell(board: &GameY, available_cells: &Vec<u32>) -> Option<Coordina
             best_cells.push(cell);
         } 
     }
-    let chosen_cell = best_cells.choose(&mut rand::rng())?;
+    let chosen_cell = best_cells.choose(rng)?;
     Some(Coordinates::from_index(*chosen_cell, board.board_size()))
 }
 
@@ -104,13 +164,23 @@ mod tests {
 
     #[test]
     fn test_priority_block_bot_name() {
-        let bot = PriorityBlockBot;
+        let bot = PriorityBlockBot::default();
         assert_eq!(bot.name(), "priority_block_bot");
     }
 
+    #[test]
+    fn test_priority_block_bot_from_params() {
+        let mut params = BotParams::new();
+        params.insert("edge_block_bonus".to_string(), serde_json::Value::from(0));
+        let bot = PriorityBlockBot::from_params(&params).unwrap();
+        assert_eq!(bot.config().ally_weight, 1);
+        assert_eq!(bot.config().enemy_weight, 2);
+        assert_eq!(bot.config().edge_block_bonus, 0);
+    }
+
     #[test]
     fn test_priority_block_bot_returns_move_on_empty_board() {
-        let bot = PriorityBlockBot;
+        let bot = PriorityBlockBot::default();
         let game = GameY::new(5);
 
         let chosen_move = bot.choose_move(&game);
@@ -119,7 +189,7 @@ mod tests {
 
     #[test]
     fn test_priority_block_bot_returns_valid_coordinates() {
-        let bot = PriorityBlockBot;
+        let bot = PriorityBlockBot::default();
         let game = GameY::new(5);
 
         let coords = bot.choose_move(&game).unwrap();
@@ -132,7 +202,7 @@ mod tests {
 
     #[test]
     fn test_priority_block_bot_returns_none_on_full_board() {
-        let bot = PriorityBlockBot;
+        let bot = PriorityBlockBot::default();
         let mut game = GameY::new(2);
 
         // Fill the board (size 2 has 3 cells)
@@ -163,7 +233,7 @@ mod tests {
 
     #[test]
     fn test_priority_block_bot_chooses_from_available_cells() {
-        let bot = PriorityBlockBot;
+        let bot = PriorityBlockBot::default();
         let mut game = GameY::new(3);
 
         // Make some moves to reduce available cells
@@ -182,7 +252,7 @@ mod tests {
 
     #[test]
     fn test_priority_block_bot_multiple_calls_return_valid_moves() {
-        let bot = PriorityBlockBot;
+        let bot = PriorityBlockBot::default();
         let game = GameY::new(7);
 
         // Call choose_move multiple times to exercise the randomness
@@ -3,7 +3,8 @@
 //! This module provides [`RandomBot`], a bot that makes random valid moves.
 //! It is useful for testing and as a baseline opponent.
 
-use crate::{Coordinates, GameY, YBot};
+use crate::{BotMetadata, Difficulty, Coordinates, GameY, YBot};
+use rand::RngCore;
 use rand::prelude::IndexedRandom;
 
 /// A bot that chooses moves randomly from the available cells.
@@ -31,9 +32,19 @@ impl YBot for RandomBot {
         "random_bot"
     }
 
+    fn metadata(&self) -> BotMetadata {
+        BotMetadata::new(self.name())
+            .with_description("Plays a random empty cell. A baseline for first games.")
+            .with_difficulty(Difficulty::Easy)
+    }
+
     fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
+        self.choose_move_with_rng(board, &mut rand::rng())
+    }
+
+    fn choose_move_with_rng(&self, board: &GameY, rng: &mut dyn RngCore) -> Option<Coordinates> {
         let available_cells = board.available_cells();
-        let cell = available_cells.choose(&mut rand::rng())?;
+        let cell = available_cells.choose(rng)?;
         let coordinates = Coordinates::from_index(*cell, board.board_size());
         Some(coordinates)
     }
@@ -0,0 +1,361 @@
+//! Implementation of a resistance-based bot
+//!
+//! This module contains the structure [`ResistanceBot`]. This opponent evaluates
+//! every candidate move with the electrical-resistance model from
+//! [`crate::bot::evaluation`], the classic evaluation used by Hex and Y engines.
+
+use crate::{
+    BotDecision, BotMetadata, Coordinates, DEFAULT_ENDGAME_CELLS, DecisionOptions, Difficulty,
+    GameY, MoveAnalysis, MoveCandidate, Movement, YBot, endgame_move, evaluate_resistance,
+    greedy_principal_variation, search_bot_decision, search_cancelled,
+};
+use rand::RngCore;
+use rand::prelude::IndexedRandom;
+
+/// A bot that chooses moves following an electrical-resistance evaluation
+///
+/// To decide where to place its next piece, this bot follows this reasoning:
+/// 1. It identifies all available (empty) cells on the board.
+/// 2. For each empty cell, it clones the board and places its piece there.
+/// 3. It models the resulting board as two resistor networks, one per player,
+///    and measures how hard it is for current to flow between each pair of sides.
+/// 4. It scores the move as `ln(rival_resistance / own_resistance)`, so moves that
+///    both shorten its own connections and cut the rival's are preferred.
+/// 5. It selects the cell with the highest score, breaking ties randomly.
+///
+/// Positions with at most [`DEFAULT_ENDGAME_CELLS`] empty cells are solved
+/// exactly with [`crate::solve_endgame`] instead.
+///
+/// Unlike [`crate::ShortestPathBot`], which only looks at the single shortest
+/// route, the resistance model rewards positions with many alternative routes.
+///
+/// # Example
+///
+/// ```
+/// use gamey::{GameY, ResistanceBot, YBot};
+///
+/// let bot = ResistanceBot;
+/// let game = GameY::new(5);
+///
+/// // The bot will always return Some when there are available moves
+/// let chosen_move = bot.choose_move(&game);
+/// assert!(chosen_move.is_some());
+/// ```
+pub struct ResistanceBot;
+
+impl YBot for ResistanceBot {
+    fn name(&self) -> &str {
+        "resistance_bot"
+    }
+
+    fn metadata(&self) -> BotMetadata {
+        BotMetadata::new(self.name())
+            .with_description("Evaluates every move with the electrical-resistance model used by Hex and Y engines.")
+            .with_difficulty(Difficulty::Expert)
+    }
+
+    fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
+        self.choose_move_with_rng(board, &mut rand::rng())
+    }
+
+    fn choose_move_with_rng(&self, board: &GameY, rng: &mut dyn RngCore) -> Option<Coordinates> {
+        if let Some(coords) = endgame_move(board, DEFAULT_ENDGAME_CELLS) {
+            return Some(coords);
+        }
+        let scores = score_moves(board);
+        let best_score = scores
+            .iter()
+            .map(|&(_, score)| score)
+            .max_by(f64::total_cmp)?;
+        let best_cells: Vec<Coordinates> = scores
+            .into_iter()
+            .filter(|&(_, score)| score == best_score)
+            .map(|(coords, _)| coords)
+            .collect();
+
+        best_cells.choose(rng).copied()
+    }
+
+    fn decide_with_rng(
+        &self,
+        board: &GameY,
+        options: &DecisionOptions,
+        rng: &mut dyn RngCore,
+    ) -> BotDecision {
+        search_bot_decision(self, board, options, rng)
+    }
+
+    /// Scores every available cell with the resistance evaluation, and builds
+    /// the principal variation by letting each side play its best cell in turn.
+    fn analyze(&self, board: &GameY) -> MoveAnalysis {
+        let analysis = |board: &GameY| {
+            MoveAnalysis::new(
+                score_moves(board)
+                    .into_iter()
+                    .map(|(coords, score)| {
+                        MoveCandidate::new(coords, score.clamp(-DECIDED_SCORE, DECIDED_SCORE))
+                    })
+                    .collect(),
+            )
+        };
+        let variation = greedy_principal_variation(board, PRINCIPAL_VARIATION_DEPTH, |board| {
+            analysis(board).best().map(|candidate| candidate.coords)
+        });
+        analysis(board).with_principal_variation(variation)
+    }
+}
+
+/// Number of moves of the principal variation reported by [`ResistanceBot::analyze`](YBot::analyze).
+const PRINCIPAL_VARIATION_DEPTH: usize = 4;
+
+/// Score reported by [`ResistanceBot::analyze`](YBot::analyze) for moves that
+/// win (or lose) outright, which the evaluation scores as infinite; JSON has no
+/// infinities.
+const DECIDED_SCORE: f64 = 1000.0;
+
+/// Scores every available cell for the player to move.
+fn score_moves(board: &GameY) -> Vec<(Coordinates, f64)> {
+    let Some(me) = board.next_player() else {
+        return Vec::new();
+    };
+    let mut scores = Vec::with_capacity(board.available_cells().len());
+    for &cell in board.available_cells() {
+        // A cancelled search keeps the cells it has scored, at least one.
+        if !scores.is_empty() && search_cancelled() {
+            break;
+        }
+        let coords = Coordinates::from_index(cell, board.board_size());
+        let mut simulated_board = board.clone();
+        let _ = simulated_board.add_move(Movement::Placement { player: me, coords });
+        scores.push((coords, evaluate_resistance(&simulated_board, me).score()));
+    }
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameStatus, PlayerId};
+
+    #[test]
+    fn test_resistance_bot_name() {
+        let bot = ResistanceBot;
+        assert_eq!(bot.name(), "resistance_bot");
+    }
+
+    #[test]
+    fn test_resistance_bot_returns_move_on_empty_board() {
+        let bot = ResistanceBot;
+        let game = GameY::new(5);
+
+        assert!(bot.choose_move(&game).is_some());
+    }
+
+    #[test]
+    fn test_resistance_bot_keeps_the_cells_scored_before_cancellation() {
+        let bot = ResistanceBot;
+        let game = GameY::new(5);
+        let token = crate::CancelToken::new();
+        token.cancel();
+
+        assert_eq!(score_moves(&game).len(), 15);
+        assert_eq!(
+            crate::with_cancel_token(&token, || score_moves(&game)).len(),
+            1
+        );
+        assert!(crate::with_cancel_token(&token, || bot.choose_move(&game)).is_some());
+    }
+
+    #[test]
+    fn test_resistance_bot_returns_none_on_full_board() {
+        let bot = ResistanceBot;
+        let mut game = GameY::new(2);
+
+        // Fill the board (size 2 has 3 cells)
+        let moves = vec![
+            Movement::Placement {
+                player: PlayerId::new(0),
+                coords: Coordinates::new(1, 0, 0),
+            },
+            Movement::Placement {
+                player: PlayerId::new(1),
+                coords: Coordinates::new(0, 1, 0),
+            },
+            Movement::Placement {
+                player: PlayerId::new(0),
+                coords: Coordinates::new(0, 0, 1),
+            },
+        ];
+
+        for mv in moves {
+            game.add_move(mv).unwrap();
+        }
+
+        assert!(game.available_cells().is_empty());
+        assert!(bot.choose_move(&game).is_none());
+    }
+
+    #[test]
+    fn test_resistance_bot_chooses_from_available_cells() {
+        let bot = ResistanceBot;
+        let mut game = GameY::new(4);
+
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(0),
+            coords: Coordinates::new(3, 0, 0),
+        })
+        .unwrap();
+
+        let coords = bot.choose_move(&game).unwrap();
+        let index = coords.to_index(game.board_size());
+
+        assert!(game.available_cells().contains(&index));
+    }
+
+    #[test]
+    fn test_resistance_bot_completes_winning_move() {
+        let bot = ResistanceBot;
+        let mut game = GameY::new(3);
+
+        // Player 0 owns two cells of side A and only needs (0, 0, 2) to win
+        let moves = vec![
+            Movement::Placement {
+                player: PlayerId::new(0),
+                coords: Coordinates::new(0, 2, 0),
+            },
+            Movement::Placement {
+                player: PlayerId::new(1),
+                coords: Coordinates::new(2, 0, 0),
+            },
+            Movement::Placement {
+                player: PlayerId::new(0),
+                coords: Coordinates::new(0, 1, 1),
+            },
+            Movement::Placement {
+                player: PlayerId::new(1),
+                coords: Coordinates::new(1, 1, 0),
+            },
+        ];
+        for mv in moves {
+            game.add_move(mv).unwrap();
+        }
+
+        let coords = bot.choose_move(&game).unwrap();
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(0),
+            coords,
+        })
+        .unwrap();
+
+        assert!(
+            matches!(game.status(), GameStatus::Finished { winner } if *winner == PlayerId::new(0))
+        );
+    }
+
+    #[test]
+    fn test_resistance_bot_multiple_calls_return_valid_moves() {
+        let bot = ResistanceBot;
+        let game = GameY::new(7);
+
+        for _ in 0..5 {
+            let coords = bot.choose_move(&game).unwrap();
+            let index = coords.to_index(game.board_size());
+            assert!(index < 28);
+            assert!(game.available_cells().contains(&index));
+        }
+    }
+
+    #[test]
+    fn test_resistance_bot_analyze_scores_winning_moves() {
+        let bot = ResistanceBot;
+        let mut game = GameY::new(3);
+        let moves = vec![
+            Movement::Placement {
+                player: PlayerId::new(0),
+                coords: Coordinates::new(0, 2, 0),
+            },
+            Movement::Placement {
+                player: PlayerId::new(1),
+                coords: Coordinates::new(2, 0, 0),
+            },
+            Movement::Placement {
+                player: PlayerId::new(0),
+                coords: Coordinates::new(0, 1, 1),
+            },
+            Movement::Placement {
+                player: PlayerId::new(1),
+                coords: Coordinates::new(1, 1, 0),
+            },
+        ];
+        for mv in moves {
+            game.add_move(mv).unwrap();
+        }
+
+        let analysis = bot.analyze(&game);
+        assert_eq!(analysis.candidates.len(), 2);
+        // Both remaining cells complete player 0's connection.
+        assert!(analysis.candidates.iter().all(|c| c.score == DECIDED_SCORE));
+        // The winning move ends the game, so the variation stops there.
+        assert_eq!(
+            analysis.principal_variation,
+            vec![analysis.best().unwrap().coords]
+        );
+    }
+
+    #[test]
+    fn test_resistance_bot_swaps_strong_opening_under_pie_rule() {
+        let bot = ResistanceBot;
+        let mut game = GameY::new(5);
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(0),
+            coords: Coordinates::new(2, 1, 1),
+        })
+        .unwrap();
+
+        let pie_rule = DecisionOptions::default().with_pie_rule(true);
+        assert_eq!(bot.decide(&game, &pie_rule), BotDecision::Swap);
+        assert!(matches!(
+            bot.decide(&game, &DecisionOptions::default()),
+            BotDecision::Place { .. }
+        ));
+    }
+
+    #[test]
+    fn test_resistance_bot_resigns_against_double_threat() {
+        let bot = ResistanceBot;
+        let mut game = GameY::new(4);
+        // Player 1 has two winning cells and player 0 cannot win at once.
+        let moves = vec![
+            Movement::Placement {
+                player: PlayerId::new(0),
+                coords: Coordinates::new(2, 1, 0),
+            },
+            Movement::Placement {
+                player: PlayerId::new(1),
+                coords: Coordinates::new(3, 0, 0),
+            },
+            Movement::Placement {
+                player: PlayerId::new(0),
+                coords: Coordinates::new(1, 1, 1),
+            },
+            Movement::Placement {
+                player: PlayerId::new(1),
+                coords: Coordinates::new(2, 0, 1),
+            },
+            Movement::Placement {
+                player: PlayerId::new(1),
+                coords: Coordinates::new(1, 0, 2),
+            },
+        ];
+        for mv in moves {
+            game.add_move(mv).unwrap();
+        }
+
+        let resign = DecisionOptions::default().with_resign(true);
+        assert_eq!(bot.decide(&game, &resign), BotDecision::Resign);
+        assert!(matches!(
+            bot.decide(&game, &DecisionOptions::default()),
+            BotDecision::Place { .. }
+        ));
+    }
+}
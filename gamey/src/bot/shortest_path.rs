@@ -1,50 +1,177 @@
 //! Implementation of a hard-level bot
 //!
-//! This module contains the structure [`ShortestPathBot`]. This opponent attempts to 
-//! play with a strategic and mathematical logic: it evaluates the connectivity 
+//! This module contains the structure [`ShortestPathBot`]. This opponent attempts to
+//! play with a strategic and mathematical logic: it evaluates the connectivity
 //! of the board to find the shortest path to connect all three sides of the triangle,
 //! while simultaneously blocking the opponent's most dangerous paths.
 
-use crate::{Coordinates, GameY, YBot, PlayerId, Movement};
+use crate::{
+    BotDecision, BotMetadata, BotParams, Coordinates, DEFAULT_ENDGAME_CELLS, DecisionOptions,
+    Difficulty, GameY, GameYError, MoveAnalysis, MoveCandidate, Movement, PlayerId, YBot,
+    check_endgame_cells, config_from_params, endgame_move, greedy_principal_variation,
+    search_bot_decision, search_cancelled,
+};
+use rand::RngCore;
 use rand::prelude::IndexedRandom;
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
+/// Tunable parameters of [`ShortestPathBot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShortestPathConfig {
+    /// Weight of each step the move removes from the bot's own shortest connection.
+    pub attack_weight: i64,
+    /// Weight of each step the move adds to the rival's shortest connection.
+    pub block_weight: i64,
+    /// Positions with at most this many empty cells (up to
+    /// [`crate::MAX_SOLVER_CELLS`]) are solved exactly with
+    /// [`crate::solve_endgame`] instead of scored; 0 turns the solver off.
+    pub endgame_cells: u32,
+}
+
+impl Default for ShortestPathConfig {
+    fn default() -> Self {
+        ShortestPathConfig {
+            attack_weight: 10,
+            block_weight: 15,
+            endgame_cells: DEFAULT_ENDGAME_CELLS,
+        }
+    }
+}
+
 /// A bot that chooses moves following a Shortest Path and Connectivity strategy
 ///
 /// To decide where to place its next piece, this bot follows this reasoning:
 /// 1. It identifies all available (empty) cells on the board.
-/// 2. For each cell, it calculates a score based on how many edges (A, B, C) 
+/// 2. For each cell, it calculates a score based on how many edges (A, B, C)
 ///    it can connect or help to connect.
-/// 3. It analyzes neighboring groups to detect "bridge" opportunities that 
+/// 3. It analyzes neighboring groups to detect "bridge" opportunities that
 ///    unite previously separated sides of the board.
-/// 4. It performs the same analysis for the opponent, assigning a high priority 
+/// 4. It performs the same analysis for the opponent, assigning a high priority
 ///    to blocking moves that prevent the rival from completing their triangle.
-/// 5. It selects the cell with the highest combined score (Attack + Defense).
+/// 5. It selects the cell with the highest combined score (Attack + Defense),
+///    weighted by [`ShortestPathConfig::attack_weight`] and
+///    [`ShortestPathConfig::block_weight`].
 ///
-/// This deterministic approach allows the bot to play perfectly in terms of 
+/// This deterministic approach allows the bot to play perfectly in terms of
 /// connectivity, making it a very difficult opponent to beat.
-pub struct ShortestPathBot;
+#[derive(Debug, Clone, Default)]
+pub struct ShortestPathBot {
+    config: ShortestPathConfig,
+}
+
+impl ShortestPathBot {
+    /// Creates a bot with the given configuration.
+    pub fn new(config: ShortestPathConfig) -> Self {
+        ShortestPathBot { config }
+    }
+
+    /// Creates a bot from request parameters, using defaults for missing fields.
+    pub fn from_params(params: &BotParams) -> Result<Self, GameYError> {
+        let config: ShortestPathConfig = config_from_params("shortest_path_bot", params)?;
+        check_endgame_cells("shortest_path_bot", config.endgame_cells)?;
+        Ok(ShortestPathBot::new(config))
+    }
+
+    /// Returns the configuration of this bot.
+    pub fn config(&self) -> &ShortestPathConfig {
+        &self.config
+    }
+}
 
 impl YBot for ShortestPathBot {
     fn name(&self) -> &str {
         "shortest_path_bot"
     }
 
+    fn metadata(&self) -> BotMetadata {
+        BotMetadata::new(self.name())
+            .with_description("Shortens its own path to the three sides while blocking the most dangerous path of the opponent.")
+            .with_difficulty(Difficulty::Hard)
+            .with_params(&ShortestPathConfig::default())
+    }
+
     fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
-        let available_cells = board.available_cells();
-        if available_cells.is_empty() { return None; }
+        self.choose_move_with_rng(board, &mut rand::rng())
+    }
+
+    fn choose_move_with_rng(&self, board: &GameY, rng: &mut dyn RngCore) -> Option<Coordinates> {
+        if let Some(coords) = endgame_move(board, self.config.endgame_cells) {
+            return Some(coords);
+        }
+        let puntuaciones = self.puntuar_casillas(board);
+        let mejor_puntuacion = puntuaciones
+            .iter()
+            .map(|&(_, puntuacion)| puntuacion)
+            .max()?;
+        let mejores_casillas: Vec<Coordinates> = puntuaciones
+            .into_iter()
+            .filter(|&(_, puntuacion)| puntuacion == mejor_puntuacion)
+            .map(|(coords, _)| coords)
+            .collect();
+
+        mejores_casillas.choose(rng).copied()
+    }
+
+    fn decide_with_rng(
+        &self,
+        board: &GameY,
+        options: &DecisionOptions,
+        rng: &mut dyn RngCore,
+    ) -> BotDecision {
+        search_bot_decision(self, board, options, rng)
+    }
 
-        let me = board.next_player()?;
-        let rival = if me.id() == 0 { PlayerId::new(1) } else { PlayerId::new(0) };
+    /// Scores every available cell with the combined attack and defense score,
+    /// and builds the principal variation by letting each side play its best
+    /// cell in turn.
+    fn analyze(&self, board: &GameY) -> MoveAnalysis {
+        let candidates = self
+            .puntuar_casillas(board)
+            .into_iter()
+            .map(|(coords, puntuacion)| MoveCandidate::new(coords, puntuacion as f64))
+            .collect();
+        let variacion = greedy_principal_variation(board, PRINCIPAL_VARIATION_DEPTH, |tablero| {
+            MoveAnalysis::new(
+                self.puntuar_casillas(tablero)
+                    .into_iter()
+                    .map(|(coords, puntuacion)| MoveCandidate::new(coords, puntuacion as f64))
+                    .collect(),
+            )
+            .best()
+            .map(|candidata| candidata.coords)
+        });
+        MoveAnalysis::new(candidates).with_principal_variation(variacion)
+    }
+}
+
+/// Number of moves of the principal variation reported by [`ShortestPathBot::analyze`](YBot::analyze).
+const PRINCIPAL_VARIATION_DEPTH: usize = 6;
+
+impl ShortestPathBot {
+    /// Scores every available cell for the player to move.
+    fn puntuar_casillas(&self, board: &GameY) -> Vec<(Coordinates, i64)> {
+        let Some(me) = board.next_player() else {
+            return Vec::new();
+        };
+        let rival = if me.id() == 0 {
+            PlayerId::new(1)
+        } else {
+            PlayerId::new(0)
+        };
 
         // Distancias iniciales usando números naturales (usize)
         let mi_distancia_antes = calcular_distancia_total(board, me);
         let rival_distancia_antes = calcular_distancia_total(board, rival);
 
-        let mut mejor_puntuacion: i64 = -999999; 
-        let mut mejores_casillas = Vec::new();
+        let mut puntuaciones = Vec::with_capacity(board.available_cells().len());
 
-        for &indice in available_cells {
+        for &indice in board.available_cells() {
+            // Una búsqueda cancelada se queda con las casillas ya puntuadas (al menos una).
+            if !puntuaciones.is_empty() && search_cancelled() {
+                break;
+            }
             let coords = Coordinates::from_index(indice, board.board_size());
 
             let mut tablero_simulado = board.clone();
@@ -58,24 +185,18 @@ impl YBot for ShortestPathBot {
             // Si su distancia sube, el perjuicio pal rival es positivo
             let rival_perjuicio = (rival_distancia_despues as i64) - (rival_distancia_antes as i64);
 
-            let puntuacion_total = (mi_mejora * 10) + (rival_perjuicio * 15);
+            let puntuacion_total = (mi_mejora * self.config.attack_weight)
+                + (rival_perjuicio * self.config.block_weight);
 
-            if puntuacion_total > mejor_puntuacion {
-                mejor_puntuacion = puntuacion_total;
-                mejores_casillas.clear();
-                mejores_casillas.push(coords);
-            } else if puntuacion_total == mejor_puntuacion {
-                mejores_casillas.push(coords);
-            }
+            puntuaciones.push((coords, puntuacion_total));
         }
-
-        mejores_casillas.choose(&mut rand::rng()).copied()
+        puntuaciones
     }
 }
 
 fn calcular_distancia_total(board: &GameY, jugador: PlayerId) -> usize {
     let size = board.board_size();
-    
+
     let dist_a = distancias_desde_borde(board, jugador, "A");
     let dist_b = distancias_desde_borde(board, jugador, "B");
     let dist_c = distancias_desde_borde(board, jugador, "C");
@@ -94,9 +215,13 @@ fn calcular_distancia_total(board: &GameY, jugador: PlayerId) -> usize {
 
             if d1 < 500 && d2 < 500 && d3 < 500 {
                 let mut suma = d1 + d2 + d3;
-                
+
                 if board.player_at(&c) == Some(jugador) || board.player_at(&c).is_none() {
-                    if suma >= 2 { suma -= 2; } else { suma = 0; }
+                    if suma >= 2 {
+                        suma -= 2;
+                    } else {
+                        suma = 0;
+                    }
                 }
 
                 if suma < distancia_minima {
@@ -113,14 +238,18 @@ fn distancias_desde_borde(board: &GameY, jugador: PlayerId, lado: &str) -> Vec<u
     let total_celdas = (size * (size + 1)) / 2;
     let mut distancias = vec![999; total_celdas as usize];
     let mut cola = VecDeque::new();
-    
-    let rival = if jugador.id() == 0 { PlayerId::new(1) } else { PlayerId::new(0) };
+
+    let rival = if jugador.id() == 0 {
+        PlayerId::new(1)
+    } else {
+        PlayerId::new(0)
+    };
 
     for x in 0..size {
         for y in 0..(size - x) {
             let z = size - 1 - x - y;
             let c = Coordinates::new(x, y, z);
-            
+
             let toca = match lado {
                 "A" => c.touches_side_a(),
                 "B" => c.touches_side_b(),
@@ -129,7 +258,11 @@ fn distancias_desde_borde(board: &GameY, jugador: PlayerId, lado: &str) -> Vec<u
             };
 
             if toca && board.player_at(&c) != Some(rival) {
-                let coste = if board.player_at(&c) == Some(jugador) { 0 } else { 1 };
+                let coste = if board.player_at(&c) == Some(jugador) {
+                    0
+                } else {
+                    1
+                };
                 let idx = c.to_index(size) as usize;
                 distancias[idx] = coste;
                 cola.push_back(c);
@@ -142,9 +275,15 @@ fn distancias_desde_borde(board: &GameY, jugador: PlayerId, lado: &str) -> Vec<u
         let d_actual = distancias[idx_actual];
 
         for vecino in board.get_neighbors(&actual) {
-            if board.player_at(&vecino) == Some(rival) { continue; }
+            if board.player_at(&vecino) == Some(rival) {
+                continue;
+            }
 
-            let coste_paso = if board.player_at(&vecino) == Some(jugador) { 0 } else { 1 };
+            let coste_paso = if board.player_at(&vecino) == Some(jugador) {
+                0
+            } else {
+                1
+            };
             let nueva_dist = d_actual + coste_paso;
             let idx_v = vecino.to_index(size) as usize;
 
@@ -157,7 +296,6 @@ fn distancias_desde_borde(board: &GameY, jugador: PlayerId, lado: &str) -> Vec<u
     distancias
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -165,22 +303,64 @@ mod tests {
 
     #[test]
     fn test_shortest_path_bot_name() {
-        let bot = ShortestPathBot;
+        let bot = ShortestPathBot::default();
         assert_eq!(bot.name(), "shortest_path_bot");
     }
 
+    #[test]
+    fn test_shortest_path_bot_default_config() {
+        let bot = ShortestPathBot::default();
+        assert_eq!(
+            bot.config(),
+            &ShortestPathConfig {
+                attack_weight: 10,
+                block_weight: 15,
+                endgame_cells: DEFAULT_ENDGAME_CELLS
+            }
+        );
+    }
+
+    #[test]
+    fn test_shortest_path_bot_from_params() {
+        let mut params = BotParams::new();
+        params.insert("block_weight".to_string(), serde_json::Value::from(30));
+        let bot = ShortestPathBot::from_params(&params).unwrap();
+        assert_eq!(bot.config().attack_weight, 10);
+        assert_eq!(bot.config().block_weight, 30);
+
+        params.insert(
+            "endgame_cells".to_string(),
+            serde_json::Value::from(crate::MAX_SOLVER_CELLS + 1),
+        );
+        assert!(ShortestPathBot::from_params(&params).is_err());
+    }
+
     #[test]
     fn test_shortest_path_bot_returns_move_on_empty_board() {
-        let bot = ShortestPathBot;
+        let bot = ShortestPathBot::default();
         let game = GameY::new(5);
 
         let chosen_move = bot.choose_move(&game);
         assert!(chosen_move.is_some());
     }
 
+    #[test]
+    fn test_shortest_path_bot_keeps_the_cells_scored_before_cancellation() {
+        let bot = ShortestPathBot::default();
+        let game = GameY::new(5);
+        let token = crate::CancelToken::new();
+        token.cancel();
+
+        assert_eq!(
+            crate::with_cancel_token(&token, || bot.puntuar_casillas(&game)).len(),
+            1
+        );
+        assert!(crate::with_cancel_token(&token, || bot.choose_move(&game)).is_some());
+    }
+
     #[test]
     fn test_shortest_path_bot_returns_valid_coordinates() {
-        let bot = ShortestPathBot;
+        let bot = ShortestPathBot::default();
         let game = GameY::new(5);
 
         let coords = bot.choose_move(&game).unwrap();
@@ -192,14 +372,23 @@ mod tests {
 
     #[test]
     fn test_shortest_path_bot_returns_none_on_full_board() {
-        let bot = ShortestPathBot;
+        let bot = ShortestPathBot::default();
         let mut game = GameY::new(2);
 
         // Llenamos el tablero (tamaño 2 tiene 3 celdas)
         let moves = vec![
-            Movement::Placement { player: PlayerId::new(0), coords: Coordinates::new(1, 0, 0) },
-            Movement::Placement { player: PlayerId::new(1), coords: Coordinates::new(0, 1, 0) },
-            Movement::Placement { player: PlayerId::new(0), coords: Coordinates::new(0, 0, 1) },
+            Movement::Placement {
+                player: PlayerId::new(0),
+                coords: Coordinates::new(1, 0, 0),
+            },
+            Movement::Placement {
+                player: PlayerId::new(1),
+                coords: Coordinates::new(0, 1, 0),
+            },
+            Movement::Placement {
+                player: PlayerId::new(0),
+                coords: Coordinates::new(0, 0, 1),
+            },
         ];
 
         for mv in moves {
@@ -212,13 +401,14 @@ mod tests {
 
     #[test]
     fn test_shortest_path_bot_chooses_from_available_cells() {
-        let bot = ShortestPathBot;
+        let bot = ShortestPathBot::default();
         let mut game = GameY::new(3);
 
         game.add_move(Movement::Placement {
             player: PlayerId::new(0),
             coords: Coordinates::new(2, 0, 0),
-        }).unwrap();
+        })
+        .unwrap();
 
         let coords = bot.choose_move(&game).unwrap();
         let index = coords.to_index(game.board_size());
@@ -228,7 +418,7 @@ mod tests {
 
     #[test]
     fn test_shortest_path_bot_multiple_calls_return_valid_moves() {
-        let bot = ShortestPathBot;
+        let bot = ShortestPathBot::default();
         let game = GameY::new(7);
 
         for _ in 0..10 {
@@ -238,4 +428,45 @@ mod tests {
             assert!(game.available_cells().contains(&index));
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_shortest_path_bot_analyze_ranks_every_cell() {
+        let bot = ShortestPathBot::default();
+        let game = GameY::new(5);
+
+        let analysis = bot.analyze(&game);
+        assert_eq!(analysis.candidates.len(), 15);
+        assert!(
+            analysis
+                .candidates
+                .windows(2)
+                .all(|w| w[0].score >= w[1].score)
+        );
+        assert_eq!(
+            analysis.principal_variation.len(),
+            PRINCIPAL_VARIATION_DEPTH
+        );
+        assert_eq!(
+            analysis.principal_variation[0],
+            analysis.candidates[0].coords
+        );
+    }
+
+    #[test]
+    fn test_shortest_path_bot_swaps_strong_opening_under_pie_rule() {
+        let bot = ShortestPathBot::default();
+        let mut game = GameY::new(5);
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(0),
+            coords: Coordinates::new(2, 1, 1),
+        })
+        .unwrap();
+
+        let pie_rule = DecisionOptions::default().with_pie_rule(true);
+        assert_eq!(bot.decide(&game, &pie_rule), BotDecision::Swap);
+        assert!(matches!(
+            bot.decide(&game, &DecisionOptions::default()),
+            BotDecision::Place { .. }
+        ));
+    }
+}
@@ -4,7 +4,8 @@
 //! play with a defensive logic: it seeks to obstruct the opponent's progress by 
 //! placing its pieces in the immediate vicinity of the rival's pieces.
 
-use crate::{Coordinates, GameY, PlayerId, YBot};
+use crate::{BotMetadata, Difficulty, Coordinates, GameY, PlayerId, YBot};
+use rand::RngCore;
 use rand::prelude::IndexedRandom;
 
 /// A bot that chooses moves following the simple blocking strategy
@@ -39,7 +40,17 @@ impl YBot for SimpleBlockerBot {
         "simple_blocker_bot"
     }
 
+    fn metadata(&self) -> BotMetadata {
+        BotMetadata::new(self.name())
+            .with_description("Plays next to the opponent's stones to get in their way.")
+            .with_difficulty(Difficulty::Easy)
+    }
+
     fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
+        self.choose_move_with_rng(board, &mut rand::rng())
+    }
+
+    fn choose_move_with_rng(&self, board: &GameY, rng: &mut dyn RngCore) -> Option<Coordinates> {
         let available_cells = board.available_cells();
         if available_cells.is_empty() {
             return None;
@@ -57,16 +68,16 @@ impl YBot for SimpleBlockerBot {
         let enemy_cells = board.cells_for_player(enemy_id);
 
         // Find available spots to block the opponent
-        let candidates = obtain_enemy_neighbors(enemy_cells, board, &available_cells);
+        let candidates = obtain_enemy_neighbors(enemy_cells, board, available_cells);
 
         if candidates.is_empty() {
             // Panic mode: if no blocking move is found, play randomly
-            let cell_index = available_cells.choose(&mut rand::rng())?;
+            let cell_index = available_cells.choose(rng)?;
             let coordinates = Coordinates::from_index(*cell_index, board.board_size());
             Some(coordinates)
         } else {
             // Blocking move: choose a random neighbor of the opponent
-            let cell_index = candidates.choose(&mut rand::rng())?;
+            let cell_index = candidates.choose(rng)?;
             let coordinates = Coordinates::from_index(*cell_index, board.board_size());
             Some(coordinates)
         }
@@ -74,7 +85,7 @@ impl YBot for SimpleBlockerBot {
 }
 
 /// Helper function to find empty cells adjacent to opponent's pieces
-fn obtain_enemy_neighbors(enemy_cells: Vec<Coordinates>,board: &GameY,available_cells: &Vec<u32>,) -> Vec<u32> {
+fn obtain_enemy_neighbors(enemy_cells: Vec<Coordinates>,board: &GameY,available_cells: &[u32],) -> Vec<u32> {
     let mut candidates = Vec::new();
 
     for cell in enemy_cells {
@@ -3,35 +3,97 @@
 //! This module provides [`TriangleAttackBot`], a bot that focuses on 
 //! connectivity. It prioritizes joining its own pieces to form a network.
 
-use crate::{Coordinates, GameY, YBot, PlayerId};
+use crate::{BotMetadata, Difficulty, BotParams, Coordinates, GameY, GameYError, YBot, PlayerId, config_from_params};
+use rand::RngCore;
 use rand::prelude::IndexedRandom;
+use serde::{Deserialize, Serialize};
+
+/// Tunable parameters of [`TriangleAttackBot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TriangleAttackConfig {
+    /// Points for every adjacent own piece.
+    pub neighbor_weight: i32,
+    /// Extra points for a junction, a cell touching two or more own pieces.
+    pub junction_bonus: i32,
+    /// Points for each side the cell lies on.
+    pub edge_weight: i32,
+    /// Points for each side the cell is one row away from.
+    pub near_edge_weight: i32,
+    /// Points taken from a cell that touches no own piece.
+    pub isolation_penalty: i32,
+}
+
+impl Default for TriangleAttackConfig {
+    fn default() -> Self {
+        TriangleAttackConfig {
+            neighbor_weight: 3,
+            junction_bonus: 5,
+            edge_weight: 3,
+            near_edge_weight: 1,
+            isolation_penalty: 2,
+        }
+    }
+}
 
 /// A bot that chooses moves to maximize its own network connectivity.
 ///
 /// This bot scans for "junctions"—empty cells that are neighbors to two
 /// or more of its own pieces. If no junctions are found, it expands
-/// from its existing pieces.
+/// from its existing pieces. The point values are set in
+/// [`TriangleAttackConfig`].
 ///
 /// # Example
 ///
 /// ```
 /// use gamey::{GameY, TriangleAttackBot, YBot};
 ///
-/// let bot = TriangleAttackBot;
+/// let bot = TriangleAttackBot::default();
 /// let game = GameY::new(5);
 ///
 /// // The bot will always return Some when there are available moves
 /// let chosen_move = bot.choose_move(&game);
 /// assert!(chosen_move.is_some());
 /// ```
-pub struct TriangleAttackBot;
+#[derive(Debug, Clone, Default)]
+pub struct TriangleAttackBot {
+    config: TriangleAttackConfig,
+}
+
+impl TriangleAttackBot {
+    /// Creates a bot with the given configuration.
+    pub fn new(config: TriangleAttackConfig) -> Self {
+        TriangleAttackBot { config }
+    }
+
+    /// Creates a bot from request parameters, using defaults for missing fields.
+    pub fn from_params(params: &BotParams) -> Result<Self, GameYError> {
+        Ok(TriangleAttackBot::new(config_from_params("triangle_attack_bot", params)?))
+    }
+
+    /// Returns the configuration of this bot.
+    pub fn config(&self) -> &TriangleAttackConfig {
+        &self.config
+    }
+}
 
 impl YBot for TriangleAttackBot {
     fn name(&self) -> &str {
         "triangle_attack_bot"
     }
+
+    fn metadata(&self) -> BotMetadata {
+        BotMetadata::new(self.name())
+            .with_description("Joins its stones into a network, favouring cells that connect several of them.")
+            .with_difficulty(Difficulty::Medium)
+            .with_params(&TriangleAttackConfig::default())
+    }
  
     fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
+        self.choose_move_with_rng(board, &mut rand::rng())
+    }
+
+    fn choose_move_with_rng(&self, board: &GameY, rng: &mut dyn RngCore) -> Option<Coordinates> {
         let available_cells = board.available_cells();
         if available_cells.is_empty() {
             return None;
@@ -44,12 +106,12 @@ impl YBot for TriangleAttackBot {
             .iter()
             .map(|&idx| {
                 let coords = Coordinates::from_index(idx, board_size);
-                let score = score_cell(board, &coords, actual_player, board_size);
+                let score = score_cell(board, &coords, actual_player, &self.config);
                 (idx, score)
             })
             .collect();
  
-        scored.sort_by(|a, b| b.1.cmp(&a.1));
+        scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
  
         let top_score = scored[0].1;
         let top_candidates: Vec<u32> = scored
@@ -58,46 +120,46 @@ impl YBot for TriangleAttackBot {
             .map(|(idx, _)| *idx)
             .collect();
  
-        let chosen = top_candidates.choose(&mut rand::rng()).copied()?;
+        let chosen = top_candidates.choose(rng).copied()?;
         Some(Coordinates::from_index(chosen, board_size))
     }
 }
  
-fn score_cell(board: &GameY, coords: &Coordinates, player: PlayerId, board_size: u32) -> i32 {
+fn score_cell(board: &GameY, coords: &Coordinates, player: PlayerId, config: &TriangleAttackConfig) -> i32 {
     let mut score = 0i32;
  
     let my_neighbors = count_my_neighbors(board, coords, player);
-    score += my_neighbors as i32 * 3;
+    score += my_neighbors as i32 * config.neighbor_weight;
  
     if my_neighbors >= 2 {
-        score += 5;
+        score += config.junction_bonus;
     }
  
-    let edge_score = edge_proximity(coords, board_size);
+    let edge_score = edge_proximity(coords, config);
     score += edge_score;
  
     if my_neighbors == 0 {
-        score -= 2;
+        score -= config.isolation_penalty;
     }
  
     score
 }
  
-fn edge_proximity(coords: &Coordinates, _board_size: u32) -> i32 {
+fn edge_proximity(coords: &Coordinates, config: &TriangleAttackConfig) -> i32 {
     let x = coords.x() as i32;
     let y = coords.y() as i32;
     let z = coords.z() as i32;
  
     let mut bonus = 0i32;
  
-    if x == 0 { bonus += 3; }
-    else if x == 1 { bonus += 1; }
+    if x == 0 { bonus += config.edge_weight; }
+    else if x == 1 { bonus += config.near_edge_weight; }
  
-    if y == 0 { bonus += 3; }
-    else if y == 1 { bonus += 1; }
+    if y == 0 { bonus += config.edge_weight; }
+    else if y == 1 { bonus += config.near_edge_weight; }
  
-    if z == 0 { bonus += 3; }
-    else if z == 1 { bonus += 1; }
+    if z == 0 { bonus += config.edge_weight; }
+    else if z == 1 { bonus += config.near_edge_weight; }
  
     bonus
 }
@@ -116,13 +178,13 @@ mod tests {
  
     #[test]
     fn test_triangle_attack_bot_name() {
-        let bot = TriangleAttackBot;
+        let bot = TriangleAttackBot::default();
         assert_eq!(bot.name(), "triangle_attack_bot");
     }
  
     #[test]
     fn test_triangle_attack_bot_returns_move_on_empty_board() {
-        let bot = TriangleAttackBot;
+        let bot = TriangleAttackBot::default();
         let game = GameY::new(5);
         let chosen_move = bot.choose_move(&game);
         assert!(chosen_move.is_some());
@@ -130,7 +192,7 @@ mod tests {
  
     #[test]
     fn test_triangle_attack_bot_returns_valid_coordinates() {
-        let bot = TriangleAttackBot;
+        let bot = TriangleAttackBot::default();
         let game = GameY::new(5);
         let coords = bot.choose_move(&game).unwrap();
         let index = coords.to_index(game.board_size());
@@ -139,7 +201,7 @@ mod tests {
  
     #[test]
     fn test_triangle_attack_bot_returns_none_on_full_board() {
-        let bot = TriangleAttackBot;
+        let bot = TriangleAttackBot::default();
         let mut game = GameY::new(2);
         let moves = vec![
             Movement::Placement {
@@ -164,7 +226,7 @@ mod tests {
  
     #[test]
     fn test_triangle_attack_bot_chooses_from_available_cells() {
-        let bot = TriangleAttackBot;
+        let bot = TriangleAttackBot::default();
         let mut game = GameY::new(3);
         game.add_move(Movement::Placement {
             player: PlayerId::new(0),
@@ -175,9 +237,21 @@ mod tests {
         assert!(game.available_cells().contains(&index));
     }
  
+    #[test]
+    fn test_triangle_attack_bot_from_params() {
+        let params: BotParams = serde_json::from_str(r#"{"junction_bonus": 8, "edge_weight": 0}"#).unwrap();
+        let bot = TriangleAttackBot::from_params(&params).unwrap();
+        assert_eq!(bot.config().junction_bonus, 8);
+        assert_eq!(bot.config().edge_weight, 0);
+        assert_eq!(bot.config().neighbor_weight, 3);
+
+        let unknown: BotParams = serde_json::from_str(r#"{"bonus": 1}"#).unwrap();
+        assert!(TriangleAttackBot::from_params(&unknown).is_err());
+    }
+
     #[test]
     fn test_triangle_attack_bot_multiple_calls_return_valid_moves() {
-        let bot = TriangleAttackBot;
+        let bot = TriangleAttackBot::default();
         let game = GameY::new(7);
         for _ in 0..10 {
             let coords = bot.choose_move(&game).unwrap();
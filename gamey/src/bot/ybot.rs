@@ -1,4 +1,5 @@
-use crate::{Coordinates, GameY};
+use crate::{BotDecision, BotMetadata, Coordinates, DecisionOptions, GameY, MoveAnalysis, single_move_analysis};
+use rand::RngCore;
 
 /// Trait representing a Y game bot (YBot)
 /// A YBot is an AI that can choose moves in the game of Y.
@@ -9,4 +10,48 @@ pub trait YBot: Send + Sync {
 
     /// Chooses a move based on the current game state.
     fn choose_move(&self, board: &GameY) -> Option<Coordinates>;
+
+    /// Describes the bot for clients choosing an opponent.
+    ///
+    /// The default implementation describes the bot by its name alone (see
+    /// [`BotMetadata::new`]).
+    fn metadata(&self) -> BotMetadata {
+        BotMetadata::new(self.name())
+    }
+
+    /// Chooses a move drawing all of its randomness from `rng`.
+    ///
+    /// Seeding `rng` makes the choice reproducible, which tournaments and
+    /// other offline tools rely on. The default implementation ignores `rng`
+    /// and calls [`YBot::choose_move`], so bots without randomness need not
+    /// override it.
+    fn choose_move_with_rng(&self, board: &GameY, rng: &mut dyn RngCore) -> Option<Coordinates> {
+        let _ = rng;
+        self.choose_move(board)
+    }
+
+    /// Scores the candidate moves of the current position.
+    ///
+    /// Bots that evaluate every cell return all of them, best first, so that
+    /// clients can show a heatmap or explain a hint. The default implementation
+    /// returns only the move chosen by [`YBot::choose_move`], with a score of 0.
+    fn analyze(&self, board: &GameY) -> MoveAnalysis {
+        single_move_analysis(self, board)
+    }
+
+    /// Decides what to do on the bot's turn: place a stone, swap or resign.
+    ///
+    /// `options` tells which actions besides placing are allowed in the match.
+    fn decide(&self, board: &GameY, options: &DecisionOptions) -> BotDecision {
+        self.decide_with_rng(board, options, &mut rand::rng())
+    }
+
+    /// Like [`YBot::decide`], drawing all of its randomness from `rng`.
+    ///
+    /// The default implementation places the stone chosen by
+    /// [`YBot::choose_move_with_rng`], and resigns only when there is none.
+    fn decide_with_rng(&self, board: &GameY, options: &DecisionOptions, rng: &mut dyn RngCore) -> BotDecision {
+        let _ = options;
+        BotDecision::from_choice(self.choose_move_with_rng(board, rng))
+    }
 }
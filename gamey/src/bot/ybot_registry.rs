@@ -1,11 +1,27 @@
 //! Registry for managing YBot implementations.
 //!
 //! The [`YBotRegistry`] provides a centralized way to register and retrieve
-//! bot implementations by name.
+//! bot implementations by name. Besides fixed bot instances, the registry can
+//! hold factories that build parameterized bots from a [`BotParams`] object,
+//! either passed explicitly or written as a query string in the bot spec
+//! (e.g. `monte_carlo_bot?simulations=500`).
 
 use std::{collections::HashMap, sync::Arc};
 
-use crate::YBot;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::{BotMetadata, GameYError, YBot};
+
+/// Parameters used to build a configurable bot.
+///
+/// Keys are the field names of the bot configuration type (for example
+/// `simulations` for [`crate::MonteCarloConfig`]).
+pub type BotParams = Map<String, Value>;
+
+/// A function that builds a bot instance from a set of parameters.
+pub type BotFactory =
+    Arc<dyn Fn(&BotParams) -> Result<Arc<dyn YBot>, GameYError> + Send + Sync>;
 
 /// A registry that stores and manages [`YBot`] implementations.
 ///
@@ -16,16 +32,22 @@ use crate::YBot;
 ///
 /// ```
 /// use std::sync::Arc;
-/// use gamey::{YBotRegistry, RandomBot,ShortestPathBot,MonteCarloBot};
+/// use gamey::{YBotRegistry, RandomBot, ShortestPathBot, MonteCarloBot};
 ///
-///let registry = YBotRegistry::new()
-///     .with_bot(Arc::new(RandomBot)).with_bot(Arc::new(ShortestPathBot)).with_bot(Arc::new(MonteCarloBot));
+/// let registry = YBotRegistry::new()
+///     .with_bot(Arc::new(RandomBot))
+///     .with_bot(Arc::new(ShortestPathBot::default()))
+///     .with_factory("monte_carlo_bot", |params| Ok(Arc::new(MonteCarloBot::from_params(params)?)));
 ///
 /// let bot = registry.find("random_bot");
 /// assert!(bot.is_some());
+///
+/// let tuned = registry.build("monte_carlo_bot?simulations=500");
+/// assert!(tuned.is_ok());
 /// ```
 pub struct YBotRegistry {
     bots: HashMap<String, Arc<dyn YBot>>,
+    factories: HashMap<String, BotFactory>,
 }
 
 impl YBotRegistry {
@@ -33,6 +55,7 @@ impl YBotRegistry {
     pub fn new() -> Self {
         YBotRegistry {
             bots: HashMap::new(),
+            factories: HashMap::new(),
         }
     }
 
@@ -44,16 +67,153 @@ impl YBotRegistry {
         self
     }
 
+    /// Adds a bot factory to the registry and returns the registry for chaining.
+    ///
+    /// See [`YBotRegistry::register_factory`].
+    pub fn with_factory<F>(mut self, name: &str, factory: F) -> Self
+    where
+        F: Fn(&BotParams) -> Result<Arc<dyn YBot>, GameYError> + Send + Sync + 'static,
+    {
+        self.register_factory(name, factory);
+        self
+    }
+
+    /// Registers a factory that builds parameterized instances of a bot.
+    ///
+    /// The factory is called with the parameters of each request. When a bot
+    /// is requested without parameters and no fixed instance was registered
+    /// with [`YBotRegistry::with_bot`], the factory is called with empty
+    /// parameters, so it must fall back to the bot's defaults.
+    pub fn register_factory<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn(&BotParams) -> Result<Arc<dyn YBot>, GameYError> + Send + Sync + 'static,
+    {
+        self.factories.insert(name.to_string(), Arc::new(factory));
+    }
+
+    /// Keeps only the bots called `names` and returns the registry for chaining.
+    ///
+    /// # Errors
+    /// [`GameYError::BotNotFound`] if one of the names is not registered.
+    pub fn with_only(mut self, names: &[String]) -> Result<Self, GameYError> {
+        if let Some(missing) = names
+            .iter()
+            .find(|name| !self.bots.contains_key(*name) && !self.factories.contains_key(*name))
+        {
+            return Err(GameYError::BotNotFound {
+                bot_id: missing.clone(),
+                available: self.names().join(", "),
+            });
+        }
+        self.bots.retain(|name, _| names.contains(name));
+        self.factories.retain(|name, _| names.contains(name));
+        Ok(self)
+    }
+
+    /// Makes `defaults` the parameters of the bot called `name` and returns
+    /// the registry for chaining.
+    ///
+    /// Requests without parameters get a bot built with `defaults`, and the
+    /// parameters of a request override them key by key.
+    ///
+    /// # Errors
+    /// The errors of [`YBotRegistry::create`] with `defaults`.
+    pub fn with_default_params(mut self, name: &str, defaults: BotParams) -> Result<Self, GameYError> {
+        let Some(factory) = self.factories.get(name).map(Arc::clone) else {
+            let checked = self.create(name, &defaults);
+            return checked.map(|_| self);
+        };
+        factory(&defaults)?;
+        // Bots built from a factory are not shared, so drop any fixed instance.
+        self.bots.remove(name);
+        self.register_factory(name, move |params| {
+            let mut merged = defaults.clone();
+            merged.extend(params.clone());
+            factory(&merged)
+        });
+        Ok(self)
+    }
+
     /// Finds a bot by name.
     ///
     /// Returns `Some(bot)` if a bot with the given name exists, `None` otherwise.
+    /// Bots registered only through a factory are built with their default
+    /// parameters; if the factory fails, the error is logged and `None` is returned.
     pub fn find(&self, name: &str) -> Option<Arc<dyn YBot>> {
-        self.bots.get(name).cloned()
+        match self.bots.get(name) {
+            Some(bot) => Some(Arc::clone(bot)),
+            None => match (self.factories.get(name)?)(&BotParams::new()) {
+                Ok(bot) => Some(bot),
+                Err(err) => {
+                    tracing::error!("Failed to build {} with its default parameters: {}", name, err);
+                    None
+                }
+            },
+        }
+    }
+
+    /// Creates a bot by name with the given parameters.
+    ///
+    /// Empty parameters behave like [`YBotRegistry::find`].
+    ///
+    /// # Errors
+    /// - [`GameYError::BotNotFound`] if no bot or factory has that name
+    /// - [`GameYError::InvalidBotParams`] if the parameters are rejected by the
+    ///   factory, or if parameters are given for a bot that has no factory
+    pub fn create(&self, name: &str, params: &BotParams) -> Result<Arc<dyn YBot>, GameYError> {
+        if params.is_empty()
+            && let Some(bot) = self.bots.get(name)
+        {
+            return Ok(Arc::clone(bot));
+        }
+        match self.factories.get(name) {
+            Some(factory) => factory(params),
+            None if self.bots.contains_key(name) => Err(GameYError::InvalidBotParams {
+                bot_id: name.to_string(),
+                message: "this bot does not accept parameters".to_string(),
+            }),
+            None => Err(GameYError::BotNotFound {
+                bot_id: name.to_string(),
+                available: self.names().join(", "),
+            }),
+        }
+    }
+
+    /// Creates a bot from a spec of the form `name` or `name?key=value&key=value`.
+    ///
+    /// Values in the query string are read as JSON scalars when possible
+    /// (`500` is a number, `true` a boolean) and as strings otherwise.
+    pub fn build(&self, spec: &str) -> Result<Arc<dyn YBot>, GameYError> {
+        let (name, params) = parse_bot_spec(spec);
+        self.create(name, &params)
     }
 
     /// Returns a list of all registered bot names.
     pub fn names(&self) -> Vec<String> {
-        self.bots.keys().cloned().collect()
+        let mut names: Vec<String> = self.bots.keys().cloned().collect();
+        for name in self.factories.keys() {
+            if !self.bots.contains_key(name) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+
+    /// Describes the bot registered under `name` (see [`YBot::metadata`]).
+    ///
+    /// Bots registered only through a factory are described by an instance
+    /// built with their default parameters.
+    pub fn metadata(&self, name: &str) -> Option<BotMetadata> {
+        let mut metadata = self.find(name)?.metadata();
+        metadata.id = name.to_string();
+        Some(metadata)
+    }
+
+    /// Describes every registered bot, sorted by id.
+    pub fn catalogue(&self) -> Vec<BotMetadata> {
+        let mut names = self.names();
+        names.sort();
+        names.iter().filter_map(|name| self.metadata(name)).collect()
     }
 }
 
@@ -63,6 +223,41 @@ impl Default for YBotRegistry {
     }
 }
 
+/// Splits a bot spec such as `monte_carlo_bot?simulations=500` into the bot
+/// name and its parameters.
+pub fn parse_bot_spec(spec: &str) -> (&str, BotParams) {
+    let (name, query) = match spec.split_once('?') {
+        Some((name, query)) => (name, query),
+        None => (spec, ""),
+    };
+    let mut params = BotParams::new();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = serde_json::from_str::<Value>(value)
+            .ok()
+            .filter(|v| !v.is_object() && !v.is_array())
+            .unwrap_or_else(|| Value::String(value.to_string()));
+        params.insert(key.to_string(), value);
+    }
+    (name, params)
+}
+
+/// Deserializes a bot configuration from its parameters.
+///
+/// Missing fields take their default value; unknown fields or values of the
+/// wrong type produce [`GameYError::InvalidBotParams`].
+pub fn config_from_params<T: DeserializeOwned>(
+    bot_id: &str,
+    params: &BotParams,
+) -> Result<T, GameYError> {
+    serde_json::from_value(Value::Object(params.clone())).map_err(|e| {
+        GameYError::InvalidBotParams {
+            bot_id: bot_id.to_string(),
+            message: e.to_string(),
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,6 +330,100 @@ mod tests {
         assert!(registry.find("random_bot").is_some());
     }
 
+    fn mock_factory(params: &BotParams) -> Result<Arc<dyn YBot>, GameYError> {
+        let name = params
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("factory_bot");
+        Ok(Arc::new(MockBot::new(name)))
+    }
+
+    #[test]
+    fn test_catalogue_describes_every_bot() {
+        let registry = YBotRegistry::new()
+            .with_bot(Arc::new(RandomBot))
+            .with_factory("factory_bot", mock_factory);
+        let catalogue = registry.catalogue();
+        let ids: Vec<&str> = catalogue.iter().map(|bot| bot.id.as_str()).collect();
+        assert_eq!(ids, vec!["factory_bot", "random_bot"]);
+        assert_eq!(catalogue[1], RandomBot.metadata());
+        // The id is the registered name even when the bot reports another one.
+        assert_eq!(registry.metadata("factory_bot").unwrap().display_name, "Factory");
+        assert!(registry.metadata("missing").is_none());
+    }
+
+    #[test]
+    fn test_with_factory_is_listed_and_found() {
+        let registry = YBotRegistry::new().with_factory("factory_bot", mock_factory);
+
+        assert_eq!(registry.names(), vec!["factory_bot".to_string()]);
+        assert_eq!(registry.find("factory_bot").unwrap().name(), "factory_bot");
+    }
+
+    #[test]
+    fn test_register_factory_builds_with_params() {
+        let mut registry = YBotRegistry::new();
+        registry.register_factory("factory_bot", mock_factory);
+
+        let bot = registry.build("factory_bot?name=tuned").unwrap();
+        assert_eq!(bot.name(), "tuned");
+    }
+
+    #[test]
+    fn test_create_without_params_prefers_instance() {
+        let registry = YBotRegistry::new()
+            .with_bot(Arc::new(MockBot::new("factory_bot")))
+            .with_factory("factory_bot", |_| Ok(Arc::new(MockBot::new("from_factory"))));
+
+        assert_eq!(registry.names().len(), 1);
+        let bot = registry.create("factory_bot", &BotParams::new()).unwrap();
+        assert_eq!(bot.name(), "factory_bot");
+    }
+
+    #[test]
+    fn test_create_unknown_bot() {
+        let registry = YBotRegistry::new().with_bot(Arc::new(RandomBot));
+
+        let err = registry.build("nonexistent").err().unwrap();
+        assert!(matches!(err, GameYError::BotNotFound { .. }));
+    }
+
+    #[test]
+    fn test_create_params_for_bot_without_factory() {
+        let registry = YBotRegistry::new().with_bot(Arc::new(RandomBot));
+
+        let err = registry.build("random_bot?seed=1").err().unwrap();
+        assert!(matches!(err, GameYError::InvalidBotParams { .. }));
+    }
+
+    #[test]
+    fn test_parse_bot_spec() {
+        let (name, params) = parse_bot_spec("monte_carlo_bot?simulations=500&rave=true&label=fast");
+        assert_eq!(name, "monte_carlo_bot");
+        assert_eq!(params.get("simulations"), Some(&Value::from(500)));
+        assert_eq!(params.get("rave"), Some(&Value::from(true)));
+        assert_eq!(params.get("label"), Some(&Value::from("fast")));
+    }
+
+    #[test]
+    fn test_parse_bot_spec_without_params() {
+        let (name, params) = parse_bot_spec("random_bot");
+        assert_eq!(name, "random_bot");
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn test_config_from_params_rejects_wrong_type() {
+        #[derive(serde::Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Config {
+            simulations: u32,
+        }
+        let (_, params) = parse_bot_spec("bot?simulations=many");
+        let err = config_from_params::<Config>("bot", &params).unwrap_err();
+        assert!(matches!(err, GameYError::InvalidBotParams { .. }));
+    }
+
     #[test]
     fn test_duplicate_name_overwrites() {
         let bot1 = Arc::new(MockBot::new("same_name"));
@@ -144,4 +433,59 @@ mod tests {
 
         assert_eq!(registry.names().len(), 1);
     }
+
+    /// A registry whose `labelled` bot is named after its `label` parameter.
+    fn labelled_registry() -> YBotRegistry {
+        YBotRegistry::new()
+            .with_bot(Arc::new(RandomBot))
+            .with_factory("labelled", |params| {
+                let label = params.get("label").and_then(Value::as_str).unwrap_or("default");
+                if label.is_empty() {
+                    return Err(GameYError::InvalidBotParams {
+                        bot_id: "labelled".to_string(),
+                        message: "label must not be empty".to_string(),
+                    });
+                }
+                Ok(Arc::new(MockBot::new(label)))
+            })
+    }
+
+    #[test]
+    fn test_with_only_keeps_the_named_bots() {
+        let registry = labelled_registry().with_only(&["labelled".to_string()]).unwrap();
+        assert_eq!(registry.names(), vec!["labelled".to_string()]);
+        assert!(registry.find("random_bot").is_none());
+
+        let err = labelled_registry().with_only(&["missing".to_string()]).map(|_| ()).unwrap_err();
+        assert!(matches!(err, GameYError::BotNotFound { bot_id, .. } if bot_id == "missing"));
+    }
+
+    #[test]
+    fn test_with_default_params() {
+        let (_, defaults) = parse_bot_spec("labelled?label=configured");
+        let registry = labelled_registry().with_default_params("labelled", defaults).unwrap();
+        assert_eq!(registry.find("labelled").unwrap().name(), "configured");
+        let (_, params) = parse_bot_spec("labelled?label=requested");
+        assert_eq!(registry.create("labelled", &params).unwrap().name(), "requested");
+
+        let (_, empty_label) = parse_bot_spec("labelled?label=");
+        let err = labelled_registry().with_default_params("labelled", empty_label).map(|_| ()).unwrap_err();
+        assert!(matches!(err, GameYError::InvalidBotParams { .. }));
+        let (_, params) = parse_bot_spec("random_bot?seed=1");
+        let err = labelled_registry().with_default_params("random_bot", params).map(|_| ()).unwrap_err();
+        assert!(matches!(err, GameYError::InvalidBotParams { .. }));
+    }
+
+    #[test]
+    fn test_find_with_a_failing_factory() {
+        let registry = YBotRegistry::new().with_factory("broken", |_| {
+            Err(GameYError::InvalidBotParams {
+                bot_id: "broken".to_string(),
+                message: "no defaults".to_string(),
+            })
+        });
+        assert!(registry.find("broken").is_none());
+        let err = registry.create("broken", &BotParams::new()).map(|_| ()).unwrap_err();
+        assert!(matches!(err, GameYError::InvalidBotParams { .. }));
+    }
 }
@@ -0,0 +1,124 @@
+//! Move analysis endpoint.
+//!
+//! Instead of a single move, this endpoint returns every candidate a bot
+//! considered with its score (see [`crate::YBot::analyze`]), so that clients
+//! can draw a heatmap of the board or explain why a hint was suggested.
+
+use crate::{
+    MoveAnalysis, check_api_version,
+    choose::{ChooseParams, ChooseRequest, parse_position},
+    error::{ErrorCode, ErrorResponse},
+    state::AppState,
+};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Query parameters accepted by the analyze endpoint.
+#[derive(Deserialize, Debug, Default)]
+pub struct AnalyzeQuery {
+    /// Maximum number of candidates to return (all of them if omitted).
+    pub limit: Option<usize>,
+}
+
+/// Response returned by the analyze endpoint on success.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct AnalyzeResponse {
+    /// The API version used for this request.
+    pub api_version: String,
+    /// The bot that analyzed the position.
+    pub bot_id: String,
+    /// The candidate moves, best first, and the principal variation.
+    #[serde(flatten)]
+    pub analysis: MoveAnalysis,
+}
+
+/// Handler for the move analysis endpoint.
+///
+/// # Route
+/// `POST /{api_version}/ybot/analyze/{bot_id}?limit={n}`
+///
+/// # Request Body
+/// The same body as the choose endpoint (see [`ChooseRequest`]). The position
+/// is analyzed for `bot_player` when it is given, and for the player to move
+/// otherwise. Only placements are ranked, so `pie_rule` and `allow_resign`
+/// have no effect here.
+///
+/// # Response
+/// On success, returns an `AnalyzeResponse` with the ranked candidates. A
+/// finished position cannot be analyzed and yields a 400 `INVALID_POSITION` error.
+#[utoipa::path(
+    post,
+    path = "/{api_version}/ybot/analyze/{bot_id}",
+    tag = "bots",
+    params(
+        ("api_version" = String, Path, description = "The API version, `v1` or `v2`"),
+        ("bot_id" = String, Path, description = "The bot that analyzes the position"),
+        ("limit" = Option<usize>, Query, description = "Maximum number of candidates to return"),
+    ),
+    request_body = ChooseRequest,
+    responses(
+        (status = 200, description = "The ranked candidate moves", body = AnalyzeResponse),
+        (status = 400, description = "Unsupported version or invalid position", body = ErrorResponse),
+        (status = 404, description = "Unknown bot", body = ErrorResponse),
+        (status = 429, description = "Too many searches running or waiting", body = ErrorResponse),
+        (status = 503, description = "No analysis finished before the deadline", body = ErrorResponse),
+    )
+)]
+#[axum::debug_handler]
+pub async fn analyze(
+    State(state): State<AppState>,
+    Path(params): Path<ChooseParams>,
+    Query(query): Query<AnalyzeQuery>,
+    Json(request): Json<ChooseRequest>,
+) -> Result<Json<AnalyzeResponse>, ErrorResponse> {
+    check_api_version(&params.api_version)?;
+    let (game_y, _) = parse_position(request.yen, request.bot_player, &params.api_version, &params.bot_id)?;
+    if game_y.check_game_over() {
+        return Err(ErrorResponse::new(
+            ErrorCode::InvalidPosition,
+            "Invalid position: the game is already over",
+            Some(params.api_version),
+            Some(params.bot_id),
+        ));
+    }
+    let bot = state
+        .create_bot(&params.bot_id, &request.params, request.session_id.as_deref())
+        .map_err(|err| ErrorResponse::from_error(&err, Some(params.api_version.clone()), Some(params.bot_id.clone())))?;
+    let timeout = state.compute().timeout(request.timeout_ms);
+    let mut analysis = state
+        .compute()
+        .run_request(timeout, &params.api_version, Some(&params.bot_id), move || Ok(bot.analyze(&game_y)))
+        .await?;
+    if let Some(limit) = query.limit {
+        analysis.truncate(limit);
+    }
+    Ok(Json(AnalyzeResponse {
+        api_version: params.api_version,
+        bot_id: params.bot_id,
+        analysis,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Coordinates, MoveCandidate};
+
+    #[test]
+    fn test_analyze_response_flattens_the_analysis() {
+        let response = AnalyzeResponse {
+            api_version: "v1".to_string(),
+            bot_id: "shortest_path_bot".to_string(),
+            analysis: MoveAnalysis::new(vec![MoveCandidate::new(Coordinates::new(1, 0, 0), 2.0)]),
+        };
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["candidates"][0]["score"], 2.0);
+        assert_eq!(json["principal_variation"][0]["x"], 1);
+        let back: AnalyzeResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(back, response);
+    }
+}
@@ -0,0 +1,298 @@
+//! Batch move endpoint.
+//!
+//! Analytics jobs and load tests ask for moves in many unrelated positions.
+//! `POST /{api_version}/ybot/choose_batch/{bot_id}` answers all of them in
+//! one request: each position is computed with its own bot in the compute
+//! pool of the server (see [`crate::limits`]), at most
+//! [`AppState::batch_concurrency`] at a time, and a position that fails
+//! yields an error in its place instead of failing the batch. The optional
+//! `timeout_ms` is a deadline for the whole batch, and a batch whose client
+//! disconnects stops computing.
+
+use crate::{
+    BotParams, DecisionOptions, MoveResponse, YBot, YEN, check_api_version,
+    choose::{ChooseParams, finished_game_response, parse_position, play_bot_move},
+    error::{ErrorCode, ErrorResponse},
+    limits::ComputePool,
+    state::AppState,
+};
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use utoipa::ToSchema;
+
+/// Largest number of positions accepted in one batch.
+pub const MAX_BATCH_POSITIONS: usize = 1000;
+
+/// Request body of the batch endpoint.
+///
+/// The `params` and the decision options (see [`DecisionOptions`]) apply to
+/// every position.
+///
+/// # Example
+/// ```json
+/// {
+///   "positions": [
+///     { "size": 3, "turn": 0, "players": ["B", "R"], "layout": "./../..." },
+///     { "size": 3, "turn": 1, "players": ["B", "R"], "layout": "B/../..." }
+///   ],
+///   "params": { "simulations": 200 },
+///   "timeout_ms": 5000
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct ChooseBatchRequest {
+    /// The positions, in YEN format.
+    pub positions: Vec<YEN>,
+    /// Parameters used to build the bot, see [`crate::YBotRegistry::create`].
+    #[serde(default, skip_serializing_if = "BotParams::is_empty")]
+    #[schema(value_type = Object)]
+    pub params: BotParams,
+    /// The actions besides placing a stone the bot may answer with.
+    #[serde(flatten)]
+    pub options: DecisionOptions,
+    /// How long the whole batch may take, in milliseconds, capped at the
+    /// server's [`crate::ComputeLimits::max_timeout_ms`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+/// The answer for one position of a batch: `{"move": {...}}` with the same
+/// [`MoveResponse`] as the choose endpoint, or `{"error": {...}}`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchMove {
+    /// The bot's move.
+    Move(MoveResponse),
+    /// Why no move could be chosen for the position.
+    Error(ErrorResponse),
+}
+
+/// Response of the batch endpoint.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct ChooseBatchResponse {
+    /// The API version used for this request.
+    pub api_version: String,
+    /// The bot that selected the moves.
+    pub bot_id: String,
+    /// The answer for each position, in the order of the request.
+    pub results: Vec<BatchMove>,
+}
+
+/// Handler for the batch move selection endpoint.
+///
+/// # Route
+/// `POST /{api_version}/ybot/choose_batch/{bot_id}`
+///
+/// # Request Body
+/// A [`ChooseBatchRequest`].
+///
+/// # Response
+/// A [`ChooseBatchResponse`] with one result per position. Unknown bots and
+/// invalid `params` fail the whole request, as do batches of more than
+/// [`MAX_BATCH_POSITIONS`] positions. Positions that are not computed
+/// before the deadline get a `TIMEOUT` error.
+#[utoipa::path(
+    post,
+    path = "/{api_version}/ybot/choose_batch/{bot_id}",
+    tag = "bots",
+    params(
+        ("api_version" = String, Path, description = "The API version, `v1` or `v2`"),
+        ("bot_id" = String, Path, description = "The bot to ask"),
+    ),
+    request_body = ChooseBatchRequest,
+    responses(
+        (status = 200, description = "A move or an error for each position", body = ChooseBatchResponse),
+        (status = 400, description = "Unsupported version, invalid parameters or too many positions", body = ErrorResponse),
+        (status = 404, description = "Unknown bot", body = ErrorResponse),
+    )
+)]
+#[axum::debug_handler]
+pub async fn choose_batch(
+    State(state): State<AppState>,
+    Path(params): Path<ChooseParams>,
+    Json(request): Json<ChooseBatchRequest>,
+) -> Result<Json<ChooseBatchResponse>, ErrorResponse> {
+    check_api_version(&params.api_version)?;
+    let api_version = params.api_version;
+    let bot_id = params.bot_id;
+    if request.positions.len() > MAX_BATCH_POSITIONS {
+        return Err(ErrorResponse::new(
+            ErrorCode::InvalidRequest,
+            &format!(
+                "Too many positions: {}, a batch holds at most {}",
+                request.positions.len(),
+                MAX_BATCH_POSITIONS
+            ),
+            Some(api_version),
+            Some(bot_id),
+        ));
+    }
+    let create_bot = || {
+        state
+            .bots()
+            .create(&bot_id, &request.params)
+            .map_err(|err| ErrorResponse::from_error(&err, Some(api_version.clone()), Some(bot_id.clone())))
+    };
+    // Unknown bots and invalid params fail even an empty batch.
+    create_bot()?;
+    let compute = state.compute();
+    let deadline = Instant::now() + compute.timeout(request.timeout_ms);
+    let semaphore = Arc::new(Semaphore::new(state.batch_concurrency()));
+    // Dropping the set, e.g. when the client disconnects, aborts the positions
+    // still waiting or running.
+    let mut tasks = JoinSet::new();
+    let mut indices = HashMap::with_capacity(request.positions.len());
+    for (index, yen) in request.positions.into_iter().enumerate() {
+        // Bots such as MCTS keep state between moves, so every position gets its own.
+        let bot = create_bot()?;
+        let (semaphore, compute) = (Arc::clone(&semaphore), Arc::clone(&compute));
+        let (api_version, bot_id, options) = (api_version.clone(), bot_id.clone(), request.options);
+        let task = tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.expect("the semaphore is never closed");
+            compute_one(&compute, deadline, bot, yen, options, api_version, bot_id).await
+        });
+        indices.insert(task.id(), index);
+    }
+    let mut results: Vec<Option<BatchMove>> = (0..indices.len()).map(|_| None).collect();
+    while let Some(joined) = tasks.join_next_with_id().await {
+        let (id, result) = match joined {
+            Ok((id, result)) => (id, result),
+            Err(err) => (
+                err.id(),
+                BatchMove::Error(ErrorResponse::new(
+                    ErrorCode::InternalError,
+                    &format!("Failed to compute the move: {}", err),
+                    Some(api_version.clone()),
+                    Some(bot_id.clone()),
+                )),
+            ),
+        };
+        results[indices[&id]] = Some(result);
+    }
+    let results = results.into_iter().map(|result| result.expect("every task was joined")).collect();
+    Ok(Json(ChooseBatchResponse {
+        api_version,
+        bot_id,
+        results,
+    }))
+}
+
+/// Answers one position in the compute pool, within what is left until the
+/// deadline of the batch.
+async fn compute_one(
+    compute: &ComputePool,
+    deadline: Instant,
+    bot: Arc<dyn YBot>,
+    yen: YEN,
+    options: DecisionOptions,
+    api_version: String,
+    bot_id: String,
+) -> BatchMove {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    let (version, id) = (api_version.clone(), bot_id.clone());
+    compute
+        .run(remaining, move || choose_one(bot.as_ref(), yen, &options, version, id))
+        .await
+        .unwrap_or_else(|err| BatchMove::Error(ErrorResponse::from_error(&err, Some(api_version), Some(bot_id))))
+}
+
+/// Answers one position like the choose endpoint does.
+fn choose_one(bot: &dyn YBot, yen: YEN, options: &DecisionOptions, api_version: String, bot_id: String) -> BatchMove {
+    let start = Instant::now();
+    let result = parse_position(yen, None, &api_version, &bot_id).and_then(|(game_y, bot_player)| {
+        match finished_game_response(&game_y, bot_player, &api_version, &bot_id) {
+            Some(response) => Ok(response),
+            None => play_bot_move(bot, game_y, bot_player, options, api_version, bot_id, start).map(|Json(response)| response),
+        }
+    });
+    match result {
+        Ok(response) => BatchMove::Move(response),
+        Err(error) => BatchMove::Error(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot_server::create_default_state;
+
+    fn request(positions: Vec<YEN>) -> Json<ChooseBatchRequest> {
+        Json(ChooseBatchRequest {
+            positions,
+            params: BotParams::new(),
+            options: DecisionOptions::default(),
+            timeout_ms: None,
+        })
+    }
+
+    fn params(bot_id: &str) -> Path<ChooseParams> {
+        Path(ChooseParams {
+            api_version: "v1".to_string(),
+            bot_id: bot_id.to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_batch_keeps_the_order_of_the_positions() {
+        let positions = vec![
+            YEN::new(3, 0, vec!['B', 'R'], "./../...".to_string()),
+            YEN::new(3, 0, vec!['B', 'R'], "invalid".to_string()),
+            YEN::new(2, 1, vec!['B', 'R'], "B/BB".to_string()),
+        ];
+        let state = create_default_state().with_batch_concurrency(2);
+        let Json(response) = choose_batch(State(state), params("random_bot"), request(positions)).await.unwrap();
+        assert_eq!(response.results.len(), 3);
+        assert!(matches!(&response.results[0], BatchMove::Move(m) if !m.status.finished));
+        assert!(matches!(&response.results[1], BatchMove::Error(e) if e.code == ErrorCode::InvalidYen));
+        assert!(matches!(&response.results[2], BatchMove::Move(m) if m.status.finished));
+    }
+
+    #[tokio::test]
+    async fn test_batch_with_unknown_bot_fails() {
+        let positions = vec![YEN::new(3, 0, vec!['B', 'R'], "./../...".to_string())];
+        let error = choose_batch(State(create_default_state()), params("missing"), request(positions))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::BotNotFound);
+    }
+
+    #[tokio::test]
+    async fn test_batch_rejects_too_many_positions() {
+        let positions = vec![YEN::new(1, 0, vec!['B', 'R'], ".".to_string()); MAX_BATCH_POSITIONS + 1];
+        let error = choose_batch(State(create_default_state()), params("random_bot"), request(positions))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidRequest);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_batch_stops_its_searches() {
+        let position = YEN::new(7, 0, vec!['B', 'R'], "./../.../..../...../....../.......".to_string());
+        let mut request = request(vec![position; 4]);
+        request.params.insert("iterations".to_string(), serde_json::Value::from(u32::MAX));
+        let state = create_default_state().with_batch_concurrency(1);
+        let batch = choose_batch(State(state.clone()), params("mcts_bot"), request);
+        assert!(tokio::time::timeout(std::time::Duration::from_millis(50), batch).await.is_err());
+
+        let start = Instant::now();
+        while state.compute().pending() > 0 {
+            assert!(start.elapsed() < std::time::Duration::from_secs(2), "the searches outlived the batch");
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+    }
+
+    #[test]
+    fn test_batch_move_json() {
+        let error = ErrorResponse::new(ErrorCode::InvalidYen, "bad", None, None);
+        let json = serde_json::to_value(BatchMove::Error(error)).unwrap();
+        assert_eq!(json["error"]["code"], "INVALID_YEN");
+    }
+}
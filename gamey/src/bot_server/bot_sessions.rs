@@ -0,0 +1,176 @@
+//! Bot instances kept per client session.
+//!
+//! Most bots are stateless and are built again on every request, but search
+//! bots such as [`crate::MctsBot`] keep their tree between moves. Requests
+//! that carry a `session_id` get the same bot instance on every move of the
+//! game, so that the bot can continue its previous search.
+
+use crate::{BotParams, GameYError, YBot};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Default number of session bots kept before the least recently used is dropped.
+pub const DEFAULT_SESSION_CAPACITY: usize = 1024;
+
+/// A bot instance and what it was built from.
+struct SessionBot {
+    params: BotParams,
+    bot: Arc<dyn YBot>,
+    last_used: Instant,
+}
+
+/// The bot instances of the active sessions, keyed by session and bot id.
+///
+/// Holds at most [`BotSessions::capacity`] bots; when full, the least
+/// recently used one is dropped.
+pub struct BotSessions {
+    bots: Mutex<HashMap<(String, String), SessionBot>>,
+    capacity: usize,
+}
+
+impl Default for BotSessions {
+    fn default() -> Self {
+        BotSessions::new(DEFAULT_SESSION_CAPACITY)
+    }
+}
+
+impl BotSessions {
+    /// Creates an empty store that keeps at most `capacity` bots.
+    pub fn new(capacity: usize) -> Self {
+        BotSessions {
+            bots: Mutex::new(HashMap::new()),
+            capacity,
+        }
+    }
+
+    /// Returns the maximum number of bots kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of bots kept.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns true if no bot is kept.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bot of `session_id` for `bot_id`, building it with `create`
+    /// the first time or when the parameters changed.
+    ///
+    /// # Errors
+    /// Returns the error of `create`; nothing is stored in that case.
+    pub fn get_or_create(
+        &self,
+        session_id: &str,
+        bot_id: &str,
+        params: &BotParams,
+        create: impl FnOnce() -> Result<Arc<dyn YBot>, GameYError>,
+    ) -> Result<Arc<dyn YBot>, GameYError> {
+        let key = (session_id.to_string(), bot_id.to_string());
+        let mut bots = self.lock();
+        if let Some(entry) = bots.get_mut(&key)
+            && entry.params == *params
+        {
+            entry.last_used = Instant::now();
+            return Ok(Arc::clone(&entry.bot));
+        }
+        let bot = create()?;
+        if !bots.contains_key(&key)
+            && bots.len() >= self.capacity
+            && let Some(oldest) = bots.iter().min_by_key(|(_, entry)| entry.last_used).map(|(key, _)| key.clone())
+        {
+            bots.remove(&oldest);
+        }
+        if self.capacity > 0 {
+            bots.insert(
+                key,
+                SessionBot {
+                    params: params.clone(),
+                    bot: Arc::clone(&bot),
+                    last_used: Instant::now(),
+                },
+            );
+        }
+        Ok(bot)
+    }
+
+    /// Drops every bot of `session_id`.
+    pub fn end_session(&self, session_id: &str) {
+        self.lock().retain(|(session, _), _| session != session_id);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<(String, String), SessionBot>> {
+        self.bots.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RandomBot;
+
+    fn random() -> Result<Arc<dyn YBot>, GameYError> {
+        Ok(Arc::new(RandomBot))
+    }
+
+    #[test]
+    fn test_same_session_gets_same_bot() {
+        let sessions = BotSessions::default();
+        let first = sessions.get_or_create("a", "random_bot", &BotParams::new(), random).unwrap();
+        let second = sessions.get_or_create("a", "random_bot", &BotParams::new(), random).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        let other = sessions.get_or_create("b", "random_bot", &BotParams::new(), random).unwrap();
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(sessions.len(), 2);
+    }
+
+    #[test]
+    fn test_changed_params_rebuild_the_bot() {
+        let sessions = BotSessions::default();
+        let first = sessions.get_or_create("a", "random_bot", &BotParams::new(), random).unwrap();
+        let mut params = BotParams::new();
+        params.insert("seed".to_string(), 1.into());
+        let second = sessions.get_or_create("a", "random_bot", &params, random).unwrap();
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert_eq!(sessions.len(), 1);
+    }
+
+    #[test]
+    fn test_capacity_drops_least_recently_used() {
+        let sessions = BotSessions::new(2);
+        let first = sessions.get_or_create("a", "random_bot", &BotParams::new(), random).unwrap();
+        sessions.get_or_create("b", "random_bot", &BotParams::new(), random).unwrap();
+        sessions.get_or_create("a", "random_bot", &BotParams::new(), random).unwrap();
+        sessions.get_or_create("c", "random_bot", &BotParams::new(), random).unwrap();
+        assert_eq!(sessions.len(), 2);
+        let again = sessions.get_or_create("a", "random_bot", &BotParams::new(), random).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+    }
+
+    #[test]
+    fn test_errors_are_not_stored() {
+        let sessions = BotSessions::default();
+        let result = sessions.get_or_create("a", "missing", &BotParams::new(), || {
+            Err(GameYError::BotNotFound {
+                bot_id: "missing".to_string(),
+                available: String::new(),
+            })
+        });
+        assert!(result.is_err());
+        assert!(sessions.is_empty());
+    }
+
+    #[test]
+    fn test_end_session() {
+        let sessions = BotSessions::default();
+        sessions.get_or_create("a", "random_bot", &BotParams::new(), random).unwrap();
+        sessions.get_or_create("a", "other_bot", &BotParams::new(), random).unwrap();
+        sessions.end_session("a");
+        assert!(sessions.is_empty());
+    }
+}
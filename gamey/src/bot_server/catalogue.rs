@@ -0,0 +1,50 @@
+//! Bot catalogue endpoint.
+//!
+//! `GET /{api_version}/ybot` lists the bots of the server with what a client
+//! needs to offer them as opponents (see [`BotMetadata`]).
+
+use crate::{BotMetadata, check_api_version, error::ErrorResponse, state::AppState};
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Response of the catalogue endpoint.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct BotCatalogueResponse {
+    /// The API version used for this request.
+    pub api_version: String,
+    /// Every registered bot, sorted by id.
+    pub bots: Vec<BotMetadata>,
+}
+
+/// Handler that lists the registered bots.
+///
+/// # Route
+/// `GET /{api_version}/ybot`
+///
+/// # Response
+/// A [`BotCatalogueResponse`] with the metadata of every bot of the registry.
+#[utoipa::path(
+    get,
+    path = "/{api_version}/ybot",
+    tag = "bots",
+    params(("api_version" = String, Path, description = "The API version, `v1` or `v2`")),
+    responses(
+        (status = 200, description = "Every registered bot", body = BotCatalogueResponse),
+        (status = 400, description = "Unsupported version", body = ErrorResponse),
+    )
+)]
+#[axum::debug_handler]
+pub async fn list_bots(
+    State(state): State<AppState>,
+    Path(api_version): Path<String>,
+) -> Result<Json<BotCatalogueResponse>, ErrorResponse> {
+    check_api_version(&api_version)?;
+    Ok(Json(BotCatalogueResponse {
+        api_version,
+        bots: state.bots().catalogue(),
+    }))
+}
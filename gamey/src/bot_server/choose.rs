@@ -1,9 +1,15 @@
-use crate::{Coordinates, GameY, YEN, check_api_version, error::ErrorResponse, state::AppState};
+use crate::{
+    BotDecision, BotParams, Coordinates, DecisionOptions, GameAction, GameStatus, GameY, PlayerId, YBot, YEN,
+    check_api_version,
+    error::{ErrorCode, ErrorResponse},
+    state::AppState,
+};
 use axum::{
     Json,
     extract::{Path, State},
 };
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 
 use crate::metrics::{GAMES_PLAYED, RESPONSE_TIME};
@@ -16,25 +22,148 @@ use std::time::Instant;
 #[derive(Deserialize)]
 pub struct ChooseParams {
     /// The API version (e.g., "v1").
-    api_version: String,
+    pub(crate) api_version: String,
     /// The identifier of the bot to use for move selection.
-    bot_id: String,
+    pub(crate) bot_id: String,
+}
+
+/// Request body of the choose endpoint.
+///
+/// The position is given in YEN format at the top level of the object, and
+/// an optional `params` object tunes the bot for this request only. The
+/// optional `pie_rule` and `allow_resign` flags (see [`DecisionOptions`]) let
+/// the bot answer with a swap or a resignation instead of a stone. Requests
+/// with the same optional `session_id` are answered by the same bot instance,
+/// so that search bots can reuse their previous search.
+///
+/// The bot plays as the player to move, as the layout of the YEN gives it,
+/// unless the optional `bot_player` says otherwise. The optional `timeout_ms` bounds the
+/// time the bot may think (see [`crate::limits`]).
+///
+/// # Example
+/// ```json
+/// {
+///   "size": 3,
+///   "turn": 0,
+///   "players": ["B", "R"],
+///   "layout": "./../...",
+///   "params": { "simulations": 500 },
+///   "allow_resign": true,
+///   "session_id": "game-42",
+///   "timeout_ms": 2000
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct ChooseRequest {
+    /// The game state.
+    #[serde(flatten)]
+    pub yen: YEN,
+    /// Parameters used to build the bot, see [`crate::YBotRegistry::create`].
+    #[serde(default, skip_serializing_if = "BotParams::is_empty")]
+    #[schema(value_type = Object)]
+    pub params: BotParams,
+    /// The actions besides placing a stone the bot may answer with.
+    #[serde(flatten)]
+    pub options: DecisionOptions,
+    /// Identifies the game, so that every move is answered by the same bot
+    /// instance (see [`crate::BotSessions`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// The player the bot plays as, when it is not the player to move of the layout.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bot_player: Option<u32>,
+    /// How long the bot may think, in milliseconds, capped at the server's
+    /// [`crate::ComputeLimits::max_timeout_ms`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+impl From<YEN> for ChooseRequest {
+    fn from(yen: YEN) -> Self {
+        ChooseRequest {
+            yen,
+            params: BotParams::new(),
+            options: DecisionOptions::default(),
+            session_id: None,
+            bot_player: None,
+            timeout_ms: None,
+        }
+    }
+}
+
+/// Whether the winner of a game is the bot or its opponent.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WinnerRole {
+    /// The bot that was asked for a move won.
+    Bot,
+    /// The other player won.
+    Opponent,
+}
+
+/// The state of the game after the bot's move.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+pub struct MoveStatus {
+    /// Whether the game is over.
+    pub finished: bool,
+    /// The player who won, once the game is over.
+    pub winner: Option<u32>,
+    /// Whether the winner is the bot, once the game is over.
+    pub winner_role: Option<WinnerRole>,
+}
+
+impl MoveStatus {
+    /// The status of `game` for the bot playing as `bot_player`.
+    pub fn new(game: &GameY, bot_player: PlayerId) -> Self {
+        match game.status() {
+            GameStatus::Ongoing { .. } => MoveStatus::default(),
+            GameStatus::Finished { winner } => MoveStatus {
+                finished: true,
+                winner: Some(winner.id()),
+                winner_role: Some(if *winner == bot_player {
+                    WinnerRole::Bot
+                } else {
+                    WinnerRole::Opponent
+                }),
+            },
+        }
+    }
+
+    /// The status as the `game_status` string: "ongoing", "bot_won" or "human_won".
+    pub fn as_str(&self) -> &'static str {
+        match self.winner_role {
+            None => "ongoing",
+            Some(WinnerRole::Bot) => "bot_won",
+            Some(WinnerRole::Opponent) => "human_won",
+        }
+    }
 }
 
 /// Response returned by the choose endpoint on success.
 ///
 /// Contains the bot's chosen move coordinates along with context
-/// about which API version and bot were used.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+/// about which API version and bot were used. When the bot swaps or resigns
+/// there are no coordinates and `action` says which action it took.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub struct MoveResponse {
     /// The API version used for this request.
     pub api_version: String,
     /// The bot that selected this move.
     pub bot_id: String,
-    /// The coordinates where the bot chooses to place its piece.
-    pub coords: Coordinates,
+    /// The coordinates where the bot chooses to place its piece, if it placed one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coords: Option<Coordinates>,
+    /// The action the bot took instead of placing a piece, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<GameAction>,
     // State of the game ("ongoing","bot_won"..)
     pub game_status:String,
+    /// The player the bot played as.
+    #[serde(default)]
+    pub bot_player: u32,
+    /// The state of the game after the move.
+    #[serde(default)]
+    pub status: MoveStatus,
 }
 
 /// Handler for the bot move selection endpoint.
@@ -46,102 +175,180 @@ pub struct MoveResponse {
 /// `POST /{api_version}/ybot/choose/{bot_id}`
 ///
 /// # Request Body
-/// A JSON object in YEN format representing the current game state.
+/// A JSON object in YEN format representing the current game state, with an
+/// optional `params` object (see [`ChooseRequest`]).
 ///
 /// # Response
 /// On success, returns a `MoveResponse` with the chosen coordinates.
 /// On failure, returns an `ErrorResponse` with details about what went wrong.
+#[utoipa::path(
+    post,
+    path = "/{api_version}/ybot/choose/{bot_id}",
+    tag = "bots",
+    params(("api_version" = String, Path, description = "The API version, `v1` (`v2` is served by its own route)"), ("bot_id" = String, Path, description = "The bot to ask")),
+    request_body = ChooseRequest,
+    responses(
+        (status = 200, description = "The bot's move", body = MoveResponse),
+        (status = 400, description = "Unsupported version, invalid position or parameters", body = ErrorResponse),
+        (status = 404, description = "Unknown bot", body = ErrorResponse),
+        (status = 429, description = "Too many searches running or waiting", body = ErrorResponse),
+        (status = 503, description = "No move found before the deadline", body = ErrorResponse),
+    )
+)]
 #[axum::debug_handler]
 pub async fn choose(
     State(state): State<AppState>,
     Path(params): Path<ChooseParams>,
-    Json(yen): Json<YEN>,
+    Json(request): Json<ChooseRequest>,
 ) -> Result<Json<MoveResponse>, ErrorResponse> {
     let start = Instant::now();
     check_api_version(&params.api_version)?;
-    let game_y = match GameY::try_from(yen) {
-        Ok(game) => game,
+    let (game_y, bot_player) = parse_position(request.yen, request.bot_player, &params.api_version, &params.bot_id)?;
+    if let Some(response) = finished_game_response(&game_y, bot_player, &params.api_version, &params.bot_id) {
+        return Ok(Json(response));
+    }
+    let bot = match state.create_bot(&params.bot_id, &request.params, request.session_id.as_deref()) {
+        Ok(bot) => bot,
         Err(err) => {
-            return Err(ErrorResponse::error(
-                &format!("Invalid YEN format: {}", err),
-                Some(params.api_version),
-                Some(params.bot_id),
-            ));
+            return Err(ErrorResponse::from_error(&err, Some(params.api_version), Some(params.bot_id)));
         }
     };
-    if let crate::GameStatus::Finished { winner } = game_y.status() {
-        let status_str = if *winner == crate::PlayerId::new(0) { "human_won" } else { "bot_won" };
-        return Ok(Json(MoveResponse {
-            api_version: params.api_version,
-            bot_id: params.bot_id,
-            coords: crate::Coordinates::new(0, 0, 0),
-            game_status: status_str.to_string(),
-        }));
-    }
-    let bot = match state.bots().find(&params.bot_id) {
-        Some(bot) => bot,
-        None => {
-            let available_bots = state.bots().names().join(", ");
-            return Err(ErrorResponse::error(
-                &format!(
-                    "Bot not found: {}, available bots: [{}]",
-                    params.bot_id, available_bots
-                ),
-                Some(params.api_version),
-                Some(params.bot_id),
-            ));
-        }
+    let timeout = state.compute().timeout(request.timeout_ms);
+    let (api_version, bot_id, options) = (params.api_version.clone(), params.bot_id.clone(), request.options);
+    state
+        .compute()
+        .run_request(timeout, &params.api_version, Some(&params.bot_id), move || {
+            play_bot_move(bot.as_ref(), game_y, bot_player, &options, api_version, bot_id, start)
+        })
+        .await
+}
+
+/// Converts the YEN of a request into a game, reporting layout errors, and
+/// returns it with the player the bot plays as.
+///
+/// The bot plays as `bot_player` when it is given, which also hands it the
+/// turn of an ongoing game. Otherwise it plays as the player to move, which
+/// the layout decides (see [`GameY::try_from`]); in a finished game that is
+/// the `turn` of the YEN, or the loser when the turn is not a player.
+pub(crate) fn parse_position(
+    yen: YEN,
+    bot_player: Option<u32>,
+    api_version: &str,
+    bot_id: &str,
+) -> Result<(GameY, PlayerId), ErrorResponse> {
+    let error = |code, message: String| {
+        ErrorResponse::new(code, &message, Some(api_version.to_string()), Some(bot_id.to_string()))
     };
-    let coords = match bot.choose_move(&game_y) {
-        Some(coords) => coords,
-        None => {
-            // Handle the case where the bot has no valid moves
-            return Err(ErrorResponse::error(
-                "No valid moves available for the bot",
-                Some(params.api_version),
-                Some(params.bot_id),
-            ));
-        }
+    if let Some(player) = bot_player.filter(|&player| player > 1) {
+        return Err(error(
+            ErrorCode::InvalidRequest,
+            format!("Invalid bot_player: expected 0 or 1, found {}", player),
+        ));
+    }
+    let turn = yen.turn();
+    let mut game_y =
+        GameY::try_from(yen).map_err(|err| error(ErrorCode::InvalidYen, format!("Invalid YEN format: {}", err)))?;
+    if let Some(player) = bot_player {
+        game_y.set_next_player(PlayerId::new(player));
+    }
+    let bot_player = match (game_y.status(), bot_player) {
+        (GameStatus::Ongoing { next_player }, _) => *next_player,
+        (GameStatus::Finished { .. }, Some(player)) => PlayerId::new(player),
+        (GameStatus::Finished { .. }, None) if turn <= 1 => PlayerId::new(turn),
+        (GameStatus::Finished { winner }, None) => PlayerId::new(1 - winner.id()),
     };
+    Ok((game_y, bot_player))
+}
+
+/// Returns the response for a position that is already finished, if it is.
+pub(crate) fn finished_game_response(
+    game_y: &GameY,
+    bot_player: PlayerId,
+    api_version: &str,
+    bot_id: &str,
+) -> Option<MoveResponse> {
+    if !game_y.check_game_over() {
+        return None;
+    }
+    let status = MoveStatus::new(game_y, bot_player);
+    Some(MoveResponse {
+        api_version: api_version.to_string(),
+        bot_id: bot_id.to_string(),
+        coords: Some(crate::Coordinates::new(0, 0, 0)),
+        action: None,
+        game_status: status.as_str().to_string(),
+        bot_player: bot_player.id(),
+        status,
+    })
+}
+
+/// Asks the bot for a decision, applies it and builds the response.
+///
+/// `bot_id` is the label reported back to the client and used for metrics.
+pub(crate) fn play_bot_move(
+    bot: &dyn YBot,
+    game_y: GameY,
+    bot_player: PlayerId,
+    options: &DecisionOptions,
+    api_version: String,
+    bot_id: String,
+    start: Instant,
+) -> Result<Json<MoveResponse>, ErrorResponse> {
+    let (decision, status) = decide_bot_move(bot, game_y, bot_player, options, &api_version, &bot_id, start)?;
+    Ok(Json(MoveResponse {
+        api_version,
+        bot_id,
+        coords: decision.coords(),
+        action: decision.action(),
+        game_status: status.as_str().to_string(),
+        bot_player: bot_player.id(),
+        status,
+    }))
+}
+
+/// Asks the bot for a decision and applies it, returning the decision with
+/// the state of the game after it. Used by every API version.
+pub(crate) fn decide_bot_move(
+    bot: &dyn YBot,
+    game_y: GameY,
+    bot_player: PlayerId,
+    options: &DecisionOptions,
+    api_version: &str,
+    bot_id: &str,
+    start: Instant,
+) -> Result<(BotDecision, MoveStatus), ErrorResponse> {
+    let api_version = Some(api_version.to_string());
+    let decision = bot.decide(&game_y, options);
+    if game_y.available_cells().is_empty() && decision == BotDecision::Resign {
+        // Handle the case where the bot has no valid moves
+        return Err(ErrorResponse::new(
+            ErrorCode::InvalidPosition,
+            "No valid moves available for the bot",
+            api_version,
+            Some(bot_id.to_string()),
+        ));
+    }
     let mut game_y_mut = game_y;
-    let bot_player_id = crate::PlayerId::new(1); //asume bot plays always as player 1
-    let bot_move = crate::Movement::Placement {
-        player: bot_player_id,
-        coords: coords,
-    };
+    let bot_move = decision.into_movement(bot_player);
     if let Err(e) = game_y_mut.add_move(bot_move) {
-        return Err(ErrorResponse::error(
+        return Err(ErrorResponse::new(
+            ErrorCode::InternalError,
             &format!("Failed to apply bot move to calculate state: {:?}", e),
-            Some(params.api_version),
-            Some(params.bot_id),
+            api_version,
+            Some(bot_id.to_string()),
         ));
     }
-    let status_str = match game_y_mut.status() {
-        crate::GameStatus::Ongoing { .. } => "ongoing".to_string(),
-        crate::GameStatus::Finished { winner } => {
-            if *winner == bot_player_id {
-                "bot_won".to_string()
-            } else {
-                "human_won".to_string()
-            }
-        }
-    };
-    let response = MoveResponse {
-        api_version: params.api_version,
-        bot_id: params.bot_id.clone(),
-        coords,
-        game_status: status_str,
-    };
+    let status = MoveStatus::new(&game_y_mut, bot_player);
     let duration = start.elapsed();
 
     // Para Prometheus
     GAMES_PLAYED
-        .with_label_values(&[&params.bot_id.clone()])
+        .with_label_values(&[bot_id])
         .inc();
 
     RESPONSE_TIME.observe(duration.as_secs_f64());
-    
-    Ok(Json(response))
+
+    Ok((decision, status))
 }
 
 use axum::extract::Query;
@@ -155,10 +362,30 @@ pub struct CompetitionParams {
     position: String,
     /// Identificador opcional del bot. Si no se envía, usaremos uno por defecto.
     bot_id: Option<String>,
+    /// Permite que el bot se rinda en posiciones perdidas (desactivado por defecto).
+    #[serde(default)]
+    allow_resign: bool,
 }
 
 /// GET /play (Exclusivo para la competición)
 /// Handler de Axum que recibe el estado de la aplicación y los parámetros de la URL.
+#[utoipa::path(
+    get,
+    path = "/play",
+    tag = "bots",
+    params(
+        ("position" = String, Query, description = "La posición en formato YEN, como texto JSON"),
+        ("bot_id" = Option<String>, Query, description = "El bot que juega (`random_bot` por defecto)"),
+        ("allow_resign" = Option<bool>, Query, description = "Permite que el bot se rinda en posiciones perdidas (`false` por defecto)"),
+    ),
+    responses(
+        (status = 200, description = "`{\"coords\": {...}}` o `{\"action\": \"swap\" | \"resign\"}`", body = Object),
+        (status = 400, description = "Posición inválida", body = ErrorResponse),
+        (status = 404, description = "Bot no encontrado", body = ErrorResponse),
+        (status = 429, description = "Demasiadas búsquedas en curso o en espera", body = ErrorResponse),
+        (status = 503, description = "El bot no decidió antes del plazo", body = ErrorResponse),
+    )
+)]
 #[axum::debug_handler]
 pub async fn play_competition(
     State(state): State<AppState>, // Acceso a los bots guardados en la memoria del servidor
@@ -174,8 +401,9 @@ pub async fn play_competition(
     // Intentamos convertir el texto JSON de la URL en nuestra estructura interna YEN.
     let yen: YEN = match serde_json::from_str(&params.position) {
         Ok(y) => y, // Si el JSON está bien formado, lo guardamos en 'yen'
-        Err(e) => return Err(ErrorResponse::error(
+        Err(e) => return Err(ErrorResponse::new(
             // Si la peticion contiene un JSON roto, devolvemos un error HTTP detallado
+            ErrorCode::InvalidYen,
             &format!("JSON inválido en position: {}", e),
             Some("v1".to_string()),
             Some(bot_name),
@@ -186,8 +414,9 @@ pub async fn play_competition(
     // Traducimos el formato YEN a la estructura lógica 'GameY' que entiende la IA.
     let game_y = match GameY::try_from(yen) {
         Ok(game) => game, // Si el tablero es lógicamente válido, lo guardamos en 'game_y'
-        Err(err) => return Err(ErrorResponse::error(
+        Err(err) => return Err(ErrorResponse::new(
             // Si el tablero tiene reglas rotas (ej. tamaño negativo), devolvemos error
+            ErrorCode::InvalidYen,
             &format!("Formato YEN inválido: {}", err),
             Some("v1".to_string()),
             Some(bot_name),
@@ -197,7 +426,8 @@ pub async fn play_competition(
     // Buscamos en el registro de nuestro servidor (state) el bot que nos han pedido.
     let bot = match state.bots().find(&bot_name) {
         Some(bot) => bot, // Bot encontrado, listo para jugar
-        None => return Err(ErrorResponse::error(
+        None => return Err(ErrorResponse::new(
+            ErrorCode::BotNotFound,
             &format!("Bot no encontrado: {}", bot_name),
             Some("v1".to_string()),
             Some(bot_name.clone()),
@@ -205,13 +435,21 @@ pub async fn play_competition(
     };
 
     // 5.Calculo y toma de decision:
-    // Le pasamos el tablero válido a la IA y le pedimos que calcule su siguiente movimiento.
-    let coords = match bot.choose_move(&game_y) {
-        Some(coords) => coords,
-        None => {
-            // Si el bot se ha quedado sin movimientos válidos resigna
+    // Le pasamos el tablero válido a la IA y le pedimos que decida su jugada.
+    // Como en el endpoint choose, el bot solo se rinde si se pide con allow_resign,
+    // y la búsqueda corre en el pool de cálculo del servidor con su plazo máximo.
+    let options = DecisionOptions::default().with_resign(params.allow_resign);
+    let timeout = state.compute().timeout(None);
+    let decision = state
+        .compute()
+        .run_request(timeout, "v1", Some(&bot_name), move || Ok(bot.decide(&game_y, &options)))
+        .await?;
+    let coords = match decision {
+        BotDecision::Place { coords } => coords,
+        BotDecision::Swap | BotDecision::Resign => {
+            // Si el bot se rinde (o intercambia) devolvemos solo la acción
             return Ok(Json(serde_json::json!({
-                "action": "resign"
+                "action": decision.action()
             })));
         }
     };
@@ -235,12 +473,15 @@ mod tests {
         let response = MoveResponse {
             api_version: "v1".to_string(),
             bot_id: "random".to_string(),
-            coords: Coordinates::new(1, 2, 3),
+            coords: Some(Coordinates::new(1, 2, 3)),
+            action: None,
             game_status: "ongoing".to_string(),
+            bot_player: 1,
+            status: MoveStatus::default(),
         };
         assert_eq!(response.api_version, "v1");
         assert_eq!(response.bot_id, "random");
-        assert_eq!(response.coords, Coordinates::new(1, 2, 3));
+        assert_eq!(response.coords, Some(Coordinates::new(1, 2, 3)));
         assert_eq!(response.game_status, "ongoing");
     }
 
@@ -249,8 +490,11 @@ mod tests {
         let response = MoveResponse {
             api_version: "v1".to_string(),
             bot_id: "random".to_string(),
-            coords: Coordinates::new(1, 2, 3),
+            coords: Some(Coordinates::new(1, 2, 3)),
+            action: None,
             game_status: "ongoing".to_string(),
+            bot_player: 1,
+            status: MoveStatus::default(),
         };
         let json = serde_json::to_string(&response).unwrap();
         assert!(json.contains("\"api_version\":\"v1\""));
@@ -272,8 +516,15 @@ mod tests {
         let response = MoveResponse {
             api_version: "v1".to_string(),
             bot_id: "random".to_string(),
-            coords: Coordinates::new(0, 0, 0),
+            coords: Some(Coordinates::new(0, 0, 0)),
+            action: None,
             game_status: "bot_won".to_string(),
+            bot_player: 1,
+            status: MoveStatus {
+                finished: true,
+                winner: Some(1),
+                winner_role: Some(WinnerRole::Bot),
+            },
         };
         let cloned = response.clone();
         assert_eq!(response, cloned);
@@ -284,20 +535,29 @@ mod tests {
         let r1 = MoveResponse {
             api_version: "v1".to_string(),
             bot_id: "random".to_string(),
-            coords: Coordinates::new(1, 1, 1),
+            coords: Some(Coordinates::new(1, 1, 1)),
+            action: None,
             game_status: "ongoing".to_string(),
+            bot_player: 1,
+            status: MoveStatus::default(),
         };
         let r2 = MoveResponse {
             api_version: "v1".to_string(),
             bot_id: "random".to_string(),
-            coords: Coordinates::new(1, 1, 1),
+            coords: Some(Coordinates::new(1, 1, 1)),
+            action: None,
             game_status: "ongoing".to_string(),
+            bot_player: 1,
+            status: MoveStatus::default(),
         };
         let r3 = MoveResponse {
             api_version: "v2".to_string(),
             bot_id: "random".to_string(),
-            coords: Coordinates::new(1, 1, 1),
+            coords: Some(Coordinates::new(1, 1, 1)),
+            action: None,
             game_status: "ongoing".to_string(),
+            bot_player: 1,
+            status: MoveStatus::default(),
         };
         assert_eq!(r1, r2);
         assert_ne!(r1, r3);
@@ -329,7 +589,7 @@ mod tests {
             api_version: "v_invalida".to_string(),
             bot_id: "random_bot".to_string(),
         });
-        let yen = Json(YEN::default());
+        let yen = Json(YEN::default().into());
 
         let result = choose(state, params, yen).await;
         
@@ -344,7 +604,7 @@ mod tests {
             bot_id: "bot_inventado".to_string(),
         });
 
-        let yen = Json(valid_ongoing_yen());
+        let yen = Json(valid_ongoing_yen().into());
 
         let result = choose(state, params, yen).await;
         
@@ -361,13 +621,13 @@ mod tests {
             bot_id: "random_bot".to_string(),
         });
         
-        let yen_finished = Json(valid_finished_yen());
+        let yen_finished = Json(valid_finished_yen().into());
 
         let result = choose(state, params, yen_finished).await;
         
         assert!(result.is_ok());
         let response = result.unwrap().0;
-        assert_eq!(response.coords, crate::Coordinates::new(0, 0, 0));
+        assert_eq!(response.coords, Some(crate::Coordinates::new(0, 0, 0)));
         assert!(response.game_status == "bot_won" || response.game_status == "human_won");
     }
 
@@ -379,7 +639,7 @@ mod tests {
             bot_id: "random_bot".to_string(),
         });
         
-        let yen_ongoing = Json(valid_ongoing_yen());
+        let yen_ongoing = Json(valid_ongoing_yen().into());
 
         let result = choose(state, params, yen_ongoing).await;
         
@@ -388,6 +648,104 @@ mod tests {
         assert!(response.game_status == "ongoing" || response.game_status == "bot_won");
     }
 
+    #[tokio::test]
+    async fn test_choose_with_params() {
+        let state = State(mock_state());
+        let params = Path(ChooseParams {
+            api_version: "v1".to_string(),
+            bot_id: "monte_carlo_bot".to_string(),
+        });
+        let mut request = ChooseRequest::from(valid_ongoing_yen());
+        request.params.insert("simulations".to_string(), serde_json::Value::from(3));
+
+        let result = choose(state, params, Json(request)).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_choose_with_invalid_params() {
+        let state = State(mock_state());
+        let params = Path(ChooseParams {
+            api_version: "v1".to_string(),
+            bot_id: "monte_carlo_bot".to_string(),
+        });
+        let mut request = ChooseRequest::from(valid_ongoing_yen());
+        request.params.insert("simulations".to_string(), serde_json::Value::from("many"));
+
+        let result = choose(state, params, Json(request)).await;
+
+        let error_msg = result.unwrap_err().message;
+        assert!(error_msg.contains("Invalid parameters for bot monte_carlo_bot"));
+    }
+
+    #[tokio::test]
+    async fn test_choose_bot_plays_the_side_to_move() {
+        let params = || {
+            Path(ChooseParams {
+                api_version: "v1".to_string(),
+                bot_id: "random_bot".to_string(),
+            })
+        };
+        // Only one cell is left and it wins the game for whoever takes it.
+        let yen = YEN::new(2, 0, vec!['B', 'R'], "B/R.".to_string());
+
+        let response = choose(State(mock_state()), params(), Json(yen.clone().into())).await.unwrap().0;
+        assert_eq!(response.bot_player, 0);
+        assert_eq!(response.game_status, "bot_won");
+        let expected = MoveStatus {
+            finished: true,
+            winner: Some(0),
+            winner_role: Some(WinnerRole::Bot),
+        };
+        assert_eq!(response.status, expected);
+
+        let mut request = ChooseRequest::from(yen.clone());
+        request.bot_player = Some(1);
+        let response = choose(State(mock_state()), params(), Json(request)).await.unwrap().0;
+        assert_eq!(response.bot_player, 1);
+        assert_eq!(response.status.winner, Some(1));
+        assert_eq!(response.status.winner_role, Some(WinnerRole::Bot));
+
+        let mut request = ChooseRequest::from(yen);
+        request.bot_player = Some(2);
+        let error = choose(State(mock_state()), params(), Json(request)).await.unwrap_err();
+        assert!(error.message.contains("Invalid bot_player"));
+
+        // Clients that always send turn 0 still get the side the layout gives.
+        let yen = YEN::new(3, 0, vec!['B', 'R'], "B/../...".to_string());
+        let response = choose(State(mock_state()), params(), Json(yen.clone().into())).await.unwrap().0;
+        assert_eq!(response.bot_player, 1);
+        let mut request = ChooseRequest::from(yen);
+        request.bot_player = Some(0);
+        let response = choose(State(mock_state()), params(), Json(request)).await.unwrap().0;
+        assert_eq!(response.bot_player, 0);
+    }
+
+    #[test]
+    fn test_move_status_of_an_ongoing_game() {
+        let status = MoveStatus::new(&GameY::new(3), PlayerId::new(0));
+        assert!(!status.finished);
+        assert_eq!(status.as_str(), "ongoing");
+        let json = serde_json::to_value(status).unwrap();
+        assert_eq!(json, serde_json::json!({ "finished": false, "winner": null, "winner_role": null }));
+    }
+
+    #[test]
+    fn test_choose_request_deserialize_with_params() {
+        let json = r#"{"size":2,"turn":0,"players":["B","R"],"layout":"./..","params":{"simulations":500}}"#;
+        let request: ChooseRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(request.yen.size(), 2);
+        assert_eq!(request.params.get("simulations"), Some(&serde_json::Value::from(500)));
+    }
+
+    #[test]
+    fn test_choose_request_deserialize_without_params() {
+        let json = r#"{"size":2,"turn":0,"players":["B","R"],"layout":"./.."}"#;
+        let request: ChooseRequest = serde_json::from_str(json).unwrap();
+        assert!(request.params.is_empty());
+    }
+
     #[tokio::test]
     async fn test_choose_invalid_yen_format() {
         let state = State(mock_state());
@@ -396,7 +754,7 @@ mod tests {
             bot_id: "random_bot".to_string(),
         });
         
-        let yen = Json(YEN::new(0, 0, vec!['B', 'R'], "".to_string()));
+        let yen = Json(YEN::new(0, 0, vec!['B', 'R'], "".to_string()).into());
 
         let result = choose(state, params, yen).await;
         
@@ -413,7 +771,7 @@ mod tests {
             bot_id: "random_bot".to_string(),
         });
         
-        let yen = Json(YEN::new(1, 1, vec!['B', 'R'], "B".to_string()));
+        let yen = Json(YEN::new(1, 1, vec!['B', 'R'], "B".to_string()).into());
 
         let result = choose(state, params, yen).await;
         
@@ -428,6 +786,7 @@ mod tests {
         let params = Query(CompetitionParams {
             position: r#"{ size: 3, turn: roto }"#.to_string(),
             bot_id: Some("random_bot".to_string()),
+            allow_resign: false,
         });
 
         let result = play_competition(state, params).await;
@@ -443,6 +802,7 @@ mod tests {
         let params = Query(CompetitionParams {
             position: r#"{"size": 0, "turn": 0, "players": ["B", "R"], "layout": ""}"#.to_string(),
             bot_id: Some("random_bot".to_string()),
+            allow_resign: false,
         });
 
         let result = play_competition(state, params).await;
@@ -458,6 +818,7 @@ mod tests {
         let params = Query(CompetitionParams {
             position: r#"{"size": 1, "turn": 1, "players": ["B", "R"], "layout": "B"}"#.to_string(),
             bot_id: Some("random_bot".to_string()),
+            allow_resign: false,
         });
 
         let result = play_competition(state, params).await;
@@ -467,12 +828,40 @@ mod tests {
         assert_eq!(json_response.get("action").unwrap().as_str().unwrap(), "resign");
     }
 
+    #[tokio::test]
+    async fn test_play_competition_resign_is_opt_in() {
+        // Player 1 needs one of two cells to win and player 0 is to move.
+        let mut game = GameY::new(4);
+        let stones = [(0, (2, 1, 0)), (1, (3, 0, 0)), (0, (1, 1, 1)), (1, (2, 0, 1)), (1, (1, 0, 2)), (1, (0, 3, 0))];
+        for (player, (x, y, z)) in stones {
+            game.add_move(crate::Movement::Placement {
+                player: crate::PlayerId::new(player),
+                coords: Coordinates::new(x, y, z),
+            })
+            .unwrap();
+        }
+        let position = serde_json::to_string(&YEN::from(&game)).unwrap();
+        let play = |allow_resign| {
+            Query(CompetitionParams {
+                position: position.clone(),
+                bot_id: Some("resistance_bot".to_string()),
+                allow_resign,
+            })
+        };
+
+        let response = play_competition(State(mock_state()), play(false)).await.unwrap().0;
+        assert!(response.get("coords").is_some());
+        let response = play_competition(State(mock_state()), play(true)).await.unwrap().0;
+        assert_eq!(response["action"], "resign");
+    }
+
     #[tokio::test]
     async fn test_play_competition_success() {
         let state = State(mock_state());
         let params = Query(CompetitionParams {
             position: r#"{"size": 3, "turn": 0, "players": ["B", "R"], "layout": "B/BR/.R."}"#.to_string(),
             bot_id: Some("random_bot".to_string()),
+            allow_resign: false,
         });
 
         let result = play_competition(state, params).await;
@@ -485,7 +874,8 @@ mod tests {
         let state = State(mock_state());
         let params = Query(CompetitionParams {
             position: r#"{"size": 3, "turn": 0, "players": ["B", "R"], "layout": "B/BR/.R."}"#.to_string(),
-            bot_id: None, 
+            bot_id: None,
+            allow_resign: false,
         });
 
         let result = play_competition(state, params).await;
@@ -501,6 +891,7 @@ mod tests {
         let params = Query(CompetitionParams {
             position: r#"{"size": 3, "turn": 0, "players": ["B", "R"], "layout": "B/BR/.R."}"#.to_string(),
             bot_id: Some("bot_que_no_existe".to_string()),
+            allow_resign: false,
         });
 
         let result = play_competition(state, params).await;
@@ -0,0 +1,533 @@
+//! Configuration of the bot server.
+//!
+//! `gamey --mode server` reads its settings from a TOML file, given with
+//! `--config` or the `GAMEY_CONFIG` environment variable, and lets `GAMEY_*`
+//! environment variables override single settings. Every setting has a
+//! default, so the file only lists what differs from them:
+//!
+//! ```toml
+//! [server]
+//! bind = "0.0.0.0:4000"
+//! cors_origins = ["http://localhost:5173"]
+//!
+//! [log]
+//! format = "json"
+//! level = "info,gamey=debug"
+//!
+//! [metrics]
+//! enabled = false
+//!
+//! [bots]
+//! enabled = ["random_bot", "shortest_path_bot", "mcts_bot"]
+//!
+//! [bots.params.mcts_bot]
+//! iterations = 2000
+//!
+//! [limits]
+//! session_capacity = 512
+//! game_ttl_secs = 900
+//! batch_concurrency = 4
+//!
+//! [limits.search]
+//! max_concurrent = 4
+//! max_queued = 32
+//! max_timeout_ms = 5000
+//! ```
+//!
+//! An environment variable names a setting by its path in upper case, with
+//! `__` between sections: `GAMEY_SERVER__BIND=127.0.0.1:8080` or
+//! `GAMEY_LIMITS__SEARCH__MAX_TIMEOUT_MS=2000`. Values are read as TOML
+//! (`GAMEY_BOTS__ENABLED='["random_bot"]'`), or as a string when they are
+//! not valid TOML.
+//!
+//! Unknown settings and invalid values are reported with the setting they
+//! belong to, so that a typo stops the server at startup instead of being
+//! ignored.
+
+use crate::{
+    BotParams, ComputeLimits, DEFAULT_GAME_TTL, DifficultyTable, DEFAULT_SESSION_CAPACITY, GameYError, default_registry,
+    state::{AppState, default_batch_concurrency},
+};
+use axum::http::HeaderValue;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use toml::{Table, Value};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tracing_subscriber::EnvFilter;
+
+/// Environment variable with the path of the configuration file.
+pub const CONFIG_PATH_ENV: &str = "GAMEY_CONFIG";
+
+/// Prefix of the environment variables that override settings.
+pub const ENV_PREFIX: &str = "GAMEY_";
+
+/// The settings of the bot server (see the [module documentation](self)).
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Where the server listens and who may call it.
+    pub server: ListenConfig,
+    /// How the server logs.
+    pub log: LogConfig,
+    /// The Prometheus metrics.
+    pub metrics: MetricsConfig,
+    /// The bots the server offers.
+    pub bots: BotsConfig,
+    /// Sizes, lifetimes and computation limits.
+    pub limits: LimitsConfig,
+}
+
+/// The `[server]` section.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ListenConfig {
+    /// The address and port to listen on.
+    pub bind: String,
+    /// The origins browsers may call the server from; `"*"` allows any.
+    pub cors_origins: Vec<String>,
+}
+
+impl Default for ListenConfig {
+    fn default() -> Self {
+        ListenConfig {
+            bind: "0.0.0.0:3000".to_string(),
+            cors_origins: vec!["*".to_string()],
+        }
+    }
+}
+
+/// How log lines are written.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per line, for log collectors.
+    Json,
+}
+
+/// The `[log]` section.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// How log lines are written.
+    pub format: LogFormat,
+    /// Which events are logged, as a `tracing` filter such as `info` or
+    /// `warn,gamey=debug`.
+    pub level: String,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            format: LogFormat::Text,
+            level: "info".to_string(),
+        }
+    }
+}
+
+impl LogConfig {
+    /// Installs the global logger of the process.
+    ///
+    /// # Errors
+    /// [`GameYError::InvalidConfig`] if the level is not a valid filter, and
+    /// [`GameYError::ServerError`] if a logger is already installed.
+    pub fn init(&self) -> Result<(), GameYError> {
+        let logger = tracing_subscriber::fmt().with_env_filter(self.filter()?);
+        let installed = match self.format {
+            LogFormat::Text => logger.try_init(),
+            LogFormat::Json => logger.json().try_init(),
+        };
+        installed.map_err(|err| GameYError::ServerError {
+            message: format!("Failed to install the logger: {}", err),
+        })
+    }
+
+    fn filter(&self) -> Result<EnvFilter, GameYError> {
+        EnvFilter::try_new(&self.level).map_err(|err| invalid("log.level", err))
+    }
+}
+
+/// The `[metrics]` section.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    /// Whether `GET /metrics` serves the Prometheus metrics.
+    pub enabled: bool,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig { enabled: true }
+    }
+}
+
+/// The `[bots]` section.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct BotsConfig {
+    /// The bots offered, every built-in bot if omitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<Vec<String>>,
+    /// Default parameters per bot, which request parameters override (see
+    /// [`crate::YBotRegistry::with_default_params`]).
+    pub params: BTreeMap<String, BotParams>,
+}
+
+/// The `[limits]` section.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// Bots kept for requests with a session id.
+    pub session_capacity: usize,
+    /// Seconds a game is kept without being used.
+    pub game_ttl_secs: u64,
+    /// Positions of a batch request computed at once.
+    pub batch_concurrency: usize,
+    /// Deadlines and concurrency of the bot computations.
+    pub search: ComputeLimits,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        LimitsConfig {
+            session_capacity: DEFAULT_SESSION_CAPACITY,
+            game_ttl_secs: DEFAULT_GAME_TTL.as_secs(),
+            batch_concurrency: default_batch_concurrency(),
+            search: ComputeLimits::default(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Loads the configuration from the file at `path`, or at
+    /// `$GAMEY_CONFIG` when no path is given, and applies the `GAMEY_*`
+    /// environment variables. Without a file only the environment applies.
+    ///
+    /// # Errors
+    /// [`GameYError::InvalidConfig`] if the file cannot be read or a setting
+    /// is unknown or invalid.
+    pub fn load(path: Option<&Path>) -> Result<Self, GameYError> {
+        let path = path.map(Path::to_path_buf).or_else(|| std::env::var_os(CONFIG_PATH_ENV).map(PathBuf::from));
+        let file = match &path {
+            Some(path) => Some(std::fs::read_to_string(path).map_err(|err| GameYError::InvalidConfig {
+                message: format!("cannot read {}: {}", path.display(), err),
+            })?),
+            None => None,
+        };
+        Self::from_sources(file.as_deref(), std::env::vars())
+    }
+
+    /// Builds the configuration from the text of a TOML file and a set of
+    /// environment variables, then checks it with [`ServerConfig::validate`].
+    ///
+    /// Variables without the [`ENV_PREFIX`], and [`CONFIG_PATH_ENV`], are ignored.
+    ///
+    /// # Errors
+    /// [`GameYError::InvalidConfig`] if a setting is unknown or invalid.
+    pub fn from_sources(
+        file: Option<&str>,
+        env: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, GameYError> {
+        let file = file.unwrap_or_default();
+        // Mistakes in the file are reported with their line before the
+        // environment changes it.
+        toml::from_str::<ServerConfig>(file).map_err(|err| invalid("config file", err))?;
+        let mut table: Table = toml::from_str(file).map_err(|err| invalid("config file", err))?;
+        let mut overrides = Vec::new();
+        for (name, value) in env {
+            let Some(path) = name.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            if name == CONFIG_PATH_ENV {
+                continue;
+            }
+            let path: Vec<String> = path.split("__").map(str::to_lowercase).collect();
+            set_setting(&mut table, &name, &path, env_value(&value))?;
+            overrides.push(name);
+        }
+        let config: ServerConfig = Value::Table(table)
+            .try_into()
+            .map_err(|err| invalid(&format!("environment ({})", overrides.join(", ")), err))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Replaces the port of `server.bind` and returns the configuration for chaining.
+    pub fn with_port(mut self, port: u16) -> Self {
+        let host = self.server.bind.rsplit_once(':').map_or("0.0.0.0", |(host, _)| host);
+        self.server.bind = format!("{}:{}", host, port);
+        self
+    }
+
+    /// Checks every setting, including that the bots and the difficulty
+    /// levels can be built.
+    ///
+    /// # Errors
+    /// [`GameYError::InvalidConfig`] naming the first invalid setting.
+    pub fn validate(&self) -> Result<(), GameYError> {
+        self.bind_address()?;
+        self.cors_layer().map(|_| ())?;
+        self.log.filter()?;
+        let limits = &self.limits;
+        for (setting, value) in [
+            ("limits.session_capacity", limits.session_capacity as u64),
+            ("limits.game_ttl_secs", limits.game_ttl_secs),
+            ("limits.batch_concurrency", limits.batch_concurrency as u64),
+            ("limits.search.max_concurrent", limits.search.max_concurrent as u64),
+            ("limits.search.max_timeout_ms", limits.search.max_timeout_ms),
+        ] {
+            if value == 0 {
+                return Err(invalid(setting, "must be greater than 0"));
+            }
+        }
+        self.build_state().map(|_| ())
+    }
+
+    /// Returns the address of `server.bind`.
+    ///
+    /// # Errors
+    /// [`GameYError::InvalidConfig`] if it is not an address such as `0.0.0.0:3000`.
+    pub fn bind_address(&self) -> Result<SocketAddr, GameYError> {
+        self.server.bind.parse().map_err(|err| {
+            invalid(
+                "server.bind",
+                format!("{} in {:?}, expected an address such as 0.0.0.0:3000", err, self.server.bind),
+            )
+        })
+    }
+
+    /// Returns the CORS layer that allows the `server.cors_origins`.
+    ///
+    /// # Errors
+    /// [`GameYError::InvalidConfig`] if an origin is not an `http` or `https` URL.
+    pub fn cors_layer(&self) -> Result<CorsLayer, GameYError> {
+        let origins = &self.server.cors_origins;
+        if origins.iter().any(|origin| origin == "*") {
+            return Ok(CorsLayer::permissive());
+        }
+        let origins = origins
+            .iter()
+            .map(|origin| {
+                let is_url = origin.starts_with("http://") || origin.starts_with("https://");
+                HeaderValue::from_str(origin.trim_end_matches('/'))
+                    .ok()
+                    .filter(|_| is_url)
+                    .ok_or_else(|| invalid("server.cors_origins", format!("{:?} is not an http(s) origin", origin)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(CorsLayer::new()
+            .allow_origin(AllowOrigin::list(origins))
+            .allow_methods(Any)
+            .allow_headers(Any))
+    }
+
+    /// Builds the state of the server: the enabled bots with their default
+    /// parameters, and the limits and metrics settings.
+    ///
+    /// The difficulty levels whose bot is not enabled are left out.
+    ///
+    /// # Errors
+    /// [`GameYError::InvalidConfig`] if a bot is unknown, or it or a
+    /// difficulty level rejects the parameters.
+    pub fn build_state(&self) -> Result<AppState, GameYError> {
+        let mut registry = default_registry();
+        if let Some(enabled) = &self.bots.enabled {
+            registry = registry.with_only(enabled).map_err(|err| invalid("bots.enabled", err))?;
+        }
+        for (bot, params) in &self.bots.params {
+            registry = registry
+                .with_default_params(bot, params.clone())
+                .map_err(|err| invalid(&format!("bots.params.{}", bot), err))?;
+        }
+        let limits = &self.limits;
+        let state = AppState::new(registry)
+            .with_session_capacity(limits.session_capacity)
+            .with_game_ttl(Duration::from_secs(limits.game_ttl_secs))
+            .with_batch_concurrency(limits.batch_concurrency)
+            .with_compute_limits(limits.search)
+            .with_metrics(self.metrics.enabled);
+        // Levels whose bot is not enabled are left out instead of failing.
+        let (bots, defaults) = (state.bots(), state.difficulties());
+        let mut difficulties = DifficultyTable::empty();
+        for difficulty in defaults.difficulties() {
+            let level = defaults.level(difficulty).expect("listed difficulties have a level");
+            if !bots.names().contains(&level.bot) {
+                tracing::warn!("The {} difficulty is disabled: its bot {} is not enabled", difficulty, level.bot);
+                continue;
+            }
+            difficulties = difficulties.with_level(difficulty, level.clone());
+            difficulties
+                .build(difficulty, &bots)
+                .map_err(|err| invalid("bots", format!("the {} difficulty cannot be built: {}", difficulty, err)))?;
+        }
+        let state = state.with_difficulties(difficulties);
+        Ok(state)
+    }
+}
+
+/// Sets the setting at `path` of `table`, creating the sections on the way.
+fn set_setting(table: &mut Table, variable: &str, path: &[String], value: Value) -> Result<(), GameYError> {
+    let (setting, sections) = path.split_last().expect("split returns at least one part");
+    let mut section = table;
+    for name in sections {
+        section = match section.entry(name.as_str()).or_insert_with(|| Value::Table(Table::new())) {
+            Value::Table(inner) => inner,
+            _ => return Err(invalid(variable, format!("{} is a setting, not a section", name))),
+        };
+    }
+    section.insert(setting.clone(), value);
+    Ok(())
+}
+
+/// Reads the value of an environment variable as TOML, or as a string.
+fn env_value(text: &str) -> Value {
+    toml::from_str::<Table>(&format!("value = {}", text))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(text.to_string()))
+}
+
+fn invalid(setting: &str, err: impl std::fmt::Display) -> GameYError {
+    GameYError::InvalidConfig {
+        message: format!("{}: {}", setting, err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    fn load(file: &str, vars: &[(&str, &str)]) -> Result<ServerConfig, GameYError> {
+        ServerConfig::from_sources(Some(file), env(vars))
+    }
+
+    fn message(err: GameYError) -> String {
+        match err {
+            GameYError::InvalidConfig { message } => message,
+            other => panic!("expected an invalid configuration, found {other}"),
+        }
+    }
+
+    #[test]
+    fn test_defaults() {
+        let config = ServerConfig::from_sources(None, Vec::new()).unwrap();
+        assert_eq!(config, ServerConfig::default());
+        assert_eq!(config.bind_address().unwrap(), "0.0.0.0:3000".parse().unwrap());
+        assert_eq!(config.log.format, LogFormat::Text);
+        assert!(config.metrics.enabled);
+        assert_eq!(config.limits.session_capacity, DEFAULT_SESSION_CAPACITY);
+    }
+
+    #[test]
+    fn test_documented_example_loads() {
+        let example = include_str!("config.rs")
+            .split("//! ```toml\n")
+            .nth(1)
+            .and_then(|rest| rest.split("//! ```").next())
+            .unwrap()
+            .lines()
+            .map(|line| line.trim_start_matches("//!").trim_start())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = load(&example, &[]).unwrap();
+        assert_eq!(config.bind_address().unwrap().port(), 4000);
+        assert_eq!(config.log.format, LogFormat::Json);
+        assert_eq!(config.limits.search.max_timeout_ms, 5000);
+        assert_eq!(config.bots.params["mcts_bot"]["iterations"], 2000);
+    }
+
+    #[test]
+    fn test_environment_overrides_the_file() {
+        let file = "[server]\nbind = \"127.0.0.1:4000\"\n[limits.search]\nmax_timeout_ms = 5000\n";
+        let config = load(
+            file,
+            &[
+                ("GAMEY_SERVER__BIND", "0.0.0.0:8080"),
+                ("GAMEY_LIMITS__SEARCH__MAX_QUEUED", "3"),
+                ("GAMEY_METRICS__ENABLED", "false"),
+                ("GAMEY_BOTS__ENABLED", r#"["random_bot", "mcts_bot"]"#),
+                ("GAMEY_CONFIG", "ignored.toml"),
+                ("HOME", "/root"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(config.server.bind, "0.0.0.0:8080");
+        assert_eq!(config.limits.search.max_queued, 3);
+        assert_eq!(config.limits.search.max_timeout_ms, 5000);
+        assert!(!config.metrics.enabled);
+        assert_eq!(config.bots.enabled, Some(vec!["random_bot".to_string(), "mcts_bot".to_string()]));
+    }
+
+    #[test]
+    fn test_unknown_settings_are_reported() {
+        let err = message(load("[server]\nbnd = \"0.0.0.0:1\"\n", &[]).unwrap_err());
+        assert!(err.contains("config file"), "{err}");
+        assert!(err.contains("bnd"), "{err}");
+
+        let err = message(load("", &[("GAMEY_LIMITS__SEARCH__MAX_THREADS", "4")]).unwrap_err());
+        assert!(err.contains("GAMEY_LIMITS__SEARCH__MAX_THREADS"), "{err}");
+        assert!(err.contains("max_threads"), "{err}");
+
+        let err = message(load("[log]\nlevel = \"info\"\n", &[("GAMEY_LOG__LEVEL__X", "1")]).unwrap_err());
+        assert!(err.contains("not a section"), "{err}");
+    }
+
+    #[test]
+    fn test_invalid_values_are_reported() {
+        let cases = [
+            ("[server]\nbind = \"localhost\"\n", "server.bind"),
+            ("[server]\ncors_origins = [\"example.com\"]\n", "server.cors_origins"),
+            ("[log]\nformat = \"xml\"\n", "config file"),
+            ("[log]\nlevel = \"gamey=loud\"\n", "log.level"),
+            ("[limits.search]\nmax_concurrent = 0\n", "limits.search.max_concurrent"),
+            ("[bots]\nenabled = [\"chess_bot\"]\n", "bots.enabled"),
+            ("[bots.params.mcts_bot]\niterations = 0\n", "bots.params.mcts_bot"),
+            ("[bots.params.random_bot]\nseed = 1\n", "bots.params.random_bot"),
+            ("[bots.params.shortest_path_bot]\nbogus = 1\n", "bots.params.shortest_path_bot"),
+        ];
+        for (file, expected) in cases {
+            let err = message(load(file, &[]).unwrap_err());
+            assert!(err.contains(expected), "{file}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_with_port_keeps_the_host() {
+        let config = load("[server]\nbind = \"127.0.0.1:4000\"\n", &[]).unwrap().with_port(5000);
+        assert_eq!(config.bind_address().unwrap(), "127.0.0.1:5000".parse().unwrap());
+    }
+
+    #[test]
+    fn test_build_state_applies_the_bots_and_limits() {
+        let file = "[metrics]\nenabled = false\n[bots.params.monte_carlo_bot]\nsimulations = 5\n[limits.search]\nmax_timeout_ms = 250\n";
+        let state = load(file, &[]).unwrap().build_state().unwrap();
+        assert!(!state.metrics_enabled());
+        assert_eq!(state.compute().limits().max_timeout_ms, 250);
+        assert!(state.bots().find("monte_carlo_bot").is_some());
+    }
+
+    #[test]
+    fn test_difficulties_of_disabled_bots_are_left_out() {
+        let state = load("[bots]\nenabled = [\"random_bot\", \"shortest_path_bot\"]\n", &[]).unwrap().build_state().unwrap();
+        let difficulties = state.difficulties().difficulties();
+        assert!(difficulties.contains(&crate::Difficulty::Easy));
+        assert!(!difficulties.contains(&crate::Difficulty::Expert));
+    }
+
+    #[test]
+    fn test_env_value_reads_toml() {
+        assert_eq!(env_value("3"), Value::Integer(3));
+        assert_eq!(env_value("true"), Value::Boolean(true));
+        assert_eq!(env_value("0.0.0.0:3000"), Value::String("0.0.0.0:3000".to_string()));
+        assert_eq!(env_value("info,gamey=debug"), Value::String("info,gamey=debug".to_string()));
+    }
+}
@@ -0,0 +1,451 @@
+//! Difficulty levels for the bot server.
+//!
+//! Clients usually want to pick "how hard" the opponent is rather than a
+//! specific bot. This module maps each [`Difficulty`] onto a bot of the
+//! registry, its parameters and an optional blunder probability (see
+//! [`BlunderBot`]), and provides [`calibrate`] to check that every level
+//! actually beats the level below it.
+
+use std::{collections::HashMap, fmt::Display, str::FromStr, sync::Arc, time::Instant};
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    BlunderBot, BotParams, DecisionOptions, GameYError, MoveResponse, PlayerId, YBot, YBotRegistry, YEN,
+    check_api_version,
+    choose::{finished_game_response, parse_position, play_bot_move},
+    error::{ErrorCode, ErrorResponse},
+    play_game,
+    state::AppState,
+};
+
+/// A difficulty level offered to players.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    /// For first games: the bot plays random moves.
+    Easy,
+    /// Plays connection-based moves but makes frequent mistakes.
+    Medium,
+    /// Plays connection-based moves without mistakes.
+    Hard,
+    /// The strongest configuration available.
+    Expert,
+}
+
+impl Difficulty {
+    /// All levels, from the weakest to the strongest.
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    /// Returns the lowercase name used in URLs and JSON.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|level| level.as_str() == s)
+            .ok_or_else(|| format!("Difficulty level not found: {}", s))
+    }
+}
+
+/// The bot configuration behind a difficulty level.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyLevel {
+    /// Name of the bot in the registry.
+    pub bot: String,
+    /// Parameters used to build the bot.
+    #[serde(default)]
+    pub params: BotParams,
+    /// Probability of replacing the bot's move with a random one.
+    #[serde(default)]
+    pub blunder_probability: f64,
+}
+
+impl DifficultyLevel {
+    /// Creates a level that plays `bot` with its default parameters and no blunders.
+    pub fn new(bot: &str) -> Self {
+        DifficultyLevel {
+            bot: bot.to_string(),
+            params: BotParams::new(),
+            blunder_probability: 0.0,
+        }
+    }
+
+    /// Sets the probability of playing a random move instead of the bot's move.
+    pub fn with_blunders(mut self, blunder_probability: f64) -> Self {
+        self.blunder_probability = blunder_probability;
+        self
+    }
+
+    /// Sets a parameter of the bot.
+    pub fn with_param(mut self, key: &str, value: impl Into<serde_json::Value>) -> Self {
+        self.params.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+/// Mapping from each [`Difficulty`] to the [`DifficultyLevel`] that implements it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyTable {
+    levels: HashMap<Difficulty, DifficultyLevel>,
+}
+
+impl DifficultyTable {
+    /// Creates a table without levels.
+    pub fn empty() -> Self {
+        DifficultyTable {
+            levels: HashMap::new(),
+        }
+    }
+
+    /// Sets the level used for `difficulty` and returns the table for chaining.
+    pub fn with_level(mut self, difficulty: Difficulty, level: DifficultyLevel) -> Self {
+        self.levels.insert(difficulty, level);
+        self
+    }
+
+    /// Returns the level configured for `difficulty`, if any.
+    pub fn level(&self, difficulty: Difficulty) -> Option<&DifficultyLevel> {
+        self.levels.get(&difficulty)
+    }
+
+    /// Returns the configured difficulties, from the weakest to the strongest.
+    pub fn difficulties(&self) -> Vec<Difficulty> {
+        let mut difficulties: Vec<Difficulty> = self.levels.keys().copied().collect();
+        difficulties.sort();
+        difficulties
+    }
+
+    /// Builds the bot for `difficulty` from the registry.
+    ///
+    /// # Errors
+    /// Returns [`GameYError::BotNotFound`] if the level is not configured or
+    /// its bot is not registered, and [`GameYError::InvalidBotParams`] if the
+    /// bot rejects the level's parameters.
+    pub fn build(
+        &self,
+        difficulty: Difficulty,
+        registry: &YBotRegistry,
+    ) -> Result<Arc<dyn YBot>, GameYError> {
+        let level = self.level(difficulty).ok_or_else(|| GameYError::BotNotFound {
+            bot_id: difficulty.to_string(),
+            available: self
+                .difficulties()
+                .iter()
+                .map(|d| d.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        })?;
+        let bot = registry.create(&level.bot, &level.params)?;
+        if level.blunder_probability > 0.0 {
+            Ok(Arc::new(BlunderBot::new(bot, level.blunder_probability)))
+        } else {
+            Ok(bot)
+        }
+    }
+}
+
+impl Default for DifficultyTable {
+    /// The levels used by the server, checked with [`calibrate`].
+    fn default() -> Self {
+        DifficultyTable::empty()
+            .with_level(Difficulty::Easy, DifficultyLevel::new("random_bot"))
+            .with_level(
+                Difficulty::Medium,
+                DifficultyLevel::new("shortest_path_bot").with_blunders(0.4),
+            )
+            .with_level(Difficulty::Hard, DifficultyLevel::new("shortest_path_bot"))
+            .with_level(Difficulty::Expert, DifficultyLevel::new("resistance_bot"))
+    }
+}
+
+/// Outcome of the match between two consecutive difficulty levels.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CalibrationResult {
+    /// The weaker level.
+    pub weaker: Difficulty,
+    /// The stronger level.
+    pub stronger: Difficulty,
+    /// Number of games played.
+    pub games: usize,
+    /// Games won by the stronger level.
+    pub stronger_wins: usize,
+    /// Minimum win rate the stronger level had to reach.
+    pub target: f64,
+}
+
+impl CalibrationResult {
+    /// Fraction of the games won by the stronger level.
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            self.stronger_wins as f64 / self.games as f64
+        }
+    }
+
+    /// Returns true if the stronger level reached the target win rate.
+    pub fn passed(&self) -> bool {
+        self.games > 0 && self.win_rate() >= self.target
+    }
+}
+
+/// Plays each configured level against the one below it.
+///
+/// Every pair plays `games` games on a board of size `size`, alternating which
+/// level moves first, and the stronger level is expected to win at least a
+/// `target` fraction of them (e.g. `0.6`).
+///
+/// # Errors
+/// Returns an error if a level cannot be built or a bot plays an invalid move.
+pub fn calibrate(
+    table: &DifficultyTable,
+    registry: &YBotRegistry,
+    size: u32,
+    games: usize,
+    target: f64,
+) -> Result<Vec<CalibrationResult>, GameYError> {
+    let difficulties = table.difficulties();
+    let mut results = Vec::new();
+    for pair in difficulties.windows(2) {
+        let (weaker, stronger) = (pair[0], pair[1]);
+        let weaker_bot = table.build(weaker, registry)?;
+        let stronger_bot = table.build(stronger, registry)?;
+        let mut stronger_wins = 0;
+        for game in 0..games {
+            // Alternate colours so that the first-move advantage is shared.
+            let stronger_seat = game % 2;
+            let mut bots: [&dyn YBot; 2] = [weaker_bot.as_ref(), weaker_bot.as_ref()];
+            bots[stronger_seat] = stronger_bot.as_ref();
+            if play_game(bots, size)? == PlayerId::new(stronger_seat as u32) {
+                stronger_wins += 1;
+            }
+        }
+        results.push(CalibrationResult {
+            weaker,
+            stronger,
+            games,
+            stronger_wins,
+            target,
+        });
+    }
+    Ok(results)
+}
+
+/// Path parameters extracted from the difficulty endpoint URL.
+#[derive(Deserialize)]
+pub struct DifficultyParams {
+    /// The API version (e.g., "v1").
+    api_version: String,
+    /// The difficulty level (`easy`, `medium`, `hard` or `expert`).
+    level: String,
+}
+
+/// Handler for the difficulty-based move selection endpoint.
+///
+/// Works like [`crate::choose::choose`], but the bot is chosen from the
+/// server's [`DifficultyTable`]. The `bot_id` of the response is the level name.
+///
+/// # Route
+/// `POST /{api_version}/ybot/difficulty/{level}`
+///
+/// # Request Body
+/// A JSON object in YEN format representing the current game state. The bot
+/// may think up to the server's [`crate::ComputeLimits::max_timeout_ms`].
+#[utoipa::path(
+    post,
+    path = "/{api_version}/ybot/difficulty/{level}",
+    tag = "bots",
+    params(("api_version" = String, Path, description = "The API version, `v1` (`v2` is served by its own route)"), ("level" = Difficulty, Path, description = "The difficulty level")),
+    request_body = YEN,
+    responses(
+        (status = 200, description = "The move of the bot behind the level", body = MoveResponse),
+        (status = 400, description = "Unsupported version or invalid position", body = ErrorResponse),
+        (status = 404, description = "Unknown level", body = ErrorResponse),
+        (status = 429, description = "Too many searches running or waiting", body = ErrorResponse),
+        (status = 503, description = "No move found before the deadline", body = ErrorResponse),
+    )
+)]
+#[axum::debug_handler]
+pub async fn choose_by_difficulty(
+    State(state): State<AppState>,
+    Path(params): Path<DifficultyParams>,
+    Json(yen): Json<YEN>,
+) -> Result<Json<MoveResponse>, ErrorResponse> {
+    let start = Instant::now();
+    check_api_version(&params.api_version)?;
+    let difficulty = parse_level(&params.level, &params.api_version)?;
+    let (game_y, bot_player) = parse_position(yen, None, &params.api_version, &params.level)?;
+    if let Some(response) = finished_game_response(&game_y, bot_player, &params.api_version, &params.level) {
+        return Ok(Json(response));
+    }
+    let bot = difficulty_bot(&state, difficulty, &params.api_version, &params.level)?;
+    let timeout = state.compute().timeout(None);
+    let (api_version, level) = (params.api_version.clone(), params.level.clone());
+    state
+        .compute()
+        .run_request(timeout, &params.api_version, Some(&params.level), move || {
+            play_bot_move(bot.as_ref(), game_y, bot_player, &DecisionOptions::default(), api_version, level, start)
+        })
+        .await
+}
+
+/// Parses the difficulty level of a request.
+pub(crate) fn parse_level(level: &str, api_version: &str) -> Result<Difficulty, ErrorResponse> {
+    level.parse().map_err(|message: String| {
+        ErrorResponse::new(
+            ErrorCode::DifficultyNotFound,
+            &message,
+            Some(api_version.to_string()),
+            Some(level.to_string()),
+        )
+    })
+}
+
+/// Builds the bot behind a difficulty level of the server.
+pub(crate) fn difficulty_bot(
+    state: &AppState,
+    difficulty: Difficulty,
+    api_version: &str,
+    level: &str,
+) -> Result<Arc<dyn YBot>, ErrorResponse> {
+    state
+        .difficulties()
+        .build(difficulty, &state.bots())
+        .map_err(|err| ErrorResponse::from_error(&err, Some(api_version.to_string()), Some(level.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameY, RandomBot, bot_server::create_default_state};
+
+    #[test]
+    fn test_difficulty_from_str() {
+        assert_eq!("easy".parse::<Difficulty>(), Ok(Difficulty::Easy));
+        assert_eq!("expert".parse::<Difficulty>(), Ok(Difficulty::Expert));
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
+
+    #[test]
+    fn test_difficulty_serde_lowercase() {
+        let json = serde_json::to_string(&Difficulty::Medium).unwrap();
+        assert_eq!(json, "\"medium\"");
+        let level: Difficulty = serde_json::from_str("\"hard\"").unwrap();
+        assert_eq!(level, Difficulty::Hard);
+    }
+
+    #[test]
+    fn test_default_table_covers_all_levels() {
+        let table = DifficultyTable::default();
+        assert_eq!(table.difficulties(), Difficulty::ALL.to_vec());
+    }
+
+    #[test]
+    fn test_default_table_builds_with_default_registry() {
+        let state = create_default_state();
+        let table = DifficultyTable::default();
+        for difficulty in Difficulty::ALL {
+            assert!(table.build(difficulty, &state.bots()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_build_missing_level() {
+        let registry = YBotRegistry::new().with_bot(Arc::new(RandomBot));
+        let table = DifficultyTable::empty().with_level(Difficulty::Easy, DifficultyLevel::new("random_bot"));
+        let err = table.build(Difficulty::Expert, &registry).err().unwrap();
+        assert!(matches!(err, GameYError::BotNotFound { .. }));
+    }
+
+    #[test]
+    fn test_difficulty_level_deserialize_defaults() {
+        let level: DifficultyLevel = serde_json::from_str(r#"{"bot":"random_bot"}"#).unwrap();
+        assert_eq!(level, DifficultyLevel::new("random_bot"));
+    }
+
+    #[test]
+    fn test_calibrate_reports_each_pair() {
+        let registry = YBotRegistry::new().with_bot(Arc::new(RandomBot));
+        let table = DifficultyTable::empty()
+            .with_level(Difficulty::Easy, DifficultyLevel::new("random_bot"))
+            .with_level(Difficulty::Medium, DifficultyLevel::new("random_bot"))
+            .with_level(Difficulty::Hard, DifficultyLevel::new("random_bot"));
+
+        let results = calibrate(&table, &registry, 3, 4, 0.5).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].weaker, Difficulty::Easy);
+        assert_eq!(results[0].stronger, Difficulty::Medium);
+        assert_eq!(results[1].stronger, Difficulty::Hard);
+        assert!(results.iter().all(|r| r.games == 4 && r.stronger_wins <= 4));
+    }
+
+    #[test]
+    fn test_calibration_result_passed() {
+        let result = CalibrationResult {
+            weaker: Difficulty::Easy,
+            stronger: Difficulty::Medium,
+            games: 10,
+            stronger_wins: 7,
+            target: 0.6,
+        };
+        assert!((result.win_rate() - 0.7).abs() < 1e-9);
+        assert!(result.passed());
+    }
+
+    #[tokio::test]
+    async fn test_choose_by_difficulty_success() {
+        let params = Path(DifficultyParams {
+            api_version: "v1".to_string(),
+            level: "easy".to_string(),
+        });
+        let yen = Json(YEN::from(&GameY::new(3)));
+
+        let result = choose_by_difficulty(State(create_default_state()), params, yen).await;
+
+        let response = result.unwrap().0;
+        assert_eq!(response.bot_id, "easy");
+    }
+
+    #[tokio::test]
+    async fn test_choose_by_unknown_difficulty() {
+        let params = Path(DifficultyParams {
+            api_version: "v1".to_string(),
+            level: "impossible".to_string(),
+        });
+        let yen = Json(YEN::from(&GameY::new(3)));
+
+        let result = choose_by_difficulty(State(create_default_state()), params, yen).await;
+
+        let error_msg = result.unwrap_err().message;
+        assert!(error_msg.contains("Difficulty level not found"));
+    }
+}
@@ -1,12 +1,109 @@
+use crate::GameYError;
 use axum::{Json, http::StatusCode, response::IntoResponse};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Stable, machine-readable identifier of an API error.
+///
+/// Clients should branch on the code rather than on the message, which is
+/// meant for people and may change. Each code has a fixed HTTP status (see
+/// [`ErrorCode::status`]).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// The API version of the URL is not supported (400).
+    UnsupportedVersion,
+    /// No bot has the requested id (404).
+    BotNotFound,
+    /// No difficulty level has the requested name (404).
+    DifficultyNotFound,
+    /// No game has the requested id, or it expired (404).
+    GameNotFound,
+    /// The position is not valid YEN (400).
+    InvalidYen,
+    /// The position is valid YEN but cannot be used for this request, for
+    /// example because the game is already over (400).
+    InvalidPosition,
+    /// The parameters of a bot were rejected (400).
+    InvalidBotParams,
+    /// The request has a field with a value that cannot be used (400).
+    InvalidRequest,
+    /// The move is not allowed by the rules of the game (400).
+    InvalidMove,
+    /// The move was made out of turn (409).
+    NotYourTurn,
+    /// The move was made in a game that is over (409).
+    GameOver,
+    /// The seat token is missing or wrong (403).
+    InvalidToken,
+    /// The server has too many searches running or waiting; retry later (429).
+    Overloaded,
+    /// The bot could not answer before the deadline of the request (503).
+    Timeout,
+    /// The server failed to handle a valid request (500).
+    InternalError,
+}
+
+impl ErrorCode {
+    /// Returns the HTTP status of responses with this code.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ErrorCode::BotNotFound | ErrorCode::DifficultyNotFound | ErrorCode::GameNotFound => StatusCode::NOT_FOUND,
+            ErrorCode::UnsupportedVersion
+            | ErrorCode::InvalidYen
+            | ErrorCode::InvalidPosition
+            | ErrorCode::InvalidBotParams
+            | ErrorCode::InvalidRequest
+            | ErrorCode::InvalidMove => StatusCode::BAD_REQUEST,
+            ErrorCode::NotYourTurn | ErrorCode::GameOver => StatusCode::CONFLICT,
+            ErrorCode::InvalidToken => StatusCode::FORBIDDEN,
+            ErrorCode::Overloaded => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::Timeout => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<&GameYError> for ErrorCode {
+    fn from(err: &GameYError) -> Self {
+        match err {
+            GameYError::BotNotFound { .. } => ErrorCode::BotNotFound,
+            GameYError::GameNotFound { .. } => ErrorCode::GameNotFound,
+            GameYError::InvalidBotParams { .. } => ErrorCode::InvalidBotParams,
+            GameYError::InvalidCharInLayout { .. }
+            | GameYError::InvalidNumPlayers { .. }
+            | GameYError::InvalidYENLayout { .. }
+            | GameYError::InvalidYENLayoutLine { .. } => ErrorCode::InvalidYen,
+            GameYError::Occupied { .. }
+            | GameYError::InvalidMove { .. }
+            | GameYError::BadCoordsNumber { .. }
+            | GameYError::CoordOutOfRange { .. } => ErrorCode::InvalidMove,
+            GameYError::InvalidPlayerTurn { .. } => ErrorCode::NotYourTurn,
+            GameYError::GameOver { .. } => ErrorCode::GameOver,
+            GameYError::InvalidToken { .. } => ErrorCode::InvalidToken,
+            GameYError::Overloaded { .. } => ErrorCode::Overloaded,
+            GameYError::SearchTimeout { .. } => ErrorCode::Timeout,
+            GameYError::InvalidGame { .. } | GameYError::SerdeError { .. } => ErrorCode::InvalidRequest,
+            GameYError::IoError { .. }
+            | GameYError::ServerError { .. }
+            | GameYError::InvalidTournament { .. }
+            | GameYError::InvalidModel { .. }
+            | GameYError::InvalidTuning { .. }
+            | GameYError::InvalidSelfPlay { .. }
+            | GameYError::InvalidConfig { .. } => ErrorCode::InternalError,
+        }
+    }
+}
 
 /// A structured error response returned by the bot server API.
 ///
 /// This type is serialized to JSON and returned when API requests fail.
-/// It includes context about which API version and bot were involved.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+/// It includes a stable [`ErrorCode`], which sets the HTTP status, and
+/// context about which API version and bot were involved.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
 pub struct ErrorResponse {
+    /// What went wrong, for programs.
+    pub code: ErrorCode,
     /// The API version that was requested, if available.
     pub api_version: Option<String>,
     /// The bot ID that was requested, if available.
@@ -16,44 +113,43 @@ pub struct ErrorResponse {
 }
 
 impl ErrorResponse {
-    /// Creates a new error response with the given message and optional context.
+    /// Creates a new error response with the given code, message and optional context.
     ///
     /// # Arguments
+    /// * `code` - What went wrong, which also sets the HTTP status
     /// * `message` - A description of the error
     /// * `api_version` - The API version from the request, if known
     /// * `bot_id` - The bot ID from the request, if known
-    pub fn error(message: &str, api_version: Option<String>, bot_id: Option<String>) -> Self {
+    pub fn new(code: ErrorCode, message: &str, api_version: Option<String>, bot_id: Option<String>) -> Self {
         Self {
+            code,
             bot_id,
             api_version,
             message: message.to_string(),
         }
     }
+
+    /// Creates the error response for `err`, with the code it converts into.
+    pub fn from_error(err: &GameYError, api_version: Option<String>, bot_id: Option<String>) -> Self {
+        Self::new(ErrorCode::from(err), &err.to_string(), api_version, bot_id)
+    }
 }
 
 impl IntoResponse for ErrorResponse {
     fn into_response(self) -> axum::response::Response {
-        let status = if self.message.contains("not found") || self.message.contains("no encontrado") {
-            StatusCode::NOT_FOUND // 404
-        } else if self.message.contains("Invalid") || self.message.contains("inválido") {
-            StatusCode::BAD_REQUEST // 400
-        } else if self.message.contains("Failed to apply") {
-            StatusCode::INTERNAL_SERVER_ERROR // 500
-        } else {
-            StatusCode::BAD_REQUEST // Por defecto 400
-        };
-
-        (status, Json(self)).into_response()
+        (self.code.status(), Json(self)).into_response()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Coordinates, Movement, PlayerId};
 
     #[test]
     fn test_error_with_all_fields() {
-        let err = ErrorResponse::error(
+        let err = ErrorResponse::new(
+            ErrorCode::InternalError,
             "Something went wrong",
             Some("v1".to_string()),
             Some("random".to_string()),
@@ -65,7 +161,7 @@ mod tests {
 
     #[test]
     fn test_error_with_no_context() {
-        let err = ErrorResponse::error("Generic error", None, None);
+        let err = ErrorResponse::new(ErrorCode::InternalError, "Generic error", None, None);
         assert_eq!(err.message, "Generic error");
         assert_eq!(err.api_version, None);
         assert_eq!(err.bot_id, None);
@@ -73,7 +169,7 @@ mod tests {
 
     #[test]
     fn test_error_with_partial_context() {
-        let err = ErrorResponse::error("Version error", Some("v2".to_string()), None);
+        let err = ErrorResponse::new(ErrorCode::UnsupportedVersion, "Version error", Some("v2".to_string()), None);
         assert_eq!(err.message, "Version error");
         assert_eq!(err.api_version, Some("v2".to_string()));
         assert_eq!(err.bot_id, None);
@@ -81,8 +177,9 @@ mod tests {
 
     #[test]
     fn test_serialize() {
-        let err = ErrorResponse::error("Test error", Some("v1".to_string()), Some("bot1".to_string()));
+        let err = ErrorResponse::new(ErrorCode::BotNotFound, "Test error", Some("v1".to_string()), Some("bot1".to_string()));
         let json = serde_json::to_string(&err).unwrap();
+        assert!(json.contains("\"code\":\"BOT_NOT_FOUND\""));
         assert!(json.contains("\"message\":\"Test error\""));
         assert!(json.contains("\"api_version\":\"v1\""));
         assert!(json.contains("\"bot_id\":\"bot1\""));
@@ -90,8 +187,9 @@ mod tests {
 
     #[test]
     fn test_deserialize() {
-        let json = r#"{"api_version":"v1","bot_id":"random","message":"error msg"}"#;
+        let json = r#"{"code":"INVALID_YEN","api_version":"v1","bot_id":"random","message":"error msg"}"#;
         let err: ErrorResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(err.code, ErrorCode::InvalidYen);
         assert_eq!(err.message, "error msg");
         assert_eq!(err.api_version, Some("v1".to_string()));
         assert_eq!(err.bot_id, Some("random".to_string()));
@@ -99,8 +197,98 @@ mod tests {
 
     #[test]
     fn test_clone() {
-        let err = ErrorResponse::error("Clone test", Some("v1".to_string()), None);
+        let err = ErrorResponse::new(ErrorCode::InternalError, "Clone test", Some("v1".to_string()), None);
         let cloned = err.clone();
         assert_eq!(err, cloned);
     }
+
+    #[test]
+    fn test_each_code_has_its_status() {
+        let cases = [
+            (ErrorCode::UnsupportedVersion, "UNSUPPORTED_VERSION", StatusCode::BAD_REQUEST),
+            (ErrorCode::BotNotFound, "BOT_NOT_FOUND", StatusCode::NOT_FOUND),
+            (ErrorCode::DifficultyNotFound, "DIFFICULTY_NOT_FOUND", StatusCode::NOT_FOUND),
+            (ErrorCode::GameNotFound, "GAME_NOT_FOUND", StatusCode::NOT_FOUND),
+            (ErrorCode::InvalidYen, "INVALID_YEN", StatusCode::BAD_REQUEST),
+            (ErrorCode::InvalidPosition, "INVALID_POSITION", StatusCode::BAD_REQUEST),
+            (ErrorCode::InvalidBotParams, "INVALID_BOT_PARAMS", StatusCode::BAD_REQUEST),
+            (ErrorCode::InvalidRequest, "INVALID_REQUEST", StatusCode::BAD_REQUEST),
+            (ErrorCode::InvalidMove, "INVALID_MOVE", StatusCode::BAD_REQUEST),
+            (ErrorCode::NotYourTurn, "NOT_YOUR_TURN", StatusCode::CONFLICT),
+            (ErrorCode::GameOver, "GAME_OVER", StatusCode::CONFLICT),
+            (ErrorCode::InvalidToken, "INVALID_TOKEN", StatusCode::FORBIDDEN),
+            (ErrorCode::Overloaded, "OVERLOADED", StatusCode::TOO_MANY_REQUESTS),
+            (ErrorCode::Timeout, "TIMEOUT", StatusCode::SERVICE_UNAVAILABLE),
+            (ErrorCode::InternalError, "INTERNAL_ERROR", StatusCode::INTERNAL_SERVER_ERROR),
+        ];
+        for (code, name, status) in cases {
+            assert_eq!(serde_json::to_value(code).unwrap(), serde_json::json!(name));
+            assert_eq!(code.status(), status);
+            let response = ErrorResponse::new(code, "message", None, None).into_response();
+            assert_eq!(response.status(), status);
+        }
+    }
+
+    #[test]
+    fn test_status_does_not_depend_on_the_message() {
+        let response = ErrorResponse::new(ErrorCode::InternalError, "Bot no encontrado: inválido", None, None);
+        assert_eq!(response.into_response().status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn test_gamey_errors_convert_into_codes() {
+        let placement = Movement::Placement {
+            player: PlayerId::new(0),
+            coords: Coordinates::new(0, 0, 0),
+        };
+        let cases = [
+            (
+                GameYError::BotNotFound {
+                    bot_id: "b".to_string(),
+                    available: String::new(),
+                },
+                ErrorCode::BotNotFound,
+            ),
+            (GameYError::GameNotFound { game_id: "g".to_string() }, ErrorCode::GameNotFound),
+            (
+                GameYError::InvalidBotParams {
+                    bot_id: "b".to_string(),
+                    message: String::new(),
+                },
+                ErrorCode::InvalidBotParams,
+            ),
+            (GameYError::InvalidYENLayout { expected: 3, found: 2 }, ErrorCode::InvalidYen),
+            (
+                GameYError::Occupied {
+                    coordinates: Coordinates::new(0, 0, 0),
+                    player: PlayerId::new(0),
+                },
+                ErrorCode::InvalidMove,
+            ),
+            (GameYError::InvalidMove { message: String::new() }, ErrorCode::InvalidMove),
+            (
+                GameYError::InvalidPlayerTurn {
+                    expected: PlayerId::new(0),
+                    found: PlayerId::new(1),
+                },
+                ErrorCode::NotYourTurn,
+            ),
+            (GameYError::GameOver { movement: placement }, ErrorCode::GameOver),
+            (
+                GameYError::InvalidToken {
+                    game_id: "g".to_string(),
+                    message: String::new(),
+                },
+                ErrorCode::InvalidToken,
+            ),
+            (GameYError::InvalidGame { message: String::new() }, ErrorCode::InvalidRequest),
+            (GameYError::Overloaded { message: String::new() }, ErrorCode::Overloaded),
+            (GameYError::SearchTimeout { timeout_ms: 10 }, ErrorCode::Timeout),
+            (GameYError::ServerError { message: String::new() }, ErrorCode::InternalError),
+        ];
+        for (err, code) in cases {
+            assert_eq!(ErrorCode::from(&err), code, "{}", err);
+            assert_eq!(ErrorResponse::from_error(&err, None, None).code, code);
+        }
+    }
 }
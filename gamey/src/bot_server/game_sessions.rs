@@ -0,0 +1,873 @@
+//! Games kept on the server and played move by move.
+//!
+//! The choose endpoint is stateless: clients send the whole position on every
+//! turn. A [`GameSession`] instead keeps the board, the players and the
+//! history on the server and checks every move against the rules. A session
+//! is played either by a human against a bot, which answers every human move,
+//! or by two humans. The bot's reply is computed outside the session (see
+//! [`GameSession::bot_turn`]), so a slow search does not block the game. Each human seat has a token that the player
+//! sends with their moves and uses to reconnect, and games may have a clock.
+//! Every change is published as a [`GameEvent`] to the clients watching the
+//! game (see [`GameSession::subscribe`]).
+//!
+//! [`GameSessions`] stores the games by id and drops the ones that have been
+//! idle for longer than its time to live.
+
+use crate::{
+    BotDecision, Coordinates, DecisionOptions, ErrorCode, GameAction, GameResponse, GameStatus, GameY, GameYError, Movement,
+    PlayerId, YBot, swap_available,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// Default time a game may stay idle before it is dropped.
+pub const DEFAULT_GAME_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// How many events a listener may fall behind before it misses some.
+const EVENT_CAPACITY: usize = 64;
+
+/// The rules a game session is played with.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameRules {
+    /// The second player may swap after the first stone.
+    pub pie_rule: bool,
+    /// The bot may resign positions it has lost by force.
+    pub allow_resign: bool,
+}
+
+impl GameRules {
+    /// The options the bot decides with under these rules.
+    pub fn decision_options(&self) -> DecisionOptions {
+        DecisionOptions::default()
+            .with_pie_rule(self.pie_rule)
+            .with_resign(self.allow_resign)
+    }
+}
+
+/// The time control of a game: each player starts with `initial_ms` and
+/// gains `increment_ms` after each of their moves. A player whose time runs
+/// out loses.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClockSettings {
+    /// Time of each player at the start, in milliseconds.
+    pub initial_ms: u64,
+    /// Time added after each move, in milliseconds.
+    #[serde(default)]
+    pub increment_ms: u64,
+}
+
+/// The clocks of a game at one moment.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub struct ClockState {
+    /// Time left of player 0 and player 1, in milliseconds.
+    pub remaining_ms: [u64; 2],
+    /// The player whose clock is running, or `None` once the game is over.
+    pub running: Option<u32>,
+}
+
+/// The running clocks of a game.
+#[derive(Debug, Clone)]
+struct Clock {
+    settings: ClockSettings,
+    remaining: [Duration; 2],
+    since: Instant,
+}
+
+impl Clock {
+    fn new(settings: ClockSettings) -> Self {
+        let initial = Duration::from_millis(settings.initial_ms);
+        Clock {
+            settings,
+            remaining: [initial, initial],
+            since: Instant::now(),
+        }
+    }
+
+    /// Time left of `player` if their clock is the one running.
+    fn left(&self, player: PlayerId) -> Duration {
+        self.remaining[player.id() as usize].saturating_sub(self.since.elapsed())
+    }
+
+    /// Charges `player` for the move they just made and starts the other clock.
+    fn charge(&mut self, player: PlayerId, increment: bool) {
+        let index = player.id() as usize;
+        self.remaining[index] = self.left(player);
+        if increment {
+            self.remaining[index] += Duration::from_millis(self.settings.increment_ms);
+        }
+        self.since = Instant::now();
+    }
+
+    fn state(&self, running: Option<PlayerId>) -> ClockState {
+        let mut remaining_ms = self.remaining.map(|left| left.as_millis() as u64);
+        if let Some(player) = running {
+            remaining_ms[player.id() as usize] = self.left(player).as_millis() as u64;
+        }
+        ClockState {
+            remaining_ms,
+            running: running.map(|player| player.id()),
+        }
+    }
+}
+
+/// A move of a game session, by a human or by the bot.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub struct SessionMove {
+    /// Id of the player who made the move.
+    pub player: u32,
+    /// The stone placed or the action taken.
+    #[serde(flatten)]
+    pub decision: BotDecision,
+}
+
+/// The outcome of a game session so far.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    /// The game goes on.
+    Ongoing,
+    /// The human won against the bot.
+    HumanWon,
+    /// The bot won.
+    BotWon,
+    /// A game between two humans is over; the winner is given apart.
+    Finished,
+}
+
+/// Why a game session ended.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EndReason {
+    /// The winner connected the three sides.
+    Connection,
+    /// The loser resigned.
+    Resign,
+    /// The loser ran out of time.
+    Timeout,
+}
+
+/// Something that happened in a game session, as sent to its listeners.
+///
+/// Serialized with a `type` field:
+/// ```json
+/// {"type": "move", "move": {"player": 0, "action": "place", "coords": {"x": 0, "y": 0, "z": 2}}, "ply": 1}
+/// {"type": "status", "status": "ongoing", "next_player": 1, "winner": null, "reason": null}
+/// {"type": "clock", "remaining_ms": [59000, 60000], "running": 1}
+/// ```
+/// Sessions publish `move`, `status` and `clock` events; the WebSocket
+/// endpoint also sends `snapshot` and `error` messages to a single client.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    /// A move was played.
+    Move {
+        /// The move.
+        #[serde(rename = "move")]
+        played: SessionMove,
+        /// Number of moves played so far, this one included.
+        ply: usize,
+    },
+    /// The status after a move, or after a player ran out of time.
+    Status {
+        /// Who has won, if anyone.
+        status: SessionStatus,
+        /// The player to move, while the game goes on.
+        next_player: Option<u32>,
+        /// The winner, once the game is over.
+        winner: Option<u32>,
+        /// Why the game ended, once it is over.
+        reason: Option<EndReason>,
+    },
+    /// The clocks of the game.
+    Clock(ClockState),
+    /// The whole state of the game.
+    Snapshot {
+        /// The game, as returned by the game endpoints.
+        game: Box<GameResponse>,
+    },
+    /// A message of the client was rejected.
+    Error {
+        /// What went wrong, as in the error responses of the HTTP API.
+        code: ErrorCode,
+        /// Why it was rejected.
+        message: String,
+    },
+}
+
+/// A position the bot of a session has to answer, taken out of the session
+/// so that the search runs without holding it (see [`GameSession::bot_turn`]).
+#[derive(Clone)]
+pub struct BotTurn {
+    bot: Arc<dyn YBot>,
+    game: GameY,
+    options: DecisionOptions,
+    /// The number of moves played when the turn was taken.
+    ply: usize,
+}
+
+impl BotTurn {
+    /// Asks the bot for its decision. This is the slow part of a turn.
+    pub fn decide(&self) -> BotDecision {
+        self.bot.decide(&self.game, &self.options)
+    }
+}
+
+/// The bot of a human-vs-bot session.
+struct SessionBot {
+    id: String,
+    bot: Arc<dyn YBot>,
+    player: PlayerId,
+}
+
+/// A game kept on the server, between a human and a bot or between two humans.
+pub struct GameSession {
+    id: String,
+    bot: Option<SessionBot>,
+    rules: GameRules,
+    game: GameY,
+    history: Vec<SessionMove>,
+    tokens: [Option<String>; 2],
+    clock: Option<Clock>,
+    end_reason: Option<EndReason>,
+    events: broadcast::Sender<GameEvent>,
+}
+
+impl GameSession {
+    /// Starts a game between a human and a bot on an empty board of `size`.
+    /// When the bot plays first, [`GameSession::bot_turn`] has its first move.
+    ///
+    /// # Errors
+    /// Returns [`GameYError::InvalidGame`] if the size is 0 or `bot_player`
+    /// is not 0 or 1.
+    pub fn new(
+        id: String,
+        bot_id: String,
+        bot: Arc<dyn YBot>,
+        bot_player: u32,
+        rules: GameRules,
+        size: u32,
+    ) -> Result<Self, GameYError> {
+        if bot_player > 1 {
+            return Err(GameYError::InvalidGame {
+                message: format!("bot_player must be 0 or 1, found {}", bot_player),
+            });
+        }
+        let mut session = Self::between_humans(id, rules, size)?;
+        session.tokens[bot_player as usize] = None;
+        session.bot = Some(SessionBot {
+            id: bot_id,
+            bot,
+            player: PlayerId::new(bot_player),
+        });
+        Ok(session)
+    }
+
+    /// Starts a game between two humans on an empty board of `size`.
+    ///
+    /// # Errors
+    /// Returns [`GameYError::InvalidGame`] if the size is 0.
+    pub fn between_humans(id: String, rules: GameRules, size: u32) -> Result<Self, GameYError> {
+        if size == 0 {
+            return Err(GameYError::InvalidGame {
+                message: "the board size must be greater than 0".to_string(),
+            });
+        }
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Ok(GameSession {
+            id,
+            bot: None,
+            rules,
+            game: GameY::new(size),
+            history: Vec::new(),
+            tokens: [Some(new_token()), Some(new_token())],
+            clock: None,
+            end_reason: None,
+            events,
+        })
+    }
+
+    /// Puts the game under `settings` and returns it for chaining. The clock
+    /// of the player to move starts right away.
+    pub fn with_clock(mut self, settings: ClockSettings) -> Self {
+        self.clock = Some(Clock::new(settings));
+        self
+    }
+
+    /// Returns the id of the game.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the id of the bot, or `None` in a game between two humans.
+    pub fn bot_id(&self) -> Option<&str> {
+        self.bot.as_ref().map(|bot| bot.id.as_str())
+    }
+
+    /// Returns the player the bot plays as, or `None` in a game between two humans.
+    pub fn bot_player(&self) -> Option<PlayerId> {
+        self.bot.as_ref().map(|bot| bot.player)
+    }
+
+    /// Returns the rules of the game.
+    pub fn rules(&self) -> GameRules {
+        self.rules
+    }
+
+    /// Returns the current position.
+    pub fn game(&self) -> &GameY {
+        &self.game
+    }
+
+    /// Returns every move played so far, in order.
+    pub fn history(&self) -> &[SessionMove] {
+        &self.history
+    }
+
+    /// Returns the token of the seat of `player`, or `None` for the bot's seat.
+    pub fn token(&self, player: PlayerId) -> Option<&str> {
+        self.tokens.get(player.id() as usize)?.as_deref()
+    }
+
+    /// Returns the clocks, or `None` if the game has no time control.
+    pub fn clock(&self) -> Option<ClockState> {
+        Some(self.clock.as_ref()?.state(self.game.next_player()))
+    }
+
+    /// Returns why the game ended, or `None` while it goes on.
+    pub fn end_reason(&self) -> Option<EndReason> {
+        self.end_reason
+    }
+
+    /// Returns the winner, if anyone.
+    pub fn winner(&self) -> Option<PlayerId> {
+        match self.game.status() {
+            GameStatus::Ongoing { .. } => None,
+            GameStatus::Finished { winner } => Some(*winner),
+        }
+    }
+
+    /// Returns who has won, if anyone.
+    pub fn status(&self) -> SessionStatus {
+        match (self.winner(), self.bot_player()) {
+            (None, _) => SessionStatus::Ongoing,
+            (Some(_), None) => SessionStatus::Finished,
+            (Some(winner), Some(bot)) if winner == bot => SessionStatus::BotWon,
+            (Some(_), Some(_)) => SessionStatus::HumanWon,
+        }
+    }
+
+    /// Returns a receiver of the events published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<GameEvent> {
+        self.events.subscribe()
+    }
+
+    /// Returns the seat that `token` belongs to.
+    ///
+    /// Without a token, the human's seat is returned in a game against a bot;
+    /// games between two humans always need one.
+    ///
+    /// # Errors
+    /// Returns [`GameYError::InvalidToken`] if the token belongs to no seat,
+    /// or none is given in a game between two humans.
+    pub fn player_for(&self, token: Option<&str>) -> Result<PlayerId, GameYError> {
+        let invalid = |message: &str| GameYError::InvalidToken {
+            game_id: self.id.clone(),
+            message: message.to_string(),
+        };
+        match (token, &self.bot) {
+            (Some(token), _) => (0..2)
+                .map(PlayerId::new)
+                .find(|&player| self.token(player) == Some(token))
+                .ok_or_else(|| invalid("the token belongs to no seat of this game")),
+            (None, Some(bot)) => Ok(PlayerId::new(1 - bot.player.id())),
+            (None, None) => Err(invalid("a seat token is needed to move in a game between two humans")),
+        }
+    }
+
+    /// Ends the game if the player to move has run out of time. Returns true
+    /// if it did.
+    pub fn check_clock(&mut self) -> bool {
+        let (Some(clock), Some(player)) = (&self.clock, self.game.next_player()) else {
+            return false;
+        };
+        if !clock.left(player).is_zero() {
+            return false;
+        }
+        let resign = Movement::Action {
+            player,
+            action: GameAction::Resign,
+        };
+        if self.game.add_move(resign).is_err() {
+            return false;
+        }
+        if let Some(clock) = &mut self.clock {
+            clock.charge(player, false);
+        }
+        self.end_reason = Some(EndReason::Timeout);
+        self.publish_status();
+        true
+    }
+
+    /// Plays the move of the human seated as `player`. In a game against a
+    /// bot, [`GameSession::bot_turn`] then has the bot's reply.
+    ///
+    /// # Errors
+    /// Returns an error, leaving the game untouched, if the game is over
+    /// (time running out included), `player` is the bot or it is not their
+    /// turn, the cell is not on the board or is taken, or the player swaps
+    /// when the rules do not allow it.
+    pub fn play(&mut self, player: PlayerId, decision: BotDecision) -> Result<(), GameYError> {
+        self.check_clock();
+        let movement = decision.into_movement(player);
+        let Some(next_player) = self.game.next_player() else {
+            return Err(GameYError::GameOver { movement });
+        };
+        if Some(player) == self.bot_player() {
+            return Err(GameYError::InvalidMove {
+                message: format!("player {} is the bot", player),
+            });
+        }
+        if next_player != player {
+            return Err(GameYError::InvalidPlayerTurn {
+                expected: next_player,
+                found: player,
+            });
+        }
+        match decision {
+            BotDecision::Place { coords } => self.check_cell(player, coords)?,
+            BotDecision::Swap if !swap_available(&self.game, &self.rules.decision_options()) => {
+                return Err(GameYError::InvalidMove {
+                    message: "a swap is only allowed under the pie rule, right after the first stone".to_string(),
+                });
+            }
+            BotDecision::Swap | BotDecision::Resign => {}
+        }
+        self.apply(player, decision)
+    }
+
+    /// Checks that `coords` is an empty cell of the board.
+    fn check_cell(&self, player: PlayerId, coords: Coordinates) -> Result<(), GameYError> {
+        let size = self.game.board_size();
+        if coords.x() + coords.y() + coords.z() != size - 1 {
+            return Err(GameYError::InvalidMove {
+                message: format!("{} is not a cell of a board of size {}", coords, size),
+            });
+        }
+        if !self.game.available_cells().contains(&coords.to_index(size)) {
+            return Err(GameYError::Occupied {
+                coordinates: coords,
+                player,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the position the bot has to answer, or `None` when it is not
+    /// the bot's turn.
+    ///
+    /// The caller computes the decision with [`BotTurn::decide`], without
+    /// holding the session, and plays it with [`GameSession::play_bot`].
+    pub fn bot_turn(&self) -> Option<BotTurn> {
+        let bot = self.bot.as_ref()?;
+        if self.game.next_player() != Some(bot.player) {
+            return None;
+        }
+        Some(BotTurn {
+            bot: Arc::clone(&bot.bot),
+            game: self.game.clone(),
+            options: self.rules.decision_options(),
+            ply: self.history.len(),
+        })
+    }
+
+    /// Plays the bot's `decision` for `turn` and returns the bot's move.
+    ///
+    /// Returns `None`, leaving the game untouched, when the game has moved on
+    /// since the turn was taken, e.g. because the bot ran out of time or the
+    /// reply was already played.
+    ///
+    /// # Errors
+    /// Returns an error if the game rejects the decision.
+    pub fn play_bot(&mut self, turn: &BotTurn, decision: BotDecision) -> Result<Option<SessionMove>, GameYError> {
+        self.check_clock();
+        let Some(player) = self.bot_player() else {
+            return Ok(None);
+        };
+        if self.history.len() != turn.ply || self.game.next_player() != Some(player) {
+            return Ok(None);
+        }
+        self.apply(player, decision)?;
+        Ok(self.history.last().copied())
+    }
+
+    fn apply(&mut self, player: PlayerId, decision: BotDecision) -> Result<(), GameYError> {
+        self.game.add_move(decision.into_movement(player))?;
+        let played = SessionMove {
+            player: player.id(),
+            decision,
+        };
+        self.history.push(played);
+        if self.winner().is_some() {
+            self.end_reason = Some(match decision {
+                BotDecision::Resign => EndReason::Resign,
+                _ => EndReason::Connection,
+            });
+        }
+        let ply = self.history.len();
+        self.publish(GameEvent::Move { played, ply });
+        self.publish_status();
+        if let Some(clock) = &mut self.clock {
+            clock.charge(player, true);
+            let state = clock.state(self.game.next_player());
+            self.publish(GameEvent::Clock(state));
+        }
+        Ok(())
+    }
+
+    fn publish_status(&self) {
+        self.publish(GameEvent::Status {
+            status: self.status(),
+            next_player: self.game.next_player().map(|player| player.id()),
+            winner: self.winner().map(|player| player.id()),
+            reason: self.end_reason,
+        });
+    }
+
+    fn publish(&self, event: GameEvent) {
+        // Sending only fails when nobody listens, which is fine.
+        let _ = self.events.send(event);
+    }
+}
+
+/// A random token that is hard to guess.
+fn new_token() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+/// A stored game and when it was last used.
+struct StoredGame {
+    session: Arc<Mutex<GameSession>>,
+    last_used: Instant,
+}
+
+/// The game sessions of the server, by id.
+///
+/// Games idle for longer than [`GameSessions::ttl`] are dropped the next time
+/// the store is used.
+pub struct GameSessions {
+    games: Mutex<HashMap<String, StoredGame>>,
+    ttl: Duration,
+}
+
+impl Default for GameSessions {
+    fn default() -> Self {
+        GameSessions::new(DEFAULT_GAME_TTL)
+    }
+}
+
+impl GameSessions {
+    /// Creates an empty store whose games expire after `ttl` without use.
+    pub fn new(ttl: Duration) -> Self {
+        GameSessions {
+            games: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// Returns how long a game may stay idle.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Returns the number of games kept, including expired ones not yet dropped.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns true if no game is kept.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an id not used by any game.
+    pub fn new_id(&self) -> String {
+        let games = self.lock();
+        loop {
+            let id = format!("{:016x}", rand::random::<u64>());
+            if !games.contains_key(&id) {
+                return id;
+            }
+        }
+    }
+
+    /// Stores `session` under its id and returns it.
+    pub fn insert(&self, session: GameSession) -> Arc<Mutex<GameSession>> {
+        let mut games = self.lock();
+        self.drop_expired(&mut games);
+        let id = session.id().to_string();
+        let session = Arc::new(Mutex::new(session));
+        games.insert(
+            id,
+            StoredGame {
+                session: Arc::clone(&session),
+                last_used: Instant::now(),
+            },
+        );
+        session
+    }
+
+    /// Returns the game with `id` and marks it as used.
+    ///
+    /// # Errors
+    /// Returns [`GameYError::GameNotFound`] if there is no such game or it expired.
+    pub fn get(&self, id: &str) -> Result<Arc<Mutex<GameSession>>, GameYError> {
+        let mut games = self.lock();
+        self.drop_expired(&mut games);
+        let stored = games.get_mut(id).ok_or_else(|| GameYError::GameNotFound {
+            game_id: id.to_string(),
+        })?;
+        stored.last_used = Instant::now();
+        Ok(Arc::clone(&stored.session))
+    }
+
+    fn drop_expired(&self, games: &mut HashMap<String, StoredGame>) {
+        games.retain(|_, stored| stored.last_used.elapsed() < self.ttl);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, StoredGame>> {
+        self.games.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Always plays the first free cell.
+    struct FirstCellBot;
+
+    impl YBot for FirstCellBot {
+        fn name(&self) -> &str {
+            "first_cell_bot"
+        }
+
+        fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
+            let cell = *board.available_cells().first()?;
+            Some(Coordinates::from_index(cell, board.board_size()))
+        }
+    }
+
+    fn session(bot_player: u32, rules: GameRules) -> GameSession {
+        let mut game =
+            GameSession::new("g".to_string(), "first_cell_bot".to_string(), Arc::new(FirstCellBot), bot_player, rules, 3)
+                .unwrap();
+        reply(&mut game);
+        game
+    }
+
+    /// Plays the bot's move, if it is its turn, the way the server does.
+    fn reply(game: &mut GameSession) -> Option<SessionMove> {
+        let turn = game.bot_turn()?;
+        game.play_bot(&turn, turn.decide()).unwrap()
+    }
+
+    fn place(x: u32, y: u32, z: u32) -> BotDecision {
+        BotDecision::Place {
+            coords: Coordinates::new(x, y, z),
+        }
+    }
+
+    #[test]
+    fn test_bot_moves_first_when_it_is_player_0() {
+        let first = session(0, GameRules::default());
+        assert_eq!(first.history().len(), 1);
+        assert_eq!(first.history()[0].player, 0);
+        let second = session(1, GameRules::default());
+        assert!(second.history().is_empty());
+    }
+
+    #[test]
+    fn test_bot_answers_the_human_move() {
+        let mut game = session(1, GameRules::default());
+        assert!(game.bot_turn().is_none());
+        game.play(PlayerId::new(0), place(0, 0, 2)).unwrap();
+        let reply = reply(&mut game).unwrap();
+        assert_eq!(reply.player, 1);
+        assert_eq!(reply.decision, place(2, 0, 0));
+        assert_eq!(game.history().len(), 2);
+        assert_eq!(game.status(), SessionStatus::Ongoing);
+    }
+
+    #[test]
+    fn test_stale_bot_turns_are_not_played() {
+        let mut game = session(1, GameRules::default());
+        game.play(PlayerId::new(0), place(0, 0, 2)).unwrap();
+        let turn = game.bot_turn().unwrap();
+        let decision = turn.decide();
+        assert!(game.play_bot(&turn, decision).unwrap().is_some());
+        assert_eq!(game.play_bot(&turn, decision).unwrap(), None);
+        assert_eq!(game.history().len(), 2);
+    }
+
+    #[test]
+    fn test_play_rejects_bad_moves_without_changing_the_game() {
+        let mut game = session(1, GameRules::default());
+        game.play(PlayerId::new(0), place(0, 0, 2)).unwrap();
+        reply(&mut game);
+        assert!(matches!(game.play(PlayerId::new(0), place(2, 0, 0)), Err(GameYError::Occupied { .. })));
+        assert!(matches!(game.play(PlayerId::new(0), place(3, 0, 0)), Err(GameYError::InvalidMove { .. })));
+        assert!(matches!(game.play(PlayerId::new(0), BotDecision::Swap), Err(GameYError::InvalidMove { .. })));
+        assert_eq!(game.history().len(), 2);
+    }
+
+    #[test]
+    fn test_swap_needs_the_pie_rule() {
+        let rules = GameRules {
+            pie_rule: true,
+            ..Default::default()
+        };
+        let mut game = session(0, rules);
+        // The human is player 1 and may swap the bot's first stone.
+        game.play(PlayerId::new(1), BotDecision::Swap).unwrap();
+        assert_eq!(game.history()[1].decision, BotDecision::Swap);
+        let mut no_pie = session(0, GameRules::default());
+        assert!(no_pie.play(PlayerId::new(1), BotDecision::Swap).is_err());
+    }
+
+    #[test]
+    fn test_resign_finishes_the_game() {
+        let mut game = session(1, GameRules::default());
+        game.play(PlayerId::new(0), BotDecision::Resign).unwrap();
+        assert!(game.bot_turn().is_none());
+        assert_eq!(game.status(), SessionStatus::BotWon);
+        assert!(matches!(game.play(PlayerId::new(0), place(0, 0, 2)), Err(GameYError::GameOver { .. })));
+    }
+
+    #[test]
+    fn test_moves_are_checked_against_the_seat() {
+        let mut game = session(1, GameRules::default());
+        assert!(matches!(
+            game.play(PlayerId::new(1), place(0, 0, 2)),
+            Err(GameYError::InvalidMove { .. })
+        ));
+        assert!(game.history().is_empty());
+    }
+
+    #[test]
+    fn test_tokens_identify_the_human_seats() {
+        let game = session(1, GameRules::default());
+        let token = game.token(PlayerId::new(0)).unwrap().to_string();
+        assert_eq!(game.token(PlayerId::new(1)), None);
+        assert_eq!(game.player_for(Some(&token)).unwrap(), PlayerId::new(0));
+        assert_eq!(game.player_for(None).unwrap(), PlayerId::new(0));
+        assert!(matches!(game.player_for(Some("wrong")), Err(GameYError::InvalidToken { .. })));
+
+        let humans = GameSession::between_humans("h".to_string(), GameRules::default(), 3).unwrap();
+        let first = humans.token(PlayerId::new(0)).unwrap();
+        let second = humans.token(PlayerId::new(1)).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(humans.player_for(Some(second)).unwrap(), PlayerId::new(1));
+        assert!(matches!(humans.player_for(None), Err(GameYError::InvalidToken { .. })));
+    }
+
+    #[test]
+    fn test_game_between_humans() {
+        let mut game = GameSession::between_humans("h".to_string(), GameRules::default(), 3).unwrap();
+        assert_eq!(game.bot_id(), None);
+        game.play(PlayerId::new(0), place(2, 0, 0)).unwrap();
+        assert!(game.bot_turn().is_none());
+        assert!(matches!(
+            game.play(PlayerId::new(0), place(0, 2, 0)),
+            Err(GameYError::InvalidPlayerTurn { .. })
+        ));
+        game.play(PlayerId::new(1), place(0, 2, 0)).unwrap();
+        assert_eq!(game.status(), SessionStatus::Ongoing);
+        game.play(PlayerId::new(0), BotDecision::Resign).unwrap();
+        assert_eq!(game.status(), SessionStatus::Finished);
+        assert_eq!(game.winner(), Some(PlayerId::new(1)));
+        assert_eq!(game.end_reason(), Some(EndReason::Resign));
+    }
+
+    #[test]
+    fn test_moves_are_published() {
+        let mut game = session(1, GameRules::default());
+        let mut events = game.subscribe();
+        game.play(PlayerId::new(0), place(0, 0, 2)).unwrap();
+        reply(&mut game);
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            received.push(event);
+        }
+        assert_eq!(received.len(), 4);
+        assert!(matches!(received[0], GameEvent::Move { ply: 1, played } if played.player == 0));
+        assert!(matches!(received[1], GameEvent::Status { next_player: Some(1), .. }));
+        assert!(matches!(received[2], GameEvent::Move { ply: 2, played } if played.player == 1));
+        assert!(matches!(received[3], GameEvent::Status { next_player: Some(0), .. }));
+    }
+
+    #[test]
+    fn test_clock_gives_the_increment() {
+        let settings = ClockSettings {
+            initial_ms: 60_000,
+            increment_ms: 1_000,
+        };
+        let mut game = session(1, GameRules::default()).with_clock(settings);
+        let mut events = game.subscribe();
+        game.play(PlayerId::new(0), place(0, 0, 2)).unwrap();
+        reply(&mut game);
+        let clock = game.clock().unwrap();
+        assert_eq!(clock.running, Some(0));
+        assert!(clock.remaining_ms.iter().all(|&ms| ms > 59_000 && ms <= 61_000));
+        assert!(std::iter::from_fn(|| events.try_recv().ok()).any(|event| matches!(event, GameEvent::Clock(_))));
+        assert!(!game.check_clock());
+    }
+
+    #[test]
+    fn test_player_out_of_time_loses() {
+        let settings = ClockSettings {
+            initial_ms: 0,
+            increment_ms: 0,
+        };
+        let mut game = session(1, GameRules::default()).with_clock(settings);
+        assert!(matches!(
+            game.play(PlayerId::new(0), place(0, 0, 2)),
+            Err(GameYError::GameOver { .. })
+        ));
+        assert_eq!(game.status(), SessionStatus::BotWon);
+        assert_eq!(game.end_reason(), Some(EndReason::Timeout));
+        assert_eq!(game.clock().unwrap().running, None);
+        assert!(game.history().is_empty());
+    }
+
+    #[test]
+    fn test_new_rejects_bad_settings() {
+        let bot: Arc<dyn YBot> = Arc::new(FirstCellBot);
+        let rules = GameRules::default();
+        let new = |bot_player, size| GameSession::new("g".into(), "b".into(), Arc::clone(&bot), bot_player, rules, size);
+        assert!(matches!(new(2, 3), Err(GameYError::InvalidGame { .. })));
+        assert!(matches!(new(1, 0), Err(GameYError::InvalidGame { .. })));
+        assert!(GameSession::between_humans("h".into(), rules, 0).is_err());
+    }
+
+    #[test]
+    fn test_store_finds_and_expires_games() {
+        let games = GameSessions::default();
+        let id = games.new_id();
+        let mut game = session(1, GameRules::default());
+        game.id = id.clone();
+        games.insert(game);
+        assert_eq!(games.get(&id).unwrap().lock().unwrap().id(), id);
+        assert!(matches!(games.get("missing"), Err(GameYError::GameNotFound { .. })));
+
+        let expiring = GameSessions::new(Duration::ZERO);
+        expiring.insert(session(1, GameRules::default()));
+        assert!(expiring.get("g").is_err());
+        assert!(expiring.is_empty());
+    }
+}
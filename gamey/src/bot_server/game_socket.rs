@@ -0,0 +1,192 @@
+//! WebSocket endpoint for live games.
+//!
+//! `GET /{api_version}/games/{game_id}/ws?token=...` follows a game session
+//! (see [`crate::GameSession`]) as it is played. Every message of the server
+//! is a [`GameEvent`] encoded as JSON text:
+//!
+//! - on connect, a `snapshot` with the whole game
+//! - every `move`, `status` and `clock` event of the game, whoever made the
+//!   move and whether it came through this socket or the HTTP endpoints
+//! - the clocks every [`CLOCK_INTERVAL`] while a game with a time control
+//!   goes on, which is also when a player running out of time loses
+//! - an `error` when a message of this client is rejected
+//!
+//! A client connected with the token of a seat plays by sending a
+//! [`BotDecision`]: `{"action": "place", "coords": {...}}`,
+//! `{"action": "swap"}` or `{"action": "resign"}`. Clients without a token
+//! only watch. The bot's reply is computed in the compute pool of the server
+//! (see [`crate::limits`]), and the events keep flowing while it thinks. A
+//! client that loses its connection reconnects with the same token and gets a
+//! fresh snapshot; the same happens when it falls too far behind the events of
+//! the game.
+
+use crate::{
+    BotDecision, GameEvent, GameResponse, GameSession, GameYError, PlayerId, check_api_version,
+    error::{ErrorCode, ErrorResponse},
+    games::{GameParams, lock, play_turn},
+    state::AppState,
+};
+use axum::{
+    extract::{
+        Path, Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::Response,
+};
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinSet;
+
+/// How often the clocks of a game are sent while it goes on.
+pub const CLOCK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Query parameters of the WebSocket endpoint.
+#[derive(Deserialize)]
+pub struct SocketQuery {
+    /// The token of the seat to play as; without it the client only watches.
+    pub(crate) token: Option<String>,
+}
+
+/// Handler that upgrades the connection to a WebSocket following a game.
+///
+/// # Route
+/// `GET /{api_version}/games/{game_id}/ws`
+///
+/// # Response
+/// `101 Switching Protocols`. Unknown or expired games yield a 404
+/// `GAME_NOT_FOUND` error and tokens of no seat of the game a 403
+/// `INVALID_TOKEN` error, before the upgrade. Rejected moves are answered
+/// with an `error` event carrying the same codes as the HTTP API.
+#[utoipa::path(
+    get,
+    path = "/{api_version}/games/{game_id}/ws",
+    tag = "games",
+    params(
+        ("api_version" = String, Path, description = "The API version, `v1` or `v2`"),
+        ("game_id" = String, Path, description = "The id of the game"),
+        ("token" = Option<String>, Query, description = "The token of the seat to play as"),
+    ),
+    responses(
+        (status = 101, description = "WebSocket of JSON `GameEvent` messages", body = GameEvent),
+        (status = 403, description = "Wrong token", body = ErrorResponse),
+        (status = 404, description = "Unknown or expired game", body = ErrorResponse),
+    )
+)]
+pub async fn game_socket(
+    State(state): State<AppState>,
+    Path(params): Path<GameParams>,
+    Query(query): Query<SocketQuery>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, ErrorResponse> {
+    check_api_version(&params.api_version)?;
+    let error = |err: GameYError| ErrorResponse::from_error(&err, Some(params.api_version.clone()), None);
+    let session = state.games().get(&params.game_id).map_err(error)?;
+    let player = match query.token.as_deref() {
+        Some(token) => Some(lock(&session).player_for(Some(token)).map_err(error)?),
+        None => None,
+    };
+    Ok(ws.on_upgrade(move |socket| run_socket(socket, state, params, session, player)))
+}
+
+/// Serves one client until it disconnects.
+async fn run_socket(
+    mut socket: WebSocket,
+    state: AppState,
+    params: GameParams,
+    session: Arc<Mutex<GameSession>>,
+    player: Option<PlayerId>,
+) {
+    let mut events = lock(&session).subscribe();
+    if send(&mut socket, &snapshot(&params.api_version, &session)).await.is_err() {
+        return;
+    }
+    let mut clock = tokio::time::interval(CLOCK_INTERVAL);
+    // The moves of this client, which wait for the bot's reply while the
+    // events keep flowing; dropped, and so cancelled, when the client leaves.
+    let mut turns = JoinSet::new();
+    loop {
+        let reply = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => play(&state, &params, player, text.as_str(), &mut turns),
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                Some(Ok(_)) => None,
+            },
+            Some(turn) = turns.join_next() => match turn {
+                Ok(Err(err)) => Some(error_event(&err)),
+                Ok(Ok(())) | Err(_) => None,
+            },
+            event = events.recv() => match event {
+                Ok(event) => Some(event),
+                Err(RecvError::Lagged(_)) => Some(snapshot(&params.api_version, &session)),
+                Err(RecvError::Closed) => break,
+            },
+            _ = clock.tick() => running_clock(&session),
+        };
+        if let Some(reply) = reply
+            && send(&mut socket, &reply).await.is_err()
+        {
+            break;
+        }
+    }
+}
+
+/// Starts playing the move sent by the client in `turns`, where the bot's
+/// reply is computed in the compute pool. Returns the error to send back if
+/// the message is not a move of this client; the move and the reply reach
+/// every client as events.
+fn play(
+    state: &AppState,
+    params: &GameParams,
+    player: Option<PlayerId>,
+    text: &str,
+    turns: &mut JoinSet<Result<(), GameYError>>,
+) -> Option<GameEvent> {
+    let result = (|| {
+        let player = player.ok_or_else(|| GameYError::InvalidToken {
+            game_id: params.game_id.clone(),
+            message: "connect with the token of a seat to play".to_string(),
+        })?;
+        let decision: BotDecision = serde_json::from_str(text).map_err(|e| GameYError::SerdeError { error: e })?;
+        Ok((state.games().get(&params.game_id)?, player, decision))
+    })();
+    match result {
+        Ok((session, player, decision)) => {
+            let state = state.clone();
+            turns.spawn(async move { play_turn(&state, &session, player, decision).await.map(|_| ()) });
+            None
+        }
+        Err(err) => Some(error_event(&err)),
+    }
+}
+
+fn error_event(err: &GameYError) -> GameEvent {
+    GameEvent::Error {
+        code: ErrorCode::from(err),
+        message: err.to_string(),
+    }
+}
+
+/// Returns the clocks if they are running, ending the game first if the
+/// player to move has run out of time.
+fn running_clock(session: &Mutex<GameSession>) -> Option<GameEvent> {
+    let mut session = lock(session);
+    session.check_clock();
+    let clock = session.clock()?;
+    clock.running.map(|_| GameEvent::Clock(clock))
+}
+
+fn snapshot(api_version: &str, session: &Mutex<GameSession>) -> GameEvent {
+    let mut session = lock(session);
+    session.check_clock();
+    GameEvent::Snapshot {
+        game: Box::new(GameResponse::new(api_version, &session, None)),
+    }
+}
+
+async fn send(socket: &mut WebSocket, event: &GameEvent) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(event).expect("game events serialize to JSON");
+    socket.send(Message::Text(text.into())).await
+}
+
@@ -0,0 +1,348 @@
+//! Game session endpoints.
+//!
+//! These endpoints play a whole game, against a bot or between two humans,
+//! without sending the board on every turn (see [`crate::GameSession`]):
+//!
+//! - `POST /{api_version}/games` starts a game and returns its id and the
+//!   tokens of the human seats
+//! - `POST /{api_version}/games/{game_id}/moves` plays a human's move and
+//!   returns the position after the bot's reply
+//! - `GET /{api_version}/games/{game_id}` returns the current position
+//!
+//! Live clients can follow a game over a WebSocket instead (see
+//! [`crate::game_socket`]). Games that are not used for a while expire (see
+//! [`crate::GameSessions`]).
+//!
+//! The bot's moves are computed in the compute pool of the server (see
+//! [`crate::limits`]) while the game is unlocked, and played once they are
+//! ready.
+
+use crate::{
+    BotDecision, BotParams, BotTurn, ClockSettings, ClockState, EndReason, GameRules, GameSession, GameYError, PlayerId,
+    SessionMove, SessionStatus, YEN, check_api_version, error::ErrorResponse, state::AppState,
+};
+use crate::metrics::GAMES_PLAYED;
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::{Mutex, MutexGuard};
+use utoipa::ToSchema;
+
+/// Path parameters of the endpoints of one game.
+#[derive(Deserialize)]
+pub struct GameParams {
+    /// The API version (e.g., "v1").
+    pub(crate) api_version: String,
+    /// The id returned when the game was created.
+    pub(crate) game_id: String,
+}
+
+/// Request body of the endpoint that starts a game.
+///
+/// Without a `bot` the game is played by two humans.
+///
+/// # Example
+/// ```json
+/// {
+///   "size": 7,
+///   "bot": "mcts_bot",
+///   "params": { "iterations": 500 },
+///   "bot_player": 1,
+///   "rules": { "pie_rule": true },
+///   "clock": { "initial_ms": 300000, "increment_ms": 5000 }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CreateGameRequest {
+    /// Size of the board.
+    pub size: u32,
+    /// The bot to play against, or `None` for a game between two humans.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bot: Option<String>,
+    /// Parameters used to build the bot, see [`crate::YBotRegistry::create`].
+    #[serde(default, skip_serializing_if = "BotParams::is_empty")]
+    #[schema(value_type = Object)]
+    pub params: BotParams,
+    /// The player the bot plays as: 0 moves first, 1 (the default) second.
+    #[serde(default = "default_bot_player")]
+    pub bot_player: u32,
+    /// The rules of the game.
+    #[serde(default)]
+    pub rules: GameRules,
+    /// The time control, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock: Option<ClockSettings>,
+}
+
+/// Request body of the endpoint that plays a move: a [`BotDecision`] and the
+/// token of the player's seat.
+///
+/// # Example
+/// ```json
+/// { "action": "place", "coords": { "x": 0, "y": 0, "z": 6 }, "token": "9f3c..." }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct MoveRequest {
+    /// The move.
+    #[serde(flatten)]
+    pub decision: BotDecision,
+    /// The token of the seat, which may be left out in a game against a bot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// The token of a human seat, returned only when the game is created.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct SeatToken {
+    /// The player of the seat.
+    pub player: u32,
+    /// The token that moves and reconnects as that player.
+    pub token: String,
+}
+
+fn default_bot_player() -> u32 {
+    1
+}
+
+/// The state of a game, returned by every game endpoint.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct GameResponse {
+    /// The API version used for this request.
+    pub api_version: String,
+    /// The id of the game.
+    pub game_id: String,
+    /// The bot the human plays against, or `None` in a game between two humans.
+    pub bot_id: Option<String>,
+    /// The player the bot plays as, or `None` in a game between two humans.
+    pub bot_player: Option<u32>,
+    /// The rules of the game.
+    pub rules: GameRules,
+    /// The current position.
+    pub position: YEN,
+    /// Who has won, if anyone.
+    pub status: SessionStatus,
+    /// The winner, once the game is over.
+    pub winner: Option<u32>,
+    /// Why the game ended, once it is over.
+    pub end_reason: Option<EndReason>,
+    /// The clocks, if the game has a time control.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock: Option<ClockState>,
+    /// Every move played so far.
+    pub history: Vec<SessionMove>,
+    /// The bot's reply to this request's move, if it made one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bot_move: Option<SessionMove>,
+    /// The tokens of the human seats; only sent when the game is created.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<SeatToken>,
+}
+
+impl GameResponse {
+    pub(crate) fn new(api_version: &str, session: &GameSession, bot_move: Option<SessionMove>) -> Self {
+        GameResponse {
+            api_version: api_version.to_string(),
+            game_id: session.id().to_string(),
+            bot_id: session.bot_id().map(str::to_string),
+            bot_player: session.bot_player().map(|player| player.id()),
+            rules: session.rules(),
+            position: YEN::from(session.game()),
+            status: session.status(),
+            winner: session.winner().map(|player| player.id()),
+            end_reason: session.end_reason(),
+            clock: session.clock(),
+            history: session.history().to_vec(),
+            bot_move,
+            tokens: Vec::new(),
+        }
+    }
+
+    fn with_tokens(mut self, session: &GameSession) -> Self {
+        self.tokens = (0..2)
+            .map(PlayerId::new)
+            .filter_map(|player| {
+                Some(SeatToken {
+                    player: player.id(),
+                    token: session.token(player)?.to_string(),
+                })
+            })
+            .collect();
+        self
+    }
+}
+
+/// Handler that starts a game.
+///
+/// # Route
+/// `POST /{api_version}/games`
+///
+/// # Response
+/// `201 Created` with a [`GameResponse`] that carries the tokens of the
+/// human seats; when the bot plays first its move is already on the board.
+#[utoipa::path(
+    post,
+    path = "/{api_version}/games",
+    tag = "games",
+    params(("api_version" = String, Path, description = "The API version, `v1` or `v2`")),
+    request_body = CreateGameRequest,
+    responses(
+        (status = 201, description = "The new game, with the tokens of the human seats", body = GameResponse),
+        (status = 400, description = "Unsupported version or invalid game", body = ErrorResponse),
+        (status = 404, description = "Unknown bot", body = ErrorResponse),
+    )
+)]
+#[axum::debug_handler]
+pub async fn create_game(
+    State(state): State<AppState>,
+    Path(api_version): Path<String>,
+    Json(request): Json<CreateGameRequest>,
+) -> Result<(StatusCode, Json<GameResponse>), ErrorResponse> {
+    check_api_version(&api_version)?;
+    let error = |err: GameYError| ErrorResponse::from_error(&err, Some(api_version.clone()), request.bot.clone());
+    let games = state.games();
+    let mut session = match &request.bot {
+        Some(bot_id) => {
+            let bot = state.bots().create(bot_id, &request.params).map_err(error)?;
+            GameSession::new(games.new_id(), bot_id.clone(), bot, request.bot_player, request.rules, request.size)
+        }
+        None => GameSession::between_humans(games.new_id(), request.rules, request.size),
+    }
+    .map_err(error)?;
+    if let Some(clock) = request.clock {
+        session = session.with_clock(clock);
+    }
+    // The game is not stored yet, so nobody else can move while the bot thinks.
+    let bot_move = match session.bot_turn() {
+        Some(turn) => {
+            let decision = decide(&state, &turn).await.map_err(error)?;
+            session.play_bot(&turn, decision).map_err(error)?
+        }
+        None => None,
+    };
+    if let (Some(bot_id), Some(_)) = (&request.bot, bot_move) {
+        GAMES_PLAYED.with_label_values(&[bot_id]).inc();
+    }
+    let response = GameResponse::new(&api_version, &session, bot_move).with_tokens(&session);
+    games.insert(session);
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// Handler that plays a human's move.
+///
+/// # Route
+/// `POST /{api_version}/games/{game_id}/moves`
+///
+/// # Request Body
+/// A [`MoveRequest`]: `{"action": "place", "coords": {...}}`,
+/// `{"action": "swap"}` or `{"action": "resign"}`, with the `token` of the
+/// player's seat.
+///
+/// # Response
+/// The [`GameResponse`] after the bot's reply. Unknown or expired games yield
+/// a 404 `GAME_NOT_FOUND` error, wrong tokens a 403 `INVALID_TOKEN` error,
+/// moves out of turn or after the end a 409 error and moves against the
+/// rules a 400 `INVALID_MOVE` error.
+#[utoipa::path(
+    post,
+    path = "/{api_version}/games/{game_id}/moves",
+    tag = "games",
+    params(("api_version" = String, Path, description = "The API version, `v1` or `v2`"), ("game_id" = String, Path, description = "The id of the game")),
+    request_body = MoveRequest,
+    responses(
+        (status = 200, description = "The game after the bot's reply", body = GameResponse),
+        (status = 400, description = "Move against the rules", body = ErrorResponse),
+        (status = 403, description = "Wrong or missing token", body = ErrorResponse),
+        (status = 404, description = "Unknown or expired game", body = ErrorResponse),
+        (status = 409, description = "Move out of turn or after the end", body = ErrorResponse),
+    )
+)]
+#[axum::debug_handler]
+pub async fn play_move(
+    State(state): State<AppState>,
+    Path(params): Path<GameParams>,
+    Json(request): Json<MoveRequest>,
+) -> Result<Json<GameResponse>, ErrorResponse> {
+    check_api_version(&params.api_version)?;
+    let error = |err: GameYError| ErrorResponse::from_error(&err, Some(params.api_version.clone()), None);
+    let session = state.games().get(&params.game_id).map_err(error)?;
+    let player = lock(&session).player_for(request.token.as_deref()).map_err(error)?;
+    let bot_move = play_turn(&state, &session, player, request.decision).await.map_err(error)?;
+    Ok(Json(GameResponse::new(&params.api_version, &lock(&session), bot_move)))
+}
+
+/// Plays the move of the human seated as `player` and then the bot's reply,
+/// if the game is against a bot and goes on. Returns the bot's reply.
+///
+/// A reply that could not be computed earlier, e.g. because the server was
+/// busy, is played first.
+pub(crate) async fn play_turn(
+    state: &AppState,
+    session: &Mutex<GameSession>,
+    player: PlayerId,
+    decision: BotDecision,
+) -> Result<Option<SessionMove>, GameYError> {
+    bot_reply(state, session).await?;
+    lock(session).play(player, decision)?;
+    bot_reply(state, session).await
+}
+
+/// Plays the bot's move if it is its turn, computing it in the compute pool
+/// without holding the lock of the game.
+async fn bot_reply(state: &AppState, session: &Mutex<GameSession>) -> Result<Option<SessionMove>, GameYError> {
+    let Some(turn) = lock(session).bot_turn() else {
+        return Ok(None);
+    };
+    let decision = decide(state, &turn).await?;
+    let mut session = lock(session);
+    let bot_move = session.play_bot(&turn, decision)?;
+    if let (Some(bot_id), Some(_)) = (session.bot_id(), bot_move) {
+        GAMES_PLAYED.with_label_values(&[bot_id]).inc();
+    }
+    Ok(bot_move)
+}
+
+/// Computes the bot's decision for `turn` in the compute pool, with the
+/// longest time the server allows.
+async fn decide(state: &AppState, turn: &BotTurn) -> Result<BotDecision, GameYError> {
+    let compute = state.compute();
+    let turn = turn.clone();
+    compute.run(compute.timeout(None), move || turn.decide()).await
+}
+
+/// Locks a game, recovering it if a thread panicked while holding it.
+pub(crate) fn lock(session: &Mutex<GameSession>) -> MutexGuard<'_, GameSession> {
+    session.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Handler that returns the state of a game.
+///
+/// # Route
+/// `GET /{api_version}/games/{game_id}`
+#[utoipa::path(
+    get,
+    path = "/{api_version}/games/{game_id}",
+    tag = "games",
+    params(("api_version" = String, Path, description = "The API version, `v1` or `v2`"), ("game_id" = String, Path, description = "The id of the game")),
+    responses(
+        (status = 200, description = "The current state of the game", body = GameResponse),
+        (status = 404, description = "Unknown or expired game", body = ErrorResponse),
+    )
+)]
+#[axum::debug_handler]
+pub async fn get_game(
+    State(state): State<AppState>,
+    Path(params): Path<GameParams>,
+) -> Result<Json<GameResponse>, ErrorResponse> {
+    check_api_version(&params.api_version)?;
+    let session = state
+        .games()
+        .get(&params.game_id)
+        .map_err(|err| ErrorResponse::from_error(&err, Some(params.api_version.clone()), None))?;
+    let mut session = lock(&session);
+    session.check_clock();
+    Ok(Json(GameResponse::new(&params.api_version, &session, None)))
+}
@@ -0,0 +1,285 @@
+//! Deadlines and concurrency limits for the bot computations of the server.
+//!
+//! Every endpoint that asks a bot for a move or an analysis runs it through
+//! the [`ComputePool`] of the server (see [`crate::state::AppState::compute`]):
+//!
+//! - the bot runs on a blocking thread, so a slow search does not hold one
+//!   of the workers that serve the other requests
+//! - at most [`ComputeLimits::max_concurrent`] searches run at once, and at
+//!   most [`ComputeLimits::max_queued`] more wait for a free slot; further
+//!   requests are refused with a 429 `OVERLOADED` error
+//! - every request has a deadline: the `timeout_ms` the client asks for,
+//!   capped at [`ComputeLimits::max_timeout_ms`]. Search bots stop at the
+//!   deadline and play the best move found so far (see [`crate::CancelToken`]);
+//!   a request that gets no move by then fails with a 503 `TIMEOUT` error
+//! - a search whose client disconnects is cancelled
+
+use crate::{CancelToken, GameYError, error::ErrorResponse, with_cancel_token};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// Time given after the deadline to bots that do not poll for cancellation
+/// before the request fails.
+pub const CANCEL_GRACE: Duration = Duration::from_millis(100);
+
+/// The limits of a [`ComputePool`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ComputeLimits {
+    /// Searches that run at once (at least one). Defaults to the number of cores.
+    pub max_concurrent: usize,
+    /// Requests that may wait for a free slot; more are refused.
+    pub max_queued: usize,
+    /// Longest time a request may take, in milliseconds.
+    pub max_timeout_ms: u64,
+}
+
+impl Default for ComputeLimits {
+    fn default() -> Self {
+        let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+        ComputeLimits {
+            max_concurrent: cores,
+            max_queued: 8 * cores,
+            max_timeout_ms: 10_000,
+        }
+    }
+}
+
+/// Runs bot computations within the [`ComputeLimits`] of the server.
+#[derive(Debug)]
+pub struct ComputePool {
+    limits: ComputeLimits,
+    permits: Arc<Semaphore>,
+    /// Requests running or waiting for a permit.
+    pending: AtomicUsize,
+}
+
+impl Default for ComputePool {
+    fn default() -> Self {
+        ComputePool::new(ComputeLimits::default())
+    }
+}
+
+impl ComputePool {
+    /// Creates a pool with the given limits.
+    pub fn new(limits: ComputeLimits) -> Self {
+        let limits = ComputeLimits {
+            max_concurrent: limits.max_concurrent.max(1),
+            ..limits
+        };
+        ComputePool {
+            limits,
+            permits: Arc::new(Semaphore::new(limits.max_concurrent)),
+            pending: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the limits of the pool.
+    pub fn limits(&self) -> ComputeLimits {
+        self.limits
+    }
+
+    /// Returns the number of requests running or waiting for a slot.
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    /// Returns the time a request gets: the `requested_ms` of the client,
+    /// capped at [`ComputeLimits::max_timeout_ms`].
+    pub fn timeout(&self, requested_ms: Option<u64>) -> Duration {
+        let max = self.limits.max_timeout_ms;
+        Duration::from_millis(requested_ms.map_or(max, |requested| requested.min(max)))
+    }
+
+    /// Runs `f` on a blocking thread once a slot is free, cancelling the
+    /// searches it makes when `timeout` passes.
+    ///
+    /// # Errors
+    /// [`GameYError::Overloaded`] when too many requests are running or
+    /// waiting, and [`GameYError::SearchTimeout`] when no slot frees up or
+    /// `f` does not return before the deadline.
+    pub async fn run<R, F>(&self, timeout: Duration, f: F) -> Result<R, GameYError>
+    where
+        R: Send + 'static,
+        F: FnOnce() -> R + Send + 'static,
+    {
+        let deadline = Instant::now() + timeout;
+        let pending = Pending::enter(&self.pending);
+        if pending.others >= self.limits.max_concurrent + self.limits.max_queued {
+            return Err(GameYError::Overloaded {
+                message: format!("{} searches are running or waiting", pending.others),
+            });
+        }
+        let timed_out = || GameYError::SearchTimeout {
+            timeout_ms: timeout.as_millis() as u64,
+        };
+        let permit = tokio::time::timeout_at(deadline.into(), Arc::clone(&self.permits).acquire_owned())
+            .await
+            .map_err(|_| timed_out())?
+            .expect("the semaphore is never closed");
+        // Dropping this future, e.g. when the client disconnects, cancels the search.
+        let cancel = CancelOnDrop(CancelToken::new().with_deadline(deadline));
+        let token = cancel.0.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            with_cancel_token(&token, f)
+        });
+        match tokio::time::timeout_at((deadline + CANCEL_GRACE).into(), task).await {
+            Ok(Ok(result)) => Ok(result),
+            Ok(Err(err)) => Err(GameYError::ServerError {
+                message: format!("The search failed: {}", err),
+            }),
+            Err(_) => Err(timed_out()),
+        }
+    }
+
+    /// Runs the computation of a request like [`ComputePool::run`], reporting
+    /// a refused or late request as an [`ErrorResponse`] (with `bot_id` when
+    /// a bot serves the request).
+    pub(crate) async fn run_request<R, F>(
+        &self,
+        timeout: Duration,
+        api_version: &str,
+        bot_id: Option<&str>,
+        f: F,
+    ) -> Result<R, ErrorResponse>
+    where
+        R: Send + 'static,
+        F: FnOnce() -> Result<R, ErrorResponse> + Send + 'static,
+    {
+        self.run(timeout, f)
+            .await
+            .map_err(|err| ErrorResponse::from_error(&err, Some(api_version.to_string()), bot_id.map(str::to_string)))?
+    }
+}
+
+/// Counts a request as pending while it lives.
+struct Pending<'a> {
+    counter: &'a AtomicUsize,
+    /// The requests that were pending before this one.
+    others: usize,
+}
+
+impl<'a> Pending<'a> {
+    fn enter(counter: &'a AtomicUsize) -> Self {
+        let others = counter.fetch_add(1, Ordering::SeqCst);
+        Pending { counter, others }
+    }
+}
+
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+struct CancelOnDrop(CancelToken);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameY, MctsBot, MctsConfig, YBot, search_cancelled};
+    use std::sync::mpsc;
+
+    fn limits(max_concurrent: usize, max_queued: usize) -> ComputeLimits {
+        ComputeLimits {
+            max_concurrent,
+            max_queued,
+            max_timeout_ms: 10_000,
+        }
+    }
+
+    #[test]
+    fn test_timeout_is_capped() {
+        let pool = ComputePool::new(ComputeLimits {
+            max_timeout_ms: 500,
+            ..ComputeLimits::default()
+        });
+        assert_eq!(pool.timeout(None), Duration::from_millis(500));
+        assert_eq!(pool.timeout(Some(100)), Duration::from_millis(100));
+        assert_eq!(pool.timeout(Some(60_000)), Duration::from_millis(500));
+        assert_eq!(ComputePool::new(limits(0, 0)).limits().max_concurrent, 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_returns_the_result() {
+        let pool = ComputePool::default();
+        assert_eq!(pool.run(Duration::from_secs(5), || 2 + 2).await.unwrap(), 4);
+        assert_eq!(pool.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_search_bots_stop_at_the_deadline() {
+        let pool = ComputePool::default();
+        let bot = MctsBot::new(MctsConfig {
+            iterations: u32::MAX,
+            ..MctsConfig::default()
+        });
+        let chosen = pool
+            .run(Duration::from_millis(20), move || bot.choose_move(&GameY::new(7)))
+            .await
+            .unwrap();
+        assert!(chosen.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_is_overloaded() {
+        let pool = Arc::new(ComputePool::new(limits(1, 0)));
+        let (release, wait) = mpsc::channel::<()>();
+        let running = tokio::spawn({
+            let pool = Arc::clone(&pool);
+            async move { pool.run(Duration::from_secs(5), move || wait.recv().is_ok()).await }
+        });
+        while pool.pending() == 0 {
+            tokio::task::yield_now().await;
+        }
+        let refused = pool.run(Duration::from_secs(5), || ()).await;
+        assert!(matches!(refused, Err(GameYError::Overloaded { .. })));
+        release.send(()).unwrap();
+        assert!(running.await.unwrap().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_waiting_past_the_deadline_times_out() {
+        let pool = Arc::new(ComputePool::new(limits(1, 1)));
+        let (release, wait) = mpsc::channel::<()>();
+        let running = tokio::spawn({
+            let pool = Arc::clone(&pool);
+            async move { pool.run(Duration::from_secs(5), move || wait.recv().is_ok()).await }
+        });
+        while pool.pending() == 0 {
+            tokio::task::yield_now().await;
+        }
+        let waited = pool.run(Duration::from_millis(20), || ()).await;
+        assert!(matches!(waited, Err(GameYError::SearchTimeout { timeout_ms: 20 })));
+        release.send(()).unwrap();
+        running.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_bots_that_ignore_the_deadline_time_out() {
+        let pool = ComputePool::default();
+        let (done, finished) = mpsc::channel();
+        let result = pool
+            .run(Duration::from_millis(10), move || {
+                // Stands for a bot that does not poll for cancellation.
+                while !search_cancelled() {
+                    std::thread::yield_now();
+                }
+                std::thread::sleep(CANCEL_GRACE * 3);
+                done.send(()).unwrap();
+            })
+            .await;
+        assert!(matches!(result, Err(GameYError::SearchTimeout { .. })));
+        finished.recv().unwrap();
+    }
+}
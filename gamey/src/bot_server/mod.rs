@@ -5,7 +5,45 @@
 //!
 //! # Endpoints
 //! - `GET /status` - Health check endpoint
-//! - `POST /{api_version}/ybot/choose/{bot_id}` - Request a move from a bot
+//! - `GET /versions` - List the API versions and whether they are deprecated
+//! - `GET /openapi.json` - The OpenAPI 3 document of these endpoints (see [`openapi`])
+//! - `GET /{api_version}/ybot` - List the bots with their description,
+//!   difficulty, board sizes and parameters
+//! - `POST /{api_version}/ybot/choose/{bot_id}` - Request a move from a bot,
+//!   optionally tuned with a `params` object in the body; requests with the
+//!   same `session_id` share one bot instance, which lets search bots reuse
+//!   their tree between moves
+//! - `POST /{api_version}/ybot/choose_batch/{bot_id}` - Request moves for many
+//!   positions at once (see [`batch`])
+//! - `POST /{api_version}/ybot/analyze/{bot_id}` - Request the scored candidate moves of a bot
+//! - `POST /{api_version}/ybot/solve` - Solve a position with few empty cells exactly
+//! - `POST /{api_version}/ybot/difficulty/{level}` - Request a move from the bot behind a
+//!   difficulty level (`easy`, `medium`, `hard`, `expert`)
+//! - `POST /{api_version}/games` - Start a game kept on the server, against a
+//!   bot or between two humans
+//! - `POST /{api_version}/games/{game_id}/moves` - Play a move and get the bot's reply
+//! - `GET /{api_version}/games/{game_id}` - Get the position and history of a game
+//! - `GET /{api_version}/games/{game_id}/ws` - Follow and play a game live over
+//!   a WebSocket
+//!
+//! The routes under `v2` answer the move endpoints with a `move` object
+//! (see [`v2`]); `v1` responses carry a `Deprecation` header (see
+//! [`version`]).
+//!
+//! The endpoints that ask a bot for a move or an analysis accept an optional
+//! `timeout_ms` and run within the limits of [`limits`]: searches stop at the
+//! deadline, and a busy server answers with a 429 or 503 error.
+//!
+//! # Configuration
+//! [`run_server`] takes a [`ServerConfig`], read from a TOML file and
+//! `GAMEY_*` environment variables (see [`config`]): the bind address, the
+//! CORS origins, the enabled bots and their parameters, the limits, the log
+//! format and whether metrics are served.
+//!
+//! # Errors
+//! Failed requests return an [`ErrorResponse`] whose `code` (an
+//! [`ErrorCode`] such as `BOT_NOT_FOUND` or `INVALID_YEN`) tells clients what
+//! went wrong and fixes the HTTP status.
 //!
 //! # Example
 //! ```no_run
@@ -21,67 +59,159 @@
 //! ```
 use axum::Json;
 use crate::{GameY, YEN, GameStatus}; 
+pub mod analyze;
+pub mod batch;
+pub mod bot_sessions;
+pub mod catalogue;
 pub mod choose;
+pub mod config;
+pub mod difficulty;
 pub mod error;
+pub mod game_sessions;
+pub mod game_socket;
+pub mod games;
+pub mod limits;
+pub mod openapi;
+pub mod solve;
 pub mod state;
+pub mod v2;
 pub mod version;
 use axum::response::IntoResponse; // Required to manage API responses.
-use tower_http::cors::CorsLayer;
+use axum::handler::Handler;
+use axum::routing::{MethodFilter, MethodRouter};
+use axum::{extract::State, http::{Method, StatusCode}};
 use std::sync::Arc;
-pub use choose::MoveResponse;
-pub use error::ErrorResponse;
+pub use analyze::AnalyzeResponse;
+pub use batch::{BatchMove, ChooseBatchRequest, ChooseBatchResponse, MAX_BATCH_POSITIONS};
+pub use bot_sessions::{BotSessions, DEFAULT_SESSION_CAPACITY};
+pub use catalogue::BotCatalogueResponse;
+pub use choose::{ChooseRequest, MoveResponse, MoveStatus, WinnerRole};
+pub use config::{
+    BotsConfig, CONFIG_PATH_ENV, ENV_PREFIX, LimitsConfig, ListenConfig, LogConfig, LogFormat, MetricsConfig, ServerConfig,
+};
+pub use error::{ErrorCode, ErrorResponse};
+pub use game_sessions::{
+    BotTurn, ClockSettings, ClockState, DEFAULT_GAME_TTL, EndReason, GameEvent, GameRules, GameSession, GameSessions,
+    SessionMove, SessionStatus,
+};
+pub use game_socket::CLOCK_INTERVAL;
+pub use games::{CreateGameRequest, GameResponse, MoveRequest, SeatToken};
+pub use limits::{CANCEL_GRACE, ComputeLimits, ComputePool};
+pub use openapi::ApiDoc;
+pub use solve::{SolveRequest, SolveResponse};
+pub use v2::BotMoveResponse;
 pub use version::*;
+pub use difficulty::{Difficulty, DifficultyLevel, DifficultyTable};
 
-use crate::{GameYError, GroupExpansionBot, MonteCarloBot, PriorityBlockBot, RandomBot, ShortestPathBot, SimpleBlockerBot, TriangleAttackBot, YBotRegistry, state::AppState};
+use crate::{GameYError, GroupExpansionBot, MctsBot, MonteCarloBot, PriorityBlockBot, RandomBot, ResistanceBot, ShortestPathBot, SimpleBlockerBot, TriangleAttackBot, YBotRegistry, state::AppState};
 
 
 
 use prometheus::{Encoder, TextEncoder};
 
-async fn metrics() -> String {
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "server",
+    responses(
+        (status = 200, description = "Prometheus metrics", body = String, content_type = "text/plain"),
+        (status = 404, description = "Metrics are disabled in the server configuration"),
+    )
+)]
+async fn metrics(State(state): State<AppState>) -> Result<String, StatusCode> {
+    if !state.metrics_enabled() {
+        return Err(StatusCode::NOT_FOUND);
+    }
     let encoder = TextEncoder::new();//Transforma métricas a texto legible
     let metric_families = prometheus::gather();//Devuelve todas las métricas
 
     let mut buffer = Vec::new();//Escribe las métricas
     encoder.encode(&metric_families, &mut buffer).unwrap();//Codifica las métricas para prometheus
 
-    String::from_utf8(buffer).unwrap()//Convierte a string
+    Ok(String::from_utf8(buffer).unwrap())//Convierte a string
 }
 
 
 
+/// A route of the server: its method, path and handler.
+pub type ApiRoute = (Method, &'static str, MethodRouter<AppState>);
+
+/// Builds an [`ApiRoute`], so that the method of the entry is the one its
+/// handler answers.
+fn api_route<H, T>(method: Method, path: &'static str, handler: H) -> ApiRoute
+where
+    H: Handler<T, AppState>,
+    T: 'static,
+{
+    let filter = MethodFilter::try_from(method.clone()).expect("routes use standard methods");
+    (method, path, axum::routing::on(filter, handler))
+}
+
+/// Returns every route of the server.
+///
+/// [`create_router`] serves exactly these, and the tests check them against
+/// the OpenAPI document (see [`openapi`]).
+pub fn api_routes() -> Vec<ApiRoute> {
+    vec![
+        api_route(Method::GET, "/status", status),
+        api_route(Method::GET, "/metrics", metrics),
+        api_route(Method::GET, "/versions", version::list_versions),
+        api_route(Method::GET, "/openapi.json", openapi::openapi_json),
+        api_route(Method::GET, "/play", choose::play_competition),
+        api_route(Method::GET, "/{api_version}/ybot", catalogue::list_bots),
+        api_route(Method::POST, "/v2/ybot/choose/{bot_id}", v2::choose),
+        api_route(Method::POST, "/v2/ybot/difficulty/{level}", v2::choose_by_difficulty),
+        api_route(Method::POST, "/{api_version}/ybot/choose/{bot_id}", choose::choose),
+        api_route(Method::POST, "/{api_version}/ybot/choose_batch/{bot_id}", batch::choose_batch),
+        api_route(Method::POST, "/{api_version}/ybot/analyze/{bot_id}", analyze::analyze),
+        api_route(Method::POST, "/{api_version}/ybot/solve", solve::solve),
+        api_route(Method::POST, "/{api_version}/ybot/difficulty/{level}", difficulty::choose_by_difficulty),
+        api_route(Method::POST, "/{api_version}/games", games::create_game),
+        api_route(Method::GET, "/{api_version}/games/{game_id}", games::get_game),
+        api_route(Method::POST, "/{api_version}/games/{game_id}/moves", games::play_move),
+        api_route(Method::GET, "/{api_version}/games/{game_id}/ws", game_socket::game_socket),
+        api_route(Method::POST, "/{api_version}/game/check_winner", check_winner),
+    ]
+}
+
 /// Creates the Axum router with the given state.
 ///
 /// This is useful for testing the API without binding to a network port.
 pub fn create_router(state: AppState) -> axum::Router {
-    axum::Router::new()
-        .route("/status", axum::routing::get(status))
-        .route("/metrics", axum::routing::get(metrics))
-        .route("/play", axum::routing::get(choose::play_competition))
-        .route(
-            "/{api_version}/ybot/choose/{bot_id}",
-            axum::routing::post(choose::choose),
-        ).route("/{api_version}/game/check_winner", axum::routing::post(check_winner))
+    api_routes()
+        .into_iter()
+        .fold(axum::Router::new(), |router, (_, path, handler)| router.route(path, handler))
+        .layer(axum::middleware::from_fn(version::deprecation_headers))
         .with_state(state)
 }
 
-/// Creates the default application state with the standard bot registry.
+/// Creates the registry with every built-in bot.
 ///
-/// The default state includes the `RandomBot` which selects moves randomly.
-pub fn create_default_state() -> AppState {
-    let bots = YBotRegistry::new()
+/// Configurable bots are registered through factories so that callers can
+/// tune them with a `params` object (or a `name?key=value` spec on the CLI).
+pub fn default_registry() -> YBotRegistry {
+    YBotRegistry::new()
         .with_bot(Arc::new(RandomBot))
-        .with_bot(Arc::new(ShortestPathBot))
-        .with_bot(Arc::new(MonteCarloBot))
+        .with_factory("shortest_path_bot", |params| Ok(Arc::new(ShortestPathBot::from_params(params)?)))
+        .with_factory("monte_carlo_bot", |params| Ok(Arc::new(MonteCarloBot::from_params(params)?)))
+        .with_factory("mcts_bot", |params| Ok(Arc::new(MctsBot::from_params(params)?)))
         .with_bot(Arc::new(GroupExpansionBot))
-        .with_bot(Arc::new(PriorityBlockBot))
+        .with_factory("priority_block_bot", |params| Ok(Arc::new(PriorityBlockBot::from_params(params)?)))
         .with_bot(Arc::new(SimpleBlockerBot))
-        .with_bot(Arc::new(TriangleAttackBot));
-    
-    AppState::new(bots)
+        .with_factory("triangle_attack_bot", |params| Ok(Arc::new(TriangleAttackBot::from_params(params)?)))
+        .with_bot(Arc::new(ResistanceBot))
 }
 
-/// Starts the bot server on the specified port.
+/// Creates the default application state with the standard bot registry.
+///
+/// The default state includes the `RandomBot` which selects moves randomly.
+/// Configurable bots are registered through factories so that requests can
+/// tune them with a `params` object.
+pub fn create_default_state() -> AppState {
+    AppState::new(default_registry())
+}
+
+/// Starts the bot server on the specified port with the default configuration.
 ///
 /// This function blocks until the server is shut down.
 ///
@@ -89,22 +219,33 @@ pub fn create_default_state() -> AppState {
 /// * `port` - The TCP port to listen on
 ///
 /// # Errors
-/// Returns `GameYError::ServerError` if:
-/// - The TCP port cannot be bound (e.g., port already in use, permission denied)
-/// - The server encounters an error while running
+/// See [`run_server`].
 pub async fn run_bot_server(port: u16) -> Result<(), GameYError> {
-    let state = create_default_state();
-    //let app = create_router(state);
-    let app = create_router(state).layer(CorsLayer::permissive()); // Enable CORS so that the Frontend (React) can connect to this port without being blocked.
+    run_server(ServerConfig::default().with_port(port)).await
+}
+
+/// Starts the bot server with the given configuration (see [`config`]).
+///
+/// This function blocks until the server is shut down.
+///
+/// # Errors
+/// Returns `GameYError::InvalidConfig` if the configuration is invalid, and
+/// `GameYError::ServerError` if:
+/// - The address cannot be bound (e.g., port already in use, permission denied)
+/// - The server encounters an error while running
+pub async fn run_server(config: ServerConfig) -> Result<(), GameYError> {
+    config.validate()?;
+    let addr = config.bind_address()?;
+    // CORS lets the Frontend (React) connect to this port without being blocked.
+    let app = create_router(config.build_state()?).layer(config.cors_layer()?);
 
-    let addr = format!("0.0.0.0:{}", port);
-    let listener = tokio::net::TcpListener::bind(&addr)
+    let listener = tokio::net::TcpListener::bind(addr)
         .await
         .map_err(|e| GameYError::ServerError {
             message: format!("Failed to bind to {}: {}", addr, e),
         })?;
 
-    println!("Server mode: Listening on http://{}", addr);
+    tracing::info!("Server mode: Listening on http://{}", addr);
     axum::serve(listener, app)
         .await
         .map_err(|e| GameYError::ServerError {
@@ -113,6 +254,14 @@ pub async fn run_bot_server(port: u16) -> Result<(), GameYError> {
 
     Ok(())
 }
+#[utoipa::path(
+    post,
+    path = "/{api_version}/game/check_winner",
+    tag = "games",
+    params(("api_version" = String, Path, description = "The API version, `v1` or `v2`")),
+    request_body = YEN,
+    responses((status = 200, description = "`{\"status\": \"win\" | \"ongoing\" | \"error\"}`", body = Object))
+)]
 pub async fn check_winner(Json(payload): Json<YEN>) -> impl IntoResponse {
     match GameY::try_from(payload) {
         Ok(game) => {
@@ -132,6 +281,12 @@ pub async fn check_winner(Json(payload): Json<YEN>) -> impl IntoResponse {
 /// Health check endpoint handler.
 ///
 /// Returns "OK" to indicate the server is running.
+#[utoipa::path(
+    get,
+    path = "/status",
+    tag = "server",
+    responses((status = 200, description = "The server is running", body = String, content_type = "text/plain"))
+)]
 pub async fn status() -> impl IntoResponse {
     "OK"
 }
@@ -0,0 +1,147 @@
+//! OpenAPI document of the bot server.
+//!
+//! The document is built from the `#[utoipa::path]` attributes of the
+//! handlers and the schemas of their request and response types, so that
+//! clients such as the users service and the webapp can generate their types
+//! instead of writing them by hand. It is served at `GET /openapi.json`.
+//!
+//! Every route of [`crate::create_router`] has to be listed in [`ApiDoc`];
+//! the `openapi_tests` integration tests fail when they drift apart.
+
+use crate::{
+    AnalyzeResponse, ApiVersionInfo, BatchMove, BoardSizes, BotCatalogueResponse, BotDecision, BotMetadata,
+    BotMoveResponse, BotParameter, ChooseBatchRequest, ChooseBatchResponse, ChooseRequest, ClockSettings, ClockState,
+    Coordinates, CreateGameRequest, DecisionOptions, Difficulty, EndReason, EndgameSolution, ErrorCode, ErrorResponse,
+    GameAction, GameEvent, GameResponse, GameRules, MoveAnalysis, MoveCandidate, MoveRequest, MoveResponse,
+    MoveStatus, SeatToken, SessionMove, SessionStatus, SolveRequest, SolveResponse, VersionsResponse, WinnerRole, YEN,
+};
+use axum::Json;
+use utoipa::OpenApi;
+
+/// The OpenAPI document of every route of the bot server.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "GameY bot server", description = "Bots, analysis and game sessions for the game of Y."),
+    paths(
+        super::status,
+        super::metrics,
+        openapi_json,
+        crate::version::list_versions,
+        crate::choose::play_competition,
+        crate::catalogue::list_bots,
+        crate::choose::choose,
+        crate::batch::choose_batch,
+        crate::analyze::analyze,
+        crate::solve::solve,
+        crate::difficulty::choose_by_difficulty,
+        crate::v2::choose,
+        crate::v2::choose_by_difficulty,
+        crate::games::create_game,
+        crate::games::get_game,
+        crate::games::play_move,
+        crate::game_socket::game_socket,
+        super::check_winner,
+    ),
+    components(schemas(
+        YEN,
+        Coordinates,
+        GameAction,
+        BotDecision,
+        DecisionOptions,
+        ChooseRequest,
+        MoveResponse,
+        MoveStatus,
+        WinnerRole,
+        BotMoveResponse,
+        ChooseBatchRequest,
+        ChooseBatchResponse,
+        BatchMove,
+        ErrorResponse,
+        ErrorCode,
+        AnalyzeResponse,
+        MoveAnalysis,
+        MoveCandidate,
+        SolveRequest,
+        SolveResponse,
+        EndgameSolution,
+        Difficulty,
+        BotCatalogueResponse,
+        BotMetadata,
+        BoardSizes,
+        BotParameter,
+        CreateGameRequest,
+        MoveRequest,
+        GameResponse,
+        GameRules,
+        ClockSettings,
+        ClockState,
+        SessionMove,
+        SessionStatus,
+        EndReason,
+        SeatToken,
+        GameEvent,
+        VersionsResponse,
+        ApiVersionInfo,
+    )),
+    tags(
+        (name = "bots", description = "Moves, analysis and the bot catalogue"),
+        (name = "games", description = "Games kept on the server"),
+        (name = "server", description = "Health, metrics, versions and this document"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Handler that returns the OpenAPI document.
+///
+/// # Route
+/// `GET /openapi.json`
+#[utoipa::path(
+    get,
+    path = "/openapi.json",
+    tag = "server",
+    responses((status = 200, description = "The OpenAPI 3 document of the server", body = Object))
+)]
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_is_openapi_3() {
+        let json = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        assert!(json["openapi"].as_str().unwrap().starts_with("3."));
+        assert_eq!(json["info"]["title"], "GameY bot server");
+    }
+
+    #[test]
+    fn test_document_has_the_handler_types() {
+        let json = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let schemas = &json["components"]["schemas"];
+        for name in ["YEN", "MoveResponse", "ErrorResponse", "ErrorCode", "GameResponse", "BotMoveResponse"] {
+            assert!(schemas.get(name).is_some(), "missing schema {name}");
+        }
+        let codes = schemas["ErrorCode"]["enum"].as_array().unwrap();
+        assert!(codes.contains(&serde_json::json!("BOT_NOT_FOUND")));
+    }
+
+    #[test]
+    fn test_choose_refers_to_the_schemas() {
+        let json = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let choose = &json["paths"]["/{api_version}/ybot/choose/{bot_id}"]["post"];
+        assert_eq!(
+            choose["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/ChooseRequest"
+        );
+        assert_eq!(
+            choose["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/MoveResponse"
+        );
+        assert_eq!(
+            choose["responses"]["404"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/ErrorResponse"
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Endgame solver endpoint.
+//!
+//! Positions with few empty cells can be solved exactly (see
+//! [`crate::solve_endgame`]). This endpoint reports who wins such a position
+//! with perfect play, how many stones it takes and the best move, so that
+//! clients can show "win in N for player X" near the end of a game.
+
+use crate::{
+    DEFAULT_ENDGAME_CELLS, EndgameSolution, MAX_SOLVER_CELLS, GameY, GameYError, YEN, check_api_version,
+    error::{ErrorCode, ErrorResponse}, solve_endgame, state::AppState,
+};
+use axum::{Json, extract::{Path, State}};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Request body of the solve endpoint: a position in YEN format and an
+/// optional limit on the empty cells to solve.
+///
+/// # Example
+/// ```json
+/// {
+///   "size": 3,
+///   "turn": 0,
+///   "players": ["B", "R"],
+///   "layout": "B/R./...",
+///   "max_empty_cells": 8,
+///   "timeout_ms": 2000
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct SolveRequest {
+    /// The game state.
+    #[serde(flatten)]
+    pub yen: YEN,
+    /// Refuse positions with more empty cells than this (defaults to
+    /// [`DEFAULT_ENDGAME_CELLS`], capped at [`MAX_SOLVER_CELLS`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_empty_cells: Option<u32>,
+    /// How long the solver may run, in milliseconds, capped at the server's
+    /// [`crate::ComputeLimits::max_timeout_ms`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Response returned by the solve endpoint on success.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct SolveResponse {
+    /// The API version used for this request.
+    pub api_version: String,
+    /// The solution, e.g. `"win in 2 for player 1"`.
+    pub summary: String,
+    /// The winner, the stones it needs and the best move.
+    #[serde(flatten)]
+    pub solution: EndgameSolution,
+}
+
+/// Handler for the endgame solver endpoint.
+///
+/// # Route
+/// `POST /{api_version}/ybot/solve`
+///
+/// # Request Body
+/// A [`SolveRequest`].
+///
+/// # Response
+/// On success, returns a `SolveResponse`. Finished positions and positions
+/// with too many empty cells yield a 400 `INVALID_POSITION` error, and a
+/// search that does not finish in time a 503 `TIMEOUT` error.
+#[utoipa::path(
+    post,
+    path = "/{api_version}/ybot/solve",
+    tag = "bots",
+    params(("api_version" = String, Path, description = "The API version, `v1` or `v2`")),
+    request_body = SolveRequest,
+    responses(
+        (status = 200, description = "The proven outcome of the position", body = SolveResponse),
+        (status = 400, description = "Unsupported version, invalid position or too many empty cells", body = ErrorResponse),
+        (status = 429, description = "Too many searches are running", body = ErrorResponse),
+        (status = 503, description = "The search did not finish in time", body = ErrorResponse),
+    )
+)]
+#[axum::debug_handler]
+pub async fn solve(
+    State(state): State<AppState>,
+    Path(api_version): Path<String>,
+    Json(request): Json<SolveRequest>,
+) -> Result<Json<SolveResponse>, ErrorResponse> {
+    check_api_version(&api_version)?;
+    let invalid = |code, message: String| ErrorResponse::new(code, &message, Some(api_version.clone()), None);
+    let game_y = GameY::try_from(request.yen)
+        .map_err(|err| invalid(ErrorCode::InvalidYen, format!("Invalid YEN format: {}", err)))?;
+    if game_y.check_game_over() {
+        return Err(invalid(
+            ErrorCode::InvalidPosition,
+            "Invalid position: the game is already over".to_string(),
+        ));
+    }
+    let limit = request.max_empty_cells.unwrap_or(DEFAULT_ENDGAME_CELLS).min(MAX_SOLVER_CELLS);
+    let empty = game_y.available_cells().len();
+    if empty as u32 > limit {
+        return Err(invalid(
+            ErrorCode::InvalidPosition,
+            format!("Invalid position: {empty} empty cells, the solver handles at most {limit}"),
+        ));
+    }
+    let timeout = state.compute().timeout(request.timeout_ms);
+    let version = api_version.clone();
+    let solution = state
+        .compute()
+        .run_request(timeout, &api_version, None, move || {
+            // The solver only gives up on a position it may solve when it is cancelled.
+            solve_endgame(&game_y, limit).ok_or_else(|| {
+                let err = GameYError::SearchTimeout {
+                    timeout_ms: timeout.as_millis() as u64,
+                };
+                ErrorResponse::from_error(&err, Some(version), None)
+            })
+        })
+        .await?;
+    Ok(Json(SolveResponse {
+        summary: solution.summary(),
+        api_version,
+        solution,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Coordinates;
+
+    #[test]
+    fn test_solve_response_flattens_the_solution() {
+        let solution = EndgameSolution {
+            winner: 1,
+            moves_to_win: 2,
+            plies: 4,
+            best_move: Some(Coordinates::new(1, 0, 0)),
+        };
+        let response = SolveResponse {
+            api_version: "v1".to_string(),
+            summary: solution.summary(),
+            solution,
+        };
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["summary"], "win in 2 for player 1");
+        assert_eq!(json["winner"], 1);
+        assert_eq!(json["best_move"]["x"], 1);
+        let back: SolveResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(back, response);
+    }
+}
@@ -1,29 +1,149 @@
-use crate::YBotRegistry;
+use crate::{
+    BotParams, GameYError, YBot, YBotRegistry,
+    bot_sessions::BotSessions,
+    difficulty::DifficultyTable,
+    game_sessions::GameSessions,
+    limits::{ComputeLimits, ComputePool},
+};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Shared application state for the bot server.
 ///
-/// This struct holds the bot registry and is shared across all request handlers
+/// This struct holds the bot registry, the difficulty levels, the bots of
+/// the active sessions and the games kept on the server, and is shared across all request handlers
 /// via Axum's state extraction. It uses `Arc` internally to allow cheap cloning
 /// for concurrent request handling.
 #[derive(Clone)]
 pub struct AppState {
     /// The registry of available bots, wrapped in Arc for thread-safe sharing.
     bots: Arc<YBotRegistry>,
+    /// The bot configuration behind each difficulty level.
+    difficulties: Arc<DifficultyTable>,
+    /// The bots kept for requests that carry a session id.
+    sessions: Arc<BotSessions>,
+    /// The games played through the game endpoints.
+    games: Arc<GameSessions>,
+    /// How many positions of a batch request are computed at once.
+    batch_concurrency: usize,
+    /// Runs the bot computations within the deadlines and concurrency limits.
+    compute: Arc<ComputePool>,
+    /// Whether `GET /metrics` serves the Prometheus metrics.
+    metrics_enabled: bool,
 }
 
 impl AppState {
-    /// Creates a new application state with the given bot registry.
+    /// Creates a new application state with the given bot registry and the
+    /// default difficulty levels.
     pub fn new(bots: YBotRegistry) -> Self {
         Self {
             bots: Arc::new(bots),
+            difficulties: Arc::new(DifficultyTable::default()),
+            sessions: Arc::new(BotSessions::default()),
+            games: Arc::new(GameSessions::default()),
+            batch_concurrency: default_batch_concurrency(),
+            compute: Arc::new(ComputePool::default()),
+            metrics_enabled: true,
         }
     }
 
+    /// Replaces the difficulty levels and returns the state for chaining.
+    pub fn with_difficulties(mut self, difficulties: DifficultyTable) -> Self {
+        self.difficulties = Arc::new(difficulties);
+        self
+    }
+
+    /// Replaces the session store with one holding at most `capacity` bots
+    /// and returns the state for chaining.
+    pub fn with_session_capacity(mut self, capacity: usize) -> Self {
+        self.sessions = Arc::new(BotSessions::new(capacity));
+        self
+    }
+
+    /// Replaces the game store with one whose games expire after `ttl`
+    /// without use and returns the state for chaining.
+    pub fn with_game_ttl(mut self, ttl: Duration) -> Self {
+        self.games = Arc::new(GameSessions::new(ttl));
+        self
+    }
+
+    /// Sets how many positions of a batch request are computed at once
+    /// (at least one) and returns the state for chaining.
+    pub fn with_batch_concurrency(mut self, concurrency: usize) -> Self {
+        self.batch_concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Returns how many positions of a batch request are computed at once.
+    pub fn batch_concurrency(&self) -> usize {
+        self.batch_concurrency
+    }
+
+    /// Replaces the compute pool with one using `limits` and returns the
+    /// state for chaining.
+    pub fn with_compute_limits(mut self, limits: ComputeLimits) -> Self {
+        self.compute = Arc::new(ComputePool::new(limits));
+        self
+    }
+
+    /// Sets whether `GET /metrics` serves the Prometheus metrics and returns
+    /// the state for chaining.
+    pub fn with_metrics(mut self, enabled: bool) -> Self {
+        self.metrics_enabled = enabled;
+        self
+    }
+
+    /// Returns true if `GET /metrics` serves the Prometheus metrics.
+    pub fn metrics_enabled(&self) -> bool {
+        self.metrics_enabled
+    }
+
+    /// Returns a clone of the Arc-wrapped compute pool.
+    pub fn compute(&self) -> Arc<ComputePool> {
+        Arc::clone(&self.compute)
+    }
+
     /// Returns a clone of the Arc-wrapped bot registry.
     pub fn bots(&self) -> Arc<YBotRegistry> {
         Arc::clone(&self.bots)
     }
+
+    /// Returns a clone of the Arc-wrapped difficulty table.
+    pub fn difficulties(&self) -> Arc<DifficultyTable> {
+        Arc::clone(&self.difficulties)
+    }
+
+    /// Returns a clone of the Arc-wrapped session store.
+    pub fn sessions(&self) -> Arc<BotSessions> {
+        Arc::clone(&self.sessions)
+    }
+
+    /// Returns a clone of the Arc-wrapped game store.
+    pub fn games(&self) -> Arc<GameSessions> {
+        Arc::clone(&self.games)
+    }
+
+    /// Builds the bot for a request: the bot kept for the session when a
+    /// session id is given (see [`BotSessions::get_or_create`]), or a fresh
+    /// one from the registry otherwise.
+    pub fn create_bot(
+        &self,
+        bot_id: &str,
+        params: &BotParams,
+        session_id: Option<&str>,
+    ) -> Result<Arc<dyn YBot>, GameYError> {
+        match session_id {
+            Some(session_id) => self
+                .sessions
+                .get_or_create(session_id, bot_id, params, || self.bots.create(bot_id, params)),
+            None => self.bots.create(bot_id, params),
+        }
+    }
+}
+
+/// One position per core by default, as bots are bound by the CPU.
+pub(crate) fn default_batch_concurrency() -> usize {
+    std::thread::available_parallelism().map_or(1, |cores| cores.get())
 }
 
 #[cfg(test)]
@@ -54,6 +174,33 @@ mod tests {
         assert_eq!(state.bots().names(), cloned.bots().names());
     }
 
+    #[test]
+    fn test_with_difficulties() {
+        use crate::difficulty::{Difficulty, DifficultyLevel};
+        let table = DifficultyTable::empty().with_level(Difficulty::Easy, DifficultyLevel::new("random_bot"));
+        let state = AppState::new(YBotRegistry::new()).with_difficulties(table);
+        assert_eq!(state.difficulties().difficulties(), vec![Difficulty::Easy]);
+    }
+
+    #[test]
+    fn test_create_bot_with_session() {
+        let state = AppState::new(YBotRegistry::new().with_bot(Arc::new(RandomBot))).with_session_capacity(4);
+        assert_eq!(state.sessions().capacity(), 4);
+        state.create_bot("random_bot", &BotParams::new(), None).unwrap();
+        assert!(state.sessions().is_empty());
+        state.create_bot("random_bot", &BotParams::new(), Some("game-1")).unwrap();
+        assert_eq!(state.sessions().len(), 1);
+        assert!(state.create_bot("missing", &BotParams::new(), Some("game-1")).is_err());
+        assert_eq!(state.sessions().len(), 1);
+    }
+
+    #[test]
+    fn test_with_game_ttl() {
+        let state = AppState::new(YBotRegistry::new()).with_game_ttl(Duration::from_secs(5));
+        assert_eq!(state.games().ttl(), Duration::from_secs(5));
+        assert!(state.games().is_empty());
+    }
+
     #[test]
     fn test_bots_arc_clone() {
         let registry = YBotRegistry::new().with_bot(Arc::new(RandomBot));
@@ -63,4 +210,30 @@ mod tests {
         // Both Arcs should point to the same registry
         assert_eq!(bots1.names(), bots2.names());
     }
+
+    #[test]
+    fn test_with_batch_concurrency() {
+        let state = AppState::new(YBotRegistry::new());
+        assert!(state.batch_concurrency() >= 1);
+        assert_eq!(state.clone().with_batch_concurrency(3).batch_concurrency(), 3);
+        assert_eq!(state.with_batch_concurrency(0).batch_concurrency(), 1);
+    }
+
+    #[test]
+    fn test_with_compute_limits() {
+        let limits = ComputeLimits {
+            max_concurrent: 2,
+            max_queued: 0,
+            max_timeout_ms: 500,
+        };
+        let state = AppState::new(YBotRegistry::new()).with_compute_limits(limits);
+        assert_eq!(state.compute().limits(), limits);
+    }
+
+    #[test]
+    fn test_with_metrics() {
+        let state = AppState::new(YBotRegistry::new());
+        assert!(state.metrics_enabled());
+        assert!(!state.with_metrics(false).metrics_enabled());
+    }
 }
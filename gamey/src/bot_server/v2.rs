@@ -0,0 +1,216 @@
+//! Handlers of API v2 that differ from v1.
+//!
+//! The move endpoints of v2 answer with a [`BotMoveResponse`]: the bot's
+//! decision as a `move` object with an `action` (the same encoding the game
+//! endpoints accept, see [`BotDecision`]) and a structured [`MoveStatus`],
+//! without the `coords`/`action` pair and the `game_status` string of
+//! [`crate::MoveResponse`]. The analysis, solver, catalogue and game session
+//! routes are shared with v1 (see [`crate::version`]).
+
+use crate::{
+    BotDecision, ChooseRequest, DecisionOptions, Difficulty, GameY, MoveStatus, PlayerId, YEN,
+    choose::{decide_bot_move, parse_position},
+    difficulty::{difficulty_bot, parse_level},
+    error::ErrorResponse,
+    state::AppState,
+};
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use std::time::Instant;
+
+/// The version served by this module.
+const VERSION: &str = "v2";
+
+/// Response of the v2 move endpoints.
+///
+/// # Example
+/// ```json
+/// {
+///   "api_version": "v2",
+///   "bot_id": "mcts_bot",
+///   "bot_player": 1,
+///   "move": { "action": "place", "coords": { "x": 1, "y": 0, "z": 1 } },
+///   "status": { "finished": false, "winner": null, "winner_role": null }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct BotMoveResponse {
+    /// The API version used for this request.
+    pub api_version: String,
+    /// The bot that selected this move, or the difficulty level.
+    pub bot_id: String,
+    /// The player the bot played as.
+    pub bot_player: u32,
+    /// The bot's decision, or `None` if the game was already over.
+    #[serde(rename = "move")]
+    pub bot_move: Option<BotDecision>,
+    /// The state of the game after the move.
+    pub status: MoveStatus,
+}
+
+impl BotMoveResponse {
+    fn new(bot_id: &str, bot_player: PlayerId, bot_move: Option<BotDecision>, status: MoveStatus) -> Self {
+        BotMoveResponse {
+            api_version: VERSION.to_string(),
+            bot_id: bot_id.to_string(),
+            bot_player: bot_player.id(),
+            bot_move,
+            status,
+        }
+    }
+
+    /// The response for a position that is already over, if it is.
+    fn finished(game_y: &GameY, bot_id: &str, bot_player: PlayerId) -> Option<Self> {
+        game_y
+            .check_game_over()
+            .then(|| BotMoveResponse::new(bot_id, bot_player, None, MoveStatus::new(game_y, bot_player)))
+    }
+}
+
+/// Handler for the v2 bot move selection endpoint.
+///
+/// Takes the same [`ChooseRequest`] as [`crate::choose::choose`].
+///
+/// # Route
+/// `POST /v2/ybot/choose/{bot_id}`
+#[utoipa::path(
+    post,
+    path = "/v2/ybot/choose/{bot_id}",
+    tag = "bots",
+    params(("bot_id" = String, Path, description = "The bot to ask")),
+    request_body = ChooseRequest,
+    responses(
+        (status = 200, description = "The bot's move", body = BotMoveResponse),
+        (status = 400, description = "Invalid position or parameters", body = ErrorResponse),
+        (status = 404, description = "Unknown bot", body = ErrorResponse),
+        (status = 429, description = "Too many searches running or waiting", body = ErrorResponse),
+        (status = 503, description = "No move found before the deadline", body = ErrorResponse),
+    )
+)]
+#[axum::debug_handler]
+pub async fn choose(
+    State(state): State<AppState>,
+    Path(bot_id): Path<String>,
+    Json(request): Json<ChooseRequest>,
+) -> Result<Json<BotMoveResponse>, ErrorResponse> {
+    let start = Instant::now();
+    let (game_y, bot_player) = parse_position(request.yen, request.bot_player, VERSION, &bot_id)?;
+    if let Some(response) = BotMoveResponse::finished(&game_y, &bot_id, bot_player) {
+        return Ok(Json(response));
+    }
+    let bot = state
+        .create_bot(&bot_id, &request.params, request.session_id.as_deref())
+        .map_err(|err| ErrorResponse::from_error(&err, Some(VERSION.to_string()), Some(bot_id.clone())))?;
+    let timeout = state.compute().timeout(request.timeout_ms);
+    let (options, label) = (request.options, bot_id.clone());
+    let (decision, status) = state
+        .compute()
+        .run_request(timeout, VERSION, Some(&bot_id), move || {
+            decide_bot_move(bot.as_ref(), game_y, bot_player, &options, VERSION, &label, start)
+        })
+        .await?;
+    Ok(Json(BotMoveResponse::new(&bot_id, bot_player, Some(decision), status)))
+}
+
+/// Handler for the v2 difficulty-based move selection endpoint.
+///
+/// # Route
+/// `POST /v2/ybot/difficulty/{level}`
+///
+/// # Request Body
+/// A JSON object in YEN format representing the current game state.
+#[utoipa::path(
+    post,
+    path = "/v2/ybot/difficulty/{level}",
+    tag = "bots",
+    params(("level" = Difficulty, Path, description = "The difficulty level")),
+    request_body = YEN,
+    responses(
+        (status = 200, description = "The move of the bot behind the level", body = BotMoveResponse),
+        (status = 400, description = "Invalid position", body = ErrorResponse),
+        (status = 404, description = "Unknown level", body = ErrorResponse),
+        (status = 429, description = "Too many searches running or waiting", body = ErrorResponse),
+        (status = 503, description = "No move found before the deadline", body = ErrorResponse),
+    )
+)]
+#[axum::debug_handler]
+pub async fn choose_by_difficulty(
+    State(state): State<AppState>,
+    Path(level): Path<String>,
+    Json(yen): Json<YEN>,
+) -> Result<Json<BotMoveResponse>, ErrorResponse> {
+    let start = Instant::now();
+    let difficulty = parse_level(&level, VERSION)?;
+    let (game_y, bot_player) = parse_position(yen, None, VERSION, &level)?;
+    if let Some(response) = BotMoveResponse::finished(&game_y, &level, bot_player) {
+        return Ok(Json(response));
+    }
+    let bot = difficulty_bot(&state, difficulty, VERSION, &level)?;
+    let timeout = state.compute().timeout(None);
+    let label = level.clone();
+    let (decision, status) = state
+        .compute()
+        .run_request(timeout, VERSION, Some(&level), move || {
+            decide_bot_move(bot.as_ref(), game_y, bot_player, &DecisionOptions::default(), VERSION, &label, start)
+        })
+        .await?;
+    Ok(Json(BotMoveResponse::new(&level, bot_player, Some(decision), status)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Coordinates, bot_server::create_default_state};
+
+    #[tokio::test]
+    async fn test_choose_returns_a_move_object() {
+        let yen = YEN::new(3, 0, vec!['B', 'R'], "./../...".to_string());
+        let Json(response) = choose(
+            State(create_default_state()),
+            Path("random_bot".to_string()),
+            Json(ChooseRequest::from(yen)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.api_version, "v2");
+        assert_eq!(response.bot_player, 0);
+        assert!(matches!(response.bot_move, Some(BotDecision::Place { .. })));
+        assert!(!response.status.finished);
+    }
+
+    #[tokio::test]
+    async fn test_choose_on_a_finished_game_has_no_move() {
+        let yen = YEN::new(2, 1, vec!['B', 'R'], "B/BB".to_string());
+        let Json(response) = choose(
+            State(create_default_state()),
+            Path("random_bot".to_string()),
+            Json(ChooseRequest::from(yen)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.bot_move, None);
+        assert!(response.status.finished);
+        assert_eq!(response.status.winner, Some(0));
+    }
+
+    #[test]
+    fn test_bot_move_response_json() {
+        let response = BotMoveResponse::new(
+            "random_bot",
+            PlayerId::new(1),
+            Some(BotDecision::Place {
+                coords: Coordinates::new(1, 0, 1),
+            }),
+            MoveStatus::default(),
+        );
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["move"]["action"], "place");
+        assert_eq!(json["move"]["coords"]["x"], 1);
+        assert!(json.get("game_status").is_none());
+        assert!(json.get("coords").is_none());
+    }
+}
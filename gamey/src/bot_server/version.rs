@@ -1,7 +1,38 @@
-use crate::error::ErrorResponse;
+//! API versions.
+//!
+//! Every route except `/status`, `/metrics` and `/play` starts with the API
+//! version. Versions coexist: a client keeps the version it was written for
+//! until it moves to a newer one.
+//!
+//! - `v1` is the original API. It is deprecated: its responses carry a
+//!   `Deprecation` header and a `Link` to the `v2` route (see
+//!   [`deprecation_headers`]).
+//! - `v2` answers the move endpoints with a `move` object with an action
+//!   and a structured status (see [`crate::v2`]); its other routes are shared
+//!   with `v1`.
+//!
+//! Clients find the versions of a server at `GET /versions` (see
+//! [`list_versions`]).
 
-/// The currently supported API version.
-pub const SUPPORTED_VERSION: &str = "v1";
+use crate::error::{ErrorCode, ErrorResponse};
+use axum::{
+    Json,
+    extract::Request,
+    http::{HeaderValue, header::LINK},
+    middleware::Next,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// The API versions served, oldest first.
+pub const SUPPORTED_VERSIONS: [&str; 2] = ["v1", "v2"];
+
+/// The newest API version.
+pub const LATEST_VERSION: &str = "v2";
+
+/// The API versions that still work but should no longer be used.
+pub const DEPRECATED_VERSIONS: [&str; 1] = ["v1"];
 
 /// Validates that the requested API version is supported.
 ///
@@ -17,14 +48,17 @@ pub const SUPPORTED_VERSION: &str = "v1";
 /// use gamey::check_api_version;
 ///
 /// assert!(check_api_version("v1").is_ok());
-/// assert!(check_api_version("v2").is_err());
+/// assert!(check_api_version("v2").is_ok());
+/// assert!(check_api_version("v3").is_err());
 /// ```
 pub fn check_api_version(version: &str) -> Result<(), ErrorResponse> {
-    if version != SUPPORTED_VERSION {
-        Err(ErrorResponse::error(
+    if !SUPPORTED_VERSIONS.contains(&version) {
+        Err(ErrorResponse::new(
+            ErrorCode::UnsupportedVersion,
             &format!(
-                "Unsupported API version: {}. Supported version is {}",
-                version, SUPPORTED_VERSION
+                "Unsupported API version: {}. Supported versions are {}",
+                version,
+                SUPPORTED_VERSIONS.join(", ")
             ),
             Some(version.to_string()),
             None,
@@ -34,6 +68,80 @@ pub fn check_api_version(version: &str) -> Result<(), ErrorResponse> {
     }
 }
 
+/// An API version offered by the server.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct ApiVersionInfo {
+    /// The version, as it appears in the routes (e.g., "v1").
+    pub version: String,
+    /// Whether the version should no longer be used.
+    pub deprecated: bool,
+}
+
+/// Response of the versions endpoint.
+///
+/// # Example
+/// ```json
+/// {
+///   "latest": "v2",
+///   "versions": [
+///     { "version": "v1", "deprecated": true },
+///     { "version": "v2", "deprecated": false }
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct VersionsResponse {
+    /// The version new clients should use.
+    pub latest: String,
+    /// Every supported version, oldest first.
+    pub versions: Vec<ApiVersionInfo>,
+}
+
+/// Handler that lists the supported API versions.
+///
+/// # Route
+/// `GET /versions`
+#[utoipa::path(
+    get,
+    path = "/versions",
+    tag = "server",
+    responses((status = 200, description = "The supported API versions", body = VersionsResponse))
+)]
+pub async fn list_versions() -> Json<VersionsResponse> {
+    Json(VersionsResponse {
+        latest: LATEST_VERSION.to_string(),
+        versions: SUPPORTED_VERSIONS
+            .iter()
+            .map(|version| ApiVersionInfo {
+                version: version.to_string(),
+                deprecated: DEPRECATED_VERSIONS.contains(version),
+            })
+            .collect(),
+    })
+}
+
+/// Middleware that marks the responses of deprecated versions.
+///
+/// Responses to a route of a deprecated version get a `Deprecation: true`
+/// header and a `Link` header pointing to the same route in
+/// [`LATEST_VERSION`], with `rel="successor-version"`.
+pub async fn deprecation_headers(request: Request, next: Next) -> Response {
+    let path = request.uri().path().to_string();
+    let mut response = next.run(request).await;
+    let Some((version, rest)) = path.trim_start_matches('/').split_once('/') else {
+        return response;
+    };
+    if DEPRECATED_VERSIONS.contains(&version) {
+        let headers = response.headers_mut();
+        headers.insert("deprecation", HeaderValue::from_static("true"));
+        let link = format!("</{}/{}>; rel=\"successor-version\"", LATEST_VERSION, rest);
+        if let Ok(link) = HeaderValue::from_str(&link) {
+            headers.insert(LINK, link);
+        }
+    }
+    response
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -41,16 +149,19 @@ mod tests {
     #[test]
     fn test_supported_version() {
         assert!(check_api_version("v1").is_ok());
+        assert!(check_api_version("v2").is_ok());
     }
 
     #[test]
-    fn test_unsupported_version_v2() {
-        let result = check_api_version("v2");
+    fn test_unsupported_version_v3() {
+        let result = check_api_version("v3");
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(err.message.contains("Unsupported API version"));
-        assert!(err.message.contains("v2"));
-        assert_eq!(err.api_version, Some("v2".to_string()));
+        assert!(err.message.contains("v3"));
+        assert!(err.message.contains("v1, v2"));
+        assert_eq!(err.code, ErrorCode::UnsupportedVersion);
+        assert_eq!(err.api_version, Some("v3".to_string()));
     }
 
     #[test]
@@ -68,7 +179,22 @@ mod tests {
     }
 
     #[test]
-    fn test_supported_version_constant() {
-        assert_eq!(SUPPORTED_VERSION, "v1");
+    fn test_version_constants() {
+        assert_eq!(SUPPORTED_VERSIONS.last(), Some(&LATEST_VERSION));
+        assert!(DEPRECATED_VERSIONS.iter().all(|version| SUPPORTED_VERSIONS.contains(version)));
+        assert!(!DEPRECATED_VERSIONS.contains(&LATEST_VERSION));
+    }
+
+    #[tokio::test]
+    async fn test_list_versions() {
+        let Json(response) = list_versions().await;
+        assert_eq!(response.latest, "v2");
+        assert_eq!(
+            response.versions,
+            vec![
+                ApiVersionInfo { version: "v1".to_string(), deprecated: true },
+                ApiVersionInfo { version: "v2".to_string(), deprecated: false },
+            ]
+        );
     }
 }
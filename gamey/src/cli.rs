@@ -5,13 +5,19 @@
 //! - Human vs Human: Two players take turns at the same terminal
 //! - Human vs Computer: Play against a bot
 //! - Server: Run as an HTTP server for bot API
+//!
+//! It also provides these subcommands:
+//! - `tournament`: Play bots against each other (see [`crate::tournament`])
+//! - `book`: Build an opening book from the games of a tournament (see [`crate::OpeningBook`])
+//! - `selfplay`: Write training data from games of the MCTS bot against itself (see [`crate::selfplay`])
+//! - `tune`: Tune the weights of a heuristic bot (see [`crate::tuning`])
 
 use crate::{
-    Coordinates, GameAction,SimpleBlockerBot, ShortestPathBot ,TriangleAttackBot, GroupExpansionBot, MonteCarloBot, Movement, PriorityBlockBot, RandomBot, RenderOptions, YBot, YBotRegistry, game
+    BotDecision, Coordinates, DecisionOptions, GameAction, Movement, RenderOptions, YBot, game
 };
 use crate::{GameStatus, GameY, PlayerId};
 use anyhow::Result;
-use clap::{Parser, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use rustyline::DefaultEditor;
 use rustyline::error::ReadlineError;
 use std::fmt::Display;
@@ -30,13 +36,179 @@ pub struct CliArgs {
     #[arg(short, long, default_value_t = Mode::Human)]
     pub mode: Mode,
 
-    /// The bot to use (only used with --mode=computer), default = random_bot
+    /// The bot to use (only used with --mode=computer), default = random_bot.
+    /// Parameters can be given as a query string, e.g. `monte_carlo_bot?simulations=500`
     #[arg(short, long, default_value = "random_bot")]
     pub bot: String,
 
+    /// Opening book the bot plays from while the position is in it (only used with --mode=computer).
+    #[arg(long)]
+    pub book: Option<std::path::PathBuf>,
+
     /// Port to run the server on (only used with --mode=server)
     #[arg(short, long, default_value_t = 3000)]
     pub port: u16,
+
+    /// TOML configuration file of the server (only used with --mode=server),
+    /// default = $GAMEY_CONFIG. A --port given here overrides its bind port.
+    #[arg(long)]
+    pub config: Option<std::path::PathBuf>,
+
+    /// Optional subcommand; without one the game runs in the selected mode.
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+/// Subcommands of the GameY application.
+#[derive(Subcommand, Debug)]
+pub enum CliCommand {
+    /// Play a round-robin tournament between bots.
+    Tournament(TournamentArgs),
+    /// Build an opening book from the game records of a tournament.
+    Book(BookArgs),
+    /// Play the MCTS bot against itself and write every position as JSON lines.
+    Selfplay(SelfPlayArgs),
+    /// Tune the weights of a heuristic bot by playing it against itself.
+    Tune(TuneArgs),
+}
+
+/// Arguments of the `tournament` subcommand.
+#[derive(Args, Debug)]
+pub struct TournamentArgs {
+    /// Comma-separated bot specs, e.g. `random_bot,monte_carlo_bot?simulations=500`.
+    #[arg(long, value_delimiter = ',', required = true)]
+    pub bots: Vec<String>,
+
+    /// Size of the triangular board.
+    #[arg(long, default_value_t = 9)]
+    pub size: u32,
+
+    /// Games played by each pair of bots.
+    #[arg(long, default_value_t = 100)]
+    pub games: u32,
+
+    /// Seed of the tournament; the same seed replays the same games.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// Output format of the report.
+    #[arg(long, default_value_t = ReportFormat::Json)]
+    pub format: ReportFormat,
+
+    /// File to write the report to (standard output if omitted).
+    #[arg(long)]
+    pub out: Option<std::path::PathBuf>,
+
+    /// File to write the moves of every game to, as JSON lines.
+    #[arg(long)]
+    pub records: Option<std::path::PathBuf>,
+}
+
+/// Arguments of the `book` subcommand.
+#[derive(Args, Debug)]
+pub struct BookArgs {
+    /// Game records written by `tournament --records`.
+    #[arg(long, required = true)]
+    pub records: Vec<std::path::PathBuf>,
+
+    /// Number of moves of each game added to the book.
+    #[arg(long, default_value_t = 8)]
+    pub depth: usize,
+
+    /// Moves won fewer times than this are left out of the book.
+    #[arg(long, default_value_t = 1)]
+    pub min_weight: u32,
+
+    /// File to write the book to.
+    #[arg(long)]
+    pub out: std::path::PathBuf,
+}
+
+/// Arguments of the `selfplay` subcommand.
+#[derive(Args, Debug)]
+pub struct SelfPlayArgs {
+    /// MCTS bot spec, e.g. `mcts` or `mcts_bot?iterations=400`.
+    #[arg(long, default_value = "mcts")]
+    pub bot: String,
+
+    /// Number of games to play.
+    #[arg(long, default_value_t = 10)]
+    pub games: u32,
+
+    /// Size of the triangular board.
+    #[arg(long, default_value_t = 7)]
+    pub size: u32,
+
+    /// Seed of the run; the same seed produces the same data.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// Moves at the start of each game drawn in proportion to their visits
+    /// (defaults to the board size).
+    #[arg(long)]
+    pub sample_moves: Option<u32>,
+
+    /// Probability of playing a uniformly random move.
+    #[arg(long, default_value_t = 0.05)]
+    pub noise: f64,
+
+    /// File to write the positions to, as JSON lines.
+    #[arg(long)]
+    pub out: std::path::PathBuf,
+}
+
+/// Arguments of the `tune` subcommand.
+#[derive(Args, Debug)]
+pub struct TuneArgs {
+    /// Bot to tune: shortest_path_bot, priority_block_bot or triangle_attack_bot.
+    #[arg(long)]
+    pub bot: String,
+
+    /// Size of the triangular board.
+    #[arg(long, default_value_t = 7)]
+    pub size: u32,
+
+    /// Number of SPSA iterations.
+    #[arg(long, default_value_t = 50)]
+    pub iterations: u32,
+
+    /// Games played on each iteration.
+    #[arg(long, default_value_t = 20)]
+    pub games: u32,
+
+    /// Games between the tuned and the starting weights at the end.
+    #[arg(long, default_value_t = 100)]
+    pub validation_games: u32,
+
+    /// Step size of the first iteration.
+    #[arg(long, default_value_t = 2.0)]
+    pub learning_rate: f64,
+
+    /// Seed of the run; the same seed produces the same weights.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// File to write the JSON report to (standard output if omitted).
+    #[arg(long)]
+    pub out: Option<std::path::PathBuf>,
+}
+
+/// Output format of a tournament report.
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+pub enum ReportFormat {
+    /// Full report as JSON.
+    Json,
+    /// Standings and crosstable as CSV.
+    Csv,
+}
+
+impl Display for ReportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportFormat::Json => write!(f, "json"),
+            ReportFormat::Csv => write!(f, "csv"),
+        }
+    }
 }
 
 /// The game mode determining how the game is played.
@@ -61,6 +233,84 @@ impl Display for Mode {
     }
 }
 
+/// Runs the `tournament` subcommand and writes the report.
+pub fn run_tournament_command(args: &TournamentArgs) -> Result<()> {
+    let config = crate::TournamentConfig::new(args.bots.clone(), args.size)
+        .with_games(args.games)
+        .with_seed(args.seed)
+        .with_records(args.records.is_some());
+    let report = crate::run_tournament(&crate::default_registry(), &config)?;
+    if let Some(path) = &args.records {
+        std::fs::write(path, report.records_to_jsonl()?)?;
+    }
+    let output = match args.format {
+        ReportFormat::Json => report.to_json()? + "\n",
+        ReportFormat::Csv => report.to_csv(),
+    };
+    match &args.out {
+        Some(path) => std::fs::write(path, output)?,
+        None => print!("{}", output),
+    }
+    Ok(())
+}
+
+/// Runs the `book` subcommand: reads the records and saves the book.
+pub fn run_book_command(args: &BookArgs) -> Result<()> {
+    let mut records = Vec::new();
+    for path in &args.records {
+        for line in std::fs::read_to_string(path)?.lines().filter(|l| !l.trim().is_empty()) {
+            records.push(serde_json::from_str::<crate::GameRecord>(line)?);
+        }
+    }
+    let mut book = crate::OpeningBook::from_records(&records, args.depth)?;
+    book.prune(args.min_weight);
+    book.save(&args.out)?;
+    println!("Opening book with {} positions written to {}", book.len(), args.out.display());
+    Ok(())
+}
+
+/// Runs the `selfplay` subcommand and writes the positions.
+pub fn run_selfplay_command(args: &SelfPlayArgs) -> Result<()> {
+    let config = crate::SelfPlayConfig::new(args.bot.clone(), args.size)
+        .with_games(args.games)
+        .with_seed(args.seed)
+        .with_sample_moves(args.sample_moves.unwrap_or(args.size))
+        .with_noise(args.noise);
+    let mut out = std::io::BufWriter::new(std::fs::File::create(&args.out)?);
+    let summary = crate::run_selfplay(&config, &mut out)?;
+    std::io::Write::flush(&mut out)?;
+    println!(
+        "{} games ({} positions) written to {}",
+        summary.games,
+        summary.positions,
+        args.out.display()
+    );
+    Ok(())
+}
+
+/// Runs the `tune` subcommand and writes the report.
+pub fn run_tune_command(args: &TuneArgs) -> Result<()> {
+    let Some(config) = crate::TuningConfig::preset(&args.bot, args.size) else {
+        anyhow::bail!("{} has no weights to tune", args.bot);
+    };
+    let config = config
+        .with_iterations(args.iterations)
+        .with_games(args.games)
+        .with_validation_games(args.validation_games)
+        .with_learning_rate(args.learning_rate)
+        .with_seed(args.seed);
+    let report = crate::run_tuning(&crate::default_registry(), &config)?;
+    let output = report.to_json()? + "\n";
+    match &args.out {
+        Some(path) => {
+            std::fs::write(path, output)?;
+            println!("Tuned bot: {}", report.spec);
+        }
+        None => print!("{}", output),
+    }
+    Ok(())
+}
+
 /// Runs the interactive CLI game loop.
 ///
 /// This function parses command-line arguments, initializes the game,
@@ -69,24 +319,18 @@ pub fn run_cli_game() -> Result<()> {
     let args = CliArgs::parse();
     let mut render_options = crate::RenderOptions::default();
     let mut rl = DefaultEditor::new()?;
-    let bots_registry = YBotRegistry::new().with_bot(Arc::new(RandomBot))
-    .with_bot(Arc::new(GroupExpansionBot))
-    .with_bot(Arc::new(PriorityBlockBot))
-    .with_bot(Arc::new(MonteCarloBot))
-    .with_bot(Arc::new(SimpleBlockerBot))
-    .with_bot(Arc::new(TriangleAttackBot))
-    .with_bot(Arc::new(ShortestPathBot));
-    let bot: Arc<dyn YBot> = match bots_registry.find(&args.bot) {
-        Some(b) => b,
-        None => {
-            println!(
-                "Bot '{}' not found. Available bots: {:?}",
-                args.bot,
-                bots_registry.names()
-            );
+    let bots_registry = crate::default_registry();
+    let bot: Arc<dyn YBot> = match bots_registry.build(&args.bot) {
+        Ok(b) => b,
+        Err(e) => {
+            println!("Bot '{}' could not be created: {}", args.bot, e);
             return Ok(());
         }
     };
+    let bot: Arc<dyn YBot> = match &args.book {
+        Some(path) => Arc::new(crate::BookBot::new(bot, Arc::new(crate::OpeningBook::load(path)?))),
+        None => bot,
+    };
     let mut game = game::GameY::new(args.size);
     loop {
         println!("{}", game.render(&render_options));
@@ -338,17 +582,18 @@ fn handle_place_command(
 
 /// AI logic extracted to its own function
 fn trigger_bot_move(game: &mut GameY, bot: &dyn YBot) {
-    if let Some(bot_coords) = bot.choose_move(game) {
-        // Assuming next_player() is safe to unwrap here because the game isn't over
-        if let Some(bot_player) = game.next_player() {
-            let bot_movement = Movement::Placement {
-                player: bot_player,
-                coords: bot_coords,
-            };
-            apply_move(game, bot_movement, "Error adding bot move");
+    // Assuming next_player() is safe to unwrap here because the game isn't over
+    if let Some(bot_player) = game.next_player() {
+        let decision = bot.decide(game, &DecisionOptions::default().with_resign(true));
+        match decision {
+            BotDecision::Resign if game.available_cells().is_empty() => {
+                println!("No available moves for the bot.");
+                return;
+            }
+            BotDecision::Resign => println!("The bot resigns."),
+            BotDecision::Place { .. } | BotDecision::Swap => {}
         }
-    } else {
-        println!("No available moves for the bot.");
+        apply_move(game, decision.into_movement(bot_player), "Error adding bot move");
     }
 }
 
@@ -1,9 +1,12 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use std::fmt::Display;
 
 /// Represents special game actions that are not regular piece placements.
 ///
 /// These actions allow players to perform non-placement moves during the game.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
 pub enum GameAction {
     /// The swap rule: allows the second player to swap colors after the first move.
     /// This is commonly used in games like Hex and Y to balance first-move advantage.
@@ -42,6 +45,13 @@ mod tests {
         assert_ne!(GameAction::Swap, GameAction::Resign);
     }
 
+    #[test]
+    fn test_serde_lowercase() {
+        assert_eq!(serde_json::to_string(&GameAction::Resign).unwrap(), "\"resign\"");
+        let action: GameAction = serde_json::from_str("\"swap\"").unwrap();
+        assert_eq!(action, GameAction::Swap);
+    }
+
     #[test]
     fn test_clone() {
         let action = GameAction::Swap;
@@ -1,6 +1,7 @@
 use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// Represents barycentric coordinates (x, y, z) on a triangular board.
 ///
@@ -12,7 +13,7 @@ use serde::{Deserialize, Serialize};
 /// - x = 0 means the cell touches side A
 /// - y = 0 means the cell touches side B
 /// - z = 0 means the cell touches side C
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
 pub struct Coordinates {
     x: u32,
     y: u32,
@@ -101,10 +101,7 @@ impl GameY {
 
     /// Permite al bot saber a qué grupo pertenece una ficha en unas coordenadas dadas.
     pub fn get_set_id(&self, coords: &Coordinates) -> Option<usize> {
-        match self.board_map.get(coords) {
-            Some((set_idx, _player)) => Some(*set_idx),
-            None => None,
-        }
+        self.board_map.get(coords).map(|(set_idx, _player)| *set_idx)
     }
 
     /// Permite al bot saber qué bordes (A, B, C) está tocando un grupo específico.
@@ -122,6 +119,18 @@ impl GameY {
         }
     }
 
+    /// Hands the turn of an ongoing game to `player`; finished games are left
+    /// unchanged.
+    ///
+    /// Loading a [`YEN`] derives the player to move from the layout. Callers
+    /// that know better, such as a request that says which side its bot
+    /// plays, set it here.
+    pub fn set_next_player(&mut self, player: PlayerId) {
+        if let GameStatus::Ongoing { .. } = self.status {
+            self.status = GameStatus::Ongoing { next_player: player };
+        }
+    }
+
     /// Loads a game state from a YEN format file.
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let filename = path.as_ref().display().to_string();
@@ -459,6 +468,7 @@ impl GameY {
         false
     }
 
+    /// Returns the cells occupied by `target_player`, ordered by cell index.
     pub fn cells_for_player(&self, target_player: PlayerId) -> Vec<Coordinates> {
         let mut player_cells = Vec::new();
         
@@ -467,6 +477,7 @@ impl GameY {
                 player_cells.push(*coords);
             }
         }       
+        player_cells.sort_by_key(|coords| coords.to_index(self.board_size));
         player_cells
     }
 }
@@ -533,7 +544,7 @@ impl From<&GameY> for YEN {
     fn from(game: &GameY) -> Self {
         let size = game.board_size;
         let turn = match game.status {
-            GameStatus::Finished { winner } => other_player(winner).id() as u32,
+            GameStatus::Finished { winner } => other_player(winner).id(),
             GameStatus::Ongoing { next_player } => next_player.id(),
         };
         let mut layout = String::new();
@@ -733,6 +744,22 @@ mod tests {
         assert_eq!(yen.layout(), yen_loaded.layout());
     }
 
+    // Test handing the turn of a loaded game to either player
+    #[test]
+    fn test_set_next_player() {
+        // The layout has one stone of each player, so either may be to move.
+        for player in [0, 1] {
+            let yen = YEN::new(3, 1, vec!['B', 'R'], "B/R./...".to_string());
+            let mut game = GameY::try_from(yen).unwrap();
+            game.set_next_player(PlayerId::new(player));
+            assert_eq!(game.next_player(), Some(PlayerId::new(player)));
+        }
+
+        let mut finished = GameY::try_from(YEN::new(2, 0, vec!['B', 'R'], "B/BB".to_string())).unwrap();
+        finished.set_next_player(PlayerId::new(1));
+        assert_eq!(finished.next_player(), None);
+    }
+
     // Test loading a YEN representation of a finished game
     #[test]
     fn test_load_yen_end2() {
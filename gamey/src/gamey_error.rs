@@ -122,6 +122,103 @@ pub enum GameYError {
         /// Description of what went wrong.
         message: String,
     },
+
+    /// No bot is registered under the requested name.
+    #[error("Bot not found: {bot_id}, available bots: [{available}]")]
+    BotNotFound {
+        /// The requested bot name.
+        bot_id: String,
+        /// Comma-separated list of the registered bot names.
+        available: String,
+    },
+
+    /// The parameters supplied for a bot could not be applied.
+    #[error("Invalid parameters for bot {bot_id}: {message}")]
+    InvalidBotParams {
+        /// The bot the parameters were meant for.
+        bot_id: String,
+        /// Description of what is wrong with the parameters.
+        message: String,
+    },
+
+    /// A tournament was configured in a way that cannot be played.
+    #[error("Invalid tournament: {message}")]
+    InvalidTournament {
+        /// Description of what is wrong with the configuration.
+        message: String,
+    },
+
+    /// The weights of an evaluator do not describe a usable network.
+    #[error("Invalid model: {message}")]
+    InvalidModel {
+        /// Description of what is wrong with the weights.
+        message: String,
+    },
+
+    /// No game session has the requested id (it never existed or expired).
+    #[error("Game not found: {game_id}")]
+    GameNotFound {
+        /// The id that was requested.
+        game_id: String,
+    },
+
+    /// A game session was requested with settings that cannot be played.
+    #[error("Invalid game: {message}")]
+    InvalidGame {
+        /// Description of what is wrong with the settings.
+        message: String,
+    },
+
+    /// A move that the rules of the game session do not allow.
+    #[error("Invalid move: {message}")]
+    InvalidMove {
+        /// Why the move is not allowed.
+        message: String,
+    },
+
+    /// A game session was used with a missing or wrong seat token.
+    #[error("Invalid token for game {game_id}: {message}")]
+    InvalidToken {
+        /// The id of the game.
+        game_id: String,
+        /// Why the token was rejected.
+        message: String,
+    },
+
+    /// A tuning run was configured in a way that cannot be played.
+    #[error("Invalid tuning: {message}")]
+    InvalidTuning {
+        /// Description of what is wrong with the configuration.
+        message: String,
+    },
+
+    /// A self-play run was configured in a way that cannot be played.
+    #[error("Invalid self-play: {message}")]
+    InvalidSelfPlay {
+        /// Description of what is wrong with the configuration.
+        message: String,
+    },
+
+    /// The server has too many searches running or waiting to take another.
+    #[error("Server overloaded: {message}")]
+    Overloaded {
+        /// Which limit was reached.
+        message: String,
+    },
+
+    /// A search did not finish before the deadline of its request.
+    #[error("Search timed out after {timeout_ms} ms")]
+    SearchTimeout {
+        /// The time the request was given, in milliseconds.
+        timeout_ms: u64,
+    },
+
+    /// The server configuration could not be read or is invalid.
+    #[error("Invalid server configuration: {message}")]
+    InvalidConfig {
+        /// Which setting is wrong and why.
+        message: String,
+    },
 }
 
 #[cfg(test)]
@@ -243,6 +340,138 @@ mod tests {
         assert!(msg.contains("Failed to bind to port 3000"));
     }
 
+    #[test]
+    fn test_bot_not_found_display() {
+        let err = GameYError::BotNotFound {
+            bot_id: "unknown".to_string(),
+            available: "random_bot, resistance_bot".to_string(),
+        };
+        let msg = format!("{}", err);
+        assert!(msg.contains("Bot not found: unknown"));
+        assert!(msg.contains("[random_bot, resistance_bot]"));
+    }
+
+    #[test]
+    fn test_invalid_bot_params_display() {
+        let err = GameYError::InvalidBotParams {
+            bot_id: "monte_carlo_bot".to_string(),
+            message: "unknown field `foo`".to_string(),
+        };
+        let msg = format!("{}", err);
+        assert!(msg.contains("Invalid parameters for bot monte_carlo_bot"));
+        assert!(msg.contains("foo"));
+    }
+
+    #[test]
+    fn test_invalid_model_display() {
+        let err = GameYError::InvalidModel {
+            message: "layer 1 expects 10 inputs, found 12".to_string(),
+        };
+        let msg = format!("{}", err);
+        assert!(msg.contains("Invalid model"));
+        assert!(msg.contains("layer 1"));
+    }
+
+    #[test]
+    fn test_invalid_tournament_display() {
+        let err = GameYError::InvalidTournament {
+            message: "at least two bots are needed".to_string(),
+        };
+        let msg = format!("{}", err);
+        assert!(msg.contains("Invalid tournament"));
+        assert!(msg.contains("two bots"));
+    }
+
+    #[test]
+    fn test_game_not_found_display() {
+        let err = GameYError::GameNotFound {
+            game_id: "a1b2".to_string(),
+        };
+        let msg = format!("{}", err);
+        assert!(msg.contains("not found"));
+        assert!(msg.contains("a1b2"));
+    }
+
+    #[test]
+    fn test_invalid_game_display() {
+        let err = GameYError::InvalidGame {
+            message: "bot_player must be 0 or 1".to_string(),
+        };
+        let msg = format!("{}", err);
+        assert!(msg.contains("Invalid game"));
+        assert!(msg.contains("bot_player"));
+    }
+
+    #[test]
+    fn test_invalid_move_display() {
+        let err = GameYError::InvalidMove {
+            message: "the pie rule is off".to_string(),
+        };
+        let msg = format!("{}", err);
+        assert!(msg.contains("Invalid move"));
+        assert!(msg.contains("pie rule"));
+    }
+
+    #[test]
+    fn test_invalid_token_display() {
+        let err = GameYError::InvalidToken {
+            game_id: "a1b2".to_string(),
+            message: "unknown token".to_string(),
+        };
+        let msg = format!("{}", err);
+        assert!(msg.contains("Invalid token"));
+        assert!(msg.contains("a1b2"));
+        assert!(msg.contains("unknown token"));
+    }
+
+    #[test]
+    fn test_invalid_tuning_display() {
+        let err = GameYError::InvalidTuning {
+            message: "no weights to tune for random_bot".to_string(),
+        };
+        let msg = format!("{}", err);
+        assert!(msg.contains("Invalid tuning"));
+        assert!(msg.contains("random_bot"));
+    }
+
+    #[test]
+    fn test_invalid_selfplay_display() {
+        let err = GameYError::InvalidSelfPlay {
+            message: "self-play needs the mcts bot".to_string(),
+        };
+        let msg = format!("{}", err);
+        assert!(msg.contains("Invalid self-play"));
+        assert!(msg.contains("mcts"));
+    }
+
+    #[test]
+    fn test_overloaded_display() {
+        let err = GameYError::Overloaded {
+            message: "8 searches are waiting".to_string(),
+        };
+        let msg = format!("{}", err);
+        assert!(msg.contains("Server overloaded"));
+        assert!(msg.contains("8 searches"));
+    }
+
+    #[test]
+    fn test_search_timeout_display() {
+        let err = GameYError::SearchTimeout { timeout_ms: 250 };
+        let msg = format!("{}", err);
+        assert!(msg.contains("timed out"));
+        assert!(msg.contains("250 ms"));
+    }
+
+    #[test]
+    fn test_invalid_config_display() {
+        let err = GameYError::InvalidConfig {
+            message: "server.bind: invalid socket address".to_string(),
+        };
+        let msg = format!("{}", err);
+        assert!(msg.contains("Invalid server configuration"));
+        assert!(msg.contains("server.bind"));
+    }
+
     #[test]
     fn test_error_is_debug() {
         let err = GameYError::IoError {
@@ -11,6 +11,9 @@
 //! - [`cli`]: Command-line interface for interactive play
 //! - [`notation`]: Game notation formats (YEN)
 //! - [`gamey_error`]: Error types for the library
+//! - [`tournament`]: Round-robin tournaments between bots
+//! - [`selfplay`]: Self-play data generation for training evaluators
+//! - [`tuning`]: SPSA tuning of the weights of the heuristic bots
 //!
 //! # Example
 //!
@@ -34,10 +37,16 @@ pub mod core;
 pub mod gamey_error;
 pub mod notation;
 pub mod bot_server;
+pub mod tournament;
+pub mod selfplay;
+pub mod tuning;
 pub use bot::*;
 pub use cli::*;
 pub use core::*;
 pub use gamey_error::*;
 pub use notation::*;
 pub use bot_server::*;
+pub use tournament::*;
+pub use selfplay::*;
+pub use tuning::*;
 pub mod metrics;
\ No newline at end of file
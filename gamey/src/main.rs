@@ -17,10 +17,30 @@
 //!
 //! # Start the bot server on port 3000
 //! gamey --mode server --port 3000
+//!
+//! # Start the bot server with a configuration file, overriding one setting
+//! GAMEY_LOG__FORMAT=json gamey --mode server --config server.toml
+//!
+//! # Play a round-robin tournament between bots
+//! gamey tournament --bots random_bot,shortest_path_bot,resistance_bot --size 9 --games 200
+//!
+//! # Build an opening book from the games of a tournament
+//! gamey tournament --bots shortest_path_bot,resistance_bot --records games.jsonl
+//! gamey book --records games.jsonl --depth 6 --out book.json
+//!
+//! # Write self-play training data from the MCTS bot
+//! gamey selfplay --bot mcts --games 100 --size 7 --out data.jsonl
+//!
+//! # Tune the weights of a heuristic bot
+//! gamey tune --bot shortest_path_bot --iterations 100 --games 20 --out tuning.json
 //! ```
 
-use clap::Parser;
-use gamey::{self, CliArgs, Mode, run_bot_server, run_cli_game};
+use clap::{CommandFactory, FromArgMatches, parser::ValueSource};
+use gamey::{
+    self, CliArgs, CliCommand, GameYError, Mode, ServerConfig, run_book_command, run_cli_game, run_selfplay_command,
+    run_server, run_tournament_command, run_tune_command,
+};
+use std::path::Path;
 use tracing_subscriber::prelude::*;
 
 /// Main entry point for the GameY application.
@@ -29,11 +49,28 @@ use tracing_subscriber::prelude::*;
 /// depending on the selected mode.
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::registry().init();
-    let args = CliArgs::parse();
+    let matches = CliArgs::command().get_matches();
+    let args = CliArgs::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
-    if args.mode == Mode::Server {
-        if let Err(e) = run_bot_server(args.port).await {
+    if args.command.is_none() && args.mode == Mode::Server {
+        // Only a --port typed on the command line overrides the configuration.
+        let port = (matches.value_source("port") == Some(ValueSource::CommandLine)).then_some(args.port);
+        if let Err(e) = serve(args.config.as_deref(), port).await {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    tracing_subscriber::registry().init();
+    if let Some(command) = &args.command {
+        let result = match command {
+            CliCommand::Tournament(tournament) => run_tournament_command(tournament),
+            CliCommand::Book(book) => run_book_command(book),
+            CliCommand::Selfplay(selfplay) => run_selfplay_command(selfplay),
+            CliCommand::Tune(tune) => run_tune_command(tune),
+        };
+        if let Err(e) = result {
             eprintln!("Error: {}", e);
             std::process::exit(1);
         }
@@ -41,3 +78,13 @@ async fn main() {
         run_cli_game().expect("End CLI game");
     }
 }
+
+/// Loads the server configuration, installs its logger and runs the server.
+async fn serve(config: Option<&Path>, port: Option<u16>) -> Result<(), GameYError> {
+    let mut config = ServerConfig::load(config)?;
+    if let Some(port) = port {
+        config = config.with_port(port);
+    }
+    config.log.init()?;
+    run_server(config).await
+}
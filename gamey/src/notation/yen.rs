@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// Y Exchange Notation (YEN) - a compact format for representing Y game states.
 ///
@@ -21,7 +22,7 @@ use serde::{Deserialize, Serialize};
 ///   "layout": "B/BR/.R."
 /// }
 /// ```
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, ToSchema)]
 pub struct YEN {
     /// The board size (length of one side of the triangle).
     size: u32,
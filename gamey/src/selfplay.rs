@@ -0,0 +1,351 @@
+//! Self-play data generation.
+//!
+//! This module plays [`MctsBot`] against itself and keeps every position of
+//! every game as a [`SelfPlayPosition`]: the board, the visits the search gave
+//! to each move and the final result. These are the datasets needed to train
+//! an [`crate::Evaluator`] or to tune heuristics.
+//!
+//! To make the games differ, the first [`SelfPlayConfig::sample_moves`] moves
+//! of each game are drawn in proportion to their visits instead of taking the
+//! most visited one, and with probability [`SelfPlayConfig::noise`] any move
+//! is replaced by a uniformly random one. All the randomness comes from
+//! [`SelfPlayConfig::seed`].
+//!
+//! # Output format
+//!
+//! [`run_selfplay`] writes one JSON object per position and line:
+//!
+//! ```json
+//! {
+//!   "game": 0,
+//!   "ply": 2,
+//!   "position": { "size": 3, "turn": 0, "players": ["B", "R"], "layout": "./B./..R" },
+//!   "to_move": 0,
+//!   "visits": [ { "coords": { "x": 0, "y": 2, "z": 0 }, "visits": 57 }, ... ],
+//!   "played": { "x": 0, "y": 2, "z": 0 },
+//!   "winner": 0,
+//!   "result": 1
+//! }
+//! ```
+//!
+//! - `game`: number of the game in the run, from 0.
+//! - `ply`: number of stones on the board before the move.
+//! - `position`: the board in YEN notation (see [`crate::YEN`]).
+//! - `to_move`: id of the player to move.
+//! - `visits`: playouts the search sent through each move at the root.
+//! - `played`: the move that was actually played (it may come from the noise).
+//! - `winner`: id of the player who won the game.
+//! - `result`: 1 if `to_move` won the game, -1 otherwise.
+//!
+//! # Example
+//!
+//! ```
+//! use gamey::{SelfPlayConfig, run_selfplay};
+//!
+//! let config = SelfPlayConfig::new("mcts_bot?iterations=20", 3).with_games(2);
+//! let mut out = Vec::new();
+//! let summary = run_selfplay(&config, &mut out).unwrap();
+//!
+//! assert_eq!(summary.games, 2);
+//! assert_eq!(String::from_utf8(out).unwrap().lines().count(), summary.positions);
+//! ```
+
+use crate::{Coordinates, GameStatus, GameY, GameYError, MctsBot, Movement, YEN, parse_bot_spec};
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/// Settings of a self-play run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfPlayConfig {
+    /// The MCTS bot spec, e.g. `mcts_bot?iterations=400`; `mcts` is accepted
+    /// as a short name.
+    pub bot: String,
+    /// Size of the board every game is played on.
+    pub size: u32,
+    /// Number of games to play.
+    pub games: u32,
+    /// Seed from which every game derives its randomness.
+    pub seed: u64,
+    /// Moves at the start of each game drawn in proportion to their visits.
+    pub sample_moves: u32,
+    /// Probability of playing a uniformly random move instead of the search's.
+    pub noise: f64,
+}
+
+impl SelfPlayConfig {
+    /// Creates a configuration with 10 games, seed 0, `size` sampled moves
+    /// and a noise of 0.05.
+    pub fn new(bot: impl Into<String>, size: u32) -> Self {
+        SelfPlayConfig {
+            bot: bot.into(),
+            size,
+            games: 10,
+            seed: 0,
+            sample_moves: size,
+            noise: 0.05,
+        }
+    }
+
+    /// Sets the number of games.
+    pub fn with_games(mut self, games: u32) -> Self {
+        self.games = games;
+        self
+    }
+
+    /// Sets the seed of the run.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the number of moves drawn in proportion to their visits.
+    pub fn with_sample_moves(mut self, sample_moves: u32) -> Self {
+        self.sample_moves = sample_moves;
+        self
+    }
+
+    /// Sets the probability of a uniformly random move.
+    pub fn with_noise(mut self, noise: f64) -> Self {
+        self.noise = noise;
+        self
+    }
+}
+
+/// The visits the search gave to one move.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveVisits {
+    /// The move.
+    pub coords: Coordinates,
+    /// Playouts that went through it.
+    pub visits: u32,
+}
+
+/// One position of a self-play game; a line of the output.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SelfPlayPosition {
+    /// Number of the game in the run.
+    pub game: u32,
+    /// Number of stones on the board before the move.
+    pub ply: u32,
+    /// The board before the move.
+    pub position: YEN,
+    /// Id of the player to move.
+    pub to_move: u32,
+    /// Visits of every move at the root of the search.
+    pub visits: Vec<MoveVisits>,
+    /// The move that was played.
+    pub played: Coordinates,
+    /// Id of the winner of the game.
+    pub winner: u32,
+    /// 1 if `to_move` won the game, -1 otherwise.
+    pub result: i8,
+}
+
+/// Totals of a self-play run.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct SelfPlaySummary {
+    /// Games played.
+    pub games: u32,
+    /// Positions written.
+    pub positions: usize,
+    /// Games won by player 0 and by player 1.
+    pub wins: [u32; 2],
+}
+
+/// Plays `config.games` self-play games and writes their positions to `out`
+/// as JSON lines (see the [module documentation](self) for the format).
+///
+/// # Errors
+/// Returns an error if the bot spec is not an MCTS bot or has invalid
+/// parameters, if the board size or the noise are out of range, or if
+/// writing fails.
+pub fn run_selfplay<W: Write>(config: &SelfPlayConfig, out: &mut W) -> Result<SelfPlaySummary, GameYError> {
+    let (name, params) = parse_bot_spec(&config.bot);
+    if name != "mcts" && name != "mcts_bot" {
+        return Err(GameYError::InvalidSelfPlay {
+            message: format!("self-play needs the mcts bot, found {}", name),
+        });
+    }
+    if config.size == 0 {
+        return Err(GameYError::InvalidSelfPlay {
+            message: "the board size must be greater than 0".to_string(),
+        });
+    }
+    if !(0.0..=1.0).contains(&config.noise) {
+        return Err(GameYError::InvalidSelfPlay {
+            message: "noise must be between 0 and 1".to_string(),
+        });
+    }
+    MctsBot::from_params(&params)?;
+
+    let mut summary = SelfPlaySummary {
+        games: 0,
+        positions: 0,
+        wins: [0, 0],
+    };
+    let mut master_rng = StdRng::seed_from_u64(config.seed);
+    for game in 0..config.games {
+        let mut rng = StdRng::seed_from_u64(master_rng.next_u64());
+        // A fresh bot per game, so that no tree is carried across games.
+        let bot = MctsBot::from_params(&params)?;
+        let positions = play_selfplay_game(&bot, config, game, &mut rng)?;
+        for position in &positions {
+            let line = serde_json::to_string(position).map_err(|e| GameYError::SerdeError { error: e })?;
+            writeln!(out, "{}", line).map_err(|e| GameYError::IoError {
+                message: "Failed to write self-play data".to_string(),
+                error: e.to_string(),
+            })?;
+        }
+        if let Some(position) = positions.first() {
+            summary.wins[position.winner as usize] += 1;
+        }
+        summary.games += 1;
+        summary.positions += positions.len();
+    }
+    Ok(summary)
+}
+
+/// Plays one game of `bot` against itself and returns its positions.
+fn play_selfplay_game(
+    bot: &MctsBot,
+    config: &SelfPlayConfig,
+    game_number: u32,
+    rng: &mut StdRng,
+) -> Result<Vec<SelfPlayPosition>, GameYError> {
+    let mut game = GameY::new(config.size);
+    let mut positions: Vec<SelfPlayPosition> = Vec::new();
+    loop {
+        let player = match game.status() {
+            GameStatus::Finished { winner } => {
+                let winner = winner.id();
+                for position in &mut positions {
+                    position.winner = winner;
+                    position.result = if position.to_move == winner { 1 } else { -1 };
+                }
+                return Ok(positions);
+            }
+            GameStatus::Ongoing { next_player } => *next_player,
+        };
+        let ply = game.total_cells() - game.available_cells().len() as u32;
+        let visits = bot.root_visits_with_rng(&game, rng);
+        let played = if rng.random_bool(config.noise) || visits.is_empty() {
+            let cells = game.available_cells();
+            Coordinates::from_index(cells[rng.random_range(0..cells.len())], config.size)
+        } else if ply < config.sample_moves {
+            sample_by_visits(&visits, rng)
+        } else {
+            visits.iter().max_by_key(|&&(_, v)| v).map_or(visits[0].0, |&(coords, _)| coords)
+        };
+        positions.push(SelfPlayPosition {
+            game: game_number,
+            ply,
+            position: YEN::from(&game),
+            to_move: player.id(),
+            visits: visits
+                .iter()
+                .map(|&(coords, visits)| MoveVisits { coords, visits })
+                .collect(),
+            played,
+            winner: 0,
+            result: 0,
+        });
+        game.add_move(Movement::Placement { player, coords: played })?;
+    }
+}
+
+/// Draws a move with probability proportional to its visits.
+fn sample_by_visits(visits: &[(Coordinates, u32)], rng: &mut dyn RngCore) -> Coordinates {
+    let total: u64 = visits.iter().map(|&(_, v)| v as u64).sum();
+    if total == 0 {
+        return visits[0].0;
+    }
+    let mut target = rng.next_u64() % total;
+    for &(coords, v) in visits {
+        if target < v as u64 {
+            return coords;
+        }
+        target -= v as u64;
+    }
+    visits[visits.len() - 1].0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> SelfPlayConfig {
+        SelfPlayConfig::new("mcts_bot?iterations=30", 4).with_games(3).with_seed(7)
+    }
+
+    fn positions(out: &[u8]) -> Vec<SelfPlayPosition> {
+        String::from_utf8(out.to_vec())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_selfplay_writes_every_position() {
+        let mut out = Vec::new();
+        let summary = run_selfplay(&config(), &mut out).unwrap();
+        assert_eq!(summary.games, 3);
+        assert_eq!(summary.wins[0] + summary.wins[1], 3);
+        let positions = positions(&out);
+        assert_eq!(positions.len(), summary.positions);
+        for position in &positions {
+            let game = GameY::try_from(position.position.clone()).unwrap();
+            assert_eq!(position.to_move, position.position.turn());
+            assert!(game.available_cells().contains(&position.played.to_index(4)));
+            // The reused subtree may bring visits from the previous moves.
+            assert!(position.visits.iter().map(|v| v.visits).sum::<u32>() >= 30);
+            let expected = if position.winner == position.to_move { 1 } else { -1 };
+            assert_eq!(position.result, expected);
+        }
+        // Plies count up from 0 within each game.
+        assert_eq!(positions[0].ply, 0);
+        assert_eq!(positions[1].ply, 1);
+    }
+
+    #[test]
+    fn test_selfplay_same_seed_same_data() {
+        let mut first = Vec::new();
+        let mut second = Vec::new();
+        run_selfplay(&config(), &mut first).unwrap();
+        run_selfplay(&config(), &mut second).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_selfplay_short_bot_name() {
+        let config = SelfPlayConfig::new("mcts?iterations=10", 3).with_games(1);
+        assert!(run_selfplay(&config, &mut Vec::new()).is_ok());
+    }
+
+    #[test]
+    fn test_selfplay_rejects_bad_configs() {
+        let not_mcts = SelfPlayConfig::new("random_bot", 4);
+        assert!(matches!(
+            run_selfplay(&not_mcts, &mut Vec::new()),
+            Err(GameYError::InvalidSelfPlay { .. })
+        ));
+        let bad_params = SelfPlayConfig::new("mcts_bot?iterations=0", 4);
+        assert!(run_selfplay(&bad_params, &mut Vec::new()).is_err());
+        let bad_noise = config().with_noise(1.5);
+        assert!(run_selfplay(&bad_noise, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_sample_by_visits_follows_the_counts() {
+        let a = Coordinates::new(1, 0, 0);
+        let b = Coordinates::new(0, 1, 0);
+        let mut rng = StdRng::seed_from_u64(1);
+        let picks_a = (0..1000)
+            .filter(|_| sample_by_visits(&[(a, 9), (b, 1)], &mut rng) == a)
+            .count();
+        assert!((850..950).contains(&picks_a));
+        assert_eq!(sample_by_visits(&[(a, 0), (b, 5)], &mut rng), b);
+    }
+}
@@ -0,0 +1,507 @@
+//! Round-robin tournaments between bots.
+//!
+//! This module plays every pair of bots against each other with
+//! [`play_recorded_game`], alternating colours so that the first-move
+//! advantage is shared, and summarises the results in a [`TournamentReport`]:
+//! a crosstable, win rates with 95% confidence intervals and Elo estimates.
+//! The games themselves can be kept as [`GameRecord`]s, e.g. to build an
+//! opening book with [`crate::OpeningBook::from_records`].
+//!
+//! All the randomness of a tournament comes from its seed, so running the same
+//! [`TournamentConfig`] twice produces the same report.
+//!
+//! # Example
+//!
+//! ```
+//! use gamey::{TournamentConfig, default_registry, run_tournament};
+//!
+//! let config = TournamentConfig::new(vec!["random_bot".into(), "shortest_path_bot".into()], 5)
+//!     .with_games(4)
+//!     .with_seed(42);
+//! let report = run_tournament(&default_registry(), &config).unwrap();
+//!
+//! assert_eq!(report.players.len(), 2);
+//! assert_eq!(report.standings[0].games, 4);
+//! ```
+
+use crate::{GameRecord, GameYError, YBot, YBotRegistry, play_recorded_game};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// z-score of the 95% confidence intervals reported in the standings.
+const Z_95: f64 = 1.96;
+
+/// Number of Bradley-Terry iterations used to estimate Elo ratings.
+const ELO_ITERATIONS: usize = 1000;
+
+/// Settings of a round-robin tournament.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TournamentConfig {
+    /// Bot specs as accepted by [`YBotRegistry::build`], e.g. `monte_carlo_bot?simulations=500`.
+    pub bots: Vec<String>,
+    /// Size of the board every game is played on.
+    pub size: u32,
+    /// Games played by each pair of bots.
+    pub games: u32,
+    /// Seed from which every game derives its randomness.
+    pub seed: u64,
+    /// Whether to keep the moves of every game in [`TournamentReport::records`].
+    pub record_games: bool,
+}
+
+impl TournamentConfig {
+    /// Creates a configuration with 100 games per pairing and seed 0.
+    pub fn new(bots: Vec<String>, size: u32) -> Self {
+        TournamentConfig {
+            bots,
+            size,
+            games: 100,
+            seed: 0,
+            record_games: false,
+        }
+    }
+
+    /// Sets the number of games played by each pair of bots.
+    pub fn with_games(mut self, games: u32) -> Self {
+        self.games = games;
+        self
+    }
+
+    /// Sets the seed of the tournament.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets whether the moves of every game are kept.
+    pub fn with_records(mut self, record_games: bool) -> Self {
+        self.record_games = record_games;
+        self
+    }
+}
+
+/// Summary of the results of one bot.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Standing {
+    /// The bot spec.
+    pub bot: String,
+    /// Games played.
+    pub games: u32,
+    /// Games won.
+    pub wins: u32,
+    /// Fraction of games won.
+    pub win_rate: f64,
+    /// Lower bound of the 95% Wilson interval of the win rate.
+    pub ci_low: f64,
+    /// Upper bound of the 95% Wilson interval of the win rate.
+    pub ci_high: f64,
+    /// Elo rating relative to the field (the ratings average 0).
+    pub elo: f64,
+}
+
+/// Results of a tournament.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TournamentReport {
+    /// Board size the games were played on.
+    pub size: u32,
+    /// Games played by each pair of bots.
+    pub games_per_pairing: u32,
+    /// Seed of the tournament.
+    pub seed: u64,
+    /// Bot specs, in the order used by `crosstable`.
+    pub players: Vec<String>,
+    /// `crosstable[i][j]` is the number of games `players[i]` won against `players[j]`.
+    pub crosstable: Vec<Vec<u32>>,
+    /// One entry per bot, sorted from the highest to the lowest Elo.
+    pub standings: Vec<Standing>,
+    /// The games played, if [`TournamentConfig::record_games`] was set. They
+    /// are not part of the JSON report; see [`TournamentReport::records_to_jsonl`].
+    #[serde(skip)]
+    pub records: Vec<GameRecord>,
+}
+
+impl TournamentReport {
+    /// Builds the report from the crosstable of a finished tournament.
+    pub fn from_crosstable(
+        players: Vec<String>,
+        crosstable: Vec<Vec<u32>>,
+        size: u32,
+        games_per_pairing: u32,
+        seed: u64,
+    ) -> Self {
+        let elos = elo_ratings(&crosstable);
+        let mut standings: Vec<Standing> = players
+            .iter()
+            .enumerate()
+            .map(|(i, bot)| {
+                let wins: u32 = crosstable[i].iter().sum();
+                let games: u32 = (0..players.len())
+                    .map(|j| crosstable[i][j] + crosstable[j][i])
+                    .sum();
+                let win_rate = if games == 0 { 0.0 } else { wins as f64 / games as f64 };
+                let (ci_low, ci_high) = wilson_interval(wins, games);
+                Standing {
+                    bot: bot.clone(),
+                    games,
+                    wins,
+                    win_rate,
+                    ci_low,
+                    ci_high,
+                    elo: elos[i],
+                }
+            })
+            .collect();
+        standings.sort_by(|a, b| b.elo.total_cmp(&a.elo));
+
+        TournamentReport {
+            size,
+            games_per_pairing,
+            seed,
+            players,
+            crosstable,
+            standings,
+            records: Vec::new(),
+        }
+    }
+
+    /// Serializes the report as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, GameYError> {
+        serde_json::to_string_pretty(self).map_err(|e| GameYError::SerdeError { error: e })
+    }
+
+    /// Serializes the game records as JSON lines, one game per line.
+    pub fn records_to_jsonl(&self) -> Result<String, GameYError> {
+        let mut lines = String::new();
+        for record in &self.records {
+            lines += &serde_json::to_string(record).map_err(|e| GameYError::SerdeError { error: e })?;
+            lines.push('\n');
+        }
+        Ok(lines)
+    }
+
+    /// Serializes the standings as CSV, one row per bot.
+    ///
+    /// Besides the summary columns, there is a `vs_<bot>` column per opponent
+    /// holding the games won against it.
+    pub fn to_csv(&self) -> String {
+        let mut header = vec![
+            "bot".to_string(),
+            "games".to_string(),
+            "wins".to_string(),
+            "win_rate".to_string(),
+            "ci_low".to_string(),
+            "ci_high".to_string(),
+            "elo".to_string(),
+        ];
+        header.extend(self.players.iter().map(|p| csv_field(&format!("vs_{}", p))));
+
+        let mut lines = vec![header.join(",")];
+        for standing in &self.standings {
+            let row = self
+                .players
+                .iter()
+                .position(|p| *p == standing.bot)
+                .expect("every standing belongs to a player");
+            let mut fields = vec![
+                csv_field(&standing.bot),
+                standing.games.to_string(),
+                standing.wins.to_string(),
+                format!("{:.4}", standing.win_rate),
+                format!("{:.4}", standing.ci_low),
+                format!("{:.4}", standing.ci_high),
+                format!("{:.1}", standing.elo),
+            ];
+            fields.extend(self.crosstable[row].iter().map(|wins| wins.to_string()));
+            lines.push(fields.join(","));
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+/// Plays a round-robin tournament between the bots of `config`.
+///
+/// Every pair of bots plays `config.games` games; the bot listed first moves
+/// first in the even games and second in the odd ones. Each game gets its own
+/// seed drawn from `config.seed`.
+///
+/// # Errors
+/// Returns an error if fewer than two distinct bots are given, if a bot spec
+/// cannot be built from `registry`, or if a bot plays an invalid move.
+pub fn run_tournament(
+    registry: &YBotRegistry,
+    config: &TournamentConfig,
+) -> Result<TournamentReport, GameYError> {
+    if config.bots.len() < 2 {
+        return Err(GameYError::InvalidTournament {
+            message: "at least two bots are needed".to_string(),
+        });
+    }
+    if let Some(duplicate) = config
+        .bots
+        .iter()
+        .enumerate()
+        .find_map(|(i, bot)| config.bots[..i].contains(bot).then_some(bot))
+    {
+        return Err(GameYError::InvalidTournament {
+            message: format!("bot {} is listed more than once", duplicate),
+        });
+    }
+    let bots = config
+        .bots
+        .iter()
+        .map(|spec| registry.build(spec))
+        .collect::<Result<Vec<Arc<dyn YBot>>, _>>()?;
+
+    let n = bots.len();
+    let mut crosstable = vec![vec![0u32; n]; n];
+    let mut records = Vec::new();
+    let mut master_rng = StdRng::seed_from_u64(config.seed);
+    for i in 0..n {
+        for j in (i + 1)..n {
+            for game in 0..config.games {
+                // Alternate colours so that the first-move advantage is shared.
+                let seats = if game % 2 == 0 { [i, j] } else { [j, i] };
+                let mut rng = StdRng::seed_from_u64(master_rng.next_u64());
+                let mut record = play_recorded_game(
+                    [bots[seats[0]].as_ref(), bots[seats[1]].as_ref()],
+                    config.size,
+                    &mut rng,
+                )?;
+                let winner_seat = record.winner as usize;
+                crosstable[seats[winner_seat]][seats[1 - winner_seat]] += 1;
+                if config.record_games {
+                    record.players = [config.bots[seats[0]].clone(), config.bots[seats[1]].clone()];
+                    records.push(record);
+                }
+            }
+        }
+    }
+
+    let mut report = TournamentReport::from_crosstable(
+        config.bots.clone(),
+        crosstable,
+        config.size,
+        config.games,
+        config.seed,
+    );
+    report.records = records;
+    Ok(report)
+}
+
+/// Returns the 95% Wilson score interval for `wins` successes out of `games`.
+///
+/// With no games played the interval is the whole `[0, 1]` range.
+pub fn wilson_interval(wins: u32, games: u32) -> (f64, f64) {
+    if games == 0 {
+        return (0.0, 1.0);
+    }
+    let n = games as f64;
+    let p = wins as f64 / n;
+    let z2 = Z_95 * Z_95;
+    let denominator = 1.0 + z2 / n;
+    let center = (p + z2 / (2.0 * n)) / denominator;
+    let half_width = Z_95 * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / denominator;
+    ((center - half_width).max(0.0), (center + half_width).min(1.0))
+}
+
+/// Estimates Elo ratings from a crosstable with a Bradley-Terry model.
+///
+/// Each pair that played is given half a virtual win each way, so that bots
+/// that won (or lost) every game still get a finite rating. The ratings are
+/// shifted to average 0.
+pub fn elo_ratings(crosstable: &[Vec<u32>]) -> Vec<f64> {
+    let n = crosstable.len();
+    let played = |i: usize, j: usize| crosstable[i][j] + crosstable[j][i] > 0;
+    let wins = |i: usize, j: usize| {
+        if played(i, j) { crosstable[i][j] as f64 + 0.5 } else { 0.0 }
+    };
+
+    // Minorization-maximization updates of the Bradley-Terry strengths.
+    let mut strengths = vec![1.0; n];
+    for _ in 0..ELO_ITERATIONS {
+        let mut next = strengths.clone();
+        for i in 0..n {
+            let total_wins: f64 = (0..n).map(|j| wins(i, j)).sum();
+            let denominator: f64 = (0..n)
+                .filter(|&j| j != i && played(i, j))
+                .map(|j| (wins(i, j) + wins(j, i)) / (strengths[i] + strengths[j]))
+                .sum();
+            if denominator > 0.0 {
+                next[i] = total_wins / denominator;
+            }
+        }
+        // Normalize to a geometric mean of 1 so the ratings average 0.
+        let mean_log = next.iter().map(|s: &f64| s.ln()).sum::<f64>() / n as f64;
+        for s in next.iter_mut() {
+            *s /= mean_log.exp();
+        }
+        strengths = next;
+    }
+
+    strengths.iter().map(|s| 400.0 * s.log10()).collect()
+}
+
+/// Quotes a CSV field if it contains a separator or a quote.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_registry;
+
+    fn config(bots: &[&str]) -> TournamentConfig {
+        TournamentConfig::new(bots.iter().map(|b| b.to_string()).collect(), 4)
+            .with_games(6)
+            .with_seed(11)
+    }
+
+    #[test]
+    fn test_wilson_interval_contains_the_win_rate() {
+        let (low, high) = wilson_interval(30, 100);
+        assert!(low < 0.3 && 0.3 < high);
+        assert!((low - 0.2189).abs() < 1e-3);
+        assert!((high - 0.3958).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_wilson_interval_edges() {
+        assert_eq!(wilson_interval(0, 0), (0.0, 1.0));
+        let (low, high) = wilson_interval(10, 10);
+        assert!(low > 0.6);
+        assert_eq!(high, 1.0);
+        let (low, _) = wilson_interval(0, 10);
+        assert_eq!(low, 0.0);
+    }
+
+    #[test]
+    fn test_elo_ratings_even_match_is_zero() {
+        let elos = elo_ratings(&[vec![0, 5], vec![5, 0]]);
+        assert!(elos.iter().all(|e| e.abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_elo_ratings_are_symmetric_and_ordered() {
+        let elos = elo_ratings(&[vec![0, 75], vec![25, 0]]);
+        assert!((elos[0] + elos[1]).abs() < 1e-6);
+        // 75.5 / 25.5 wins gives a difference of about 189 Elo.
+        assert!((elos[0] - elos[1] - 400.0 * (75.5f64 / 25.5).log10()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_elo_ratings_are_finite_for_a_clean_sweep() {
+        let elos = elo_ratings(&[vec![0, 10, 10], vec![0, 0, 10], vec![0, 0, 0]]);
+        assert!(elos.iter().all(|e| e.is_finite()));
+        assert!(elos[0] > elos[1] && elos[1] > elos[2]);
+    }
+
+    #[test]
+    fn test_run_tournament_counts_every_game() {
+        let report = run_tournament(
+            &default_registry(),
+            &config(&["random_bot", "shortest_path_bot", "resistance_bot"]),
+        )
+        .unwrap();
+        assert_eq!(report.players.len(), 3);
+        let total: u32 = report.crosstable.iter().flatten().sum();
+        assert_eq!(total, 3 * 6);
+        for standing in &report.standings {
+            assert_eq!(standing.games, 12);
+            assert!(standing.ci_low <= standing.win_rate && standing.win_rate <= standing.ci_high);
+        }
+        assert!(report.standings.windows(2).all(|w| w[0].elo >= w[1].elo));
+    }
+
+    #[test]
+    fn test_run_tournament_same_seed_same_report() {
+        let registry = default_registry();
+        let config = TournamentConfig::new(vec!["random_bot".into(), "group_expansion_bot".into()], 5)
+            .with_games(10)
+            .with_seed(3);
+        let first = run_tournament(&registry, &config).unwrap();
+        let second = run_tournament(&registry, &config).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_run_tournament_records_games() {
+        let registry = default_registry();
+        let report = run_tournament(&registry, &config(&["random_bot", "group_expansion_bot"])).unwrap();
+        assert!(report.records.is_empty());
+
+        let report = run_tournament(
+            &registry,
+            &config(&["random_bot", "group_expansion_bot"]).with_records(true),
+        )
+        .unwrap();
+        assert_eq!(report.records.len(), 6);
+        assert_eq!(report.records[1].players, ["group_expansion_bot".to_string(), "random_bot".to_string()]);
+        assert_eq!(report.records_to_jsonl().unwrap().lines().count(), 6);
+        assert!(!report.to_json().unwrap().contains("records"));
+    }
+
+    #[test]
+    fn test_run_tournament_compares_bot_variants() {
+        let plain = "mcts_bot?iterations=20&endgame_cells=0";
+        let rave = "mcts_bot?iterations=20&endgame_cells=0&rave=true";
+        let report = run_tournament(&default_registry(), &config(&[plain, rave])).unwrap();
+        assert_eq!(report.players, vec![plain.to_string(), rave.to_string()]);
+        assert_eq!(report.crosstable[0][1] + report.crosstable[1][0], 6);
+    }
+
+    #[test]
+    fn test_run_tournament_needs_two_distinct_bots() {
+        let registry = default_registry();
+        assert!(matches!(
+            run_tournament(&registry, &config(&["random_bot"])),
+            Err(GameYError::InvalidTournament { .. })
+        ));
+        assert!(matches!(
+            run_tournament(&registry, &config(&["random_bot", "random_bot"])),
+            Err(GameYError::InvalidTournament { .. })
+        ));
+    }
+
+    #[test]
+    fn test_run_tournament_unknown_bot() {
+        let result = run_tournament(&default_registry(), &config(&["random_bot", "nope_bot"]));
+        assert!(matches!(result, Err(GameYError::BotNotFound { .. })));
+    }
+
+    #[test]
+    fn test_report_csv_and_json() {
+        let report = TournamentReport::from_crosstable(
+            vec!["a".into(), "b".into()],
+            vec![vec![0, 3], vec![1, 0]],
+            5,
+            4,
+            0,
+        );
+        let csv = report.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("bot,games,wins,win_rate,ci_low,ci_high,elo,vs_a,vs_b")
+        );
+        assert!(lines.next().unwrap().starts_with("a,4,3,0.7500,"));
+        assert!(lines.next().unwrap().starts_with("b,4,1,0.2500,"));
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["crosstable"][0][1], 3);
+        assert_eq!(json["standings"][0]["bot"], "a");
+    }
+
+    #[test]
+    fn test_csv_field_quotes_separators() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,466 @@
+//! Automatic weight tuning for the heuristic bots.
+//!
+//! This module tunes the integer weights of a configurable bot with SPSA
+//! (simultaneous perturbation stochastic approximation): on every iteration
+//! all the weights are nudged at once in a random direction, the bot with the
+//! weights moved one way plays the bot with the weights moved the other way,
+//! and the weights take a step towards the side that won more games. Each
+//! iteration costs [`TuningConfig::games`] games whatever the number of
+//! weights, which keeps the search cheap enough to run on a laptop.
+//!
+//! [`TuningConfig::preset`] knows the weights of [`crate::ShortestPathBot`],
+//! [`crate::PriorityBlockBot`] and [`crate::TriangleAttackBot`], starting
+//! from their defaults. After the search the tuned weights play the starting
+//! ones in [`TuningConfig::validation_games`] games, so that the report says
+//! whether the new weights are actually better.
+//!
+//! All the randomness comes from [`TuningConfig::seed`], so the same
+//! configuration produces the same report.
+//!
+//! # Example
+//!
+//! ```
+//! use gamey::{TuningConfig, default_registry, run_tuning};
+//!
+//! let config = TuningConfig::preset("priority_block_bot", 4)
+//!     .unwrap()
+//!     .with_iterations(2)
+//!     .with_games(2)
+//!     .with_validation_games(2);
+//! let report = run_tuning(&default_registry(), &config).unwrap();
+//!
+//! assert_eq!(report.history.len(), 2);
+//! assert!(report.spec.starts_with("priority_block_bot?"));
+//! ```
+
+use crate::{
+    GameYError, PriorityBlockConfig, ShortestPathConfig, TriangleAttackConfig, YBot, YBotRegistry,
+    play_game_with_rng,
+};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Decay exponent of the SPSA step size (the value suggested by Spall).
+const STEP_DECAY: f64 = 0.602;
+
+/// Decay exponent of the SPSA perturbation size (the value suggested by Spall).
+const PERTURBATION_DECAY: f64 = 0.101;
+
+/// One weight to tune.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TunedParam {
+    /// Name of the parameter in the bot's configuration.
+    pub name: String,
+    /// Starting value.
+    pub start: i64,
+    /// Smallest value allowed.
+    pub min: i64,
+    /// Largest value allowed.
+    pub max: i64,
+    /// How much the weight moves in one perturbation at the start of the
+    /// search; weights on different scales need different steps.
+    pub step: f64,
+}
+
+impl TunedParam {
+    /// Creates a parameter starting at `start` and allowed in `min..=max`.
+    pub fn new(name: &str, start: i64, min: i64, max: i64, step: f64) -> Self {
+        TunedParam {
+            name: name.to_string(),
+            start,
+            min,
+            max,
+            step,
+        }
+    }
+}
+
+/// Settings of a tuning run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningConfig {
+    /// Registry name of the bot, e.g. `shortest_path_bot`.
+    pub bot: String,
+    /// The weights to tune.
+    pub params: Vec<TunedParam>,
+    /// Size of the board every game is played on.
+    pub size: u32,
+    /// Number of SPSA iterations.
+    pub iterations: u32,
+    /// Games between the two perturbed bots on each iteration.
+    pub games: u32,
+    /// Games between the tuned and the starting weights at the end.
+    pub validation_games: u32,
+    /// Step size of the first iteration, in units of [`TunedParam::step`].
+    pub learning_rate: f64,
+    /// Seed from which every game derives its randomness.
+    pub seed: u64,
+}
+
+impl TuningConfig {
+    /// Creates a configuration with 50 iterations of 20 games, 100
+    /// validation games, a learning rate of 2 and seed 0.
+    pub fn new(bot: &str, params: Vec<TunedParam>, size: u32) -> Self {
+        TuningConfig {
+            bot: bot.to_string(),
+            params,
+            size,
+            iterations: 50,
+            games: 20,
+            validation_games: 100,
+            learning_rate: 2.0,
+            seed: 0,
+        }
+    }
+
+    /// Returns the configuration that tunes every weight of `bot`, starting
+    /// from its defaults, or `None` if `bot` has no known weights.
+    pub fn preset(bot: &str, size: u32) -> Option<Self> {
+        let params = match bot {
+            "shortest_path_bot" => {
+                let defaults = ShortestPathConfig::default();
+                vec![
+                    TunedParam::new("attack_weight", defaults.attack_weight, 0, 100, 3.0),
+                    TunedParam::new("block_weight", defaults.block_weight, 0, 100, 3.0),
+                ]
+            }
+            "priority_block_bot" => {
+                let defaults = PriorityBlockConfig::default();
+                vec![
+                    TunedParam::new("ally_weight", defaults.ally_weight.into(), 0, 20, 1.0),
+                    TunedParam::new("enemy_weight", defaults.enemy_weight.into(), 0, 20, 1.0),
+                    TunedParam::new("edge_block_bonus", defaults.edge_block_bonus.into(), 0, 20, 1.0),
+                ]
+            }
+            "triangle_attack_bot" => {
+                let defaults = TriangleAttackConfig::default();
+                vec![
+                    TunedParam::new("neighbor_weight", defaults.neighbor_weight.into(), 0, 20, 1.0),
+                    TunedParam::new("junction_bonus", defaults.junction_bonus.into(), 0, 20, 1.0),
+                    TunedParam::new("edge_weight", defaults.edge_weight.into(), 0, 20, 1.0),
+                    TunedParam::new("near_edge_weight", defaults.near_edge_weight.into(), 0, 20, 1.0),
+                    TunedParam::new("isolation_penalty", defaults.isolation_penalty.into(), 0, 20, 1.0),
+                ]
+            }
+            _ => return None,
+        };
+        Some(TuningConfig::new(bot, params, size))
+    }
+
+    /// Sets the number of SPSA iterations.
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Sets the games played on each iteration.
+    pub fn with_games(mut self, games: u32) -> Self {
+        self.games = games;
+        self
+    }
+
+    /// Sets the games played between the tuned and the starting weights.
+    pub fn with_validation_games(mut self, validation_games: u32) -> Self {
+        self.validation_games = validation_games;
+        self
+    }
+
+    /// Sets the step size of the first iteration.
+    pub fn with_learning_rate(mut self, learning_rate: f64) -> Self {
+        self.learning_rate = learning_rate;
+        self
+    }
+
+    /// Sets the seed of the run.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+/// What happened on one SPSA iteration.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TuningStep {
+    /// Number of the iteration, from 0.
+    pub iteration: u32,
+    /// Weights of the bot perturbed one way.
+    pub plus: BTreeMap<String, i64>,
+    /// Weights of the bot perturbed the other way.
+    pub minus: BTreeMap<String, i64>,
+    /// Games won by `plus` minus games won by `minus`, over the games played.
+    pub score: f64,
+    /// The weights after the iteration.
+    pub weights: BTreeMap<String, i64>,
+}
+
+/// How the tuned weights did against the starting ones.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TuningValidation {
+    /// Games played.
+    pub games: u32,
+    /// Games won by the tuned weights.
+    pub wins: u32,
+    /// Share of the games won by the tuned weights.
+    pub win_rate: f64,
+    /// 95% Wilson interval of the win rate.
+    pub ci_low: f64,
+    /// Upper end of the interval.
+    pub ci_high: f64,
+}
+
+/// The result of [`run_tuning`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TuningReport {
+    /// Registry name of the tuned bot.
+    pub bot: String,
+    /// Board size of the games.
+    pub size: u32,
+    /// Seed of the run.
+    pub seed: u64,
+    /// The starting weights.
+    pub start: BTreeMap<String, i64>,
+    /// The tuned weights.
+    pub tuned: BTreeMap<String, i64>,
+    /// The tuned bot as a spec for [`YBotRegistry::build`].
+    pub spec: String,
+    /// Every iteration of the search.
+    pub history: Vec<TuningStep>,
+    /// The tuned weights against the starting ones, if validation games were played.
+    pub validation: Option<TuningValidation>,
+}
+
+impl TuningReport {
+    /// Serializes the report as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, GameYError> {
+        serde_json::to_string_pretty(self).map_err(|e| GameYError::SerdeError { error: e })
+    }
+}
+
+/// Tunes the weights of `config.bot` with SPSA (see the [module documentation](self)).
+///
+/// # Errors
+/// Returns an error if there is nothing to tune, if a starting value is out
+/// of its range, if no games are played per iteration, or if the bot cannot be
+/// built from `registry` with the weights.
+pub fn run_tuning(registry: &YBotRegistry, config: &TuningConfig) -> Result<TuningReport, GameYError> {
+    validate(config)?;
+    let params = &config.params;
+    let start: Vec<i64> = params.iter().map(|p| p.start).collect();
+    // Work in units of each parameter's step so that all weights move alike.
+    let mut theta: Vec<f64> = params.iter().map(|p| p.start as f64 / p.step).collect();
+    let stability = config.iterations as f64 / 10.0;
+    let mut master_rng = StdRng::seed_from_u64(config.seed);
+    let mut history = Vec::new();
+
+    for iteration in 0..config.iterations {
+        let k = iteration as f64 + 1.0;
+        let step = config.learning_rate / (k + stability).powf(STEP_DECAY);
+        let perturbation = 1.0 / k.powf(PERTURBATION_DECAY);
+        let delta: Vec<f64> = params
+            .iter()
+            .map(|_| if master_rng.next_u32() % 2 == 0 { 1.0 } else { -1.0 })
+            .collect();
+        let plus = weights(params, &theta, &delta, perturbation);
+        let minus = weights(params, &theta, &delta, -perturbation);
+        let plus_bot = build(registry, config, &plus)?;
+        let minus_bot = build(registry, config, &minus)?;
+        let wins = play_match(&plus_bot, &minus_bot, config.size, config.games, master_rng.next_u64())?;
+        let score = (2.0 * wins as f64 - config.games as f64) / config.games as f64;
+        for ((value, d), param) in theta.iter_mut().zip(&delta).zip(params) {
+            let next = *value + step * score / (2.0 * perturbation) * d;
+            *value = next.clamp(param.min as f64 / param.step, param.max as f64 / param.step);
+        }
+        history.push(TuningStep {
+            iteration,
+            plus: named(params, &plus),
+            minus: named(params, &minus),
+            score,
+            weights: named(params, &weights(params, &theta, &delta, 0.0)),
+        });
+    }
+
+    let tuned = weights(params, &theta, &vec![0.0; params.len()], 0.0);
+    let validation = if config.validation_games > 0 {
+        let tuned_bot = build(registry, config, &tuned)?;
+        let start_bot = build(registry, config, &start)?;
+        let games = config.validation_games;
+        let wins = play_match(&tuned_bot, &start_bot, config.size, games, master_rng.next_u64())?;
+        let (ci_low, ci_high) = crate::wilson_interval(wins, games);
+        Some(TuningValidation {
+            games,
+            wins,
+            win_rate: wins as f64 / games as f64,
+            ci_low,
+            ci_high,
+        })
+    } else {
+        None
+    };
+
+    Ok(TuningReport {
+        bot: config.bot.clone(),
+        size: config.size,
+        seed: config.seed,
+        start: named(params, &start),
+        spec: spec(&config.bot, params, &tuned),
+        tuned: named(params, &tuned),
+        history,
+        validation,
+    })
+}
+
+fn validate(config: &TuningConfig) -> Result<(), GameYError> {
+    let invalid = |message: String| Err(GameYError::InvalidTuning { message });
+    if config.params.is_empty() {
+        return invalid(format!("no weights to tune for {}", config.bot));
+    }
+    if config.games == 0 {
+        return invalid("at least one game per iteration is needed".to_string());
+    }
+    if !(config.learning_rate > 0.0 && config.learning_rate.is_finite()) {
+        return invalid("the learning rate must be a positive number".to_string());
+    }
+    for param in &config.params {
+        if !(param.min..=param.max).contains(&param.start) {
+            return invalid(format!(
+                "{} starts at {}, outside {}..={}",
+                param.name, param.start, param.min, param.max
+            ));
+        }
+        if !(param.step > 0.0 && param.step.is_finite()) {
+            return invalid(format!("the step of {} must be a positive number", param.name));
+        }
+    }
+    Ok(())
+}
+
+/// Rounds `theta + offset * delta` back to weights within their ranges.
+fn weights(params: &[TunedParam], theta: &[f64], delta: &[f64], offset: f64) -> Vec<i64> {
+    params
+        .iter()
+        .zip(theta.iter().zip(delta))
+        .map(|(param, (value, d))| (((value + offset * d) * param.step).round() as i64).clamp(param.min, param.max))
+        .collect()
+}
+
+fn named(params: &[TunedParam], values: &[i64]) -> BTreeMap<String, i64> {
+    params.iter().map(|p| p.name.clone()).zip(values.iter().copied()).collect()
+}
+
+fn spec(bot: &str, params: &[TunedParam], values: &[i64]) -> String {
+    let query: Vec<String> = params
+        .iter()
+        .zip(values)
+        .map(|(param, value)| format!("{}={}", param.name, value))
+        .collect();
+    format!("{}?{}", bot, query.join("&"))
+}
+
+fn build(registry: &YBotRegistry, config: &TuningConfig, values: &[i64]) -> Result<Arc<dyn YBot>, GameYError> {
+    registry.build(&spec(&config.bot, &config.params, values))
+}
+
+/// Plays `games` games alternating colours and returns the wins of `first`.
+fn play_match(
+    first: &Arc<dyn YBot>,
+    second: &Arc<dyn YBot>,
+    size: u32,
+    games: u32,
+    seed: u64,
+) -> Result<u32, GameYError> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut wins = 0;
+    for game in 0..games {
+        let seats = if game % 2 == 0 { [first, second] } else { [second, first] };
+        let winner = play_game_with_rng([seats[0].as_ref(), seats[1].as_ref()], size, &mut rng)?;
+        let first_seat = if game % 2 == 0 { 0 } else { 1 };
+        if winner.id() == first_seat {
+            wins += 1;
+        }
+    }
+    Ok(wins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::default_registry;
+
+    fn quick(bot: &str) -> TuningConfig {
+        TuningConfig::preset(bot, 4)
+            .unwrap()
+            .with_iterations(3)
+            .with_games(4)
+            .with_validation_games(4)
+            .with_seed(3)
+    }
+
+    #[test]
+    fn test_presets_start_from_the_defaults() {
+        let config = TuningConfig::preset("shortest_path_bot", 7).unwrap();
+        let starts: Vec<i64> = config.params.iter().map(|p| p.start).collect();
+        assert_eq!(starts, vec![10, 15]);
+        assert_eq!(TuningConfig::preset("triangle_attack_bot", 7).unwrap().params.len(), 5);
+        assert!(TuningConfig::preset("random_bot", 7).is_none());
+    }
+
+    #[test]
+    fn test_run_tuning_reports_every_iteration() {
+        let report = run_tuning(&default_registry(), &quick("priority_block_bot")).unwrap();
+        assert_eq!(report.history.len(), 3);
+        assert_eq!(report.start.get("enemy_weight"), Some(&2));
+        for step in &report.history {
+            assert!((-1.0..=1.0).contains(&step.score));
+            assert!(step.weights.values().all(|v| (0..=20).contains(v)));
+        }
+        assert_eq!(&report.history[2].weights, &report.tuned);
+        let validation = report.validation.unwrap();
+        assert_eq!(validation.games, 4);
+        assert!(validation.ci_low <= validation.win_rate && validation.win_rate <= validation.ci_high);
+        // The tuned spec builds a bot.
+        assert!(default_registry().build(&report.spec).is_ok());
+    }
+
+    #[test]
+    fn test_run_tuning_same_seed_same_report() {
+        let first = run_tuning(&default_registry(), &quick("triangle_attack_bot")).unwrap();
+        let second = run_tuning(&default_registry(), &quick("triangle_attack_bot")).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_run_tuning_without_validation() {
+        let config = quick("shortest_path_bot").with_validation_games(0).with_iterations(1);
+        let report = run_tuning(&default_registry(), &config).unwrap();
+        assert!(report.validation.is_none());
+        assert!(report.to_json().unwrap().contains("\"tuned\""));
+    }
+
+    #[test]
+    fn test_run_tuning_rejects_bad_configs() {
+        let registry = default_registry();
+        let empty = TuningConfig::new("random_bot", Vec::new(), 4);
+        assert!(matches!(run_tuning(&registry, &empty), Err(GameYError::InvalidTuning { .. })));
+        assert!(run_tuning(&registry, &quick("priority_block_bot").with_games(0)).is_err());
+        assert!(run_tuning(&registry, &quick("priority_block_bot").with_learning_rate(0.0)).is_err());
+        let out_of_range = TuningConfig::new(
+            "priority_block_bot",
+            vec![TunedParam::new("ally_weight", 50, 0, 20, 1.0)],
+            4,
+        );
+        assert!(run_tuning(&registry, &out_of_range).is_err());
+        let unknown = TuningConfig::new("priority_block_bot", vec![TunedParam::new("speed", 1, 0, 2, 1.0)], 4)
+            .with_iterations(1);
+        assert!(matches!(run_tuning(&registry, &unknown), Err(GameYError::InvalidBotParams { .. })));
+    }
+
+    #[test]
+    fn test_weights_round_and_clamp() {
+        let params = vec![TunedParam::new("a", 10, 0, 12, 3.0)];
+        assert_eq!(weights(&params, &[10.0 / 3.0], &[1.0], 0.0), vec![10]);
+        assert_eq!(weights(&params, &[10.0 / 3.0], &[1.0], 1.0), vec![12]);
+        assert_eq!(weights(&params, &[10.0 / 3.0], &[-1.0], 1.0), vec![7]);
+    }
+}
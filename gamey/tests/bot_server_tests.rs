@@ -2,7 +2,8 @@ use axum::{
     body::Body,
     http::{Request, StatusCode},
 };
-use gamey::{YBotRegistry, YEN, create_default_state, create_router, state::AppState, RandomBot, MoveResponse, ErrorResponse};
+use futures_util::{SinkExt, StreamExt};
+use gamey::{YBotRegistry, YEN, create_default_state, create_router, state::AppState, RandomBot, MoveResponse, ErrorCode, ErrorResponse, AnalyzeResponse, SolveResponse, GameResponse, SessionStatus, GameEvent, EndReason, BotCatalogueResponse, Difficulty, BotMoveResponse, BotDecision, VersionsResponse, BatchMove, ChooseBatchResponse, ComputeLimits, ServerConfig, GameY, PlayerId, ShortestPathBot, YBot};
 use http_body_util::BodyExt;
 use std::sync::Arc;
 use tower::ServiceExt;
@@ -117,7 +118,7 @@ async fn test_choose_endpoint_with_invalid_api_version() {
         .oneshot(
             Request::builder()
                 .method("POST")
-                .uri("/v2/ybot/choose/random_bot") // v2 is not supported
+                .uri("/v3/ybot/choose/random_bot") // v3 is not supported
                 .header("content-type", "application/json")
                 .body(Body::from(serde_json::to_string(&yen).unwrap()))
                 .unwrap(),
@@ -131,7 +132,8 @@ async fn test_choose_endpoint_with_invalid_api_version() {
     let error_response: ErrorResponse = serde_json::from_slice(&body).unwrap();
 
     assert!(error_response.message.contains("Unsupported API version"));
-    assert_eq!(error_response.api_version, Some("v2".to_string()));
+    assert_eq!(error_response.code, ErrorCode::UnsupportedVersion);
+    assert_eq!(error_response.api_version, Some("v3".to_string()));
 }
 
 #[tokio::test]
@@ -159,6 +161,7 @@ async fn test_choose_endpoint_with_unknown_bot() {
 
     assert!(error_response.message.contains("Bot not found"));
     assert!(error_response.message.contains("unknown_bot"));
+    assert_eq!(error_response.code, ErrorCode::BotNotFound);
     assert_eq!(error_response.bot_id, Some("unknown_bot".to_string()));
 }
 
@@ -204,6 +207,1043 @@ async fn test_choose_endpoint_with_missing_content_type() {
     assert!(response.status().is_client_error());
 }
 
+#[tokio::test]
+async fn test_choose_endpoint_with_bot_params() {
+    let app = test_app();
+
+    let body = serde_json::json!({
+        "size": 3,
+        "turn": 0,
+        "players": ["B", "R"],
+        "layout": "./../...",
+        "params": { "simulations": 5 }
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/ybot/choose/monte_carlo_bot")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let move_response: MoveResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(move_response.bot_id, "monte_carlo_bot");
+}
+
+#[tokio::test]
+async fn test_choose_endpoint_with_invalid_bot_params() {
+    let app = test_app();
+
+    let body = serde_json::json!({
+        "size": 3,
+        "turn": 0,
+        "players": ["B", "R"],
+        "layout": "./../...",
+        "params": { "unknown_param": 1 }
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/ybot/choose/shortest_path_bot")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let error_response: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert!(error_response.message.contains("unknown_param"));
+    assert_eq!(error_response.code, ErrorCode::InvalidBotParams);
+}
+
+#[tokio::test]
+async fn test_choose_endpoint_swaps_under_pie_rule() {
+    let app = test_app();
+
+    // Player B opened in the centre of a size 5 board
+    let body = serde_json::json!({
+        "size": 5,
+        "turn": 1,
+        "players": ["B", "R"],
+        "layout": "./../.B./..../.....",
+        "pie_rule": true
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/ybot/choose/shortest_path_bot")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["action"], "swap");
+    assert!(json.get("coords").is_none());
+    assert_eq!(json["game_status"], "ongoing");
+}
+
+// ============================================================================
+// Catalogue endpoint tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_catalogue_lists_every_bot() {
+    let app = test_app();
+
+    let (status, body) = send_json(&app, "GET", "/v1/ybot", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let catalogue: BotCatalogueResponse = serde_json::from_slice(&body).unwrap();
+    let mut expected = create_default_state().bots().names();
+    expected.sort();
+    let ids: Vec<String> = catalogue.bots.iter().map(|bot| bot.id.clone()).collect();
+    assert_eq!(ids, expected);
+    assert!(catalogue.bots.iter().all(|bot| !bot.description.is_empty() && bot.difficulty.is_some()));
+
+    let monte_carlo = catalogue.bots.iter().find(|bot| bot.id == "monte_carlo_bot").unwrap();
+    assert_eq!(monte_carlo.difficulty, Some(Difficulty::Hard));
+    assert!(monte_carlo.params.iter().any(|param| param.name == "simulations"));
+    let random = catalogue.bots.iter().find(|bot| bot.id == "random_bot").unwrap();
+    assert!(random.params.is_empty());
+
+    let (status, _) = send_json(&app, "GET", "/v3/ybot", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+// ============================================================================
+// Analyze endpoint tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_analyze_endpoint_ranks_candidates() {
+    let app = test_app();
+
+    let yen = YEN::new(4, 0, vec!['B', 'R'], "./../.../....".to_string());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/ybot/analyze/shortest_path_bot")
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_string(&yen).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let analyze_response: AnalyzeResponse = serde_json::from_slice(&body).unwrap();
+    let analysis = analyze_response.analysis;
+    assert_eq!(analyze_response.bot_id, "shortest_path_bot");
+    assert_eq!(analysis.candidates.len(), 10);
+    assert!(analysis.candidates.windows(2).all(|w| w[0].score >= w[1].score));
+    assert_eq!(analysis.principal_variation[0], analysis.candidates[0].coords);
+}
+
+#[tokio::test]
+async fn test_analyze_endpoint_with_limit_and_params() {
+    let app = test_app();
+
+    let body = serde_json::json!({
+        "size": 3,
+        "turn": 0,
+        "players": ["B", "R"],
+        "layout": "./../...",
+        "params": { "simulations": 4 }
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/ybot/analyze/monte_carlo_bot?limit=2")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let analyze_response: AnalyzeResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(analyze_response.analysis.candidates.len(), 2);
+    assert!(analyze_response.analysis.candidates.iter().all(|c| c.win_rate.is_some()));
+}
+
+#[tokio::test]
+async fn test_analyze_endpoint_with_bot_player() {
+    // The layout gives the turn to R
+    let layout = "./B./.../....";
+    let analyze = |bot_player: Option<u32>| async move {
+        let mut body = serde_json::json!({ "size": 4, "turn": 1, "players": ["B", "R"], "layout": layout });
+        if let Some(player) = bot_player {
+            body["bot_player"] = serde_json::json!(player);
+        }
+        let response = test_app()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/ybot/analyze/shortest_path_bot")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice::<AnalyzeResponse>(&body).unwrap().analysis
+    };
+
+    let mut game = GameY::try_from(YEN::new(4, 1, vec!['B', 'R'], layout.to_string())).unwrap();
+    game.set_next_player(PlayerId::new(0));
+    let expected = ShortestPathBot::default().analyze(&game);
+
+    let as_blue = analyze(Some(0)).await;
+    assert_eq!(as_blue, expected);
+    assert_ne!(as_blue, analyze(None).await);
+}
+
+#[tokio::test]
+async fn test_analyze_endpoint_with_finished_game() {
+    let app = test_app();
+
+    // Player B owns the whole size 2 board
+    let yen = YEN::new(2, 1, vec!['B', 'R'], "B/BB".to_string());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/ybot/analyze/random_bot")
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_string(&yen).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_difficulty_endpoint_with_valid_level() {
+    let app = test_app();
+
+    let yen = YEN::new(3, 0, vec!['B', 'R'], "./../...".to_string());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/ybot/difficulty/hard")
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_string(&yen).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let move_response: MoveResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(move_response.bot_id, "hard");
+}
+
+#[tokio::test]
+async fn test_difficulty_endpoint_with_unknown_level() {
+    let app = test_app();
+
+    let yen = YEN::new(3, 0, vec!['B', 'R'], "./../...".to_string());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/ybot/difficulty/impossible")
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_string(&yen).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// Session tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_choose_with_session_keeps_the_bot() {
+    let state = create_default_state();
+    let app = test_app_with_state(state.clone());
+
+    for layout in ["./../.../..../.....", "./../.../..../B...."] {
+        let body = serde_json::json!({
+            "size": 5,
+            "turn": 1,
+            "players": ["B", "R"],
+            "layout": layout,
+            "params": { "iterations": 50 },
+            "session_id": "game-1"
+        });
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/ybot/choose/mcts_bot")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let sessions = state.sessions();
+    assert_eq!(sessions.len(), 1);
+    let params = serde_json::from_str(r#"{"iterations": 50}"#).unwrap();
+    let kept = sessions.get_or_create("game-1", "mcts_bot", &params, || unreachable!("the bot is kept"));
+    assert_eq!(kept.unwrap().name(), "mcts_bot");
+}
+
+// ============================================================================
+// Game session endpoint tests
+// ============================================================================
+
+async fn send_json(app: &axum::Router, method: &str, uri: &str, body: Option<serde_json::Value>) -> (StatusCode, Vec<u8>) {
+    let builder = Request::builder().method(method).uri(uri).header("content-type", "application/json");
+    let body = body.map_or_else(Body::empty, |body| Body::from(body.to_string()));
+    let response = app.clone().oneshot(builder.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    (status, response.into_body().collect().await.unwrap().to_bytes().to_vec())
+}
+
+#[tokio::test]
+async fn test_game_session_plays_move_by_move() {
+    let app = test_app();
+
+    let (status, body) = send_json(
+        &app,
+        "POST",
+        "/v1/games",
+        Some(serde_json::json!({ "size": 3, "bot": "random_bot" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let created: GameResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(created.bot_player, Some(1));
+    assert!(created.history.is_empty());
+    assert_eq!(created.status, SessionStatus::Ongoing);
+
+    let uri = format!("/v1/games/{}/moves", created.game_id);
+    let (status, body) = send_json(
+        &app,
+        "POST",
+        &uri,
+        Some(serde_json::json!({ "action": "place", "coords": { "x": 1, "y": 0, "z": 1 } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let played: GameResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(played.history.len(), 2);
+    assert_eq!(played.history[0].player, 0);
+    assert_eq!(played.bot_move, Some(played.history[1]));
+
+    // The same cell cannot be taken twice.
+    let (status, _) = send_json(
+        &app,
+        "POST",
+        &uri,
+        Some(serde_json::json!({ "action": "place", "coords": { "x": 1, "y": 0, "z": 1 } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = send_json(&app, "GET", &format!("/v1/games/{}", created.game_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let fetched: GameResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(fetched.history, played.history);
+    assert!(fetched.bot_move.is_none());
+}
+
+#[tokio::test]
+async fn test_game_session_bot_moves_first() {
+    let app = test_app();
+
+    let (status, body) = send_json(
+        &app,
+        "POST",
+        "/v1/games",
+        Some(serde_json::json!({ "size": 4, "bot": "shortest_path_bot", "bot_player": 0, "rules": { "pie_rule": true } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let created: GameResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(created.history.len(), 1);
+    assert!(created.rules.pie_rule);
+    assert_eq!(created.bot_move, Some(created.history[0]));
+}
+
+#[tokio::test]
+async fn test_game_stays_readable_while_the_bot_thinks() {
+    let limits = ComputeLimits { max_timeout_ms: 500, ..ComputeLimits::default() };
+    let app = test_app_with_state(create_default_state().with_compute_limits(limits));
+
+    let body = serde_json::json!({ "size": 7, "bot": "mcts_bot", "params": { "iterations": u32::MAX } });
+    let (status, body) = send_json(&app, "POST", "/v1/games", Some(body)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let created: GameResponse = serde_json::from_slice(&body).unwrap();
+
+    let uri = format!("/v1/games/{}/moves", created.game_id);
+    let thinking = tokio::spawn({
+        let app = app.clone();
+        let body = serde_json::json!({ "action": "place", "coords": { "x": 3, "y": 2, "z": 1 }, "token": created.tokens[0].token });
+        async move { send_json(&app, "POST", &uri, Some(body)).await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    let start = std::time::Instant::now();
+    let (status, body) = send_json(&app, "GET", &format!("/v1/games/{}", created.game_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(start.elapsed() < std::time::Duration::from_millis(200));
+    let fetched: GameResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(fetched.history.len(), 1);
+
+    let (status, body) = thinking.await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+    let played: GameResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(played.history.len(), 2);
+    assert!(played.bot_move.is_some());
+}
+
+#[tokio::test]
+async fn test_game_session_errors() {
+    let app = test_app();
+
+    let (status, _) = send_json(&app, "GET", "/v1/games/unknown", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send_json(&app, "POST", "/v1/games/unknown/moves", Some(serde_json::json!({ "action": "resign" }))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send_json(&app, "POST", "/v1/games", Some(serde_json::json!({ "size": 3, "bot": "missing_bot" }))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, body) = send_json(
+        &app,
+        "POST",
+        "/v1/games",
+        Some(serde_json::json!({ "size": 3, "bot": "random_bot", "bot_player": 2 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert!(error.message.contains("bot_player"));
+    let (status, _) = send_json(&app, "POST", "/v3/games", Some(serde_json::json!({ "size": 3, "bot": "random_bot" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_game_sessions_expire() {
+    let state = create_default_state().with_game_ttl(std::time::Duration::ZERO);
+    let app = test_app_with_state(state);
+
+    let (_, body) = send_json(&app, "POST", "/v1/games", Some(serde_json::json!({ "size": 3, "bot": "random_bot" }))).await;
+    let created: GameResponse = serde_json::from_slice(&body).unwrap();
+    let (status, _) = send_json(&app, "GET", &format!("/v1/games/{}", created.game_id), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_game_between_humans_needs_seat_tokens() {
+    let app = test_app();
+
+    let (status, body) = send_json(&app, "POST", "/v1/games", Some(serde_json::json!({ "size": 3 }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let created: GameResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(created.bot_id, None);
+    assert_eq!(created.tokens.len(), 2);
+
+    let uri = format!("/v1/games/{}/moves", created.game_id);
+    let place = |token: &str| serde_json::json!({ "action": "place", "coords": { "x": 1, "y": 0, "z": 1 }, "token": token });
+    let (status, _) = send_json(&app, "POST", &uri, Some(serde_json::json!({ "action": "resign" }))).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, body) = send_json(&app, "POST", &uri, Some(place("wrong"))).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, ErrorCode::InvalidToken);
+    let (status, body) = send_json(&app, "POST", &uri, Some(place(&created.tokens[1].token))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, ErrorCode::NotYourTurn);
+    let (status, body) = send_json(&app, "POST", &uri, Some(place(&created.tokens[0].token))).await;
+    assert_eq!(status, StatusCode::OK);
+    let played: GameResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(played.history.len(), 1);
+    assert!(played.bot_move.is_none());
+    // Tokens are only handed out when the game is created.
+    assert!(played.tokens.is_empty());
+}
+
+/// Serves `state` on a local port and returns the base WebSocket URL.
+async fn serve(state: AppState) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, create_router(state)).await.unwrap() });
+    format!("ws://{}", addr)
+}
+
+type Socket = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+async fn connect(url: &str) -> Socket {
+    tokio_tungstenite::connect_async(url).await.unwrap().0
+}
+
+/// Reads events until one that is not a clock tick.
+async fn next_event(socket: &mut Socket) -> GameEvent {
+    loop {
+        let message = tokio::time::timeout(std::time::Duration::from_secs(5), socket.next())
+            .await
+            .expect("the server sent nothing")
+            .unwrap()
+            .unwrap();
+        if let tokio_tungstenite::tungstenite::Message::Text(text) = message {
+            let event: GameEvent = serde_json::from_str(text.as_str()).unwrap();
+            if !matches!(event, GameEvent::Clock(_)) {
+                return event;
+            }
+        }
+    }
+}
+
+async fn send_text(socket: &mut Socket, value: serde_json::Value) {
+    socket
+        .send(tokio_tungstenite::tungstenite::Message::Text(value.to_string().into()))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_game_socket_streams_a_game_between_humans() {
+    let state = create_default_state();
+    let app = test_app_with_state(state.clone());
+    let base = serve(state).await;
+
+    let (_, body) = send_json(
+        &app,
+        "POST",
+        "/v1/games",
+        Some(serde_json::json!({ "size": 2, "clock": { "initial_ms": 60000, "increment_ms": 1000 } })),
+    )
+    .await;
+    let created: GameResponse = serde_json::from_slice(&body).unwrap();
+    let url = format!("{}/v1/games/{}/ws", base, created.game_id);
+    let mut first = connect(&format!("{}?token={}", url, created.tokens[0].token)).await;
+    let mut second = connect(&format!("{}?token={}", url, created.tokens[1].token)).await;
+    let mut watcher = connect(&url).await;
+    for socket in [&mut first, &mut second, &mut watcher] {
+        assert!(matches!(next_event(socket).await, GameEvent::Snapshot { game } if game.history.is_empty()));
+    }
+
+    send_text(&mut first, serde_json::json!({ "action": "place", "coords": { "x": 1, "y": 0, "z": 0 } })).await;
+    for socket in [&mut first, &mut second, &mut watcher] {
+        assert!(matches!(next_event(socket).await, GameEvent::Move { ply: 1, played } if played.player == 0));
+        assert!(matches!(next_event(socket).await, GameEvent::Status { next_player: Some(1), .. }));
+    }
+
+    // Moves out of turn and moves of watchers are rejected to their sender only.
+    send_text(&mut first, serde_json::json!({ "action": "place", "coords": { "x": 0, "y": 1, "z": 0 } })).await;
+    assert!(matches!(next_event(&mut first).await, GameEvent::Error { .. }));
+    send_text(&mut watcher, serde_json::json!({ "action": "resign" })).await;
+    assert!(matches!(next_event(&mut watcher).await, GameEvent::Error { .. }));
+
+    // A player that reconnects with their token gets the game so far.
+    first.close(None).await.unwrap();
+    let mut first = connect(&format!("{}?token={}", url, created.tokens[0].token)).await;
+    match next_event(&mut first).await {
+        GameEvent::Snapshot { game } => {
+            assert_eq!(game.history.len(), 1);
+            assert_eq!(game.clock.unwrap().running, Some(1));
+        }
+        other => panic!("expected a snapshot, got {:?}", other),
+    }
+
+    send_text(&mut second, serde_json::json!({ "action": "resign" })).await;
+    for socket in [&mut first, &mut second, &mut watcher] {
+        assert!(matches!(next_event(socket).await, GameEvent::Move { ply: 2, .. }));
+        match next_event(socket).await {
+            GameEvent::Status { status, winner, reason, .. } => {
+                assert_eq!(status, SessionStatus::Finished);
+                assert_eq!(winner, Some(0));
+                assert_eq!(reason, Some(EndReason::Resign));
+            }
+            other => panic!("expected a status, got {:?}", other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_game_socket_against_a_bot() {
+    let state = create_default_state();
+    let app = test_app_with_state(state.clone());
+    let base = serve(state).await;
+
+    let (_, body) = send_json(&app, "POST", "/v1/games", Some(serde_json::json!({ "size": 3, "bot": "random_bot" }))).await;
+    let created: GameResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(created.tokens.len(), 1);
+    let url = format!("{}/v1/games/{}/ws?token={}", base, created.game_id, created.tokens[0].token);
+    let mut socket = connect(&url).await;
+    assert!(matches!(next_event(&mut socket).await, GameEvent::Snapshot { .. }));
+
+    send_text(&mut socket, serde_json::json!({ "action": "place", "coords": { "x": 1, "y": 0, "z": 1 } })).await;
+    assert!(matches!(next_event(&mut socket).await, GameEvent::Move { played, .. } if played.player == 0));
+    assert!(matches!(next_event(&mut socket).await, GameEvent::Status { .. }));
+    assert!(matches!(next_event(&mut socket).await, GameEvent::Move { played, .. } if played.player == 1));
+
+    // Unknown games and tokens are refused before the upgrade.
+    assert!(tokio_tungstenite::connect_async(format!("{}/v1/games/unknown/ws", base)).await.is_err());
+    let wrong = format!("{}/v1/games/{}/ws?token=wrong", base, created.game_id);
+    assert!(tokio_tungstenite::connect_async(wrong).await.is_err());
+}
+
+#[tokio::test]
+async fn test_game_socket_streams_events_while_the_bot_thinks() {
+    let limits = ComputeLimits { max_timeout_ms: 1_000, ..ComputeLimits::default() };
+    let state = create_default_state().with_compute_limits(limits);
+    let app = test_app_with_state(state.clone());
+    let base = serve(state).await;
+
+    let body = serde_json::json!({ "size": 7, "bot": "mcts_bot", "params": { "iterations": u32::MAX } });
+    let (_, body) = send_json(&app, "POST", "/v1/games", Some(body)).await;
+    let created: GameResponse = serde_json::from_slice(&body).unwrap();
+    let url = format!("{}/v1/games/{}/ws?token={}", base, created.game_id, created.tokens[0].token);
+    let mut socket = connect(&url).await;
+    assert!(matches!(next_event(&mut socket).await, GameEvent::Snapshot { .. }));
+
+    let start = std::time::Instant::now();
+    send_text(&mut socket, serde_json::json!({ "action": "place", "coords": { "x": 3, "y": 2, "z": 1 } })).await;
+    assert!(matches!(next_event(&mut socket).await, GameEvent::Move { played, .. } if played.player == 0));
+    assert!(matches!(next_event(&mut socket).await, GameEvent::Status { .. }));
+    assert!(start.elapsed() < std::time::Duration::from_millis(500));
+    assert!(matches!(next_event(&mut socket).await, GameEvent::Move { played, .. } if played.player == 1));
+}
+
+#[tokio::test]
+async fn test_game_socket_reports_timeouts() {
+    let state = create_default_state();
+    let app = test_app_with_state(state.clone());
+    let base = serve(state).await;
+
+    let (_, body) = send_json(
+        &app,
+        "POST",
+        "/v1/games",
+        Some(serde_json::json!({ "size": 3, "bot": "random_bot", "clock": { "initial_ms": 200 } })),
+    )
+    .await;
+    let created: GameResponse = serde_json::from_slice(&body).unwrap();
+    let mut socket = connect(&format!("{}/v1/games/{}/ws", base, created.game_id)).await;
+    assert!(matches!(next_event(&mut socket).await, GameEvent::Snapshot { .. }));
+    match next_event(&mut socket).await {
+        GameEvent::Status { status, reason, .. } => {
+            assert_eq!(status, SessionStatus::BotWon);
+            assert_eq!(reason, Some(EndReason::Timeout));
+        }
+        other => panic!("expected a status, got {:?}", other),
+    }
+}
+
+// ============================================================================
+// Solve endpoint tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_solve_endpoint_reports_the_winner() {
+    let app = test_app();
+
+    let yen = YEN::new(3, 0, vec!['B', 'R'], "./../...".to_string());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/ybot/solve")
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_string(&yen).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let solve_response: SolveResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(solve_response.solution.winner, 0);
+    assert_eq!(
+        solve_response.summary,
+        format!("win in {} for player 0", solve_response.solution.moves_to_win)
+    );
+    assert!(solve_response.solution.best_move.is_some());
+}
+
+#[tokio::test]
+async fn test_solve_endpoint_rejects_large_positions() {
+    let app = test_app();
+
+    let body = serde_json::json!({
+        "size": 5,
+        "turn": 0,
+        "players": ["B", "R"],
+        "layout": "./../.../..../.....",
+        "max_empty_cells": 100
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/ybot/solve")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert!(error.message.contains("at most 12"));
+}
+
+#[tokio::test]
+async fn test_choose_batch_answers_every_position() {
+    let app = test_app_with_state(create_default_state().with_batch_concurrency(2));
+
+    let body = serde_json::json!({
+        "positions": [
+            { "size": 3, "turn": 0, "players": ["B", "R"], "layout": "./../..." },
+            { "size": 3, "turn": 0, "players": ["B", "R"], "layout": "./.." },
+            { "size": 3, "turn": 1, "players": ["B", "R"], "layout": "B/../..." }
+        ]
+    });
+    let (status, body) = send_json(&app, "POST", "/v1/ybot/choose_batch/random_bot", Some(body)).await;
+    assert_eq!(status, StatusCode::OK);
+    let response: ChooseBatchResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response.bot_id, "random_bot");
+    assert_eq!(response.results.len(), 3);
+    assert!(matches!(&response.results[0], BatchMove::Move(m) if m.bot_player == 0));
+    assert!(matches!(&response.results[1], BatchMove::Error(e) if e.code == ErrorCode::InvalidYen));
+    assert!(matches!(&response.results[2], BatchMove::Move(m) if m.bot_player == 1));
+
+    let (status, body) = send_json(&app, "POST", "/v1/ybot/choose_batch/unknown_bot", Some(serde_json::json!({ "positions": [] }))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, ErrorCode::BotNotFound);
+}
+
+// ============================================================================
+// Compute limit tests
+// ============================================================================
+
+/// A choose body for an MCTS search that only stops when it is cancelled.
+fn endless_search_body(timeout_ms: Option<u64>) -> serde_json::Value {
+    let mut body = serde_json::json!({
+        "size": 7, "turn": 0, "players": ["B", "R"], "layout": "./../.../..../...../....../.......",
+        "params": { "iterations": u32::MAX }
+    });
+    if let Some(timeout_ms) = timeout_ms {
+        body["timeout_ms"] = timeout_ms.into();
+    }
+    body
+}
+
+#[tokio::test]
+async fn test_search_stops_at_the_deadline() {
+    let limits = ComputeLimits { max_timeout_ms: 200, ..ComputeLimits::default() };
+    let app = test_app_with_state(create_default_state().with_compute_limits(limits));
+
+    for timeout_ms in [Some(50), Some(60_000), None] {
+        let start = std::time::Instant::now();
+        let (status, body) = send_json(&app, "POST", "/v1/ybot/choose/mcts_bot", Some(endless_search_body(timeout_ms))).await;
+        assert_eq!(status, StatusCode::OK);
+        let response: MoveResponse = serde_json::from_slice(&body).unwrap();
+        assert!(response.coords.is_some());
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+}
+
+#[tokio::test]
+async fn test_solver_stops_at_the_deadline() {
+    let app = test_app();
+
+    let body = serde_json::json!({
+        "size": 5, "turn": 1, "players": ["B", "R"], "layout": "./B./.R./..B./.....",
+        "max_empty_cells": 12, "timeout_ms": 0
+    });
+    let (status, body) = send_json(&app, "POST", "/v1/ybot/solve", Some(body)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, ErrorCode::Timeout);
+}
+
+#[tokio::test]
+async fn test_busy_server_answers_429() {
+    let limits = ComputeLimits { max_concurrent: 1, max_queued: 0, max_timeout_ms: 10_000 };
+    let state = create_default_state().with_compute_limits(limits);
+    let app = test_app_with_state(state.clone());
+
+    let running = tokio::spawn({
+        let app = app.clone();
+        async move { send_json(&app, "POST", "/v2/ybot/choose/mcts_bot", Some(endless_search_body(Some(1_000)))).await }
+    });
+    while state.compute().pending() == 0 {
+        tokio::task::yield_now().await;
+    }
+    let yen = serde_json::json!({ "size": 3, "turn": 0, "players": ["B", "R"], "layout": "./../..." });
+    let (status, body) = send_json(&app, "POST", "/v1/ybot/choose/random_bot", Some(yen)).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, ErrorCode::Overloaded);
+
+    let (status, _) = running.await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+}
+
+/// Builds the URI of the competition route for a position.
+fn play_uri(position: &serde_json::Value, bot_id: &str) -> String {
+    let encoded: String = position
+        .to_string()
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+    format!("/play?position={}&bot_id={}", encoded, bot_id)
+}
+
+/// A bot that keeps thinking after its search is cancelled.
+struct StubbornBot;
+
+impl YBot for StubbornBot {
+    fn name(&self) -> &str {
+        "stubborn_bot"
+    }
+
+    fn choose_move(&self, board: &GameY) -> Option<gamey::Coordinates> {
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        RandomBot.choose_move(board)
+    }
+}
+
+#[tokio::test]
+async fn test_play_route_answers_503_after_the_deadline() {
+    let limits = ComputeLimits { max_timeout_ms: 50, ..ComputeLimits::default() };
+    let bots = YBotRegistry::new().with_bot(Arc::new(StubbornBot));
+    let app = test_app_with_state(AppState::new(bots).with_compute_limits(limits));
+
+    let position = serde_json::json!({ "size": 3, "turn": 0, "players": ["B", "R"], "layout": "./../..." });
+    let (status, body) = send_json(&app, "GET", &play_uri(&position, "stubborn_bot"), None).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, ErrorCode::Timeout);
+}
+
+#[tokio::test]
+async fn test_play_route_answers_429_on_a_busy_server() {
+    let limits = ComputeLimits { max_concurrent: 1, max_queued: 0, max_timeout_ms: 10_000 };
+    let state = create_default_state().with_compute_limits(limits);
+    let app = test_app_with_state(state.clone());
+
+    let running = tokio::spawn({
+        let app = app.clone();
+        async move { send_json(&app, "POST", "/v1/ybot/choose/mcts_bot", Some(endless_search_body(Some(1_000)))).await }
+    });
+    while state.compute().pending() == 0 {
+        tokio::task::yield_now().await;
+    }
+    let position = serde_json::json!({ "size": 3, "turn": 0, "players": ["B", "R"], "layout": "./../..." });
+    let (status, body) = send_json(&app, "GET", &play_uri(&position, "random_bot"), None).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, ErrorCode::Overloaded);
+
+    let (status, _) = running.await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = send_json(&app, "GET", &play_uri(&position, "random_bot"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let reply: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(reply["coords"].is_object());
+}
+
+// ============================================================================
+// Server configuration tests
+// ============================================================================
+
+/// Builds the app the way `run_server` does.
+fn configured_app(file: &str) -> axum::Router {
+    let config = ServerConfig::from_sources(Some(file), Vec::new()).unwrap();
+    create_router(config.build_state().unwrap()).layer(config.cors_layer().unwrap())
+}
+
+#[tokio::test]
+async fn test_configured_bots_and_metrics() {
+    let app = configured_app("[metrics]\nenabled = false\n[bots]\nenabled = [\"random_bot\"]\n");
+
+    let (status, _) = send_json(&app, "GET", "/metrics", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = send_json(&app, "GET", "/v1/ybot", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let catalogue: BotCatalogueResponse = serde_json::from_slice(&body).unwrap();
+    let ids: Vec<String> = catalogue.bots.into_iter().map(|bot| bot.id).collect();
+    assert_eq!(ids, vec!["random_bot".to_string()]);
+
+    let yen = serde_json::json!({ "size": 3, "turn": 0, "players": ["B", "R"], "layout": "./../..." });
+    let (status, _) = send_json(&app, "POST", "/v1/ybot/choose/mcts_bot", Some(yen)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_cors_allows_only_the_configured_origins() {
+    let app = configured_app("[server]\ncors_origins = [\"http://localhost:5173\"]\n");
+
+    for (origin, allowed) in [("http://localhost:5173", true), ("http://evil.example", false)] {
+        let request = Request::builder()
+            .uri("/status")
+            .header("origin", origin)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let header = response.headers().get("access-control-allow-origin");
+        assert_eq!(header.map(|value| value.to_str().unwrap()), allowed.then_some(origin), "{origin}");
+    }
+}
+
+// ============================================================================
+// API version tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_versions_endpoint_lists_v1_and_v2() {
+    let app = test_app();
+
+    let (status, body) = send_json(&app, "GET", "/versions", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let versions: VersionsResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(versions.latest, "v2");
+    let deprecated: Vec<(String, bool)> = versions.versions.into_iter().map(|v| (v.version, v.deprecated)).collect();
+    assert_eq!(deprecated, vec![("v1".to_string(), true), ("v2".to_string(), false)]);
+}
+
+#[tokio::test]
+async fn test_v2_choose_returns_a_move_object() {
+    let app = test_app();
+
+    let yen = serde_json::to_value(YEN::new(3, 0, vec!['B', 'R'], "./../...".to_string())).unwrap();
+    let (status, body) = send_json(&app, "POST", "/v2/ybot/choose/random_bot", Some(yen.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    let response: BotMoveResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response.api_version, "v2");
+    assert!(matches!(response.bot_move, Some(BotDecision::Place { .. })));
+    assert!(!response.status.finished);
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(json.get("game_status").is_none());
+
+    let (status, body) = send_json(&app, "POST", "/v2/ybot/difficulty/easy", Some(yen.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    let response: BotMoveResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response.bot_id, "easy");
+    assert!(response.bot_move.is_some());
+
+    let (status, body) = send_json(&app, "POST", "/v2/ybot/choose/unknown_bot", Some(yen)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, ErrorCode::BotNotFound);
+    assert_eq!(error.api_version, Some("v2".to_string()));
+}
+
+#[tokio::test]
+async fn test_v2_shares_the_other_routes_with_v1() {
+    let app = test_app();
+
+    let (status, body) = send_json(&app, "GET", "/v2/ybot", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let catalogue: BotCatalogueResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(catalogue.api_version, "v2");
+
+    let (status, body) = send_json(&app, "POST", "/v2/games", Some(serde_json::json!({ "size": 3, "bot": "random_bot" }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let created: GameResponse = serde_json::from_slice(&body).unwrap();
+    let (status, _) = send_json(&app, "GET", &format!("/v2/games/{}", created.game_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_only_v1_responses_are_deprecated() {
+    let app = test_app();
+    let yen = serde_json::to_string(&YEN::new(3, 0, vec!['B', 'R'], "./../...".to_string())).unwrap();
+    let request = |uri: &str| {
+        Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(yen.clone()))
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(request("/v1/ybot/choose/random_bot")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["deprecation"], "true");
+    assert_eq!(
+        response.headers()["link"],
+        "</v2/ybot/choose/random_bot>; rel=\"successor-version\""
+    );
+
+    // Errors of v1 routes are marked too.
+    let response = app.clone().oneshot(request("/v1/ybot/choose/unknown_bot")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()["deprecation"], "true");
+
+    let response = app.clone().oneshot(request("/v2/ybot/choose/random_bot")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("deprecation").is_none());
+    assert!(response.headers().get("link").is_none());
+}
+
 // ============================================================================
 // Custom state tests
 // ============================================================================
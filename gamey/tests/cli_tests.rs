@@ -341,6 +341,13 @@ fn test_cli_args_custom_port_short() {
     assert_eq!(args.port, 9000);
 }
 
+#[test]
+fn test_cli_args_config_file() {
+    let args = CliArgs::try_parse_from(["gamey", "--mode", "server", "--config", "server.toml"]).unwrap();
+    assert_eq!(args.config, Some(std::path::PathBuf::from("server.toml")));
+    assert!(CliArgs::try_parse_from(["gamey"]).unwrap().config.is_none());
+}
+
 #[test]
 fn test_cli_args_combined_options() {
     let args = CliArgs::try_parse_from([
@@ -390,3 +397,127 @@ fn test_cli_args_version_flag() {
     let result = CliArgs::try_parse_from(["gamey", "--version"]);
     assert!(result.is_err()); // --version causes an error (but it's intentional)
 }
+
+// =============================================================================
+// Tournament subcommand Tests
+// =============================================================================
+
+use gamey::{CliCommand, ReportFormat};
+
+#[test]
+fn test_cli_args_tournament_subcommand() {
+    let args = CliArgs::try_parse_from([
+        "gamey", "tournament", "--bots", "random_bot,shortest_path_bot", "--size", "5", "--games", "20",
+    ])
+    .unwrap();
+    let Some(CliCommand::Tournament(tournament)) = args.command else {
+        panic!("expected the tournament subcommand");
+    };
+    assert_eq!(tournament.bots, vec!["random_bot", "shortest_path_bot"]);
+    assert_eq!(tournament.size, 5);
+    assert_eq!(tournament.games, 20);
+    assert_eq!(tournament.seed, 0);
+    assert_eq!(tournament.format, ReportFormat::Json);
+    assert!(tournament.out.is_none());
+}
+
+#[test]
+fn test_cli_args_tournament_requires_bots() {
+    let result = CliArgs::try_parse_from(["gamey", "tournament"]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_cli_args_without_subcommand() {
+    let args = CliArgs::try_parse_from(["gamey"]).unwrap();
+    assert!(args.command.is_none());
+}
+
+#[test]
+fn test_cli_args_book_subcommand() {
+    let args = CliArgs::try_parse_from([
+        "gamey", "book", "--records", "a.jsonl", "--records", "b.jsonl", "--out", "book.json",
+    ])
+    .unwrap();
+    let Some(CliCommand::Book(book)) = args.command else {
+        panic!("expected the book subcommand");
+    };
+    assert_eq!(book.records.len(), 2);
+    assert_eq!(book.depth, 8);
+    assert_eq!(book.min_weight, 1);
+    assert_eq!(book.out, std::path::PathBuf::from("book.json"));
+}
+
+#[test]
+fn test_cli_args_selfplay_subcommand() {
+    let args = CliArgs::try_parse_from([
+        "gamey", "selfplay", "--bot", "mcts", "--games", "5", "--size", "6", "--out", "data.jsonl",
+    ])
+    .unwrap();
+    let Some(CliCommand::Selfplay(selfplay)) = args.command else {
+        panic!("expected the selfplay subcommand");
+    };
+    assert_eq!(selfplay.bot, "mcts");
+    assert_eq!(selfplay.games, 5);
+    assert_eq!(selfplay.size, 6);
+    assert_eq!(selfplay.seed, 0);
+    assert!(selfplay.sample_moves.is_none());
+    assert_eq!(selfplay.noise, 0.05);
+    assert_eq!(selfplay.out, std::path::PathBuf::from("data.jsonl"));
+}
+
+#[test]
+fn test_run_selfplay_command_writes_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("data.jsonl");
+    let args = CliArgs::try_parse_from([
+        "gamey",
+        "selfplay",
+        "--bot",
+        "mcts?iterations=10",
+        "--games",
+        "2",
+        "--size",
+        "3",
+        "--out",
+        out.to_str().unwrap(),
+    ])
+    .unwrap();
+    let Some(CliCommand::Selfplay(selfplay)) = args.command else {
+        panic!("expected the selfplay subcommand");
+    };
+    gamey::run_selfplay_command(&selfplay).unwrap();
+    let data = std::fs::read_to_string(&out).unwrap();
+    let first: gamey::SelfPlayPosition = serde_json::from_str(data.lines().next().unwrap()).unwrap();
+    assert_eq!(first.game, 0);
+    assert_eq!(first.ply, 0);
+}
+
+#[test]
+fn test_cli_args_tune_subcommand() {
+    let args = CliArgs::try_parse_from(["gamey", "tune", "--bot", "shortest_path_bot", "--iterations", "5"]).unwrap();
+    let Some(CliCommand::Tune(tune)) = args.command else {
+        panic!("expected the tune subcommand");
+    };
+    assert_eq!(tune.bot, "shortest_path_bot");
+    assert_eq!(tune.iterations, 5);
+    assert_eq!(tune.games, 20);
+    assert_eq!(tune.validation_games, 100);
+    assert!(tune.out.is_none());
+    assert!(CliArgs::try_parse_from(["gamey", "tune"]).is_err());
+}
+
+#[test]
+fn test_run_tune_command_rejects_bots_without_weights() {
+    let args = CliArgs::try_parse_from(["gamey", "tune", "--bot", "random_bot"]).unwrap();
+    let Some(CliCommand::Tune(tune)) = args.command else {
+        panic!("expected the tune subcommand");
+    };
+    assert!(gamey::run_tune_command(&tune).is_err());
+}
+
+#[test]
+fn test_cli_args_book_requires_out() {
+    let result = CliArgs::try_parse_from(["gamey", "book", "--records", "a.jsonl"]);
+    assert!(result.is_err());
+}
@@ -0,0 +1,73 @@
+//! Checks that the OpenAPI document matches the routes of the bot server.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use gamey::{ApiDoc, api_routes, create_default_state, create_router};
+use http_body_util::BodyExt;
+use std::collections::BTreeSet;
+use tower::ServiceExt;
+use utoipa::OpenApi;
+
+/// Returns the `(method, path)` of every route of the router.
+fn router_routes() -> BTreeSet<(String, String)> {
+    api_routes()
+        .into_iter()
+        .map(|(method, path, _)| (method.as_str().to_lowercase(), path.to_string()))
+        .collect()
+}
+
+/// Returns the `(method, path)` of every operation of the document.
+fn documented_routes(document: &serde_json::Value) -> BTreeSet<(String, String)> {
+    document["paths"]
+        .as_object()
+        .unwrap()
+        .iter()
+        .flat_map(|(path, operations)| {
+            operations
+                .as_object()
+                .unwrap()
+                .keys()
+                .map(move |method| (method.clone(), path.clone()))
+        })
+        .collect()
+}
+
+#[test]
+fn test_every_route_is_documented() {
+    let routes = router_routes();
+    assert!(routes.contains(&("post".to_string(), "/{api_version}/ybot/choose/{bot_id}".to_string())));
+    let document = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    let documented = documented_routes(&document);
+
+    let undocumented: Vec<_> = routes.difference(&documented).collect();
+    assert!(undocumented.is_empty(), "routes missing from the OpenAPI document: {undocumented:?}");
+    let unrouted: Vec<_> = documented.difference(&routes).collect();
+    assert!(unrouted.is_empty(), "documented operations without a route: {unrouted:?}");
+}
+
+#[test]
+fn test_every_schema_reference_resolves() {
+    let document = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    let schemas = document["components"]["schemas"].as_object().unwrap();
+    let text = document.to_string();
+    for reference in text.split("\"#/components/schemas/").skip(1) {
+        let name = reference.split('"').next().unwrap();
+        assert!(schemas.contains_key(name), "unknown schema {name}");
+    }
+}
+
+#[tokio::test]
+async fn test_openapi_endpoint_serves_the_document() {
+    let app = create_router(create_default_state());
+
+    let response = app
+        .oneshot(Request::builder().uri("/openapi.json").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let served: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(served, serde_json::to_value(ApiDoc::openapi()).unwrap());
+}